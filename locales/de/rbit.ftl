@@ -0,0 +1,657 @@
+# rbits Meldungen auf Deutsch. Fehlt hier eine Meldung, erscheint die englische.
+
+## Fehler
+
+error = Fehler:
+warning = Warnung:
+error-partial-failure = { $failed } von { $total } Einträgen fehlgeschlagen
+error-interrupted = abgebrochen
+error-absent = { $missing } von { $total } Torrents sind nicht auf dem Server
+torrent-incomplete = { $name } ist noch nicht vollständig ({ $percent } %)
+
+## Warnungen
+
+stale-lock = eine verwaiste Sperre von Prozess { $pid } wird übernommen ({ $path })
+data-not-moved = die Daten von rbit konnten nicht nach { $path } verschoben werden: { $reason }
+desktop-notification-failed = Desktop-Benachrichtigung fehlgeschlagen: { $reason }
+webhook-failed = Webhook { $address } fehlgeschlagen: { $reason }
+config-unknown-key = { $path }: unbekannter Schlüssel `{ $name }` wird ignoriert (--strict-config macht das zum Fehler)
+deluge-no-category = Deluge verwaltet Kategorien im Label-Plugin; die Kategorie wird nicht gesetzt
+profile-skipped = Serverprofil '{ $profile }' wird übersprungen: { $reason }
+
+## Warten auf andere rbit-Prozesse
+
+lock-waiting = warte, bis { $holder } fertig ist …
+lock-holder-daemon = der laufende Durchgang des rbit-Daemons
+
+## rbit status
+
+status-yes = ja ({ $connection })
+status-no = nein: { $reason }
+status-unreachable = kein Server erreichbar
+
+## rbit list
+
+filter-all = alle
+filter-active = aktive
+filter-downloading = ladende
+filter-seeding = seedende
+list-hidden = { $hidden ->
+        [one] 1 weiterer Torrent nicht angezeigt
+       *[other] { $hidden } weitere Torrents nicht angezeigt
+    }: angezeigt werden { $filter } Torrents (--all zeigt alle)
+list-summary = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    }: { $groups }
+list-summary-group = { $count } { $group }
+list-stale = veraltete Daten von vor { $age }, gespeichert beim letzten Kontakt mit dem Server
+list-no-snapshot = noch keine Liste von { $host } gespeichert; `rbit list` speichert eine, sobald es den Server erreicht
+state-downloading = ladend
+state-seeding = seedend
+state-stalled = stockend
+state-paused = pausiert
+state-queued = wartend
+state-checking = prüfend
+state-error = fehlerhaft
+state-other = sonstige
+
+## rbit export
+
+export-saved = gespeichert  { $path }
+export-skipped = übersprungen { $name }: schon exportiert
+export-failed = FEHLER       { $name } ({ $id }): { $reason }
+export-summary = { $saved } von { $total } nach { $dir } exportiert, { $skipped } schon vorhanden
+
+## rbit verify
+
+verify-unreachable = { $path } ist von diesem Rechner aus nicht erreichbar; verify liest die Daten dort, wo der Server sie ablegt, am path_map des Profils, oder unter --data-root, wo das eingehängt ist
+verify-no-data-root = { $path } ist kein Ordner; --data-root gibt an, wo { $save_path } des Servers auf diesem Rechner eingehängt ist
+verify-other-torrent = { $path } ist die .torrent-Datei eines anderen Torrents als { $name }
+verify-missing = fehlt
+verify-size = { $size } auf der Platte, erwartet { $expected }
+verify-bad-pieces = { $count ->
+        [one] 1 fehlerhaftes Stück
+       *[other] { $count } fehlerhafte Stücke
+    }
+verify-file-failed = FEHLER  { $path }: { $reason }
+verify-files-missing = { $failed } von { $total } Dateien fehlen oder haben die falsche Größe
+verify-sizes-ok = Alle { $total } Dateien von { $name } sind da und haben die richtige Größe (--deep prüft ihren Inhalt)
+verify-progress = Prüfe
+verify-summary = { $name }: { $ok } von { $total } Stücken stimmen, { $size } gelesen in { $elapsed }
+verify-unchecked = , { $unchecked } nicht geprüft (Dateien nicht heruntergeladen)
+verify-bad-list = Stücke, die nicht stimmen: { $pieces }
+verify-bad = { $bad } von { $total } Stücken stimmen nicht; `rbit recheck { $id }` lässt qBittorrent sie prüfen und neu herunterladen
+verify-wrong-size = { $failed } von { $total } Dateien haben nicht die richtige Größe
+
+## rbit cross-seed
+
+cross-seed-exists = { $name } wird schon bei { $tracker } geseedet ({ $id })
+cross-seed-added = { $name } wird jetzt auch bei { $tracker } geseedet, als { $id }, aus { $path }
+
+## rbit doctor
+
+doctor-config = Profil { $profile }, { $host }, Konfiguration aus { $files }
+doctor-no-config = Profil { $profile }, { $host }, keine Konfigurationsdatei (nur Vorgaben und Optionen)
+doctor-proxy = die Verbindung läuft über einen Proxy, der den Host selbst auflöst und verbindet
+doctor-dns = { $host } wird zu { $addresses } aufgelöst
+doctor-dns-failed = { $host } lässt sich nicht auflösen; den Hostnamen in --host oder der Konfiguration prüfen
+doctor-tcp = mit { $host }:{ $port } in { $ms } ms verbunden
+doctor-tcp-failed = keine Verbindung zu { $host }:{ $port } ({ $reason }); ist das Web UI aktiviert und lauscht auf diesem Port?
+doctor-tcp-timeout = keine Antwort von { $host }:{ $port } in { $seconds } s; eine Firewall verwirft die Verbindung vielleicht
+doctor-login = angemeldet als { $user }
+doctor-anonymous = ohne Benutzernamen angenommen
+doctor-torrents = { $count ->
+        [one] die Torrent-Liste ist lesbar (1 Torrent)
+       *[other] die Torrent-Liste ist lesbar ({ $count } Torrents)
+    }
+doctor-ok = Alles funktioniert.
+
+## Rückfragen
+
+confirm-prompt = { $question } [j/N]
+confirm-yes = j
+confirm-refused = ohne Bestätigung nicht möglich ({ $what }): --yes angeben oder no_confirm = true in der Konfiguration setzen
+nothing-done = Nichts geändert
+nothing-deleted = Nichts gelöscht
+delete-what = { $count ->
+        [one] 1 Torrent löschen
+       *[other] { $count } Torrents löschen
+    }
+
+## rbit pause und resume
+
+pause-confirm = { $count } Torrents auf { $host } anhalten?
+pause-what = { $count } Torrents anhalten
+pause-done = Angehalten:
+resume-done = Fortgesetzt:
+bulk-done = { $what } { $name } ({ $id })
+bulk-all = { $what } alle { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    }
+
+## rbit prune
+
+prune-none = Keine Torrents zum Aufräumen
+prune-confirm = { $count ->
+        [one] Diesen Torrent löschen? Seine Daten bleiben auf der Platte.
+       *[other] Diese { $count } Torrents löschen? Ihre Daten bleiben auf der Platte.
+    }
+prune-confirm-data = { $count ->
+        [one] Diesen Torrent samt Daten löschen?
+       *[other] Diese { $count } Torrents samt Daten löschen?
+    }
+prune-deleting = Lösche
+prune-deleted = { $done } von { $total } Torrents gelöscht
+prune-deleted-data = { $done } von { $total } Torrents samt Daten gelöscht
+
+## rbit cleanup
+
+cleanup-no-policies = Keine [[retention]]-Regeln in der Konfiguration
+cleanup-running = ein anderes rbit cleanup läuft bereits für { $host }
+cleanup-none = Kein Torrent trägt ein Tag mit einer Aufbewahrungsregel
+cleanup-confirm = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } löschen, { $with_files } davon samt Dateien?
+cleanup-deleted = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } gelöscht ({ $with_files } samt Dateien)
+
+## rbit shutdown
+
+shutdown-confirm = qBittorrent { $version } auf { $host } beenden? Seine Torrents stehen still, bis es wieder gestartet wird.
+shutdown-what = den Server beenden
+shutdown-done = qBittorrent auf { $host } wird beendet
+
+## rbit add
+
+add-unchecked = { $path } ist kein Torrent, den rbit lesen kann ({ $why }); wird hochgeladen, ohne zu prüfen, ob der Server ihn schon hat
+add-done = Zu qBittorrent hinzugefügt (Ziel: { $destination })
+add-done-to = { $name } zu { $client } hinzugefügt (Ziel: { $destination })
+add-duplicate = Schon auf dem Server als { $existing }, übersprungen (--force fügt ihn trotzdem hinzu)
+add-summary = { $done } von { $total } hinzugefügt, { $skipped } schon vorhanden (Ziel: { $destination })
+add-force-hint = Mit --force werden die übersprungenen Torrents trotzdem hinzugefügt
+add-nothing = Nichts hinzuzufügen
+add-automatic = automatisch (Speicherort der Kategorie)
+add-server-default = der Standard-Speicherort des Servers
+add-unless-rule = { $destination }, sofern keine Regel einen anderen vorgibt
+add-delete-failed = { $path } konnte nicht gelöscht werden: { $reason }
+add-deleted-sources = { $count ->
+        [one] 1 Quelldatei
+       *[other] { $count } Quelldateien
+    } gelöscht
+add-cannot-follow = { $name } kann nicht verfolgt werden: sein Info-Hash ist unbekannt
+add-no-space = zum Hinzufügen werden { $needed } gebraucht, der Server hat aber nur { $free } frei (--ignore-space fügt sie trotzdem hinzu)
+add-no-space-anyway = zum Hinzufügen werden { $needed } gebraucht, der Server hat aber nur { $free } frei; wird trotzdem hinzugefügt (--ignore-space)
+login-no-password = Benutzer '{ $user }' hat kein Passwort (--password oder { $variable } angeben); Anmeldung wird übersprungen
+login-password-prompt = Passwort für { $user }@{ $host }
+
+## rbit add --follow
+
+follow-fetching = { $name } (lade Metadaten)
+follow-absent = nicht auf dem Server
+follow-absent-failed = FEHLER  { $id }: ist nicht auf dem Server aufgetaucht
+follow-complete = fertig
+follow-completed = Fertig: { $name } ({ $size })
+follow-failed = FEHLER  { $name }: qBittorrent meldet { $state }
+follow-hook-failed = FEHLER  { $reason }
+follow-stopped = nicht mehr verfolgt
+follow-interrupted = Verfolgung beendet; die Torrents laden auf dem Server weiter
+follow-incomplete = der Torrent wurde nicht fertig
+
+## rbit wait
+
+wait-stopped = Warten beendet
+wait-interrupted = Warten beendet; die Torrents laden auf dem Server weiter
+wait-removed = { $name } wurde während des Wartens vom Server entfernt
+wait-failed = { $name } angehalten: qBittorrent meldet { $state }
+wait-hook-failed = der On-Complete-Hook ist bei { $failed } von { $total } Torrents fehlgeschlagen
+wait-gave-up = aufgegeben
+wait-timeout = nach { $limit } aufgegeben; es wird noch gewartet auf { $what }
+
+## rbit tui
+
+tui-needs-terminal = rbit tui braucht ein Terminal, um darauf zu zeichnen
+tui-title = { $host }: { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    }
+tui-title-filtered = { $host }: { $count } von { $total } Torrents
+tui-marked = { $count } markiert
+tui-keys = / filtern  Leertaste markieren  v Bereich  * alle  Esc aufheben  p anhalten  r fortsetzen  d löschen  c Kategorie  a hinzufügen  q beenden
+tui-category-prompt = Kategorie (leer für keine):
+tui-confirm-pause = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } anhalten?
+tui-confirm-resume = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } fortsetzen?
+tui-confirm-category = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } in die Kategorie { $name } verschieben?
+tui-confirm-no-category = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } aus ihrer Kategorie nehmen?
+tui-more = … und { $count } weitere
+tui-answer = y: ja   n: nein
+tui-answer-delete = y: ja   n: nein   f: { $with_files ->
+        [0] die Daten auch löschen
+       *[other] die Daten behalten
+    }
+tui-deleted = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } gelöscht
+tui-deleted-data = { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } samt Daten gelöscht
+tui-done = { $what } { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    }
+tui-category-done = { $count ->
+        [one] 1 Torrent ist
+       *[other] { $count } Torrents sind
+    } jetzt in der Kategorie { $name }
+tui-add-title = Torrent hinzufügen
+tui-add-link = Magnet, URL oder Datei
+tui-add-destination = Ziel
+tui-add-server-default = (Vorgabe des Servers)
+tui-add-category = Kategorie
+tui-add-paused = Angehalten
+tui-add-keys = Tab nächstes Feld   Leertaste umschalten   Enter hinzufügen   Esc abbrechen
+tui-added = Hinzugefügt; der Torrent erscheint in der Liste, sobald der Server ihn hat
+tui-duplicate = Schon auf dem Server als { $existing }
+
+## rbit daemon
+
+daemon-added = hinzugefügt   { $name } (Ziel: { $destination })
+daemon-duplicate = übersprungen { $name }: schon auf dem Server als { $existing }
+daemon-add-failed = FEHLER  { $name }: { $reason }
+daemon-nothing-to-do = nichts zu beobachten: --watch-dir angeben oder [[watch]]-Tabellen (oder eine [prune]-Regel, [[schedule]]-Zeitfenster, eine [disk]-Grenze oder eine [guard]-Prüfung) in die Konfiguration schreiben
+daemon-no-watch-dir = Ordner { $path } gibt es nicht
+daemon-no-disk-path = [disk] paths: { $path } gibt es nicht
+daemon-watching = Beobachte { $dirs } alle { $every } auf .torrent- und .magnet-Dateien
+daemon-pruning = Räume alle { $every } nach der [prune]-Regel auf
+daemon-scheduling = Schalte die alternativen Geschwindigkeitsgrenzen nach dem [[schedule]]
+daemon-disk-pause = Halte Downloads an, wenn der freie Platz unter { $limit } fällt
+daemon-disk-notify = Melde, wenn der freie Platz unter { $limit } fällt
+daemon-guarding = Halte alle Torrents an, solange `{ $command }` fehlschlägt
+daemon-serving = Der Zustand des Daemons steht unter http://{ $address }/status, /metrics und /events
+daemon-serving-stopped = der Status-Endpunkt ist ausgefallen: { $reason }
+daemon-running = ein anderer rbit-Daemon beobachtet bereits { $host }
+daemon-paused-torrent = angehalten  { $name } { $why }
+daemon-while-low-space = solange der Platz knapp ist
+daemon-while-guard = solange die [guard]-Prüfung fehlschlägt
+daemon-low-space = Der freie Platz auf { $host } ist auf { $free } gefallen (Grenze { $limit })
+daemon-low-space-pausing = Der freie Platz auf { $host } ist auf { $free } gefallen (Grenze { $limit }); halte Downloads an
+daemon-space-back = Auf { $host } sind wieder { $free } frei; { $count ->
+        [one] 1 Download
+       *[other] { $count } Downloads
+    } fortgesetzt
+daemon-guard-failed = Die [guard]-Prüfung ist fehlgeschlagen ({ $reason }); halte die Torrents auf { $host } an
+daemon-guard-passes = Die [guard]-Prüfung gelingt wieder; { $count ->
+        [one] 1 Torrent
+       *[other] { $count } Torrents
+    } auf { $host } fortgesetzt
+daemon-hook-ran = on-complete-Befehl für { $name } ausgeführt
+daemon-pruned = aufgeräumt  { $name } ({ $id }): { $reason }
+daemon-guard-error = konnte die Torrents für die [guard]-Prüfung nicht anhalten oder fortsetzen: { $reason }
+daemon-space-error = konnte den freien Platz nicht prüfen: { $reason }
+daemon-check-error = konnte die Torrents auf dem Server nicht prüfen: { $reason }
+daemon-watch-error = { $path }: { $reason }; neuer Versuch in { $every }
+daemon-prune-error = konnte nicht aufräumen: { $reason }; neuer Versuch in { $every }
+daemon-schedule-error = konnte die alternativen Geschwindigkeitsgrenzen nicht umschalten: { $reason }; neuer Versuch in { $every }
+daemon-reload-broken = die Konfiguration hat sich geändert, lässt sich aber nicht laden; der Daemon macht weiter wie bisher: { $reason }
+daemon-reloaded-nothing = Konfiguration neu geladen: nichts geändert, was der Daemon nutzt
+daemon-reloaded = Konfiguration neu geladen: { $sections } geändert
+daemon-restart-needed = Änderungen an { $sections } gelten erst nach einem Neustart des Daemons
+daemon-held = Angehalten, während ein anderer rbit-Befehl Torrents auf { $host } ändert
+daemon-resuming = Geht weiter
+daemon-stopped = Beobachtung beendet
+
+## rbit stats
+
+on = an
+off = aus
+yes = ja
+no = nein
+stats-connection = Verbindung
+stats-dht-nodes = DHT-Knoten
+stats-peers = Peers
+stats-download = Download
+stats-upload = Upload
+stats-limited = { $rate } (Limit { $limit })
+stats-alt-limits = Alt. Limits
+stats-session-data = Diese Sitzung
+stats-all-time-data = Insgesamt
+stats-down-up = { $down } herunter, { $up } hoch
+stats-global-ratio = Gesamtverhältnis
+stats-free-space = Freier Platz
+stats-free-on = { $size } (auf { $path })
+stats-recorded = { $count } Torrents um { $time } in { $path } aufgezeichnet
+stats-since = Seit { $time } ({ $elapsed }, { $count } Aufzeichnungen)
+stats-downloaded = Heruntergeladen
+stats-uploaded = Hochgeladen
+stats-average = { $size } (im Schnitt { $rate })
+stats-peak-download = Spitze Download
+stats-peak-upload = Spitze Upload
+stats-peak = { $rate } um { $time }
+stats-most-uploaded = Am meisten hochgeladen
+
+## rbit move, rename, recheck, queue, sequential und first-last
+
+move-done = { $name } ({ $id }) nach { $path } verschoben
+rename-done = { $name } ({ $id }) in { $new } umbenannt
+rename-file-done = { $old } in { $name } ({ $id }) in { $new } umbenannt
+recheck-doing = Wird geprüft:
+recheck-done = { $done } von { $total } Torrents werden geprüft
+queue-top = An den Anfang der Warteschlange:
+queue-bottom = Ans Ende der Warteschlange:
+queue-up = In der Warteschlange nach vorn:
+queue-down = In der Warteschlange nach hinten:
+queue-disabled = (ist die Warteschlange in den Einstellungen von qBittorrent eingeschaltet?)
+switch-sequential = Sequenzieller Download
+switch-first-last = Vorrang für erstes und letztes Stück
+switch-already = { $what } ist für { $name } ({ $id }) schon { $state }
+switch-done = { $what } für { $name } ({ $id }) { $state }geschaltet
+
+## rbit pieces
+
+pieces-legend-cells = Jede Zelle steht für { $count } Stücke: █ heruntergeladen, ▒ teilweise, ▓ wird geladen, · fehlt
+pieces-legend = █ heruntergeladen, ▓ wird geladen, · fehlt
+pieces-counts = { $count } Stücke: { $done } heruntergeladen ({ $share } %), { $downloading } werden geladen, { $missing } fehlen
+pieces-availability = Verfügbarkeit: { $availability }
+pieces-complete = vollständig
+pieces-unknown = unbekannt, solange der Torrent angehalten ist oder wartet
+pieces-short = { $copies } Kopien, { $share } % der Stücke; der Rest ist bei keinem verbundenen Peer, daher kann der Torrent erst fertig werden, wenn einer ihn hat
+pieces-available = { $copies } Kopien, 100 % der Stücke
+pieces-not-reported = vom Server nicht gemeldet
+pieces-no-metadata = { $name } ({ $id }) hat noch keine Metadaten, daher sind seine Stücke unbekannt
+
+## rbit self-update
+
+self-update-current = rbit { $version } ist aktuell (neueste Version: { $latest })
+self-update-available = rbit { $latest } ist verfügbar (dies ist { $version }); `rbit self-update` installiert es
+self-update-downloading = Lade { $name } { $latest } herunter
+self-update-done = { $path } von { $version } auf { $latest } aktualisiert
+
+## rbit history und timeline
+
+history-none = Noch kein Verlauf für { $id }; Aufzeichnungen machen `rbit stats record`, `rbit list` und `rbit daemon`
+history-title = { $name } ({ $id }), { $count } Schnappschüsse seit { $time }:
+history-over = In { $elapsed }: heruntergeladen { $down }, hochgeladen { $up }, Verhältnis { $old } -> { $new }
+timeline-title = { $name } ({ $id }), { $count } Aufzeichnungen:
+timeline-from-to = { $from } bis { $to }
+timeline-downloaded = { $size } heruntergeladen zwischen { $from } und { $to }
+timeline-nothing-downloaded = In diesen Aufzeichnungen wurde nichts heruntergeladen
+
+## rbit log
+
+log-empty = Das Log hat keine Einträge
+log-empty-at-level = Das Log hat keine Einträge auf dieser Stufe
+
+## rbit rss
+
+rss-no-feeds = Keine RSS-Feeds
+rss-feed-added = Feed { $path } hinzugefügt
+rss-removed = { $path } entfernt
+rss-refreshing = { $path } wird aktualisiert
+rss-refreshing-all = { $count } Einträge der obersten Ebene werden aktualisiert
+rss-no-articles = Keine Artikel
+rss-folder-created = Ordner { $path } angelegt
+rss-already-there = { $name } ist schon dort
+rss-moved = { $name } nach { $destination } verschoben
+rss-rules-written = { $count } Regeln nach { $path } geschrieben
+rss-import-skipped = übersp. { $name }: schon auf dem Server (--overwrite ersetzt sie)
+rss-import-ok = ok      { $name }
+rss-imported = { $done } von { $total } Regeln importiert, { $skipped } übersprungen
+rss-no-rules = Keine RSS-Regeln
+rss-rule = Regel
+rss-rule-enabled = Aktiv
+rss-rule-must-contain = Muss enthalten
+rss-rule-must-not-contain = Darf nicht enthalten
+rss-rule-episode-filter = Episodenfilter
+rss-rule-smart-filter = Intelligenter Filter
+rss-rule-category = Kategorie
+rss-rule-save-path = Speicherpfad
+rss-rule-last-match = Letzter Treffer
+rss-rule-feeds = Feeds
+rss-not-subscribed = nicht abonniert
+rss-rule-created = Regel { $name } angelegt
+rss-rule-updated = Regel { $name } geändert
+rss-rule-removed = Regel { $name } entfernt
+rss-no-config-rules = Keine [rss.rules] in der Konfiguration
+
+## rbit port
+
+port-already = schon { $port }
+port-listening = Port
+port-server-reports = Der Server meldet
+port-connected = { $state }, Peers haben sich mit dem Port verbunden
+port-firewalled = { $state }, noch hat sich kein Peer mit dem Port verbunden
+port-disconnected = { $state }, der Server hat keine Netzwerkverbindung
+port-direct-dial = Direkter Verbindungsversuch
+port-open = offen
+port-closed = geschlossen
+port-dial-open = { $state } unter { $address }
+port-dial-open-local = { $state } unter { $address } (nur von diesem Rechner oder Netz aus, was nichts über das Internet sagt)
+port-from-internet = Aus dem Internet
+port-no-check-url = nicht geprüft; [port] check_url auf einen Port-Prüfdienst setzen
+port-no-external-address = nicht geprüft; der Server meldet seine externe Adresse nicht (qBittorrent ab 4.6 tut das)
+port-not-checked = nicht geprüft; { $reason }
+port-unclear = unklar, der Dienst antwortete: { $reason }
+port-looks-closed = Port { $port } scheint geschlossen: am Router an den Server weiterleiten (oder UPnP/NAT-PMP einschalten) und in der Firewall freigeben, oder mit `rbit port random` auf einen anderen Port wechseln
+port-local-only = Der Port antwortet im lokalen Netz; ob er aus dem Internet weitergeleitet wird, ist noch unbekannt
+
+## rbit limit und alt
+
+limit-download = Download-Limit
+limit-upload = Upload-Limit
+limit-download-set = Globales Download-Limit auf { $rate } gesetzt
+limit-upload-set = Globales Upload-Limit auf { $rate } gesetzt
+limit-alt-state = Alternative Geschwindigkeitslimits sind { $state }
+limit-alt-already = Alternative Geschwindigkeitslimits sind schon { $state }
+limit-alt-turned = Alternative Geschwindigkeitslimits { $state }geschaltet
+limit-download-rate = Download { $rate }
+limit-upload-rate = Upload { $rate }
+limit-torrent-set = { $name } ({ $id }) begrenzt: { $what }
+limit-share-set = Teilungslimits für { $name } ({ $id }): Verhältnis { $ratio }, Seed-Zeit { $seeding }, inaktive Seed-Zeit { $inactive }
+
+## rbit inspect
+
+inspect-name = Name
+inspect-info-hash = Info-Hash
+inspect-total-size = Gesamtgröße
+inspect-bytes = { $size } ({ $count } Bytes)
+inspect-piece-size = Stückgröße
+inspect-pieces = { $size } ({ $count } Stücke)
+inspect-private = Privat
+inspect-comment = Kommentar
+inspect-trackers = Tracker ({ $count }):
+inspect-files = Dateien ({ $count }):
+
+## rbit config
+
+config-written = Konfiguration nach { $path } geschrieben
+config-skipped = { $what } übersprungen: schon konfiguriert (--overwrite ersetzt es)
+config-import-password = Passwort für { $user }@{ $host } (leer zum Überspringen)
+config-imported = { $count } Profil(e) nach { $path } importiert
+config-ask-host = Adresse der Web-UI
+config-ask-user = Benutzername (leer, wenn die Web-UI keine Anmeldung braucht)
+config-ask-password = Passwort (leer, um bei jedem Aufruf gefragt zu werden)
+config-ask-keyring = Im Schlüsselbund des Systems speichern? [J/n]
+config-ask-plaintext = Im Klartext in der Konfigurationsdatei speichern? [j/N]
+config-init-writing = { $path } wird geschrieben; Enter übernimmt einen [Standardwert].
+config-init-default-server = Standardserver ([qbittorrent]):
+config-ask-save-path = Standard-Speicherpfad für hinzugefügte Torrents (leer für das aktuelle Verzeichnis)
+config-ask-profile = Name eines weiteren Serverprofils (leer zum Beenden)
+config-profile-exists = '{ $profile }' ist schon konfiguriert
+config-init-done = { $path } geschrieben. Mit `rbit config show` und `rbit status --all-profiles` prüfen.
+config-files = Konfigurationsdateien
+config-files-none = keine gefunden (gesucht: { $path })
+config-count-profiles = { $count } Serverprofile
+config-count-searches = { $count } gespeicherte Suchen
+config-count-rss-rules = { $count } RSS-Regeln
+config-count-categories = { $count } Kategorien
+config-count-retention = { $count } Aufbewahrungsregeln
+config-count-schedule = { $count } Zeitfenster
+config-counts = { $what }; `rbit config export` gibt die ganze zusammengeführte Konfiguration aus
+
+## rbit peers
+
+peers-peers = Peers
+peers-connected = { $count } verbunden ({ $seeds } Seeds, { $leechers } Leecher){ $what }
+peers-swarm = {" "}von { $total } im Schwarm ({ $seeds } Seeds, { $leechers } Leecher)
+peers-transfer = Übertragung
+peers-transferring = Download von { $downloading }, Upload zu { $uploading }; { $down } herunter, { $up } hoch
+peers-encryption = Verschlüsselung
+peers-encrypted = { $count } von { $total } verschlüsselt ({ $percent } %)
+peers-connection = Verbindung
+peers-clients = Clients
+peers-countries = Länder
+peers-more = +{ $count } weitere
+peers-banned = { $addresses } gesperrt
+peers-none = Keine Peers mit { $name } ({ $id }) verbunden
+
+## rbit search
+
+search-saved = Suche '{ $name }' in { $path } gespeichert
+search-no-results = Keine Ergebnisse für '{ $what }'
+search-not-remembered = die Ergebnisse für --add konnten nicht gespeichert werden: { $reason }
+search-summary = { $total } Ergebnisse, { $count } Releases, { $shown } angezeigt
+search-add-hint = Eins mit `rbit search --add <#>` hinzufügen
+search-adding = '{ $name }' wird hinzugefügt
+
+## rbit schedule
+
+schedule-windows = Die alternativen Geschwindigkeitslimits gelten (Ortszeit):
+schedule-now = Jetzt sollten sie { $state } sein ({ $why }); auf dem Server sind sie { $limit }
+schedule-apply-hint = `rbit schedule --apply` schaltet sie um, und `rbit daemon` folgt dem Zeitplan weiter
+
+## rbit webseeds
+
+webseeds-removed = { $count } Webseed(s) von { $name } ({ $id }) entfernt
+webseeds-added = { $count } Webseed(s) zu { $name } ({ $id }) hinzugefügt
+webseeds-none = { $name } ({ $id }) hat keine Webseeds
+webseeds-list = Webseeds von { $name } ({ $id }):
+
+## rbit reannounce
+
+reannounce-too-recent = rbit hat ihn vor { $age } announcen lassen
+reannounce-tracker-wait = der Tracker nimmt das nächste Announce in { $limit } an
+reannounce-skipping = { $name } ({ $id }) übersprungen: { $why }
+reannounce-doing = { $name } ({ $id }) wird neu announct
+reannounce-done = { $count } Torrent(s) bei { $tracker } Tracker(n) neu announct
+reannounce-skipped = ; { $skipped } übersprungen, die zu kurz zuvor announct haben (--force fragt trotzdem)
+
+## rbit trackers
+
+trackers-replaced = { $old } durch { $new } ersetzt bei { $name } ({ $id })
+trackers-removed = { $count } Tracker von { $name } ({ $id }) entfernt
+trackers-added = { $count } Tracker zu { $name } ({ $id }) hinzugefügt
+
+## rbit note
+
+note-saved = Notiz zu { $name } ({ $id }) gespeichert
+note-noted = { $name } ({ $id }), notiert { $time }:
+note-from-tag = { $name } ({ $id }), aus seinem Tag:
+note-none = { $name } ({ $id }) hat keine Notiz
+note-removed = Notiz zu { $name } ({ $id }) entfernt
+note-no-local = { $name } ({ $id }) hatte keine lokale Notiz
+note-nothing = Kein Torrent auf dem Server hat eine Notiz
+
+## rbit forecast
+
+forecast-measuring = Download-Geschwindigkeiten werden { $limit } lang gemessen...
+forecast-complete = Dieser Torrent ist vollständig
+forecast-nothing = Nichts wird heruntergeladen
+forecast-queueing = Warteschlange: { $count } Downloads gleichzeitig; ein wartender Torrent übernimmt die Geschwindigkeit dessen, dessen Platz er bekommt
+forecast-all-done = Alle { $count } Downloads fertig in { $elapsed } ({ $time })
+forecast-never = { $failed } von { $total } Downloads werden bei diesen Geschwindigkeiten nicht fertig: angehalten, blockiert oder hinter blockierten eingereiht
+
+## rbit auth
+
+auth-username = Benutzername
+auth-stored = Passwort für '{ $profile }' im Schlüsselbund des Systems gespeichert.
+auth-reference = In der Konfiguration statt eines Klartext-Passworts darauf verweisen:
+auth-removed = Schlüsselbund-Eintrag für '{ $profile }' entfernt.
+
+## rbit stream
+
+stream-largest = Die größte von { $count } Dateien wird abgespielt, { $name } ({ $size }); --file <index> wählt eine andere
+stream-queued = { $name } wartet in der Warteschlange und lädt, sobald ein Platz frei ist; `rbit queue top { $id }` zieht ihn vor
+stream-buffering = Puffern: { $percent } % ({ $elapsed })
+stream-playing = { $path } wird mit { $command } abgespielt
+
+## rbit report
+
+report-ratio = Teilungsverhältnisse
+report-trackers = Tracker
+report-storage = Speicher
+report-torrents = Torrents
+report-uploaded = Hochgeladen
+report-downloaded = Heruntergeladen
+report-overall-ratio = Gesamtverhältnis
+report-below-one = Unter 1,0
+report-total-size = Gesamtgröße
+report-free-space = Freier Speicher
+report-generated = Server `{ $host }`, erstellt { $time }.
+
+## rbit exporter
+
+exporter-grafana = Der Statistikverlauf wird als Grafana-JSON-Datenquelle unter http://{ $address }/ bereitgestellt
+exporter-prometheus = Der neueste Statistik-Schnappschuss wird als Prometheus-Metriken unter http://{ $address }/metrics bereitgestellt
+exporter-no-history = { $path } gibt es noch nicht; `rbit stats record` legt es an
+
+## rbit category
+
+category-none = Keine Kategorien
+category-no-config = Keine [categories] in der Konfiguration
+
+## rbit prefs
+
+prefs-already = schon { $what }
+prefs-unknown = der Server hat keine Einstellung '{ $name }'; qBittorrent ignoriert unbekannte Schlüssel
+prefs-nothing = Nichts zu ändern
+
+## rbit man
+
+man-written = { $count } Manpages nach { $path } geschrieben
+
+## rbit apply-rules
+
+apply-rules-none = Keine [[rules]] in der Konfiguration
+
+## rbit import
+
+import-done = { $done } von { $total } Torrents aus { $host } importiert ({ $skipped } schon hier)
+import-nothing = { $host } hat keine Torrents
+
+## rbit run-once
+
+run-once-forgot = '{ $name }' für { $id } vergessen; es läuft wieder
+run-once-not-run = '{ $name }' ist für { $id } noch nicht gelaufen
+run-once-done = '{ $name }' lief für { $id } schon am { $time }, übersprungen
+run-once-running = '{ $name }' läuft für { $id } schon (Prozess { $pid }, seit { $time }), übersprungen
+run-once-failed = '{ $name }' ist für { $id } fehlgeschlagen ({ $reason }); es läuft beim nächsten Mal wieder
+
+## rbit exists
+
+exists-found = auf dem Server als { $name }
+exists-missing = nicht auf dem Server
+
+## Pläne der sync-Befehle
+
+plan-created = angelegt
+plan-updated = geändert
+plan-removed = entfernt
+plan-applied = { $created } angelegt, { $updated } geändert, { $unchanged } unverändert, { $removed } entfernt
@@ -0,0 +1,659 @@
+# rbit's messages in English, the reference for translations. A message missing from a
+# translation falls back to the one here.
+
+## Errors
+
+error = Error:
+warning = warning:
+error-partial-failure = { $failed } of { $total } items failed
+error-interrupted = interrupted
+error-absent = { $missing } of { $total } torrents are not on the server
+torrent-incomplete = { $name } is not complete yet ({ $percent }%)
+
+## Warnings
+
+stale-lock = taking over a stale lock from process { $pid } ({ $path })
+data-not-moved = could not move rbit's data to { $path }: { $reason }
+desktop-notification-failed = desktop notification failed: { $reason }
+webhook-failed = webhook { $address } failed: { $reason }
+config-unknown-key = { $path }: unknown key `{ $name }` is ignored (--strict-config makes this an error)
+deluge-no-category = Deluge keeps categories in its Label plugin; the category is not set
+profile-skipped = skipping server profile '{ $profile }': { $reason }
+
+## Waiting for other rbit processes
+
+lock-waiting = waiting for { $holder } to finish…
+lock-holder-daemon = the rbit daemon's current pass
+
+## rbit status
+
+status-yes = yes ({ $connection })
+status-no = no: { $reason }
+status-unreachable = no server could be reached
+
+## rbit list
+
+filter-all = all
+filter-active = active
+filter-downloading = downloading
+filter-seeding = seeding
+list-hidden = { $hidden ->
+        [one] 1 more torrent not shown
+       *[other] { $hidden } more torrents not shown
+    }: listing { $filter } torrents (--all shows every one)
+list-summary = { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }: { $groups }
+list-summary-group = { $count } { $group }
+list-stale = stale data from { $age } ago, saved the last time the server could be reached
+list-no-snapshot = no listing of { $host } saved yet; `rbit list` saves one whenever it reaches the server
+state-downloading = downloading
+state-seeding = seeding
+state-stalled = stalled
+state-paused = paused
+state-queued = queued
+state-checking = checking
+state-error = error
+state-other = other
+
+## rbit export
+
+export-saved = saved   { $path }
+export-skipped = skip    { $name }: already exported
+export-failed = FAILED  { $name } ({ $id }): { $reason }
+export-summary = Exported { $saved } of { $total } to { $dir }, { $skipped } already there
+
+## rbit verify
+
+verify-unreachable = { $path } is not reachable from this machine; verify reads the data where the server keeps it, at the path_map of the profile, or under --data-root where that is mounted
+verify-no-data-root = { $path } is not a folder; --data-root names where the server's { $save_path } is mounted on this machine
+verify-other-torrent = { $path } is the .torrent file of another torrent than { $name }
+verify-missing = missing
+verify-size = { $size } on disk, expected { $expected }
+verify-bad-pieces = { $count ->
+        [one] 1 bad piece
+       *[other] { $count } bad pieces
+    }
+verify-file-failed = FAILED  { $path }: { $reason }
+verify-files-missing = { $failed } of { $total } files are missing or have the wrong size
+verify-sizes-ok = All { $total } files of { $name } are present with the right sizes (--deep checks their contents)
+verify-progress = Verifying
+verify-summary = { $name }: { $ok } of { $total } pieces match, { $size } read in { $elapsed }
+verify-unchecked = , { $unchecked } not checked (files not downloaded)
+verify-bad-list = Pieces that do not match: { $pieces }
+verify-bad = { $bad } of { $total } pieces do not match; `rbit recheck { $id }` makes qBittorrent check and download them again
+verify-wrong-size = { $failed } of { $total } files do not have the right size
+
+## rbit cross-seed
+
+cross-seed-exists = { $name } is already cross-seeded to { $tracker } ({ $id })
+cross-seed-added = Cross-seeding { $name } to { $tracker } as { $id }, from { $path }
+
+## rbit doctor
+
+doctor-config = profile { $profile }, { $host }, config from { $files }
+doctor-no-config = profile { $profile }, { $host }, no config file (defaults and flags only)
+doctor-proxy = going through a proxy, so the host is looked up and connected to there
+doctor-dns = { $host } resolves to { $addresses }
+doctor-dns-failed = { $host } does not resolve; check the host name in --host or the config
+doctor-tcp = connected to { $host }:{ $port } in { $ms } ms
+doctor-tcp-failed = cannot connect to { $host }:{ $port } ({ $reason }); is the Web UI enabled and listening on that port?
+doctor-tcp-timeout = no answer from { $host }:{ $port } in { $seconds }s; a firewall may be dropping the connection
+doctor-login = logged in as { $user }
+doctor-anonymous = accepted without a username
+doctor-torrents = { $count ->
+        [one] can read the torrent list (1 torrent)
+       *[other] can read the torrent list ({ $count } torrents)
+    }
+doctor-ok = Everything works.
+
+## Confirmations
+
+confirm-prompt = { $question } [y/N]
+# what an answer starts with to say yes; "y" always does
+confirm-yes = y
+confirm-refused = not going to { $what } without confirmation: pass --yes, or set no_confirm = true in the config
+nothing-done = Nothing done
+nothing-deleted = Nothing deleted
+delete-what = delete { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }
+
+## rbit pause and resume
+
+pause-confirm = Pause { $count } torrents on { $host }?
+pause-what = pause { $count } torrents
+pause-done = Paused
+resume-done = Resumed
+bulk-done = { $what } { $name } ({ $id })
+bulk-all = { $what } all { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }
+
+## rbit prune
+
+prune-none = No torrents to prune
+prune-confirm = Delete these { $count ->
+        [one] 1 torrent? Its data stays on disk.
+       *[other] { $count } torrents? Their data stays on disk.
+    }
+prune-confirm-data = Delete these { $count ->
+        [one] 1 torrent and its data?
+       *[other] { $count } torrents and their data?
+    }
+prune-deleting = Deleting
+prune-deleted = Deleted { $done } of { $total } torrents
+prune-deleted-data = Deleted { $done } of { $total } torrents with their data
+
+## rbit cleanup
+
+cleanup-no-policies = No [[retention]] policies in the config
+cleanup-running = another rbit cleanup is running against { $host }
+cleanup-none = No torrents carry a tag with a retention policy
+cleanup-confirm = Delete { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }, { $with_files } with their files?
+cleanup-deleted = Deleted { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    } ({ $with_files } with their files)
+
+## rbit shutdown
+
+shutdown-confirm = Shut down qBittorrent { $version } at { $host }? Its torrents stop until it is started again.
+shutdown-what = shut the server down
+shutdown-done = qBittorrent at { $host } is shutting down
+
+## rbit add
+
+add-unchecked = { $path } is not a torrent rbit can read ({ $why }); uploading it without checking whether the server has it
+add-done = Added to qBittorrent (destination: { $destination })
+add-done-to = Added { $name } to { $client } (destination: { $destination })
+add-duplicate = Already on the server as { $existing }, skipped (--force adds it anyway)
+add-summary = Added { $done } of { $total }, { $skipped } already present (destination: { $destination })
+add-force-hint = Use --force to add the skipped torrents anyway
+add-nothing = Nothing to add
+add-automatic = automatic (category save path)
+add-server-default = the server's default save path
+add-unless-rule = { $destination } unless a rule gives another
+add-delete-failed = could not delete { $path }: { $reason }
+add-deleted-sources = Deleted { $count ->
+        [one] 1 source file
+       *[other] { $count } source files
+    }
+add-cannot-follow = cannot follow { $name }: its info-hash is not known
+add-no-space = adding needs { $needed } but the server has { $free } free (--ignore-space adds them anyway)
+add-no-space-anyway = adding needs { $needed } but the server has { $free } free; adding anyway (--ignore-space)
+login-no-password = username '{ $user }' has no password (use --password or { $variable }); skipping login
+login-password-prompt = Password for { $user }@{ $host }
+
+## rbit add --follow
+
+follow-fetching = { $name } (fetching metadata)
+follow-absent = not on the server
+follow-absent-failed = FAILED  { $id }: did not show up on the server
+follow-complete = complete
+follow-completed = Complete { $name } ({ $size })
+follow-failed = FAILED  { $name }: qBittorrent reports { $state }
+follow-hook-failed = FAILED  { $reason }
+follow-stopped = stopped following
+follow-interrupted = Stopped following; the torrents keep downloading on the server
+follow-incomplete = the torrent did not complete
+
+## rbit wait
+
+wait-stopped = stopped waiting
+wait-interrupted = Stopped waiting; the torrents keep downloading on the server
+wait-removed = { $name } was removed from the server while waiting
+wait-failed = { $name } stopped: qBittorrent reports { $state }
+wait-hook-failed = the on-complete hook failed for { $failed } of { $total } torrents
+wait-gave-up = gave up
+wait-timeout = gave up after { $limit }; still waiting for { $what }
+
+## rbit tui
+
+tui-needs-terminal = rbit tui needs a terminal to draw on
+tui-title = { $host }: { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }
+tui-title-filtered = { $host }: { $count } of { $total } torrents
+tui-marked = { $count } marked
+tui-keys = / filter  space mark  v range  * all  esc clear  p pause  r resume  d delete  c category  a add  q quit
+tui-category-prompt = Category (empty for none):
+tui-confirm-pause = Pause { $count ->
+        [one] 1 torrent?
+       *[other] { $count } torrents?
+    }
+tui-confirm-resume = Resume { $count ->
+        [one] 1 torrent?
+       *[other] { $count } torrents?
+    }
+tui-confirm-category = Put { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    } in category { $name }?
+tui-confirm-no-category = Take { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    } out of their category?
+tui-more = … and { $count } more
+tui-answer = y: yes   n: no
+tui-answer-delete = y: yes   n: no   f: { $with_files ->
+        [0] delete the data too
+       *[other] keep the data
+    }
+tui-deleted = Deleted { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }
+tui-deleted-data = Deleted { $count ->
+        [one] 1 torrent and its data
+       *[other] { $count } torrents and their data
+    }
+tui-done = { $what } { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    }
+tui-category-done = { $count ->
+        [one] 1 torrent is
+       *[other] { $count } torrents are
+    } in category { $name } now
+tui-add-title = Add a torrent
+tui-add-link = Magnet, URL or file
+tui-add-destination = Destination
+tui-add-server-default = (the server's default)
+tui-add-category = Category
+tui-add-paused = Paused
+tui-add-keys = Tab next field   space switch   Enter add   Esc cancel
+tui-added = Added; it shows up in the list once the server has it
+tui-duplicate = Already on the server as { $existing }
+
+## rbit daemon
+
+daemon-added = added   { $name } (destination: { $destination })
+daemon-duplicate = skip    { $name }: already on the server as { $existing }
+daemon-add-failed = FAILED  { $name }: { $reason }
+daemon-nothing-to-do = nothing to watch: pass --watch-dir or add [[watch]] tables (or a [prune] policy, [[schedule]] windows, a [disk] limit or a [guard] check) to the config
+daemon-no-watch-dir = watch folder { $path } does not exist
+daemon-no-disk-path = [disk] paths: { $path } does not exist
+daemon-watching = Watching { $dirs } for .torrent and .magnet files every { $every }
+daemon-pruning = Pruning by the [prune] policy every { $every }
+daemon-scheduling = Following the [[schedule]] for the alternative speed limits
+daemon-disk-pause = Pausing downloads when free space drops below { $limit }
+daemon-disk-notify = Notifying when free space drops below { $limit }
+daemon-guarding = Pausing every torrent while `{ $command }` fails
+daemon-serving = Serving the daemon's state on http://{ $address }/status, /metrics and /events
+daemon-serving-stopped = the status endpoint stopped: { $reason }
+daemon-running = another rbit daemon is already watching { $host }
+daemon-paused-torrent = paused  { $name } { $why }
+daemon-while-low-space = while space is low
+daemon-while-guard = while the [guard] check fails
+daemon-low-space = Free space on { $host } is down to { $free } (limit { $limit })
+daemon-low-space-pausing = Free space on { $host } is down to { $free } (limit { $limit }); pausing downloads
+daemon-space-back = Free space on { $host } is back to { $free }; resumed { $count ->
+        [one] 1 download
+       *[other] { $count } downloads
+    }
+daemon-guard-failed = The [guard] check failed ({ $reason }); pausing the torrents on { $host }
+daemon-guard-passes = The [guard] check passes again; resumed { $count ->
+        [one] 1 torrent
+       *[other] { $count } torrents
+    } on { $host }
+daemon-hook-ran = ran the on-complete hook for { $name }
+daemon-pruned = pruned  { $name } ({ $id }): { $reason }
+daemon-guard-error = could not pause or resume the torrents for the [guard] check: { $reason }
+daemon-space-error = could not check the free space: { $reason }
+daemon-check-error = could not check the torrents on the server: { $reason }
+daemon-watch-error = { $path }: { $reason }; trying again in { $every }
+daemon-prune-error = could not prune: { $reason }; trying again in { $every }
+daemon-schedule-error = could not switch the alternative speed limits: { $reason }; trying again in { $every }
+daemon-reload-broken = the config changed but does not load, so the daemon goes on as before: { $reason }
+daemon-reloaded-nothing = Reloaded the config: nothing the daemon uses changed
+daemon-reloaded = Reloaded the config: { $sections } changed
+daemon-restart-needed = Changes to { $sections } take effect when the daemon is restarted
+daemon-held = Paused while another rbit command changes torrents on { $host }
+daemon-resuming = Resuming
+daemon-stopped = Stopped watching
+
+## rbit stats
+
+on = on
+off = off
+yes = yes
+no = no
+stats-connection = Connection
+stats-dht-nodes = DHT nodes
+stats-peers = Peers
+stats-download = Download
+stats-upload = Upload
+stats-limited = { $rate } (limit { $limit })
+stats-alt-limits = Alt. limits
+stats-session-data = Session data
+stats-all-time-data = All-time data
+stats-down-up = { $down } down, { $up } up
+stats-global-ratio = Global ratio
+stats-free-space = Free space
+stats-free-on = { $size } (on { $path })
+stats-recorded = Recorded { $count } torrents at { $time } to { $path }
+stats-since = Since { $time } ({ $elapsed }, { $count } snapshots)
+stats-downloaded = Downloaded
+stats-uploaded = Uploaded
+stats-average = { $size } (avg { $rate })
+stats-peak-download = Peak download
+stats-peak-upload = Peak upload
+stats-peak = { $rate } at { $time }
+stats-most-uploaded = Most uploaded
+
+## rbit move, rename, recheck, queue, sequential and first-last
+
+move-done = Moved { $name } ({ $id }) to { $path }
+rename-done = Renamed { $name } ({ $id }) to { $new }
+rename-file-done = Renamed { $old } to { $new } in { $name } ({ $id })
+recheck-doing = Rechecking
+recheck-done = Rechecking { $done } of { $total } torrents
+queue-top = Moved to the top of the queue:
+queue-bottom = Moved to the bottom of the queue:
+queue-up = Moved up in the queue:
+queue-down = Moved down in the queue:
+queue-disabled = (is torrent queueing enabled in qBittorrent's settings?)
+switch-sequential = Sequential download
+switch-first-last = First/last piece priority
+switch-already = { $what } is already { $state } for { $name } ({ $id })
+switch-done = { $what } turned { $state } for { $name } ({ $id })
+
+## rbit pieces
+
+pieces-legend-cells = Each cell is { $count } pieces: █ downloaded, ▒ partly, ▓ downloading, · missing
+pieces-legend = █ downloaded, ▓ downloading, · missing
+pieces-counts = { $count } pieces: { $done } downloaded ({ $share }%), { $downloading } downloading, { $missing } missing
+pieces-availability = Availability: { $availability }
+pieces-complete = complete
+pieces-unknown = unknown while the torrent is paused or queued
+pieces-short = { $copies } copies, { $share }% of the pieces; the rest are on no connected peer, so it cannot complete until one has them
+pieces-available = { $copies } copies, 100% of the pieces
+pieces-not-reported = not reported by the server
+pieces-no-metadata = { $name } ({ $id }) has no metadata yet, so its pieces are not known
+
+## rbit self-update
+
+self-update-current = rbit { $version } is up to date (latest release: { $latest })
+self-update-available = rbit { $latest } is available (this is { $version }); `rbit self-update` installs it
+self-update-downloading = Downloading { $name } { $latest }
+self-update-done = Updated { $path } from { $version } to { $latest }
+
+## rbit history and timeline
+
+history-none = No history for { $id } yet; snapshots are recorded by `rbit stats record`, `rbit list` and `rbit daemon`
+history-title = { $name } ({ $id }), { $count } snapshots since { $time }:
+history-over = Over { $elapsed }: downloaded { $down }, uploaded { $up }, ratio { $old } -> { $new }
+timeline-title = { $name } ({ $id }), { $count } snapshots:
+timeline-from-to = { $from } to { $to }
+timeline-downloaded = Downloaded { $size } between { $from } and { $to }
+timeline-nothing-downloaded = Nothing was downloaded over these snapshots
+
+## rbit log
+
+log-empty = The log has no entries
+log-empty-at-level = The log has no entries at this level
+
+## rbit rss
+
+rss-no-feeds = No RSS feeds
+rss-feed-added = Added feed { $path }
+rss-removed = Removed { $path }
+rss-refreshing = Refreshing { $path }
+rss-refreshing-all = Refreshing { $count } top-level items
+rss-no-articles = No articles
+rss-folder-created = Created folder { $path }
+rss-already-there = { $name } is already there
+rss-moved = Moved { $name } to { $destination }
+rss-rules-written = Wrote { $count } rules to { $path }
+rss-import-skipped = skip    { $name }: already on the server (use --overwrite to replace it)
+rss-import-ok = ok      { $name }
+rss-imported = Imported { $done } of { $total } rules, { $skipped } skipped
+rss-no-rules = No RSS rules
+rss-rule = Rule
+rss-rule-enabled = Enabled
+rss-rule-must-contain = Must contain
+rss-rule-must-not-contain = Must not contain
+rss-rule-episode-filter = Episode filter
+rss-rule-smart-filter = Smart filter
+rss-rule-category = Category
+rss-rule-save-path = Save path
+rss-rule-last-match = Last match
+rss-rule-feeds = Feeds
+rss-not-subscribed = not subscribed
+rss-rule-created = Created rule { $name }
+rss-rule-updated = Updated rule { $name }
+rss-rule-removed = Removed rule { $name }
+rss-no-config-rules = No [rss.rules] in the config
+
+## rbit port
+
+port-already = already { $port }
+port-listening = Listening port
+port-server-reports = Server reports
+port-connected = { $state }, peers have connected to the port
+port-firewalled = { $state }, no peer has connected to the port yet
+port-disconnected = { $state }, the server has no network connection
+port-direct-dial = Direct dial
+port-open = open
+port-closed = closed
+port-dial-open = { $state } at { $address }
+port-dial-open-local = { $state } at { $address } (from this machine or network only, which says nothing about the internet)
+port-from-internet = From the internet
+port-no-check-url = not checked; set [port] check_url to a port-check service
+port-no-external-address = not checked; the server does not report its external address (qBittorrent 4.6 or newer does)
+port-not-checked = not checked; { $reason }
+port-unclear = unclear, the service answered: { $reason }
+port-looks-closed = port { $port } looks closed: forward it to the server on the router (or turn on UPnP/NAT-PMP) and allow it through the firewall, or move to another port with `rbit port random`
+port-local-only = The port answers on the local network; whether it is forwarded from the internet is not known yet
+
+## rbit limit and alt
+
+limit-download = Download limit
+limit-upload = Upload limit
+limit-download-set = Global download limit set to { $rate }
+limit-upload-set = Global upload limit set to { $rate }
+limit-alt-state = Alternative speed limits are { $state }
+limit-alt-already = Alternative speed limits are already { $state }
+limit-alt-turned = Alternative speed limits turned { $state }
+limit-download-rate = download { $rate }
+limit-upload-rate = upload { $rate }
+limit-torrent-set = Limited { $name } ({ $id }): { $what }
+limit-share-set = Share limits for { $name } ({ $id }): ratio { $ratio }, seeding time { $seeding }, inactive seeding time { $inactive }
+
+## rbit inspect
+
+inspect-name = Name
+inspect-info-hash = Info hash
+inspect-total-size = Total size
+inspect-bytes = { $size } ({ $count } bytes)
+inspect-piece-size = Piece size
+inspect-pieces = { $size } ({ $count } pieces)
+inspect-private = Private
+inspect-comment = Comment
+inspect-trackers = Trackers ({ $count }):
+inspect-files = Files ({ $count }):
+
+## rbit config
+
+config-written = Wrote configuration to { $path }
+config-skipped = skipped { $what }: already configured (use --overwrite)
+config-import-password = Password for { $user }@{ $host } (empty to skip)
+config-imported = Imported { $count } profile(s) into { $path }
+config-ask-host = Web UI address
+config-ask-user = Username (empty if the Web UI needs no login)
+config-ask-password = Password (empty to be asked on every run)
+config-ask-keyring = Store it in the OS keyring? [Y/n]
+config-ask-plaintext = Save it in the config file in plaintext? [y/N]
+config-init-writing = Writing { $path }; press Enter to accept a [default].
+config-init-default-server = Default server ([qbittorrent]):
+config-ask-save-path = Default save path for added torrents (empty for the current directory)
+config-ask-profile = Name of another server profile (empty to finish)
+config-profile-exists = '{ $profile }' is already configured
+config-init-done = Wrote { $path }. Check it with `rbit config show` and `rbit status --all-profiles`.
+config-files = Config files
+config-files-none = none found (looked for { $path })
+config-count-profiles = { $count } server profiles
+config-count-searches = { $count } saved searches
+config-count-rss-rules = { $count } rss rules
+config-count-categories = { $count } categories
+config-count-retention = { $count } retention policies
+config-count-schedule = { $count } schedule windows
+config-counts = { $what }; `rbit config export` prints the whole merged configuration
+
+## rbit peers
+
+peers-peers = Peers
+peers-connected = { $count } connected ({ $seeds } seeds, { $leechers } leechers){ $what }
+peers-swarm = {" "}of { $total } in the swarm ({ $seeds } seeds, { $leechers } leechers)
+peers-transfer = Transfer
+peers-transferring = downloading from { $downloading }, uploading to { $uploading }; { $down } down, { $up } up
+peers-encryption = Encryption
+peers-encrypted = { $count } of { $total } encrypted ({ $percent }%)
+peers-connection = Connection
+peers-clients = Clients
+peers-countries = Countries
+peers-more = +{ $count } more
+peers-banned = Banned { $addresses }
+peers-none = No peers connected to { $name } ({ $id })
+
+## rbit search
+
+search-saved = Saved search '{ $name }' in { $path }
+search-no-results = No results for '{ $what }'
+search-not-remembered = could not remember the results for --add: { $reason }
+search-summary = { $total } results, { $count } releases, showing { $shown }
+search-add-hint = Add one with `rbit search --add <#>`
+search-adding = Adding '{ $name }'
+
+## rbit schedule
+
+schedule-windows = The alternative speed limits are on during (local time):
+schedule-now = Now they should be { $state } ({ $why }); the server has them { $limit }
+schedule-apply-hint = `rbit schedule --apply` switches them, and `rbit daemon` keeps following the schedule
+
+## rbit webseeds
+
+webseeds-removed = Removed { $count } web seed(s) from { $name } ({ $id })
+webseeds-added = Added { $count } web seed(s) to { $name } ({ $id })
+webseeds-none = { $name } ({ $id }) has no web seeds
+webseeds-list = Web seeds of { $name } ({ $id }):
+
+## rbit reannounce
+
+reannounce-too-recent = rbit made it announce { $age } ago
+reannounce-tracker-wait = the tracker accepts the next announce in { $limit }
+reannounce-skipping = Skipping { $name } ({ $id }): { $why }
+reannounce-doing = Reannouncing { $name } ({ $id })
+reannounce-done = Reannounced { $count } torrent(s) to { $tracker } tracker(s)
+reannounce-skipped = ; skipped { $skipped } that announced too recently (--force asks anyway)
+
+## rbit trackers
+
+trackers-replaced = Replaced { $old } with { $new } on { $name } ({ $id })
+trackers-removed = Removed { $count } tracker(s) from { $name } ({ $id })
+trackers-added = Added { $count } tracker(s) to { $name } ({ $id })
+
+## rbit note
+
+note-saved = Noted on { $name } ({ $id })
+note-noted = { $name } ({ $id }), noted { $time }:
+note-from-tag = { $name } ({ $id }), from its tag:
+note-none = { $name } ({ $id }) has no note
+note-removed = Removed the note on { $name } ({ $id })
+note-no-local = { $name } ({ $id }) had no local note
+note-nothing = No torrent on the server has a note
+
+## rbit forecast
+
+forecast-measuring = Measuring download speeds for { $limit }...
+forecast-complete = That torrent is complete
+forecast-nothing = Nothing is downloading
+forecast-queueing = Queueing: { $count } downloads at a time; a queued torrent takes over the speed of the one whose slot it gets
+forecast-all-done = All { $count } downloads done in { $elapsed } ({ $time })
+forecast-never = { $failed } of { $total } downloads will not finish at these speeds: paused, stalled, or queued behind stalled ones
+
+## rbit auth
+
+auth-username = Username
+auth-stored = Stored password for '{ $profile }' in the OS keyring.
+auth-reference = Reference it from the config instead of a plaintext password:
+auth-removed = Removed keyring entry for '{ $profile }'.
+
+## rbit stream
+
+stream-largest = Playing the largest of { $count } files, { $name } ({ $size }); pick another with --file <index>
+stream-queued = { $name } is queued and downloads once a slot is free; `rbit queue top { $id }` moves it up
+stream-buffering = Buffering: { $percent }% ({ $elapsed })
+stream-playing = Playing { $path } with { $command }
+
+## rbit report
+
+report-ratio = Share ratios
+report-trackers = Trackers
+report-storage = Storage
+report-torrents = Torrents
+report-uploaded = Uploaded
+report-downloaded = Downloaded
+report-overall-ratio = Overall ratio
+report-below-one = Below 1.0
+report-total-size = Total size
+report-free-space = Free space
+report-generated = Server `{ $host }`, generated { $time }.
+
+## rbit exporter
+
+exporter-grafana = Serving the stats history as a Grafana JSON datasource on http://{ $address }/
+exporter-prometheus = Serving the newest stats snapshot as Prometheus metrics on http://{ $address }/metrics
+exporter-no-history = { $path } does not exist yet; it is created by `rbit stats record`
+
+## rbit category
+
+category-none = No categories
+category-no-config = No [categories] in the config
+
+## rbit prefs
+
+prefs-already = already { $what }
+prefs-unknown = the server has no preference '{ $name }'; qBittorrent ignores unknown keys
+prefs-nothing = Nothing to change
+
+## rbit man
+
+man-written = Wrote { $count } man pages to { $path }
+
+## rbit apply-rules
+
+apply-rules-none = No [[rules]] in the config
+
+## rbit import
+
+import-done = Imported { $done } of { $total } torrents from { $host } ({ $skipped } already here)
+import-nothing = { $host } has no torrents
+
+## rbit run-once
+
+run-once-forgot = Forgot '{ $name }' for { $id }; it will run again
+run-once-not-run = '{ $name }' has not run for { $id }
+run-once-done = '{ $name }' already ran for { $id } at { $time }, skipped
+run-once-running = '{ $name }' is already running for { $id } (process { $pid }, since { $time }), skipped
+run-once-failed = '{ $name }' failed for { $id } ({ $reason }); it will run again next time
+
+## rbit exists
+
+exists-found = on the server as { $name }
+exists-missing = not on the server
+
+## Plans of the sync commands
+
+plan-created = created
+plan-updated = updated
+plan-removed = removed
+plan-applied = { $created } created, { $updated } updated, { $unchanged } unchanged, { $removed } removed
@@ -0,0 +1,150 @@
+//! Commands that act on many torrents with a request each (`add` with several inputs, `prune`,
+//! `recheck`): up to `session.concurrency` requests at once, a progress bar on stderr with a
+//! line per request in flight, and a table of what became of each item at the end. An item that
+//! fails does not stop the others; the command then exits with [`RbitError::PartialFailure`].
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use tabled::Tabled;
+
+use crate::api::Session;
+use crate::error::RbitError;
+use crate::format::truncate;
+use crate::theme;
+
+/// What became of an item that did not fail, with a word on it for the table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Done(String),
+    Skipped(String),
+}
+
+/// Run `op` for each of `items`, up to `session.concurrency` at once, showing `verb` and the
+/// `label` of each item in flight. The results are in the order of `items`.
+pub async fn run<'a, T, F, Fut, O>(session: &Session, verb: &str, items: &'a [T], label: impl Fn(&T) -> String, op: F) -> Vec<anyhow::Result<O>>
+where
+    F: Fn(&'a T) -> Fut,
+    Fut: Future<Output = anyhow::Result<O>>,
+{
+    // dry runs print the requests they would send, which the bars would draw over
+    let target = if session.dry_run { ProgressDrawTarget::hidden() } else { ProgressDrawTarget::stderr() };
+    let multi = MultiProgress::with_draw_target(target);
+    let overall = multi.add(ProgressBar::new(items.len() as u64));
+    overall.set_style(ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len}").expect("valid template").progress_chars("=> "));
+    overall.set_prefix(verb.to_string());
+    let spinner = ProgressStyle::with_template("  {spinner} {msg}").expect("valid template");
+
+    let mut results: Vec<Option<anyhow::Result<O>>> = items.iter().map(|_| None).collect();
+    let mut running = stream::iter(items.iter().enumerate())
+        .map(|(i, item)| {
+            let line = multi.insert_before(&overall, ProgressBar::new_spinner().with_style(spinner.clone()).with_message(truncate(&label(item), 60)));
+            line.enable_steady_tick(Duration::from_millis(120));
+            let done = op(item);
+            async move {
+                let result = done.await;
+                line.finish_and_clear();
+                (i, result)
+            }
+        })
+        .buffer_unordered(session.concurrency.max(1));
+    while let Some((i, result)) = running.next().await {
+        overall.inc(1);
+        results[i] = Some(result);
+    }
+    overall.finish_and_clear();
+    results.into_iter().map(|r| r.expect("every item gets a result")).collect()
+}
+
+#[derive(Tabled)]
+struct ReportRow {
+    item: String,
+    result: &'static str,
+    details: String,
+}
+
+/// What became of each item of a batch, in order.
+#[derive(Default)]
+pub struct Report {
+    rows: Vec<(String, anyhow::Result<Outcome>)>,
+}
+
+impl Report {
+    pub fn push(&mut self, label: String, result: anyhow::Result<Outcome>) {
+        self.rows.push((label, result));
+    }
+
+    fn count(&self, wanted: fn(&anyhow::Result<Outcome>) -> bool) -> usize {
+        self.rows.iter().filter(|(_, r)| wanted(r)).count()
+    }
+
+    pub fn done(&self) -> usize {
+        self.count(|r| matches!(r, Ok(Outcome::Done(_))))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|r| matches!(r, Ok(Outcome::Skipped(_))))
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(Result::is_err)
+    }
+
+    /// Print the table of items, failed ones with the reason.
+    pub fn print(&self) {
+        let rows = self.rows.iter().map(|(label, result)| {
+            let (result, details) = match result {
+                Ok(Outcome::Done(detail)) => ("ok", detail.clone()),
+                Ok(Outcome::Skipped(why)) => ("skipped", why.clone()),
+                Err(e) => ("FAILED", format!("{:#}", e)),
+            };
+            ReportRow { item: truncate(label, 60), result, details: truncate(&details, 80) }
+        });
+        println!("{}", theme::table(rows));
+    }
+
+    /// `Ok` when no item failed.
+    pub fn result(&self) -> anyhow::Result<()> {
+        match self.failed() {
+            0 => Ok(()),
+            failed => Err(RbitError::PartialFailure { failed, total: self.rows.len() }.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn runs_every_item_within_the_limit() {
+        let mut session = Session::new("http://127.0.0.1:1".to_string(), None, None, true).unwrap();
+        session.concurrency = 3;
+        let (running, most) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let items: Vec<u64> = (0..10).collect();
+        let results = run(&session, "Testing", &items, |i| i.to_string(), |&i| {
+            let (running, most) = (&running, &most);
+            async move {
+                most.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10 + (i * 7) % 20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if i % 4 == 1 { anyhow::bail!("item {} failed", i) } else { Ok(i * 2) }
+            }
+        })
+        .await;
+        assert!(most.load(Ordering::SeqCst) <= 3);
+        let values: Vec<Option<u64>> = results.iter().map(|r| r.as_ref().ok().copied()).collect();
+        assert_eq!(values, [Some(0), None, Some(4), Some(6), Some(8), None, Some(12), Some(14), Some(16), None]);
+
+        let mut report = Report::default();
+        for (i, r) in results.into_iter().enumerate() {
+            report.push(i.to_string(), r.map(|v| if v == 0 { Outcome::Skipped("zero".to_string()) } else { Outcome::Done(v.to_string()) }));
+        }
+        assert_eq!((report.done(), report.skipped(), report.failed()), (6, 1, 3));
+        assert!(report.result().is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! Small JSON files rbit keeps between invocations under `~/.cache/rbit`.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use directories::BaseDirs;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::api::TorrentInfo;
+
+/// `~/.cache/rbit` on Linux, `~/Library/Caches/rbit` on macOS and `%LOCALAPPDATA%\rbit\cache`
+/// on Windows, where the cache folder is shared with the application's data.
+pub fn cache_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|b| match cfg!(windows) {
+        true => b.cache_dir().join("rbit").join("cache"),
+        false => b.cache_dir().join("rbit"),
+    })
+}
+
+/// Turn a host URL into something safe to use in a file name.
+pub fn file_key(host: &str) -> String {
+    let key = host.split_once("://").map(|(_, rest)| rest).unwrap_or(host);
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect::<String>()
+        .trim_matches('_')
+        .to_string()
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn read<T: DeserializeOwned>(name: &str) -> Option<T> {
+    let path = cache_dir()?.join(name);
+    let data = fs::read(path).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Write a cache file readable only by the current user.
+pub fn write<T: Serialize>(name: &str, value: &T) -> anyhow::Result<()> {
+    let dir = cache_dir().ok_or_else(|| anyhow::anyhow!("no cache directory available"))?;
+    fs::create_dir_all(&dir)?;
+    write_private(&dir.join(name), &serde_json::to_vec_pretty(value)?)
+}
+
+/// Write `data` to `path` readable only by the current user, also when the file was there
+/// before with wider permissions. It is written next to its place and renamed over it, so
+/// another rbit process reading it at the same time sees the old or the new contents, never
+/// half of them.
+pub fn write_private(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        opts.mode(0o600);
+    }
+    let name = path.file_name().ok_or_else(|| anyhow::anyhow!("{} is not a file name", path.display()))?;
+    let tmp = path.with_file_name(format!("{}.{}.tmp", name.to_string_lossy(), std::process::id()));
+    let mut f = opts.open(&tmp).map_err(|e| anyhow::anyhow!("{}: {}", tmp.display(), e))?;
+    f.write_all(data)?;
+    drop(f);
+    fs::rename(&tmp, path).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    Ok(())
+}
+
+pub fn remove(name: &str) {
+    if let Some(dir) = cache_dir() {
+        let _ = fs::remove_file(dir.join(name));
+    }
+}
+
+/// qBittorrent `SID` cookie saved after a successful login.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SessionCookie {
+    pub host: String,
+    pub username: String,
+    pub sid: String,
+    pub saved_at: u64,
+}
+
+fn session_file(host: &str) -> String {
+    format!("session-{}.json", file_key(host))
+}
+
+pub fn load_session(host: &str, username: &str) -> Option<SessionCookie> {
+    read::<SessionCookie>(&session_file(host)).filter(|s| s.host == host && s.username == username)
+}
+
+pub fn save_session(host: &str, username: &str, sid: &str) -> anyhow::Result<()> {
+    let cookie = SessionCookie {
+        host: host.to_string(),
+        username: username.to_string(),
+        sid: sid.to_string(),
+        saved_at: now(),
+    };
+    write(&session_file(host), &cookie)
+}
+
+pub fn clear_session(host: &str) {
+    remove(&session_file(host));
+}
+
+/// The torrents the last `rbit list` showed for a server, in order, for `%N` references.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Listing {
+    pub host: String,
+    pub hashes: Vec<String>,
+    pub saved_at: u64,
+}
+
+fn listing_file(host: &str) -> String {
+    format!("listing-{}.json", file_key(host))
+}
+
+pub fn load_listing(host: &str) -> Option<Listing> {
+    read::<Listing>(&listing_file(host)).filter(|l| l.host == host)
+}
+
+pub fn save_listing(host: &str, hashes: Vec<String>) -> anyhow::Result<()> {
+    write(&listing_file(host), &Listing { host: host.to_string(), hashes, saved_at: now() })
+}
+
+/// The torrents a server last listed, all of them, for `rbit list --cached` and for when the
+/// server cannot be reached.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    pub host: String,
+    pub torrents: Vec<TorrentInfo>,
+    /// Whether the server had queueing on, for the queue column
+    #[serde(default)]
+    pub queueing: bool,
+    pub saved_at: u64,
+}
+
+fn snapshot_file(host: &str) -> String {
+    format!("torrents-{}.json", file_key(host))
+}
+
+pub fn load_snapshot(host: &str) -> Option<Snapshot> {
+    read::<Snapshot>(&snapshot_file(host)).filter(|s| s.host == host)
+}
+
+pub fn save_snapshot(host: &str, torrents: &[TorrentInfo], queueing: bool) -> anyhow::Result<()> {
+    write(&snapshot_file(host), &Snapshot { host: host.to_string(), torrents: torrents.to_vec(), queueing, saved_at: now() })
+}
+
+/// When rbit last made each torrent of a server announce, so `rbit reannounce` can keep to the
+/// trackers' minimum intervals across runs.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Announces {
+    pub host: String,
+    /// Unix time by lowercase info-hash
+    pub torrents: std::collections::BTreeMap<String, u64>,
+}
+
+fn announces_file(host: &str) -> String {
+    format!("announces-{}.json", file_key(host))
+}
+
+pub fn load_announces(host: &str) -> Announces {
+    read::<Announces>(&announces_file(host))
+        .filter(|a| a.host == host)
+        .unwrap_or_else(|| Announces { host: host.to_string(), ..Default::default() })
+}
+
+pub fn save_announces(announces: &Announces) -> anyhow::Result<()> {
+    write(&announces_file(&announces.host), announces)
+}
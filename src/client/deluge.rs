@@ -0,0 +1,262 @@
+//! Deluge's web UI JSON-RPC: `{method, params, id}` POSTed to `/json`. The web UI has a
+//! password of its own and proxies `core.*` calls to a daemon it must be connected to; rbit
+//! connects it to the first daemon it knows of when it is not.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use base64::Engine;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{connection_error, Exported, TorrentClient, INFINITE_RATIO};
+use crate::api::{AddOptions, ConnectOptions, TorrentInfo};
+use crate::error::RbitError;
+use crate::i18n::tr;
+
+/// The torrent fields rbit asks for
+const FIELDS: [&str; 20] = [
+    "name",
+    "state",
+    "progress",
+    "download_payload_rate",
+    "upload_payload_rate",
+    "total_wanted",
+    "total_size",
+    "all_time_download",
+    "total_uploaded",
+    "ratio",
+    "tracker_host",
+    "save_path",
+    "label",
+    "completed_time",
+    "time_added",
+    "eta",
+    "seeding_time",
+    "num_seeds",
+    "num_peers",
+    "queue",
+];
+
+pub struct Deluge {
+    client: Client,
+    host: String,
+    password: Option<String>,
+    dry_run: bool,
+    next_id: AtomicU64,
+}
+
+/// One torrent of `core.get_torrents_status`
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct Torrent {
+    name: String,
+    state: String,
+    /// Percent
+    progress: f64,
+    download_payload_rate: u64,
+    upload_payload_rate: u64,
+    total_wanted: u64,
+    total_size: u64,
+    all_time_download: u64,
+    total_uploaded: u64,
+    /// -1 for an infinite ratio
+    ratio: f64,
+    tracker_host: String,
+    save_path: String,
+    /// Set by the Label plugin
+    label: String,
+    completed_time: i64,
+    /// Unix time, with fractions
+    time_added: f64,
+    /// Seconds, 0 when it cannot be estimated
+    eta: f64,
+    seeding_time: i64,
+    num_seeds: i64,
+    num_peers: i64,
+    /// Position in the download queue, -1 when not queued
+    queue: i64,
+}
+
+/// qBittorrent's name for a Deluge state.
+fn state(t: &Torrent) -> &'static str {
+    let complete = t.progress >= 100.0;
+    match t.state.as_str() {
+        "Downloading" if t.download_payload_rate == 0 => "stalledDL",
+        "Downloading" => "downloading",
+        "Seeding" if t.upload_payload_rate == 0 => "stalledUP",
+        "Seeding" => "uploading",
+        "Paused" if complete => "pausedUP",
+        "Paused" => "pausedDL",
+        "Checking" if complete => "checkingUP",
+        "Checking" => "checkingDL",
+        "Queued" if complete => "queuedUP",
+        "Queued" => "queuedDL",
+        "Moving" => "moving",
+        "Allocating" => "allocating",
+        _ => "error",
+    }
+}
+
+fn torrent_info(hash: String, t: Torrent) -> TorrentInfo {
+    TorrentInfo {
+        state: state(&t).to_string(),
+        hash: hash.to_lowercase(),
+        name: t.name,
+        progress: Some(t.progress / 100.0),
+        dlspeed: Some(t.download_payload_rate),
+        upspeed: Some(t.upload_payload_rate),
+        size: Some(t.total_wanted),
+        total_size: Some(t.total_size),
+        downloaded: Some(t.all_time_download),
+        uploaded: Some(t.total_uploaded),
+        ratio: Some(if t.ratio == -1.0 { INFINITE_RATIO } else { t.ratio.max(0.0) }),
+        tracker: Some(t.tracker_host).filter(|h| !h.is_empty()),
+        save_path: Some(t.save_path),
+        category: Some(t.label).filter(|l| !l.is_empty()),
+        completion_on: Some(if t.completed_time > 0 { t.completed_time } else { -1 }),
+        added_on: Some(t.time_added as i64),
+        eta: Some(if t.eta > 0.0 { t.eta as i64 } else { -1 }),
+        seeding_time: Some(t.seeding_time),
+        num_seeds: Some(t.num_seeds),
+        num_leechs: Some(t.num_peers),
+        priority: Some(t.queue + 1),
+        ..Default::default()
+    }
+}
+
+impl Deluge {
+    pub fn new(host: String, password: Option<String>, connect: &ConnectOptions, dry_run: bool) -> anyhow::Result<Self> {
+        // the web UI keeps the login in a `_session_id` cookie
+        let client = connect.apply(Client::builder().cookie_store(true))?.build()?;
+        Ok(Deluge { client, host, password, dry_run, next_id: AtomicU64::new(1) })
+    }
+
+    fn url(&self) -> String {
+        format!("{}/json", self.host)
+    }
+
+    /// Call `method` and return its result.
+    async fn call(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        let body = json!({ "method": method, "params": params, "id": self.next_id.fetch_add(1, Ordering::Relaxed) });
+        let url = self.url();
+        let res = self.client.post(&url).json(&body).send().await.map_err(|e| connection_error("the Deluge web UI", &self.host, e))?;
+        let status = res.status();
+        let text = res.text().await?;
+        tracing::debug!("POST {} {} -> {}", url, method, status);
+        tracing::trace!("response: {}", text);
+        if !status.is_success() {
+            anyhow::bail!("{} failed with {}: {}", method, status, text);
+        }
+        let mut reply: Value = serde_json::from_str(&text)?;
+        match reply["error"]["message"].as_str() {
+            // code 1 is "Not authenticated"
+            Some(message) if reply["error"]["code"] == 1 => Err(RbitError::Auth(format!("{}: {}", method, message)).into()),
+            Some(message) => anyhow::bail!("{} failed: {}", method, message),
+            None => Ok(reply["result"].take()),
+        }
+    }
+
+    /// Call a method that changes server state; with `dry_run` it is only printed.
+    async fn change(&self, method: &str, params: Value) -> anyhow::Result<Value> {
+        if self.dry_run {
+            println!("[dry-run] POST {}", self.url());
+            println!("[dry-run] {} {}", method, params);
+            return Ok(Value::Null);
+        }
+        self.call(method, params).await
+    }
+}
+
+impl TorrentClient for Deluge {
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn username(&self) -> Option<&str> {
+        // the web UI has a password only
+        None
+    }
+
+    async fn login(&self) -> anyhow::Result<()> {
+        let password = self.password.as_deref().ok_or_else(|| RbitError::Auth("the Deluge web UI needs its password: pass --password".to_string()))?;
+        if !self.call("auth.login", json!([password])).await?.as_bool().unwrap_or(false) {
+            return Err(RbitError::Auth(format!("{} refused the password", self.host)).into());
+        }
+        if self.call("web.connected", json!([])).await?.as_bool().unwrap_or(false) {
+            return Ok(());
+        }
+        let hosts = self.call("web.get_hosts", json!([])).await?;
+        let id = hosts[0][0].as_str().ok_or_else(|| anyhow::anyhow!("the Deluge web UI at {} knows no daemon to connect to; add one in its connection manager", self.host))?;
+        tracing::debug!("connecting the web UI to daemon {}", id);
+        self.call("web.connect", json!([id])).await.map(drop)
+    }
+
+    async fn version(&self) -> anyhow::Result<String> {
+        let version = self.call("daemon.info", json!([])).await?;
+        Ok(format!("Deluge {}", version.as_str().unwrap_or("?")))
+    }
+
+    async fn torrents(&self) -> anyhow::Result<Vec<TorrentInfo>> {
+        let reply = self.call("core.get_torrents_status", json!([{}, FIELDS])).await?;
+        let torrents: BTreeMap<String, Torrent> = serde_json::from_value(reply)?;
+        Ok(torrents.into_iter().map(|(hash, t)| torrent_info(hash, t)).collect())
+    }
+
+    async fn add(&self, input: &str, options: &AddOptions) -> anyhow::Result<()> {
+        let mut settings = json!({ "add_paused": options.paused });
+        if let Some(ref path) = options.save_path {
+            settings["download_location"] = json!(path.to_string_lossy());
+        }
+        if options.category.is_some() {
+            eprintln!("{} {}", tr!("warning"), tr!("deluge-no-category"));
+        }
+        if input.starts_with("magnet:") {
+            self.change("core.add_torrent_magnet", json!([input, settings])).await?;
+        } else if crate::torrent::is_torrent_url(input) {
+            self.change("core.add_torrent_url", json!([input, settings])).await?;
+        } else {
+            let path = Path::new(input);
+            let data = std::fs::read(path).map_err(|e| anyhow::anyhow!("{}: {}", input, e))?;
+            let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("upload.torrent");
+            let data = base64::engine::general_purpose::STANDARD.encode(data);
+            self.change("core.add_torrent_file", json!([name, data, settings])).await?;
+        }
+        Ok(())
+    }
+
+    async fn reannounce(&self, hashes: &[&str]) -> anyhow::Result<()> {
+        self.change("core.force_reannounce", json!([hashes])).await.map(drop)
+    }
+
+    async fn recheck(&self, hashes: &[&str]) -> anyhow::Result<()> {
+        self.change("core.force_recheck", json!([hashes])).await.map(drop)
+    }
+
+    /// Deluge does not give out .torrent files, only magnet links (from 2.1 on).
+    async fn export(&self, t: &TorrentInfo) -> anyhow::Result<Exported> {
+        let link = self.call("core.get_magnet_uri", json!([t.hash])).await.map_err(|e| anyhow::anyhow!("{:#} (needs Deluge 2.1 or later)", e))?;
+        link.as_str().map(|l| Exported::Magnet(l.to_string())).ok_or_else(|| anyhow::anyhow!("Deluge gave no magnet link for {}", t.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_are_named_as_qbittorrent_does() {
+        let torrent = |state: &str, progress, rate| Torrent { state: state.to_string(), progress, download_payload_rate: rate, ..Default::default() };
+        assert_eq!(state(&torrent("Downloading", 40.0, 100)), "downloading");
+        assert_eq!(state(&torrent("Downloading", 40.0, 0)), "stalledDL");
+        assert_eq!(state(&torrent("Paused", 100.0, 0)), "pausedUP");
+        assert_eq!(state(&torrent("Queued", 10.0, 0)), "queuedDL");
+        assert_eq!(state(&torrent("Error", 10.0, 0)), "error");
+        let info = torrent_info("ABC".to_string(), torrent("Seeding", 100.0, 0));
+        assert_eq!((info.hash.as_str(), info.progress, info.tracker), ("abc", Some(1.0), None));
+        let info = torrent_info("abc".to_string(), Torrent { ratio: -1.0, ..torrent("Seeding", 100.0, 0) });
+        assert_eq!(info.ratio, Some(INFINITE_RATIO));
+    }
+}
@@ -0,0 +1,162 @@
+//! The torrent clients rbit can talk to. qBittorrent is the reference: every command works with
+//! it through [`Session`]. Transmission and Deluge implement [`TorrentClient`], the part of a
+//! client the basic commands need, and report their torrents as qBittorrent would so listings
+//! and filters work the same.
+
+pub mod deluge;
+pub mod transmission;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, AddOptions, Session, TorrentInfo};
+use crate::commands::export;
+use crate::commands::manage::short_id;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::select::Selector;
+use crate::torrent::magnet_uri;
+
+/// The share ratio qBittorrent reports at most, and so what stands for the infinite ratio of a
+/// torrent seeded without having been downloaded.
+pub const INFINITE_RATIO: f64 = 9999.0;
+
+/// `type` of a server profile
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Qbittorrent,
+    Transmission,
+    Deluge,
+}
+
+impl Backend {
+    pub fn is_default(&self) -> bool {
+        *self == Backend::Qbittorrent
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Qbittorrent => "qBittorrent",
+            Backend::Transmission => "Transmission",
+            Backend::Deluge => "Deluge",
+        }
+    }
+}
+
+/// A torrent as a client hands it out, for `rbit import`: its .torrent file, or a magnet link
+/// from clients that cannot give out the file.
+pub enum Exported {
+    File(Vec<u8>),
+    Magnet(String),
+}
+
+/// What rbit needs from a torrent client for `list`, `add`, `reannounce`, `recheck` and
+/// `import`.
+pub trait TorrentClient {
+    /// The server's address, which also keys rbit's caches
+    fn host(&self) -> &str;
+    /// Who rbit logs in as, if anyone
+    fn username(&self) -> Option<&str>;
+    async fn login(&self) -> anyhow::Result<()>;
+    /// The client's name and version, e.g. `Transmission 4.0.5`
+    async fn version(&self) -> anyhow::Result<String>;
+    /// Every torrent, with qBittorrent's state names
+    async fn torrents(&self) -> anyhow::Result<Vec<TorrentInfo>>;
+    /// Add a magnet link, a torrent URL or a local .torrent file. Of the options only the save
+    /// path, `paused` and the category are used.
+    async fn add(&self, input: &str, options: &AddOptions) -> anyhow::Result<()>;
+    async fn reannounce(&self, hashes: &[&str]) -> anyhow::Result<()>;
+    async fn recheck(&self, hashes: &[&str]) -> anyhow::Result<()>;
+    async fn export(&self, t: &TorrentInfo) -> anyhow::Result<Exported>;
+}
+
+impl TorrentClient for Session {
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    async fn login(&self) -> anyhow::Result<()> {
+        Session::login(self).await
+    }
+
+    async fn version(&self) -> anyhow::Result<String> {
+        let (version, api) = tokio::try_join!(self.get_text("app/version"), self.get_text("app/webapiVersion"))?;
+        Ok(format!("qBittorrent {} (Web API {})", version.trim(), api.trim()))
+    }
+
+    async fn torrents(&self) -> anyhow::Result<Vec<TorrentInfo>> {
+        Session::torrents(self, "all").await
+    }
+
+    async fn add(&self, input: &str, options: &AddOptions) -> anyhow::Result<()> {
+        Session::add(self, input, options).await
+    }
+
+    async fn reannounce(&self, hashes: &[&str]) -> anyhow::Result<()> {
+        self.post_form("torrents/reannounce", &[("hashes", &hashes.join("|"))]).await.map(drop)
+    }
+
+    async fn recheck(&self, hashes: &[&str]) -> anyhow::Result<()> {
+        self.post_form("torrents/recheck", &[("hashes", &hashes.join("|"))]).await.map(drop)
+    }
+
+    async fn export(&self, t: &TorrentInfo) -> anyhow::Result<Exported> {
+        match export::fetch(self, t).await {
+            Ok(data) => Ok(Exported::File(data)),
+            // servers before 4.5 cannot export; the swarm has the metadata too
+            Err(e) if e.downcast_ref::<RbitError>().is_some() => {
+                let trackers: Vec<String> = t.tracker.iter().filter(|tr| !tr.is_empty()).cloned().collect();
+                Ok(Exported::Magnet(magnet_uri(&t.hash, Some(&t.name), &trackers)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The torrents `ids` (hash prefixes or `%N` references) name, each exactly one.
+pub async fn resolve(client: &impl TorrentClient, ids: &[String]) -> anyhow::Result<Vec<TorrentInfo>> {
+    let ids = api::expand_refs(client.host(), ids)?;
+    api::pick(&client.torrents().await?, ids)
+}
+
+/// The actions of `rbit reannounce` and `rbit recheck` for clients other than qBittorrent.
+#[derive(Debug, Clone, Copy)]
+pub enum Bulk {
+    Reannounce,
+    Recheck,
+}
+
+/// Reannounce or recheck the torrents `ids` name, or all of them, that `selector` matches.
+pub async fn bulk(client: &impl TorrentClient, action: Bulk, ids: &[String], all: bool, selector: &Selector, dry_run: bool) -> anyhow::Result<()> {
+    client.login().await?;
+    let torrents = selector.select(client, ids, all, dry_run).await?;
+    let hashes: Vec<&str> = torrents.iter().map(|t| t.hash.as_str()).collect();
+    match action {
+        Bulk::Reannounce => client.reannounce(&hashes).await?,
+        Bulk::Recheck => client.recheck(&hashes).await?,
+    }
+    for t in &torrents {
+        match action {
+            Bulk::Reannounce => println!("{}", tr!("reannounce-doing", name = t.name.as_str(), id = short_id(t))),
+            Bulk::Recheck => println!("{} {} ({})", tr!("recheck-doing"), t.name, short_id(t)),
+        }
+    }
+    Ok(())
+}
+
+/// A transport error as a [`RbitError::Connection`] saying what to check.
+fn connection_error(client: &str, host: &str, e: reqwest::Error) -> anyhow::Error {
+    let message = if e.is_connect() {
+        format!("cannot connect to {} — is {} running there with remote access enabled? try --host", host, client)
+    } else if e.is_timeout() {
+        format!("request to {} timed out — the server may be overloaded or unreachable", host)
+    } else {
+        format!("request to {} failed: {}", host, e)
+    };
+    RbitError::Connection(message).into()
+}
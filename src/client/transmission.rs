@@ -0,0 +1,288 @@
+//! Transmission's RPC (<https://github.com/transmission/transmission/blob/main/docs/rpc-spec.md>):
+//! JSON requests POSTed to `/transmission/rpc`, with HTTP basic auth and a session id the
+//! server hands out in a 409 answer to the first request.
+
+use std::path::Path;
+use std::sync::Mutex;
+
+use base64::Engine;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{connection_error, Exported, TorrentClient, INFINITE_RATIO};
+use crate::api::{AddOptions, ConnectOptions, TorrentInfo};
+use crate::error::RbitError;
+
+const SESSION_HEADER: &str = "X-Transmission-Session-Id";
+
+/// The torrent fields rbit asks for
+const FIELDS: [&str; 23] = [
+    "hashString",
+    "name",
+    "status",
+    "error",
+    "percentDone",
+    "rateDownload",
+    "rateUpload",
+    "sizeWhenDone",
+    "totalSize",
+    "downloadedEver",
+    "uploadedEver",
+    "uploadRatio",
+    "downloadDir",
+    "labels",
+    "doneDate",
+    "addedDate",
+    "activityDate",
+    "eta",
+    "secondsSeeding",
+    "peersSendingToUs",
+    "peersGettingFromUs",
+    "queuePosition",
+    "trackers",
+];
+
+pub struct Transmission {
+    client: Client,
+    host: String,
+    username: Option<String>,
+    password: Option<String>,
+    dry_run: bool,
+    /// The `X-Transmission-Session-Id` the server last asked for
+    session_id: Mutex<Option<String>>,
+}
+
+/// One torrent of `torrent-get`
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct Torrent {
+    hash_string: String,
+    name: String,
+    status: i64,
+    error: i64,
+    percent_done: f64,
+    rate_download: u64,
+    rate_upload: u64,
+    size_when_done: u64,
+    total_size: u64,
+    downloaded_ever: u64,
+    uploaded_ever: u64,
+    upload_ratio: f64,
+    download_dir: String,
+    labels: Vec<String>,
+    done_date: i64,
+    added_date: i64,
+    activity_date: i64,
+    /// -1 when not downloading, -2 when it cannot be estimated
+    eta: i64,
+    seconds_seeding: i64,
+    peers_sending_to_us: i64,
+    peers_getting_from_us: i64,
+    queue_position: i64,
+    trackers: Vec<Tracker>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Tracker {
+    announce: String,
+}
+
+/// qBittorrent's name for a torrent's state.
+fn state(t: &Torrent) -> &'static str {
+    let complete = t.percent_done >= 1.0;
+    match t.status {
+        _ if t.error != 0 => "error",
+        0 if complete => "pausedUP",
+        0 => "pausedDL",
+        1 | 2 if complete => "checkingUP",
+        1 | 2 => "checkingDL",
+        3 => "queuedDL",
+        4 if t.rate_download == 0 => "stalledDL",
+        4 => "downloading",
+        5 => "queuedUP",
+        _ if t.rate_upload == 0 => "stalledUP",
+        _ => "uploading",
+    }
+}
+
+impl From<Torrent> for TorrentInfo {
+    fn from(t: Torrent) -> Self {
+        TorrentInfo {
+            state: state(&t).to_string(),
+            name: t.name,
+            hash: t.hash_string.to_lowercase(),
+            progress: Some(t.percent_done),
+            dlspeed: Some(t.rate_download),
+            upspeed: Some(t.rate_upload),
+            size: Some(t.size_when_done),
+            total_size: Some(t.total_size),
+            downloaded: Some(t.downloaded_ever),
+            uploaded: Some(t.uploaded_ever),
+            // -1 before anything was downloaded, -2 for an infinite ratio
+            ratio: Some(if t.upload_ratio == -2.0 { INFINITE_RATIO } else { t.upload_ratio.max(0.0) }),
+            tracker: t.trackers.into_iter().next().map(|tr| tr.announce),
+            save_path: Some(t.download_dir),
+            category: t.labels.into_iter().next(),
+            completion_on: Some(if t.done_date > 0 { t.done_date } else { -1 }),
+            added_on: Some(t.added_date),
+            last_activity: Some(t.activity_date),
+            eta: Some(t.eta),
+            seeding_time: Some(t.seconds_seeding),
+            num_seeds: Some(t.peers_sending_to_us),
+            num_leechs: Some(t.peers_getting_from_us),
+            priority: Some(t.queue_position + 1),
+            ..Default::default()
+        }
+    }
+}
+
+impl Transmission {
+    pub fn new(host: String, username: Option<String>, password: Option<String>, connect: &ConnectOptions, dry_run: bool) -> anyhow::Result<Self> {
+        let client = connect.apply(Client::builder())?.build()?;
+        Ok(Transmission { client, host, username, password, dry_run, session_id: Mutex::new(None) })
+    }
+
+    fn url(&self) -> String {
+        format!("{}/transmission/rpc", self.host)
+    }
+
+    /// Call `method` and return its `arguments`. A changed session id is picked up and the call
+    /// sent again.
+    async fn call(&self, method: &str, arguments: Value) -> anyhow::Result<Value> {
+        let body = json!({ "method": method, "arguments": arguments });
+        let url = self.url();
+        for _ in 0..2 {
+            let mut request = self.client.post(&url).json(&body);
+            if let Some(ref user) = self.username {
+                request = request.basic_auth(user, self.password.as_deref());
+            }
+            if let Some(id) = self.session_id.lock().unwrap().clone() {
+                request = request.header(SESSION_HEADER, id);
+            }
+            let res = request.send().await.map_err(|e| connection_error("Transmission", &self.host, e))?;
+            let status = res.status();
+            tracing::debug!("POST {} {} -> {}", url, method, status);
+            if status == StatusCode::CONFLICT {
+                let id = res.headers().get(SESSION_HEADER).and_then(|v| v.to_str().ok()).map(str::to_string);
+                *self.session_id.lock().unwrap() = id;
+                continue;
+            }
+            if status == StatusCode::UNAUTHORIZED {
+                return Err(RbitError::Auth(format!("{} refused the credentials (401): check --username/--password", self.host)).into());
+            }
+            let text = res.text().await?;
+            tracing::trace!("response: {}", text);
+            if !status.is_success() {
+                anyhow::bail!("{} failed with {}: {}", method, status, text);
+            }
+            let reply: Value = serde_json::from_str(&text)?;
+            return match reply["result"].as_str() {
+                Some("success") => Ok(reply["arguments"].clone()),
+                result => anyhow::bail!("{} failed: {}", method, result.unwrap_or("no result")),
+            };
+        }
+        anyhow::bail!("{} kept asking for a new session id", self.host)
+    }
+
+    /// Call a method that changes server state; with `dry_run` it is only printed.
+    async fn change(&self, method: &str, arguments: Value) -> anyhow::Result<Value> {
+        if self.dry_run {
+            println!("[dry-run] POST {}", self.url());
+            println!("[dry-run] {} {}", method, arguments);
+            return Ok(Value::Null);
+        }
+        self.call(method, arguments).await
+    }
+}
+
+impl TorrentClient for Transmission {
+    fn host(&self) -> &str {
+        &self.host
+    }
+
+    fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    async fn login(&self) -> anyhow::Result<()> {
+        // there is no login; fetching the session id checks the credentials too
+        self.call("session-get", json!({ "fields": ["version"] })).await.map(drop)
+    }
+
+    async fn version(&self) -> anyhow::Result<String> {
+        let session = self.call("session-get", json!({ "fields": ["version", "rpc-version"] })).await?;
+        let version = session["version"].as_str().unwrap_or("?");
+        Ok(match session["rpc-version"].as_u64() {
+            Some(rpc) => format!("Transmission {} (RPC {})", version, rpc),
+            None => format!("Transmission {}", version),
+        })
+    }
+
+    async fn torrents(&self) -> anyhow::Result<Vec<TorrentInfo>> {
+        let reply = self.call("torrent-get", json!({ "fields": FIELDS })).await?;
+        let torrents: Vec<Torrent> = serde_json::from_value(reply["torrents"].clone())?;
+        Ok(torrents.into_iter().map(TorrentInfo::from).collect())
+    }
+
+    async fn add(&self, input: &str, options: &AddOptions) -> anyhow::Result<()> {
+        let mut arguments = json!({ "paused": options.paused });
+        if input.starts_with("magnet:") || crate::torrent::is_torrent_url(input) {
+            arguments["filename"] = json!(input);
+        } else {
+            let data = std::fs::read(Path::new(input)).map_err(|e| anyhow::anyhow!("{}: {}", input, e))?;
+            arguments["metainfo"] = json!(base64::engine::general_purpose::STANDARD.encode(data));
+        }
+        if let Some(ref path) = options.save_path {
+            arguments["download-dir"] = json!(path.to_string_lossy());
+        }
+        if let Some(ref category) = options.category {
+            arguments["labels"] = json!([category]);
+        }
+        self.change("torrent-add", arguments).await.map(drop)
+    }
+
+    async fn reannounce(&self, hashes: &[&str]) -> anyhow::Result<()> {
+        self.change("torrent-reannounce", json!({ "ids": hashes })).await.map(drop)
+    }
+
+    async fn recheck(&self, hashes: &[&str]) -> anyhow::Result<()> {
+        self.change("torrent-verify", json!({ "ids": hashes })).await.map(drop)
+    }
+
+    /// The RPC only tells where Transmission keeps the .torrent file, which rbit can read when
+    /// it runs on the same machine; otherwise the magnet link.
+    async fn export(&self, t: &TorrentInfo) -> anyhow::Result<Exported> {
+        let reply = self.call("torrent-get", json!({ "ids": [t.hash], "fields": ["torrentFile", "magnetLink"] })).await?;
+        let torrent = &reply["torrents"][0];
+        if let Some(data) = torrent["torrentFile"].as_str().and_then(|path| std::fs::read(path).ok()) {
+            return Ok(Exported::File(data));
+        }
+        match torrent["magnetLink"].as_str() {
+            Some(link) => Ok(Exported::Magnet(link.to_string())),
+            None => Err(RbitError::NotFound(format!("{} has no torrent {}", self.host, t.hash)).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn torrents_are_reported_as_qbittorrent_would() {
+        let reply = json!([
+            { "hashString": "ABC", "name": "a", "status": 6, "percentDone": 1.0, "rateUpload": 10, "uploadRatio": -1, "labels": ["tv"] },
+            { "hashString": "def", "name": "b", "status": 4, "percentDone": 0.5, "eta": 95, "peersSendingToUs": 3, "trackers": [{ "announce": "https://t/ann" }] },
+            { "hashString": "123", "name": "c", "status": 0, "percentDone": 1.0, "uploadRatio": -2 },
+            { "hashString": "456", "name": "d", "status": 4, "error": 2, "rateDownload": 5 },
+        ]);
+        let torrents: Vec<TorrentInfo> = serde_json::from_value::<Vec<Torrent>>(reply).unwrap().into_iter().map(TorrentInfo::from).collect();
+        let states: Vec<&str> = torrents.iter().map(|t| t.state.as_str()).collect();
+        assert_eq!(states, ["uploading", "stalledDL", "pausedUP", "error"]);
+        assert_eq!((torrents[0].hash.as_str(), torrents[0].ratio, torrents[0].category.as_deref()), ("abc", Some(0.0), Some("tv")));
+        assert_eq!(torrents[1].tracker.as_deref(), Some("https://t/ann"));
+        assert_eq!(torrents[2].ratio, Some(INFINITE_RATIO));
+        assert_eq!((torrents[1].eta, torrents[1].num_seeds), (Some(95), Some(3)));
+    }
+}
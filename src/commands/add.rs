@@ -0,0 +1,566 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use crate::api::{AddOptions, ContentLayout, MainData, Session};
+use crate::batch::{self, Report};
+use crate::error::RbitError;
+use crate::format::{self, size_human, truncate, Rate};
+use crate::glob::{expand_glob, has_wildcard, torrent_files_in};
+use crate::i18n::tr;
+use crate::logging::redact_url;
+use crate::commands::follow::follow;
+use crate::rules::Rules;
+use crate::torrent::{info_hash_hex, is_info_hash, is_torrent_url, magnet_info_hash, magnet_size, magnet_uri, Metainfo};
+
+/// `rbit add` flags that apply to every added torrent; most map to fields of the `torrents/add` form.
+#[derive(clap::Args, Debug)]
+pub struct AddFlags {
+    /// Stop seeding at this share ratio, e.g. `2.0`, `global` or `unlimited`
+    #[arg(long, value_name = "RATIO", value_parser = format::parse_ratio_limit)]
+    pub ratio_limit: Option<f64>,
+
+    /// Stop seeding after this long, e.g. `90` (minutes), `36h`, `7d`, `global` or `unlimited`
+    #[arg(long, value_name = "TIME", value_parser = format::parse_minutes_limit)]
+    pub seeding_time_limit: Option<i64>,
+
+    /// Download pieces in order
+    #[arg(long)]
+    pub sequential: bool,
+
+    /// Add without starting the download
+    #[arg(long)]
+    pub paused: bool,
+
+    /// Skip hash checking (only for data that is known to be complete)
+    #[arg(long)]
+    pub skip_checking: bool,
+
+    /// Name to give the torrent instead of the one in its metadata
+    #[arg(long, value_name = "NAME", conflicts_with = "batch")]
+    pub rename: Option<String>,
+
+    /// Category to add the torrents in
+    #[arg(long, value_name = "NAME")]
+    pub category: Option<String>,
+
+    /// Cookie qBittorrent sends when downloading a torrent URL
+    #[arg(long, value_name = "COOKIE")]
+    pub cookie: Option<String>,
+
+    /// Always create a top-level folder (same as `--content-layout subfolder`)
+    #[arg(long, conflicts_with = "content_layout")]
+    pub root_folder: bool,
+
+    /// How to lay out the torrent's files under the save path
+    #[arg(long, value_enum, value_name = "LAYOUT")]
+    pub content_layout: Option<ContentLayout>,
+
+    /// Upload limit, e.g. `500k` or `2M`
+    #[arg(long, value_name = "RATE")]
+    pub upload_limit: Option<Rate>,
+
+    /// Download limit, e.g. `500k` or `2M`
+    #[arg(long, value_name = "RATE")]
+    pub download_limit: Option<Rate>,
+
+    /// Use automatic torrent management: the save path follows the category unless --dest is given
+    #[arg(long)]
+    pub auto_tmm: bool,
+
+    /// Download http(s) torrent URLs here and upload the file, instead of letting qBittorrent fetch them
+    #[arg(long)]
+    pub download_first: bool,
+
+    /// Add torrents even if the server already has them, instead of skipping them
+    #[arg(long)]
+    pub force: bool,
+
+    /// Add even if the torrents are larger than the free space the server reports (only warn)
+    #[arg(long)]
+    pub ignore_space: bool,
+
+    /// Then show the torrents' progress, with speed and ETA, until they are complete (or Ctrl-C);
+    /// exits with an error if one fails
+    #[arg(long, conflicts_with = "paused")]
+    pub follow: bool,
+
+    /// With --follow, shell command to run for each torrent once it is complete (overrides
+    /// `[hooks] on_complete`)
+    #[arg(long, value_name = "COMMAND", requires = "follow")]
+    pub on_complete: Option<String>,
+}
+
+impl AddFlags {
+    pub fn into_options(self, save_path: Option<PathBuf>) -> AddOptions {
+        AddOptions {
+            save_path,
+            ratio_limit: self.ratio_limit,
+            seeding_time_limit: self.seeding_time_limit,
+            sequential: self.sequential,
+            paused: self.paused,
+            skip_checking: self.skip_checking,
+            rename: self.rename,
+            category: self.category,
+            tags: None,
+            cookie: self.cookie,
+            content_layout: if self.root_folder { Some(ContentLayout::Subfolder) } else { self.content_layout },
+            upload_limit: self.upload_limit,
+            download_limit: self.download_limit,
+            auto_tmm: self.auto_tmm,
+            download_first: self.download_first,
+            force: self.force,
+            ignore_space: self.ignore_space,
+            follow: self.follow,
+            on_complete: self.on_complete,
+            rules: Rules::default(),
+            fixed_save_path: false,
+        }
+    }
+}
+
+pub enum Outcome {
+    Added,
+    /// Already on the server under this name
+    Duplicate(String),
+}
+
+impl Outcome {
+    fn reported(self) -> batch::Outcome {
+        match self {
+            Outcome::Added => batch::Outcome::Done("added".to_string()),
+            Outcome::Duplicate(name) => batch::Outcome::Skipped(format!("already on the server as {}", name)),
+        }
+    }
+}
+
+/// File name for a .torrent downloaded from `link`: its last path segment, if that looks like one.
+fn url_file_name(link: &str) -> String {
+    reqwest::Url::parse(link)
+        .ok()
+        .and_then(|u| u.path_segments().and_then(|mut segments| segments.next_back().map(str::to_string)))
+        .filter(|name| name.ends_with(".torrent"))
+        .unwrap_or_else(|| "download.torrent".to_string())
+}
+
+/// Fetch a torrent URL locally, skip it if the server already has it, otherwise upload the file.
+async fn add_downloaded(session: &Session, link: &str, options: &AddOptions) -> anyhow::Result<Outcome> {
+    if session.dry_run {
+        println!("[dry-run] GET {}", link);
+        println!("[dry-run] then upload the downloaded file as {}", url_file_name(link));
+        return Ok(Outcome::Added);
+    }
+    let data = session.download(link, options.cookie.as_deref()).await?;
+    let meta = Metainfo::parse(&data).map_err(|e| anyhow::anyhow!("{} did not return a .torrent file: {}", redact_url(link), e))?;
+    if !options.force {
+        if let Some(existing) = session.find_torrent(&meta.info_hash).await? {
+            return Ok(Outcome::Duplicate(existing.name));
+        }
+    }
+    session.add_torrent_data(&[(url_file_name(link), data)], options).await?;
+    Ok(Outcome::Added)
+}
+
+/// Add one item, skipping .torrent files and magnets whose info-hash the server already has
+/// (unless `--force`). A bare info-hash is turned into a magnet link; http(s) URLs go to
+/// qBittorrent as they are unless `--download-first` is set, so they cannot be checked.
+pub async fn add_item(session: &Session, input: &str, options: &AddOptions) -> anyhow::Result<Outcome> {
+    let synthesized;
+    let input = if is_info_hash(input) && !Path::new(input).exists() {
+        synthesized = magnet_uri(input, None, &[]);
+        synthesized.as_str()
+    } else {
+        input
+    };
+    if is_torrent_url(input) && options.download_first {
+        return add_downloaded(session, input, options).await;
+    }
+    if !options.force && !session.dry_run {
+        let hash = if input.starts_with("magnet:") {
+            magnet_info_hash(input)
+        } else if is_torrent_url(input) {
+            None
+        } else {
+            duplicate_key(Path::new(input), &fs::read(input)?)
+        };
+        if let Some(hash) = hash {
+            if let Some(existing) = session.find_torrent(&hash).await? {
+                return Ok(Outcome::Duplicate(existing.name));
+            }
+        }
+    }
+    session.add(input, options).await?;
+    Ok(Outcome::Added)
+}
+
+/// Bytes an input will take, when rbit can tell without the server: .torrent files, and
+/// magnets that give their length (`xl=`).
+fn known_size(input: &str) -> Option<u64> {
+    if input.starts_with("magnet:") {
+        magnet_size(input)
+    } else if is_torrent_url(input) || (is_info_hash(input) && !Path::new(input).exists()) {
+        None
+    } else {
+        Metainfo::from_file(Path::new(input)).ok().map(|meta| meta.total_size())
+    }
+}
+
+/// The info-hash of a magnet link or bare info-hash, to follow it; torrent URLs have none
+/// until qBittorrent has fetched them.
+fn link_hash(link: &str) -> Option<String> {
+    magnet_info_hash(link).or_else(|| info_hash_hex(link).filter(|_| !Path::new(link).exists()))
+}
+
+/// Hashes to follow after adding `inputs`, warning about the ones that cannot be followed.
+fn follow_hashes(inputs: Vec<(String, Option<String>)>) -> Vec<String> {
+    let mut hashes = Vec::new();
+    for (label, hash) in inputs {
+        match hash {
+            Some(hash) if !hashes.contains(&hash) => hashes.push(hash),
+            Some(_) => {}
+            None => eprintln!("{} {}", tr!("warning"), tr!("add-cannot-follow", name = label)),
+        }
+    }
+    hashes
+}
+
+/// Refuse to add torrents larger than the server's free space, or with `--ignore-space` only
+/// warn. qBittorrent reports the free space of its default save path, so that is what counts.
+async fn check_space(session: &Session, sizes: &[Option<u64>], options: &AddOptions) -> anyhow::Result<()> {
+    let needed: u64 = sizes.iter().flatten().sum();
+    if session.dry_run || needed == 0 {
+        return Ok(());
+    }
+    let unknown = sizes.iter().filter(|s| s.is_none()).count();
+    if unknown > 0 {
+        tracing::debug!("size of {} of {} inputs is unknown until qBittorrent has the metadata", unknown, sizes.len());
+    }
+    let maindata: MainData = session.get_json("sync/maindata").await?;
+    let Some(free) = maindata.server_state.and_then(|s| s.free_space_on_disk) else {
+        return Ok(());
+    };
+    if needed <= free {
+        return Ok(());
+    }
+    let (needed, free) = (size_human(needed), size_human(free));
+    if options.ignore_space {
+        eprintln!("{} {}", tr!("warning"), tr!("add-no-space-anyway", needed = needed, free = free));
+        Ok(())
+    } else {
+        anyhow::bail!(tr!("add-no-space", needed = needed, free = free))
+    }
+}
+
+/// A command-line input after expansion.
+#[derive(Debug)]
+enum Item {
+    /// Magnet link, torrent URL or bare info-hash
+    Link(String),
+    File(PathBuf),
+}
+
+/// Turn the command-line inputs into items: directories become the .torrent files in them and
+/// patterns with `*` or `?` (quoted, so the shell left them alone) the files they match.
+fn expand_inputs(inputs: &[String], recursive: bool) -> anyhow::Result<Vec<Item>> {
+    let mut items = Vec::new();
+    let mut seen = HashSet::new();
+    let mut push_file = |items: &mut Vec<Item>, path: PathBuf| {
+        if seen.insert(path.clone()) {
+            items.push(Item::File(path));
+        }
+    };
+    for input in inputs {
+        let path = Path::new(input);
+        if input.starts_with("magnet:") || is_torrent_url(input) || (is_info_hash(input) && !path.exists()) {
+            items.push(Item::Link(input.clone()));
+        } else if path.is_dir() {
+            let files = torrent_files_in(path, recursive)?;
+            if files.is_empty() {
+                let hint = if recursive { "" } else { " (--recursive also searches subfolders)" };
+                anyhow::bail!("no .torrent files in {}{}", path.display(), hint);
+            }
+            for file in files {
+                push_file(&mut items, file);
+            }
+        } else if !path.exists() && has_wildcard(input) {
+            let files = expand_glob(input);
+            if files.is_empty() {
+                anyhow::bail!("no files match {}", input);
+            }
+            for file in files {
+                push_file(&mut items, file);
+            }
+        } else {
+            push_file(&mut items, path.to_path_buf());
+        }
+    }
+    Ok(items)
+}
+
+/// The info-hash to look for on the server before uploading `path`. A file rbit cannot parse
+/// (a v2-only torrent, say) is uploaded unchecked, with a warning, and qBittorrent decides.
+fn duplicate_key(path: &Path, data: &[u8]) -> Option<String> {
+    match Metainfo::parse(data) {
+        Ok(meta) => Some(meta.info_hash),
+        Err(e) => {
+            eprintln!("{} {}", tr!("warning"), tr!("add-unchecked", path = path.display().to_string(), why = e.to_string()));
+            None
+        }
+    }
+}
+
+/// Upload local .torrent files in a single request, skipping the ones the server already has.
+/// Each file's outcome is stored at its index in `outcomes`.
+async fn add_files(
+    session: &Session,
+    files: &[(usize, &Path)],
+    options: &AddOptions,
+    outcomes: &mut [Option<anyhow::Result<Outcome>>],
+) -> anyhow::Result<()> {
+    let check = !options.force && !session.dry_run;
+    let mut parsed = Vec::new();
+    for &(i, path) in files {
+        match fs::read(path) {
+            Ok(data) => {
+                let hash = if check { duplicate_key(path, &data) } else { None };
+                parsed.push((i, path, data, hash));
+            }
+            Err(e) => outcomes[i] = Some(Err(e.into())),
+        }
+    }
+
+    let hashes: Vec<String> = parsed.iter().filter_map(|(_, _, _, hash)| hash.clone()).collect();
+    let existing: HashMap<String, String> = if hashes.is_empty() {
+        HashMap::new()
+    } else {
+        session.find_torrents(&hashes).await?.into_iter().map(|t| (t.hash.to_lowercase(), t.name)).collect()
+    };
+
+    let mut first_with_hash: HashMap<String, &Path> = HashMap::new();
+    let mut upload = Vec::new();
+    let mut uploaded = Vec::new();
+    for (i, path, data, hash) in parsed {
+        if let Some(name) = hash.as_ref().and_then(|h| existing.get(h)) {
+            outcomes[i] = Some(Ok(Outcome::Duplicate(name.clone())));
+        } else if let Some(first) = hash.as_ref().and_then(|h| first_with_hash.get(h)) {
+            outcomes[i] = Some(Err(anyhow::anyhow!("same torrent as {}", first.display())));
+        } else {
+            if let Some(hash) = hash {
+                first_with_hash.insert(hash, path);
+            }
+            let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("upload.torrent").to_string();
+            upload.push((name, data));
+            uploaded.push(i);
+        }
+    }
+    if upload.is_empty() {
+        return Ok(());
+    }
+    let result = session.add_torrent_data(&upload, options).await;
+    for i in uploaded {
+        outcomes[i] = Some(match result {
+            Ok(()) => Ok(Outcome::Added),
+            Err(ref e) => Err(anyhow::anyhow!("{}", e)),
+        });
+    }
+    Ok(())
+}
+
+/// Add the command-line inputs. Links are added one by one (up to `session.concurrency` at
+/// once); all local files go up in one multipart request. With `delete_after_add`, source files
+/// the server now has (added or already present) are removed.
+pub async fn add_inputs(
+    session: &Session,
+    inputs: &[String],
+    options: &AddOptions,
+    recursive: bool,
+    delete_after_add: bool,
+) -> anyhow::Result<()> {
+    let items = expand_inputs(inputs, recursive)?;
+    if options.rename.is_some() && items.len() > 1 {
+        anyhow::bail!("--rename applies to a single torrent, but the input expands to {}", items.len());
+    }
+    if !session.dry_run {
+        session.login().await?;
+    }
+    let sizes: Vec<Option<u64>> = items
+        .iter()
+        .map(|item| match item {
+            Item::Link(link) => known_size(link),
+            Item::File(path) => Metainfo::from_file(path).ok().map(|meta| meta.total_size()),
+        })
+        .collect();
+    check_space(session, &sizes, options).await?;
+
+    let mut outcomes: Vec<Option<anyhow::Result<Outcome>>> = items.iter().map(|_| None).collect();
+    let links = items.iter().enumerate().filter_map(|(i, item)| match item {
+        Item::Link(link) => Some((i, link)),
+        Item::File(_) => None,
+    });
+    let links: Vec<(usize, &String)> = links.collect();
+    let results = batch::run(session, "Adding", &links, |(_, link)| link.to_string(), |&(_, link)| add_item(session, link, options)).await;
+    for (&(i, _), result) in links.iter().zip(results) {
+        outcomes[i] = Some(result);
+    }
+    let files: Vec<(usize, &Path)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| match item {
+            Item::File(path) => Some((i, path.as_path())),
+            Item::Link(_) => None,
+        })
+        .collect();
+    add_files(session, &files, options, &mut outcomes).await?;
+
+    let mut delete = Vec::new();
+    let mut to_follow = Vec::new();
+    let mut report = Report::default();
+    for (item, outcome) in items.iter().zip(outcomes) {
+        let outcome = outcome.expect("every item gets an outcome");
+        let label = match item {
+            Item::Link(link) => truncate(link, 60),
+            Item::File(path) => path.display().to_string(),
+        };
+        if let (Item::File(path), Ok(_)) = (item, &outcome) {
+            delete.push(path);
+        }
+        if options.follow && outcome.is_ok() {
+            let hash = match item {
+                Item::Link(link) => link_hash(link),
+                Item::File(path) => Metainfo::from_file(path).ok().map(|meta| meta.info_hash),
+            };
+            to_follow.push((label.clone(), hash));
+        }
+        if items.len() == 1 {
+            let outcome = match item {
+                Item::File(_) => outcome.map_err(|e| anyhow::anyhow!("{}: {}", label, e)),
+                Item::Link(_) => outcome,
+            };
+            match outcome? {
+                Outcome::Added => println!("{}", tr!("add-done", destination = options.destination())),
+                Outcome::Duplicate(name) => println!("{}", tr!("add-duplicate", existing = name)),
+            }
+            continue;
+        }
+        report.push(label, outcome.map(Outcome::reported));
+    }
+    let mut failed = report.failed();
+    if items.len() > 1 {
+        summarise(&report, items.len(), options);
+    }
+
+    if delete_after_add {
+        for path in &delete {
+            if session.dry_run {
+                println!("[dry-run] would delete {}", path.display());
+            } else if let Err(e) = fs::remove_file(path) {
+                failed += 1;
+                println!("{}", tr!("add-delete-failed", path = path.display().to_string(), reason = e.to_string()));
+            }
+        }
+        if !session.dry_run && !delete.is_empty() {
+            println!("{}", tr!("add-deleted-sources", count = delete.len()));
+        }
+    }
+    let followed = follow(session, &follow_hashes(to_follow), options.on_complete.as_deref()).await;
+    if failed > 0 {
+        return Err(RbitError::PartialFailure { failed, total: items.len() }.into());
+    }
+    followed
+}
+
+/// The table of a batch of several torrents, and how many were added.
+fn summarise(report: &Report, total: usize, options: &AddOptions) {
+    report.print();
+    println!("{}", tr!("add-summary", done = report.done(), total = total, skipped = report.skipped(), destination = options.destination()));
+    if report.skipped() > 0 {
+        println!("{}", tr!("add-force-hint"));
+    }
+}
+
+/// Read batch input lines from a file, or from stdin when the path is `-`.
+/// Blank lines and lines starting with `#` are skipped.
+fn read_batch(path: &Path) -> anyhow::Result<Vec<String>> {
+    let reader: Box<dyn BufRead> = if path == Path::new("-") {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        Box::new(BufReader::new(File::open(path)?))
+    };
+    let mut items = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        items.push(line.to_string());
+    }
+    Ok(items)
+}
+
+/// Add every item listed in `batch`, running up to `session.concurrency` requests at once.
+/// Results are reported in input order.
+pub async fn add_batch(session: &Session, batch: &Path, options: &AddOptions) -> anyhow::Result<()> {
+    let items = read_batch(batch)?;
+    if items.is_empty() {
+        println!("{}", tr!("add-nothing"));
+        return Ok(());
+    }
+
+    // log in once for the whole batch
+    if !session.dry_run {
+        session.login().await?;
+    }
+    let sizes: Vec<Option<u64>> = items.iter().map(|item| known_size(item)).collect();
+    check_space(session, &sizes, options).await?;
+
+    let results = batch::run(session, "Adding", &items, |item| item.clone(), |item| add_item(session, item, options)).await;
+    let mut to_follow = Vec::new();
+    let mut report = Report::default();
+    for (item, result) in items.iter().zip(results) {
+        if options.follow && result.is_ok() {
+            to_follow.push((item.clone(), link_hash(item)));
+        }
+        report.push(item.clone(), result.map(Outcome::reported));
+    }
+    summarise(&report, items.len(), options);
+    let followed = follow(session, &follow_hashes(to_follow), options.on_complete.as_deref()).await;
+    report.result()?;
+    followed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inputs_expand_to_links_and_files() {
+        let dir = std::env::temp_dir().join(format!("rbit-add-inputs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("empty")).unwrap();
+        for file in ["a.torrent", "b.torrent"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        let hash = "0123456789abcdef0123456789abcdef01234567";
+        let at = |name: &str| format!("{}/{}", dir.display(), name);
+        let shown = |items: Vec<Item>| {
+            items
+                .into_iter()
+                .map(|item| match item {
+                    Item::Link(link) => link,
+                    Item::File(path) => path.strip_prefix(&dir).map_or_else(|_| path.display().to_string(), |p| p.display().to_string()),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let inputs = ["magnet:?xt=urn:btih:aaaa".to_string(), hash.to_string(), dir.display().to_string(), at("*.torrent"), at("a.torrent")];
+        // the folder and the pattern find the same files, which are added once
+        assert_eq!(shown(expand_inputs(&inputs, false).unwrap()), ["magnet:?xt=urn:btih:aaaa", hash, "a.torrent", "b.torrent"]);
+        // a missing file is kept, for adding it to report the error
+        assert_eq!(shown(expand_inputs(&[at("gone.torrent")], false).unwrap()), ["gone.torrent"]);
+        assert!(expand_inputs(&[at("empty")], false).unwrap_err().to_string().contains("--recursive"));
+        assert!(expand_inputs(&[at("*.iso")], false).unwrap_err().to_string().starts_with("no files match"));
+        assert!(expand_inputs(&[at("missing/*.torrent")], false).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
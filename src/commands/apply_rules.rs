@@ -0,0 +1,99 @@
+//! `rbit apply-rules`: file torrents already on the server by the `[[rules]]`, as `rbit add`
+//! does with new ones. Each torrent the first matching rule would change gets the rule's
+//! category (created when the server lacks it), its missing tags and its save path, which
+//! moves the data there. A dry run shows the plan.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde_json::Value;
+
+use crate::api::{Session, TorrentInfo};
+use crate::commands::manage::short_id;
+use crate::i18n::tr;
+use crate::plan::{Action, Plan};
+use crate::rules::Rules;
+use crate::savepath::{self, Vars};
+use crate::select::Selector;
+
+/// Save paths compare equal with or without a trailing separator.
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['/', '\\']) == b.trim_end_matches(['/', '\\'])
+}
+
+/// The tracker a rule is matched against: the working one, or the first the torrent has.
+async fn tracker_of(session: &Session, t: &TorrentInfo, rules: &Rules) -> anyhow::Result<Option<String>> {
+    match t.tracker.clone().filter(|url| !url.is_empty()) {
+        Some(url) => Ok(Some(url)),
+        // the DHT, PeX and LSD show up as trackers without a URL
+        None if rules.use_trackers() => Ok(session.trackers(&t.hash).await?.into_iter().map(|tr| tr.url).find(|url| url.contains("://"))),
+        None => Ok(None),
+    }
+}
+
+pub async fn apply_rules(session: &Session, rules: &Rules, ids: &[String], all: bool, selector: &Selector, json: bool) -> anyhow::Result<()> {
+    if rules.is_empty() {
+        println!("{}", tr!("apply-rules-none"));
+        return Ok(());
+    }
+    session.login().await?;
+    let torrents = selector.select(session, ids, all, session.dry_run).await?;
+    let categories: BTreeMap<String, Value> = session.get_json("torrents/categories").await?;
+    let mut known: HashSet<String> = categories.into_keys().collect();
+
+    let mut plan = Plan::new("apply-rules");
+    for t in &torrents {
+        let tracker = tracker_of(session, t, rules).await?;
+        let Some((n, rule)) = rules.find(Some(&t.name), tracker.as_deref()) else {
+            plan.unchanged += 1;
+            continue;
+        };
+        let mut changes = Vec::new();
+        let current = t.category.clone().unwrap_or_default();
+        let category = rule.category.clone().unwrap_or_else(|| current.clone());
+        if category != current {
+            if !category.is_empty() && known.insert(category.clone()) {
+                plan.change(Action::Create, "category", &category, format!("for rule #{}", n));
+                plan.request("torrents/createCategory", &[("category", &category)]);
+            }
+            changes.push(format!("category {:?} -> {:?}", current, category));
+            plan.request("torrents/setCategory", &[("hashes", &t.hash), ("category", &category)]);
+        }
+
+        let has: Vec<&str> = t.tags.as_deref().unwrap_or_default().split(',').map(str::trim).collect();
+        let missing: Vec<&str> = rule.tags.iter().map(String::as_str).filter(|tag| !has.contains(tag)).collect();
+        if !missing.is_empty() {
+            let joined = missing.join(",");
+            changes.push(format!("tags +{}", joined));
+            plan.request("torrents/addTags", &[("hashes", &t.hash), ("tags", &joined)]);
+        }
+
+        let mut moves = false;
+        if let Some(ref template) = rule.save_path {
+            // dates in the template are those of the add, as if the rule had applied then
+            let vars = Vars { name: Some(t.name.clone()), category: Some(category.clone()), tracker, now: t.added_on.unwrap_or(0).max(0) as u64 };
+            let path = session.server_path(&savepath::expand(template, &vars)?).await?;
+            let from = t.save_path.clone().unwrap_or_default();
+            if !same_path(&from, &path) {
+                changes.push(format!("save path {:?} -> {:?}", from, path));
+                plan.request("torrents/setLocation", &[("hashes", &t.hash), ("location", &path)]);
+                moves = true;
+            }
+        }
+
+        if changes.is_empty() {
+            plan.unchanged += 1;
+            continue;
+        }
+        let change = plan.change(Action::Update, "torrent", &format!("{} {}", short_id(t), t.name), format!("rule #{}: {}", n, changes.join(", ")));
+        // the bytes that move, when the data does
+        if moves {
+            change.size = t.size;
+        }
+    }
+    if session.dry_run {
+        return plan.print(json);
+    }
+    plan.apply(session).await?;
+    plan.print_applied();
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::api::Session;
+use crate::credentials;
+use crate::i18n::tr;
+
+/// Prompt for credentials, check them against the server and store the password in the OS keyring.
+pub async fn login(session: &mut Session, profile: &str) -> anyhow::Result<()> {
+    let username = credentials::prompt(&tr!("auth-username"), session.username.as_deref())?;
+    if username.is_empty() {
+        anyhow::bail!("a username is required");
+    }
+    let password = credentials::prompt_password(&tr!("login-password-prompt", user = username.as_str(), host = session.host.as_str()))?;
+
+    session.username = Some(username.clone());
+    session.password = Some(password.clone());
+    if session.dry_run {
+        println!("[dry-run] would log in as {} and store the password for '{}' in the OS keyring", username, profile);
+        return Ok(());
+    }
+    session.login().await?;
+
+    credentials::store_password(profile, &password)?;
+    println!("{}", tr!("auth-stored", profile = profile));
+    println!("{}", tr!("auth-reference"));
+    println!();
+    if profile == crate::config::DEFAULT_PROFILE {
+        println!("[qbittorrent]");
+    } else {
+        println!("[servers.{}]", profile);
+    }
+    println!("host = \"{}\"", session.host);
+    println!("username = \"{}\"", username);
+    println!("keyring = true");
+    Ok(())
+}
+
+pub fn logout(profile: &str, dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        println!("[dry-run] would remove the keyring entry for '{}'", profile);
+        return Ok(());
+    }
+    credentials::delete_password(profile)?;
+    println!("{}", tr!("auth-removed", profile = profile));
+    Ok(())
+}
@@ -0,0 +1,115 @@
+//! `rbit category`: list the server's categories and keep them in line with the `[categories]`
+//! config table.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use tabled::Tabled;
+
+use crate::api::Session;
+use crate::config::CategoryConfig;
+use crate::i18n::tr;
+use crate::plan::{Action, Plan};
+use crate::theme;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Category {
+    #[serde(default)]
+    save_path: String,
+}
+
+#[derive(Tabled)]
+struct CategoryRow {
+    name: String,
+    #[tabled(rename = "save path")]
+    save_path: String,
+    torrents: usize,
+}
+
+/// Save paths compare equal with or without a trailing separator.
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['/', '\\']) == b.trim_end_matches(['/', '\\'])
+}
+
+/// Torrents per category name.
+async fn usage(session: &Session) -> anyhow::Result<BTreeMap<String, usize>> {
+    let mut counts = BTreeMap::new();
+    for t in session.torrents("all").await? {
+        if let Some(category) = t.category.filter(|c| !c.is_empty()) {
+            *counts.entry(category).or_insert(0) += 1;
+        }
+    }
+    Ok(counts)
+}
+
+pub async fn list(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    let (categories, counts) =
+        tokio::try_join!(session.get_json::<BTreeMap<String, Category>>("torrents/categories"), usage(session))?;
+    if categories.is_empty() {
+        println!("{}", tr!("category-none"));
+        return Ok(());
+    }
+    let rows = categories.into_iter().map(|(name, c)| CategoryRow {
+        torrents: counts.get(&name).copied().unwrap_or(0),
+        save_path: if c.save_path.is_empty() { "(default)".to_string() } else { session.path_map.show(&c.save_path) },
+        name,
+    });
+    println!("{}", theme::table(rows));
+    Ok(())
+}
+
+/// Create the configured categories the server lacks and fix the save paths that differ; with
+/// `prune`, remove the server's categories the config does not list.
+pub async fn sync(session: &Session, wanted: &BTreeMap<String, CategoryConfig>, prune: bool, json: bool) -> anyhow::Result<()> {
+    if wanted.is_empty() && !prune {
+        println!("{}", tr!("category-no-config"));
+        return Ok(());
+    }
+    session.login().await?;
+    let existing: BTreeMap<String, Category> = session.get_json("torrents/categories").await?;
+    let mut plan = Plan::new("category sync");
+    let mut names = Vec::new();
+    for (key, category) in wanted {
+        let name = category.name.clone().unwrap_or_else(|| key.clone());
+        if names.contains(&name) {
+            anyhow::bail!("[categories.{}]: category '{}' is configured twice", key, name);
+        }
+        names.push(name.clone());
+        let save_path = session.server_path(&category.save_path).await.map_err(|e| e.context(format!("[categories.{}]", key)))?;
+        let params = [("category", name.as_str()), ("savePath", save_path.as_str())];
+        match existing.get(&name) {
+            Some(current) if same_path(&current.save_path, &save_path) => plan.unchanged += 1,
+            Some(current) => {
+                plan.change(Action::Update, "category", &name, format!("save path {:?} -> {:?}", current.save_path, save_path));
+                plan.request("torrents/editCategory", &params);
+            }
+            None => {
+                plan.change(Action::Create, "category", &name, format!("save path {:?}", save_path));
+                plan.request("torrents/createCategory", &params);
+            }
+        }
+    }
+    if prune {
+        let extra: Vec<&String> = existing.keys().filter(|name| !names.contains(name)).collect();
+        if !extra.is_empty() {
+            let counts = usage(session).await?;
+            for name in &extra {
+                let detail = match counts.get(*name) {
+                    Some(n) => format!("{} torrents are left without a category", n),
+                    None => String::new(),
+                };
+                plan.change(Action::Delete, "category", name, detail);
+            }
+            let joined: Vec<&str> = extra.iter().map(|name| name.as_str()).collect();
+            plan.request("torrents/removeCategories", &[("categories", &joined.join("\n"))]);
+        }
+    }
+    if session.dry_run {
+        return plan.print(json);
+    }
+    plan.apply(session).await?;
+    plan.print_applied();
+    Ok(())
+}
@@ -0,0 +1,192 @@
+//! `rbit cleanup`: delete torrents according to the `[[retention]]` tag policies, printing the
+//! reason each tagged torrent is kept, waited on or deleted. Runs against one server do not
+//! overlap, so a cron job and a run by hand cannot both delete the same torrents. Deleting
+//! asks for confirmation first (see [`crate::confirm`]).
+
+use tabled::Tabled;
+
+use crate::api::{Session, TorrentInfo};
+use crate::cache;
+use crate::commands::manage::short_id;
+use crate::config::RetentionPolicy;
+use crate::confirm::confirm;
+use crate::format::{minutes_limit_human, parse_minutes_limit, truncate};
+use crate::i18n::tr;
+use crate::lock;
+use crate::plan::{Action, Plan};
+use crate::theme;
+
+/// A policy with its delay parsed; `None` means never delete.
+struct Policy<'a> {
+    tag: &'a str,
+    after_minutes: Option<i64>,
+    delete_files: bool,
+}
+
+fn parse_policies(policies: &[RetentionPolicy]) -> anyhow::Result<Vec<Policy<'_>>> {
+    policies
+        .iter()
+        .map(|p| {
+            let after_minutes = match p.delete_after.trim().to_ascii_lowercase().as_str() {
+                "never" => None,
+                _ => match parse_minutes_limit(&p.delete_after) {
+                    Ok(m) if m >= 0 => Some(m),
+                    Ok(_) => anyhow::bail!("[[retention]] tag '{}': delete_after must be a duration like 7d, or never", p.tag),
+                    Err(e) => anyhow::bail!("[[retention]] tag '{}': {}", p.tag, e),
+                },
+            };
+            Ok(Policy { tag: &p.tag, after_minutes, delete_files: p.delete_files })
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Verdict {
+    Keep(String),
+    Wait(String),
+    Delete { reason: String, files: bool },
+}
+
+/// What the policies say about a torrent; `None` when none of its tags has a policy. A `never`
+/// policy wins over every other; otherwise the policy that deletes soonest applies.
+fn evaluate(torrent: &TorrentInfo, policies: &[Policy], now: u64) -> Option<Verdict> {
+    let tags: Vec<&str> = torrent.tags.as_deref().unwrap_or("").split(',').map(str::trim).collect();
+    let matched: Vec<&Policy> = policies.iter().filter(|p| tags.contains(&p.tag)).collect();
+    if matched.is_empty() {
+        return None;
+    }
+    if let Some(p) = matched.iter().find(|p| p.after_minutes.is_none()) {
+        return Some(Verdict::Keep(format!("tag '{}': never deleted", p.tag)));
+    }
+    let policy = matched.iter().min_by_key(|p| p.after_minutes).expect("matched is not empty");
+    let after = policy.after_minutes.unwrap_or(0);
+    let completed = torrent.completion_on.unwrap_or(-1);
+    if completed <= 0 {
+        return Some(Verdict::Wait(format!("tag '{}': not completed yet", policy.tag)));
+    }
+    let age = (now as i64 - completed).max(0) / 60;
+    if age >= after {
+        Some(Verdict::Delete {
+            reason: format!("tag '{}': completed {} ago, kept for {}", policy.tag, minutes_limit_human(age), minutes_limit_human(after)),
+            files: policy.delete_files,
+        })
+    } else {
+        Some(Verdict::Wait(format!(
+            "tag '{}': completed {} ago, deleted in {}",
+            policy.tag,
+            minutes_limit_human(age),
+            minutes_limit_human(after - age)
+        )))
+    }
+}
+
+#[derive(Tabled)]
+struct CleanupRow {
+    id: String,
+    name: String,
+    tags: String,
+    action: String,
+    reason: String,
+}
+
+pub async fn cleanup(session: &Session, policies: &[RetentionPolicy], json: bool) -> anyhow::Result<()> {
+    if policies.is_empty() {
+        println!("{}", tr!("cleanup-no-policies"));
+        return Ok(());
+    }
+    let policies = parse_policies(policies)?;
+    let _running = match session.dry_run {
+        true => None,
+        false => match lock::try_exclusive(&lock::for_host("cleanup", &session.host))? {
+            Some(held) => Some(held),
+            None => anyhow::bail!(tr!("cleanup-running", host = session.host.as_str())),
+        },
+    };
+    session.login().await?;
+    let torrents = session.torrents("all").await?;
+    let now = cache::now();
+
+    let mut rows = Vec::new();
+    let mut plan = Plan::new("cleanup");
+    let (mut with_files, mut without_files) = (Vec::new(), Vec::new());
+    for t in &torrents {
+        let Some(verdict) = evaluate(t, &policies, now) else {
+            continue;
+        };
+        let (action, reason) = match verdict {
+            Verdict::Keep(reason) => ("keep", reason),
+            Verdict::Wait(reason) => ("wait", reason),
+            Verdict::Delete { reason, files } => {
+                let detail = if files { format!("{}, with its files", reason) } else { reason.clone() };
+                plan.change(Action::Delete, "torrent", &format!("{} {}", short_id(t), t.name), detail).size = t.size;
+                if files {
+                    with_files.push(t.hash.as_str());
+                    ("delete + files", reason)
+                } else {
+                    without_files.push(t.hash.as_str());
+                    ("delete", reason)
+                }
+            }
+        };
+        rows.push(CleanupRow {
+            id: short_id(t).to_string(),
+            name: truncate(&t.name, 40),
+            tags: t.tags.clone().unwrap_or_default(),
+            action: action.to_string(),
+            reason,
+        });
+    }
+    if rows.is_empty() && !json {
+        println!("{}", tr!("cleanup-none"));
+        return Ok(());
+    }
+    for (hashes, files) in [(&without_files, false), (&with_files, true)] {
+        if !hashes.is_empty() {
+            plan.request("torrents/delete", &[("hashes", &hashes.join("|")), ("deleteFiles", if files { "true" } else { "false" })]);
+        }
+    }
+    if !json {
+        println!("{}", theme::table(rows));
+    }
+    if session.dry_run {
+        return plan.print(json);
+    }
+    let count = with_files.len() + without_files.len();
+    if count > 0 && !confirm(&tr!("cleanup-confirm", count = count, with_files = with_files.len()), &tr!("delete-what", count = count))? {
+        println!("{}", tr!("nothing-deleted"));
+        return Ok(());
+    }
+    plan.apply(session).await?;
+    println!("{}", tr!("cleanup-deleted", count = count, with_files = with_files.len()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(tags: &str, completion_on: i64) -> TorrentInfo {
+        serde_json::from_value(serde_json::json!({
+            "name": "t", "hash": "aaaabbbbcccc", "state": "uploading", "tags": tags, "completion_on": completion_on
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn keep_wins_and_due_torrents_are_deleted() {
+        let config = [
+            RetentionPolicy { tag: "keep".into(), delete_after: "never".into(), delete_files: false },
+            RetentionPolicy { tag: "temp".into(), delete_after: "7d".into(), delete_files: true },
+        ];
+        let policies = parse_policies(&config).unwrap();
+        let now = 100 * 86400;
+        let ten_days_ago = (now - 10 * 86400) as i64;
+        assert!(evaluate(&torrent("iso", ten_days_ago), &policies, now).is_none());
+        assert!(matches!(evaluate(&torrent("temp, keep", ten_days_ago), &policies, now), Some(Verdict::Keep(_))));
+        assert!(matches!(evaluate(&torrent("temp", -1), &policies, now), Some(Verdict::Wait(_))));
+        assert!(matches!(evaluate(&torrent("temp", now as i64 - 86400), &policies, now), Some(Verdict::Wait(_))));
+        assert!(matches!(evaluate(&torrent("temp", ten_days_ago), &policies, now), Some(Verdict::Delete { files: true, .. })));
+        let bad = [RetentionPolicy { tag: "x".into(), delete_after: "soon".into(), delete_files: false }];
+        assert!(parse_policies(&bad).is_err());
+    }
+}
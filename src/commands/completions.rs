@@ -0,0 +1,169 @@
+//! `rbit completions <shell>`: completion scripts generated from the command-line definition.
+//! For bash, zsh and fish the script also completes torrent ids, asking the server through the
+//! hidden `rbit __complete-hashes` helper.
+
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use clap::Command;
+use clap_complete::Shell;
+
+use crate::api::Session;
+use crate::commands::manage::short_id;
+
+/// Subcommands that take torrent ids, by path (`move`, `limit torrent`), and whether they take
+/// several.
+fn id_commands(cmd: &Command, path: &str, found: &mut Vec<(String, bool)>) {
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set()) {
+        let sub_path = if path.is_empty() { sub.get_name().to_string() } else { format!("{} {}", path, sub.get_name()) };
+        let ids = sub.get_positionals().find(|a| a.get_id() == "id" || a.get_id() == "ids");
+        if let Some(arg) = ids {
+            found.push((sub_path.clone(), arg.get_id() == "ids"));
+        }
+        id_commands(sub, &sub_path, found);
+    }
+}
+
+/// Top-level options that take a value, so their values are not mistaken for subcommands.
+fn value_options(cmd: &Command) -> Vec<String> {
+    let mut names = Vec::new();
+    for arg in cmd.get_arguments().filter(|a| !a.is_positional() && a.get_action().takes_values()) {
+        if let Some(short) = arg.get_short() {
+            names.push(format!("-{}", short));
+        }
+        if let Some(long) = arg.get_long() {
+            names.push(format!("--{}", long));
+        }
+    }
+    names
+}
+
+/// Shell `case` patterns matching the words typed so far when a torrent id comes next.
+fn id_patterns(cmd: &Command) -> Vec<String> {
+    let mut found = Vec::new();
+    id_commands(cmd, "", &mut found);
+    let mut patterns = Vec::new();
+    for (path, several) in found {
+        patterns.push(format!("'{}'", path));
+        if several {
+            patterns.push(format!("'{} '*", path));
+        }
+    }
+    patterns
+}
+
+const BASH: &str = r#"
+# torrent ids, from the server
+_rbit_with_ids() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" words=() opts=() i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${COMP_WORDS[i]}" in
+            @OPTIONS@) opts+=("${COMP_WORDS[i]}" "${COMP_WORDS[i+1]}"); ((i++)) ;;
+            -*) ;;
+            *) words+=("${COMP_WORDS[i]}") ;;
+        esac
+    done
+    if [[ "$cur" != -* ]]; then
+        case "${words[*]}" in
+            @PATTERNS@)
+                COMPREPLY=($(compgen -W "$(rbit "${opts[@]}" __complete-hashes 2>/dev/null | cut -f1)" -- "$cur"))
+                return ;;
+        esac
+    fi
+    _rbit "$@"
+}
+complete -F _rbit_with_ids -o bashdefault -o default rbit
+"#;
+
+const ZSH: &str = r#"
+# torrent ids, from the server; needs the script to be sourced, e.g. `source <(rbit completions zsh)`
+_rbit_with_ids() {
+    local -a typed opts ids
+    local i
+    for ((i = 2; i < CURRENT; i++)); do
+        case ${words[i]} in
+            @OPTIONS@) opts+=(${words[i]} ${words[i+1]}); ((i++)) ;;
+            -*) ;;
+            *) typed+=(${words[i]}) ;;
+        esac
+    done
+    if [[ ${words[CURRENT]} != -* ]]; then
+        case "${typed[*]}" in
+            @PATTERNS@)
+                ids=(${(f)"$(rbit $opts __complete-hashes 2>/dev/null | tr '\t' ':')"})
+                _describe 'torrent' ids
+                return ;;
+        esac
+    fi
+    _rbit "$@"
+}
+compdef _rbit_with_ids rbit
+"#;
+
+const FISH: &str = r#"
+# torrent ids, from the server
+function __rbit_id_context --description 'Print the options reaching the server when a torrent id comes next'
+    set -l tokens (commandline -opc)
+    set -l opts
+    set -l typed
+    set -l i 2
+    while test $i -le (count $tokens)
+        switch $tokens[$i]
+            case @OPTIONS@
+                set -a opts $tokens[$i] $tokens[(math $i + 1)]
+                set i (math $i + 1)
+            case '-*'
+            case '*'
+                set -a typed $tokens[$i]
+        end
+        set i (math $i + 1)
+    end
+    switch "$typed"
+        case @PATTERNS@
+            test (count $opts) -gt 0; and printf '%s\n' $opts
+            return 0
+    end
+    return 1
+end
+complete -c rbit -n __rbit_id_context -f -a '(rbit (__rbit_id_context) __complete-hashes 2>/dev/null)'
+"#;
+
+/// Print the completion script for `shell`.
+pub fn completions(cmd: &mut Command, shell: Shell) {
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(shell, cmd, name, &mut io::stdout());
+    let (template, separator) = match shell {
+        Shell::Bash => (BASH, "|"),
+        Shell::Zsh => (ZSH, "|"),
+        Shell::Fish => (FISH, " "),
+        _ => return,
+    };
+    let script = template
+        .replace("@OPTIONS@", &value_options(cmd).join(separator))
+        .replace("@PATTERNS@", &id_patterns(cmd).join(separator));
+    print!("{}", script);
+}
+
+/// `rbit __complete-hashes`: one `id<TAB>name` line per torrent, for the completion scripts.
+/// Best effort: never prompts, gives up quickly, and prints nothing on errors.
+pub async fn hashes(session: &mut Session) {
+    session.interactive = false;
+    session.retry.retries = 0;
+    let listing = async {
+        session.login().await?;
+        session.torrents("all").await
+    };
+    let Ok(Ok(torrents)) = tokio::time::timeout(Duration::from_secs(3), listing).await else {
+        return;
+    };
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for t in &torrents {
+        *seen.entry(short_id(t)).or_insert(0) += 1;
+    }
+    for t in &torrents {
+        // the short id unless two torrents share it
+        let id = if seen[short_id(t)] > 1 { t.hash.as_str() } else { short_id(t) };
+        println!("{}\t{}", id, t.name.replace(['\t', '\n'], " "));
+    }
+}
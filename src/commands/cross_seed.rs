@@ -0,0 +1,106 @@
+//! `rbit cross-seed <id> --tracker <url>`: seed a complete torrent's data on another tracker as
+//! well. The torrent is exported, its trackers are replaced with the new one and it is added back
+//! as a second torrent on the same files, without checking them again.
+
+use std::path::PathBuf;
+
+use crate::api::{AddOptions, ContentLayout, Session};
+use crate::commands::export;
+use crate::commands::manage::short_id;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::torrent::{self, Metainfo, Value};
+
+/// The .torrent in `data` announcing to `tracker` only, with `source` in its info dictionary when
+/// given. Private trackers usually want their own source tag, which also gives the copy an
+/// info-hash of its own.
+fn for_tracker(data: &[u8], tracker: &str, source: Option<&str>) -> anyhow::Result<Vec<u8>> {
+    let Value::Dict(mut root) = torrent::decode(data)? else {
+        anyhow::bail!("not a torrent file: top level is not a dictionary");
+    };
+    root.remove(b"announce-list".as_slice());
+    root.insert(b"announce".to_vec(), Value::Bytes(tracker.as_bytes().to_vec()));
+    if let Some(source) = source {
+        let Some(Value::Dict(info)) = root.get_mut(b"info".as_slice()) else {
+            anyhow::bail!("not a torrent file: missing info dictionary");
+        };
+        info.insert(b"source".to_vec(), Value::Bytes(source.as_bytes().to_vec()));
+    }
+    Ok(torrent::encode(&Value::Dict(root)))
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['/', '\\']) == b.trim_end_matches(['/', '\\'])
+}
+
+pub async fn cross_seed(session: &Session, id: &str, tracker: &str, source: Option<&str>, check: bool) -> anyhow::Result<()> {
+    let host = reqwest::Url::parse(tracker)
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https" | "udp"))
+        .and_then(|u| u.host_str().map(str::to_string))
+        .ok_or_else(|| RbitError::InvalidInput(format!("{} is not an http(s) or udp announce URL", tracker)))?;
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let progress = t.progress.unwrap_or(0.0);
+    if progress < 1.0 {
+        let percent = format!("{:.1}", progress * 100.0);
+        return Err(RbitError::InvalidInput(tr!("torrent-incomplete", name = t.name.as_str(), percent = percent)).into());
+    }
+
+    let data = for_tracker(&export::fetch(session, &t).await?, tracker, source)?;
+    let meta = Metainfo::parse(&data)?;
+    if meta.info_hash.eq_ignore_ascii_case(&t.hash) {
+        return Err(RbitError::InvalidInput(format!(
+            "the copy would have the same info-hash as {}, so qBittorrent would not add it; give the tracker's --source tag, or add the tracker to the torrent with `rbit trackers {} --add URL`",
+            t.name,
+            short_id(&t)
+        ))
+        .into());
+    }
+    if let Some(existing) = session.find_torrent(&meta.info_hash).await? {
+        println!("{}", tr!("cross-seed-exists", name = t.name.as_str(), tracker = host, id = short_id(&existing)));
+        return Ok(());
+    }
+
+    let save_path = t.save_path.clone().unwrap_or_default();
+    // a multi-file torrent added without its top folder has its files right in the save path
+    let layout = match t.content_path.as_deref() {
+        Some(content) if meta.files.len() > 1 && same_path(content, &save_path) => ContentLayout::NoSubfolder,
+        _ => ContentLayout::Original,
+    };
+    let options = AddOptions {
+        save_path: Some(PathBuf::from(&save_path)),
+        skip_checking: !check,
+        category: t.category.clone().filter(|c| !c.is_empty()),
+        content_layout: Some(layout),
+        ..Default::default()
+    };
+    let file_name = format!("{}.torrent", export::file_name_for(&t.name));
+    session.add_torrent_data(&[(file_name, data)], &options).await?;
+    if !session.dry_run {
+        println!("{}", tr!("cross-seed-added", name = t.name.as_str(), tracker = host, id = &meta.info_hash[..8], path = save_path.as_str()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trackers_are_replaced_and_the_source_set() {
+        let original = b"d8:announce12:http://a/ann13:announce-listll12:http://a/ann12:http://b/annee4:infod6:lengthi5e4:name1:x12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaa7:privatei1eee";
+        assert_eq!(torrent::encode(&torrent::decode(original).unwrap()), original.to_vec());
+        let before = Metainfo::parse(original).unwrap();
+
+        let same = Metainfo::parse(&for_tracker(original, "https://c/ann", None).unwrap()).unwrap();
+        assert_eq!(same.trackers, ["https://c/ann"]);
+        assert_eq!(same.info_hash, before.info_hash);
+
+        let copy = for_tracker(original, "https://c/ann", Some("C")).unwrap();
+        let after = Metainfo::parse(&copy).unwrap();
+        assert_ne!(after.info_hash, before.info_hash);
+        assert_eq!(torrent::decode(&copy).unwrap().get("info").and_then(|i| i.get("source")).and_then(Value::as_str).as_deref(), Some("C"));
+        assert!(after.private);
+    }
+}
@@ -0,0 +1,694 @@
+//! `rbit daemon`: watch folders for .torrent and .magnet files and add them as they appear.
+//!
+//! Each pass adds the files that have settled (not modified for a couple of seconds, so half
+//! written downloads are left alone). Files the server now has, added or already there, go to
+//! `done/` in their folder and files that cannot be added to `failed/`. While the server cannot
+//! be reached, or rejects the login, files stay where they are and are tried again next pass.
+//!
+//! Only one daemon watches a server at a time, and a pass is skipped while another rbit command
+//! changes many torrents on it (see [`crate::lock`]).
+//!
+//! Each pass also looks at every torrent on the server, to record a stats history snapshot every
+//! few minutes and, with `[notifications]` set up, to notify about the torrents that completed,
+//! failed or stalled since the last pass.
+//!
+//! With a `[prune]` policy in the config, it also deletes the torrents that policy says are
+//! done seeding, every [`PRUNE_EVERY`].
+//!
+//! With `[[schedule]]` windows, it turns the alternative speed limits on and off as the
+//! schedule says. It only switches when a window starts or ends, so switching by hand in
+//! between holds until the next change.
+//!
+//! With `[hooks] on_complete`, it runs that command for every torrent that completes, as `wait`
+//! and `add --follow` do for the torrents they watch.
+//!
+//! With `[disk] min_free`, it pauses downloads while the disk is low on space, and resumes them
+//! once there is enough again (see [`crate::space`]). With a `[guard] command`, it pauses every
+//! torrent while that check fails (see [`crate::guard`]).
+//!
+//! When a config file changes, the daemon reads it again before its next pass and goes on with
+//! the new folders, rules, prune policy, schedule, hooks and notifications, saying which parts
+//! changed. A config that does not load is reported and the old one kept. The server and its
+//! connection settings stay as they were until a restart.
+//!
+//! With `--listen`, it serves what it has been doing over HTTP (see [`daemon_state`]).
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{AddOptions, MainData, Session, TorrentInfo};
+use crate::cache;
+use crate::commands::add::{add_item, Outcome};
+use crate::commands::daemon_state::{self, State};
+use crate::commands::manage::stop_start_api;
+use crate::commands::prune::{self, Policy};
+use crate::commands::schedule::{self, Schedule};
+use crate::config::{config_sources, read_config, Config};
+use crate::error::RbitError;
+use crate::format::{duration_human, minutes_limit_human, size_human};
+use crate::guard;
+use crate::history;
+use crate::hold::{Change, Hold};
+use crate::hooks;
+use crate::i18n::tr;
+use crate::interrupt::{self, Interrupt};
+use crate::lock;
+use crate::notifications::{self, Event, EventKind, Tracker};
+use crate::rules::Rules;
+use crate::savepath::Vars;
+use crate::space::{self, Threshold};
+use crate::torrent::Metainfo;
+
+/// Files modified more recently than this may still be being written.
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// Time between two prunes; seeding limits are hours or days, so checking more often would
+/// only cost requests.
+const PRUNE_EVERY: Duration = Duration::from_secs(15 * 60);
+
+/// `[[watch]]`: a folder `rbit daemon` adds torrents from. A list rather than a table keyed by
+/// folder, because the config loader lowercases keys.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchDir {
+    /// `~/` is the home directory
+    pub dir: PathBuf,
+    /// Category given to torrents from this folder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Save path on the server for torrents from this folder (default: `default_save_path`, or
+    /// the server's)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+}
+
+impl WatchDir {
+    fn path(&self) -> PathBuf {
+        match (self.dir.strip_prefix("~"), BaseDirs::new()) {
+            (Ok(rest), Some(dirs)) => dirs.home_dir().join(rest),
+            _ => self.dir.clone(),
+        }
+    }
+}
+
+/// .torrent and .magnet files in `dir` ready to be added, oldest first. Hidden files are skipped.
+fn settled_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let now = SystemTime::now();
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| anyhow::anyhow!("{}: {}", dir.display(), e))? {
+        let entry = entry?;
+        let path = entry.path();
+        let wanted = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("torrent") || ext.eq_ignore_ascii_case("magnet"));
+        if !wanted || entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        let Ok(modified) = metadata.modified() else { continue };
+        if metadata.is_file() && now.duration_since(modified).unwrap_or_default() >= SETTLE {
+            files.push((modified, path));
+        }
+    }
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// What to add for a file: the file itself, or the magnet links in a .magnet file (one per
+/// line; blank lines and `#` comments are skipped).
+fn inputs_of(path: &Path) -> anyhow::Result<Vec<String>> {
+    if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("magnet")) {
+        return Ok(vec![path.to_string_lossy().to_string()]);
+    }
+    let text = fs::read_to_string(path)?;
+    let links: Vec<String> = text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')).map(str::to_string).collect();
+    if let Some(bad) = links.iter().find(|l| !l.starts_with("magnet:")) {
+        anyhow::bail!("not a magnet link: {}", bad);
+    }
+    if links.is_empty() {
+        anyhow::bail!("no magnet links in the file");
+    }
+    Ok(links)
+}
+
+/// Move a processed file into `sub` next to it, keeping both when the name is taken.
+fn move_into(path: &Path, sub: &str) -> anyhow::Result<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new(".")).join(sub);
+    fs::create_dir_all(&dir)?;
+    let name = path.file_name().expect("a file in a watched folder").to_string_lossy().to_string();
+    let mut target = dir.join(&name);
+    if target.exists() {
+        target = dir.join(format!("{}-{}", cache::now(), name));
+    }
+    fs::rename(path, &target).map_err(|e| anyhow::anyhow!("could not move {} to {}: {}", path.display(), dir.display(), e))?;
+    Ok(target)
+}
+
+/// Failures worth trying again later rather than giving up on the file.
+fn is_transient(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| matches!(c.downcast_ref::<RbitError>(), Some(RbitError::Connection(_) | RbitError::Auth(_))))
+}
+
+/// The number of the rule that applies to `input`, a .torrent file or magnet link, as far as
+/// it tells before the add.
+fn rule_for(input: &str, rules: &Rules) -> Option<usize> {
+    let vars = match input.starts_with("magnet:") {
+        true => Vars::of_link(input),
+        false => Metainfo::from_file(Path::new(input)).map(|meta| Vars::of_metainfo(&meta)).unwrap_or_default(),
+    };
+    rules.find(vars.name.as_deref(), vars.tracker.as_deref()).map(|(n, _)| n)
+}
+
+/// Add the settled files of one folder. Stops at the first transient failure, leaving the rest.
+async fn scan(session: &Session, watch: &WatchDir, base: &AddOptions, state: &State) -> anyhow::Result<()> {
+    let dir = watch.path();
+    let mut options = base.clone();
+    if let Some(ref path) = watch.save_path {
+        options.save_path = Some(PathBuf::from(path));
+        options.fixed_save_path = true;
+    }
+    options.category = watch.category.clone().or(options.category);
+
+    let files = settled_files(&dir)?;
+    if !files.is_empty() && !session.dry_run {
+        // cheap with a cached session; a server that is down at startup is retried like any other
+        session.login().await?;
+    }
+    for file in files {
+        let label = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let mut result = inputs_of(&file);
+        if let Ok(ref inputs) = result {
+            for input in inputs {
+                if !options.rules.is_empty() {
+                    state.rule_result(rule_for(input, &options.rules));
+                }
+                match add_item(session, input, &options).await {
+                    Ok(Outcome::Added) => {
+                        println!("{}", tr!("daemon-added", name = label.as_str(), destination = options.destination()));
+                        state.record("added", &label, options.destination());
+                    }
+                    Ok(Outcome::Duplicate(name)) => {
+                        println!("{}", tr!("daemon-duplicate", name = label.as_str(), existing = name.as_str()));
+                        state.record("duplicate", &label, name);
+                    }
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+        }
+        match result {
+            Err(e) if is_transient(&e) => return Err(e),
+            Ok(_) if session.dry_run => println!("[dry-run] would move {} to done/", label),
+            Ok(_) => {
+                move_into(&file, "done")?;
+            }
+            Err(e) => {
+                println!("{}", tr!("daemon-add-failed", name = label.as_str(), reason = format!("{:#}", e)));
+                state.record("failed", &label, format!("{:#}", e));
+                if session.dry_run {
+                    println!("[dry-run] would move {} to failed/", label);
+                } else {
+                    move_into(&file, "failed")?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// What the daemon does, from the command line and the config.
+#[derive(Debug)]
+pub struct Settings {
+    pub watches: Vec<WatchDir>,
+    /// How torrents from the folders are added
+    pub options: AddOptions,
+    /// Record stats history snapshots (`[history] auto_record`)
+    pub record_history: bool,
+    pub prune: Option<Policy>,
+    pub schedule: Schedule,
+    /// `[hooks] on_complete`
+    pub on_complete: Option<String>,
+    /// `[disk]` limits
+    pub disk: Option<Threshold>,
+    /// `[guard] command`
+    pub guard: Option<String>,
+}
+
+impl Settings {
+    /// The settings in `config`, with the folders given on the command line besides its
+    /// `[[watch]]` tables.
+    pub fn from_config(config: &Config, cli_watches: &[WatchDir]) -> anyhow::Result<Settings> {
+        let settings = Settings {
+            watches: config.watch.iter().chain(cli_watches).cloned().collect(),
+            options: AddOptions {
+                save_path: config.default_save_path.as_ref().map(PathBuf::from),
+                rules: Rules::from_config(&config.rules)?,
+                ..AddOptions::default()
+            },
+            record_history: config.history.auto_record(),
+            prune: config.prune.policy()?,
+            schedule: Schedule::from_config(&config.schedule)?,
+            on_complete: config.hooks.on_complete.clone(),
+            disk: config.disk.threshold()?,
+            guard: config.guard.command.clone(),
+        };
+        if settings.watches.is_empty() && settings.prune.is_none() && settings.schedule.is_empty() && settings.disk.is_none() && settings.guard.is_none() {
+            return Err(RbitError::InvalidInput(tr!("daemon-nothing-to-do")).into());
+        }
+        for watch in &settings.watches {
+            if !watch.path().is_dir() {
+                return Err(RbitError::InvalidInput(tr!("daemon-no-watch-dir", path = watch.path().display().to_string())).into());
+            }
+        }
+        for path in settings.disk.iter().flat_map(|d| &d.paths) {
+            if !path.is_dir() {
+                return Err(RbitError::InvalidInput(tr!("daemon-no-disk-path", path = path.display().to_string())).into());
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Say what the daemon is going to do.
+    fn announce(&self, poll: Duration) {
+        let dirs: Vec<String> = self.watches.iter().map(|w| w.path().display().to_string()).collect();
+        if !dirs.is_empty() {
+            println!("{}", tr!("daemon-watching", dirs = dirs.join(", "), every = duration_human(poll)));
+        }
+        if self.prune.is_some() {
+            println!("{}", tr!("daemon-pruning", every = minutes_limit_human(PRUNE_EVERY.as_secs() as i64 / 60)));
+        }
+        if !self.schedule.is_empty() {
+            println!("{}", tr!("daemon-scheduling"));
+        }
+        if let Some(ref disk) = self.disk {
+            let message = if disk.pause { "daemon-disk-pause" } else { "daemon-disk-notify" };
+            println!("{}", tr!(message, limit = size_human(disk.min_free)));
+        }
+        if let Some(ref command) = self.guard {
+            println!("{}", tr!("daemon-guarding", command = command.as_str()));
+        }
+    }
+
+    /// Whether a pass needs to look at every torrent on the server.
+    fn tracks_torrents(&self) -> bool {
+        notifications::enabled() || self.record_history || self.on_complete.is_some() || self.disk.is_some() || self.guard.is_some()
+    }
+}
+
+/// Pause (or resume) the torrents with `hashes`.
+async fn set_paused(session: &Session, hashes: &[String], pause: bool) -> anyhow::Result<()> {
+    let endpoint = match (pause, stop_start_api(session).await?) {
+        (true, true) => "torrents/stop",
+        (true, false) => "torrents/pause",
+        (false, true) => "torrents/start",
+        (false, false) => "torrents/resume",
+    };
+    session.post_form(endpoint, &[("hashes", &hashes.join("|"))]).await?;
+    Ok(())
+}
+
+/// The lowest free space of the server's default save path and the `[disk] paths`.
+async fn free_space(session: &Session, disk: &Threshold) -> anyhow::Result<Option<u64>> {
+    let maindata: MainData = session.get_json("sync/maindata").await?;
+    let mut free = maindata.server_state.and_then(|s| s.free_space_on_disk);
+    for path in &disk.paths {
+        let local = space::local_free(path)?;
+        free = Some(free.map_or(local, |f| f.min(local)));
+    }
+    Ok(free)
+}
+
+/// What the daemon pauses torrents for.
+#[derive(Default)]
+struct Holds {
+    space: Hold,
+    guard: Hold,
+}
+
+/// Say that a hold started or ended, and notify about it.
+async fn announce(session: &Session, state: &State, kind: EventKind, message: String) {
+    println!("{}", message);
+    let what = if kind == EventKind::Guard { "guard" } else { "low_space" };
+    state.record(what, "", message.clone());
+    notifications::notify_server(kind, &session.host, &message).await;
+}
+
+/// Pause `pause` and, when the hold ended, resume what it paused; how many were resumed. While
+/// the `other` hold is on, it takes them over instead.
+#[allow(clippy::too_many_arguments)]
+async fn enforce(session: &Session, hold: &mut Hold, other: &mut Hold, change: Option<Change>, pause: &[String], torrents: &[TorrentInfo], state: &State, why: &str) -> anyhow::Result<usize> {
+    let mut resumed = 0;
+    if change == Some(Change::Ended) {
+        let resume = hold.take_paused();
+        if other.is_on() {
+            other.adopt(resume);
+        } else if !resume.is_empty() {
+            set_paused(session, &resume, false).await?;
+            resumed = resume.len();
+        }
+    }
+    if !pause.is_empty() {
+        set_paused(session, pause, true).await?;
+        for t in torrents.iter().filter(|t| pause.contains(&t.hash.to_lowercase())) {
+            println!("{}", tr!("daemon-paused-torrent", name = t.name.as_str(), why = why));
+            state.record("paused", &t.name, why);
+        }
+    }
+    Ok(resumed)
+}
+
+/// Pause the downloads when space runs low, and resume them once it recovers.
+async fn guard_space(session: &Session, disk: &Threshold, holds: &mut Holds, torrents: &[TorrentInfo], state: &State) -> anyhow::Result<()> {
+    let Holds { space: hold, guard } = holds;
+    let Some(free) = free_space(session, disk).await? else {
+        return Ok(());
+    };
+    let (change, pause) = hold.update(disk.low(hold.is_on(), free), disk.pause, torrents, space::downloading);
+    if change == Some(Change::Started) {
+        let message = if disk.pause { "daemon-low-space-pausing" } else { "daemon-low-space" };
+        let message = tr!(message, host = session.host.as_str(), free = size_human(free), limit = size_human(disk.min_free));
+        announce(session, state, EventKind::LowSpace, message).await;
+    }
+    let resumed = enforce(session, hold, guard, change, &pause, torrents, state, &tr!("daemon-while-low-space")).await?;
+    if change == Some(Change::Ended) {
+        let message = tr!("daemon-space-back", host = session.host.as_str(), free = size_human(free), count = resumed);
+        announce(session, state, EventKind::LowSpace, message).await;
+    }
+    Ok(())
+}
+
+/// Pause every torrent while the `[guard]` check fails (`failure` says why), and resume them
+/// once it passes.
+async fn guard_torrents(session: &Session, failure: Option<&str>, holds: &mut Holds, torrents: &[TorrentInfo], state: &State) -> anyhow::Result<()> {
+    let Holds { guard: hold, space } = holds;
+    let (change, pause) = hold.update(failure.is_some(), true, torrents, guard::running);
+    if let (Some(Change::Started), Some(why)) = (change, failure) {
+        announce(session, state, EventKind::Guard, tr!("daemon-guard-failed", reason = why, host = session.host.as_str())).await;
+    }
+    let resumed = enforce(session, hold, space, change, &pause, torrents, state, &tr!("daemon-while-guard")).await?;
+    if change == Some(Change::Ended) {
+        announce(session, state, EventKind::Guard, tr!("daemon-guard-passes", count = resumed, host = session.host.as_str())).await;
+    }
+    Ok(())
+}
+
+/// Notify about what changed on the server since the last pass, run the on-complete hook for
+/// the torrents that completed, pause or resume torrents for the free space and the `[guard]`
+/// check (which failed if `guard_failure` says why), and record a history snapshot when one is
+/// due.
+async fn check(session: &Session, tracker: &mut Tracker, holds: &mut Holds, guard_failure: Option<&str>, settings: &Settings, state: &State) -> anyhow::Result<()> {
+    session.login().await?;
+    let torrents = session.torrents("all").await?;
+    state.synced(torrents.len());
+    for (kind, torrent) in tracker.update(&torrents) {
+        let what = match kind {
+            EventKind::Complete => "complete",
+            EventKind::Error => "error",
+            EventKind::Stalled => "stalled",
+            EventKind::LowSpace => "low_space",
+            EventKind::Guard => "guard",
+        };
+        state.record(what, &torrent.name, torrent.state.clone());
+        notifications::notify(Event { kind, torrent, host: &session.host }).await;
+        if let (EventKind::Complete, Some(command)) = (kind, &settings.on_complete) {
+            match hooks::on_complete(command, torrent, &session.host, session.dry_run).await {
+                Ok(()) => {
+                    println!("{}", tr!("daemon-hook-ran", name = torrent.name.as_str()));
+                    state.record("hook", &torrent.name, command);
+                }
+                Err(e) => {
+                    eprintln!("{} {:#}", tr!("warning"), e);
+                    state.record("hook-failed", &torrent.name, format!("{:#}", e));
+                }
+            }
+        }
+    }
+    if settings.guard.is_some() {
+        if let Err(e) = guard_torrents(session, guard_failure, holds, &torrents, state).await {
+            eprintln!("{} {}", tr!("warning"), tr!("daemon-guard-error", reason = format!("{:#}", e)));
+        }
+    }
+    if let Some(ref disk) = settings.disk {
+        if let Err(e) = guard_space(session, disk, holds, &torrents, state).await {
+            eprintln!("{} {}", tr!("warning"), tr!("daemon-space-error", reason = format!("{:#}", e)));
+        }
+    }
+    if settings.record_history {
+        history::record_due(session, &torrents).await;
+    }
+    Ok(())
+}
+
+/// Top-level config keys the daemon picks up when they change.
+const RELOADED: [&str; 10] = ["watch", "default_save_path", "rules", "prune", "schedule", "hooks", "notifications", "history", "disk", "guard"];
+
+/// Top-level config keys that only apply once the daemon is started again.
+const NEEDS_RESTART: [&str; 5] = ["qbittorrent", "servers", "http", "language", "units"];
+
+/// The config files the daemon was started with, to read them again when they change.
+pub struct Reload {
+    /// `--config`, if given
+    path: Option<PathBuf>,
+    strict: bool,
+    /// Watch folders from the command line, kept across reloads
+    cli_watches: Vec<WatchDir>,
+    /// When each file was last modified, `None` for those that do not exist
+    stamps: Vec<Option<SystemTime>>,
+    /// The config as last loaded
+    table: toml::Table,
+}
+
+/// Modification times of `files`.
+fn stamps(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files.iter().map(|file| fs::metadata(file).and_then(|m| m.modified()).ok()).collect()
+}
+
+/// The top-level keys whose values differ between `old` and `new`.
+fn changed_keys(old: &toml::Table, new: &toml::Table) -> Vec<String> {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter().filter(|k| old.get(*k) != new.get(*k)).cloned().collect()
+}
+
+/// `key` as a config file writes it: `[hooks]` for a table, `[[watch]]` for a list of them.
+fn header(key: &str) -> String {
+    match key {
+        "watch" | "rules" | "schedule" | "retention" => format!("[[{}]]", key),
+        "default_save_path" | "language" | "units" | "no_confirm" | "no_self_update" => key.to_string(),
+        _ => format!("[{}]", key),
+    }
+}
+
+impl Reload {
+    /// Keep an eye on the files `read_config(path, strict)` reads, which gave `config`.
+    pub fn new(path: Option<PathBuf>, strict: bool, config: &Config, cli_watches: Vec<WatchDir>) -> Reload {
+        let stamps = stamps(&config_sources(path.clone()));
+        let table = toml::Table::try_from(config).unwrap_or_default();
+        Reload { path, strict, cli_watches, stamps, table }
+    }
+
+    /// New settings when a config file changed since the last call and still loads, with the
+    /// sections that changed; `None` otherwise.
+    fn check(&mut self) -> Option<(Settings, Vec<String>)> {
+        let now = stamps(&config_sources(self.path.clone()));
+        if now == self.stamps {
+            return None;
+        }
+        self.stamps = now;
+        let loaded = read_config(self.path.clone(), self.strict).and_then(|config| Ok((Settings::from_config(&config, &self.cli_watches)?, config)));
+        let (settings, config) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("{} {}", tr!("warning"), tr!("daemon-reload-broken", reason = format!("{:#}", e)));
+                return None;
+            }
+        };
+        let table = toml::Table::try_from(&config).unwrap_or_default();
+        let changed = changed_keys(&self.table, &table);
+        self.table = table;
+        let applied: Vec<String> = changed.iter().filter(|k| RELOADED.contains(&k.as_str())).map(|k| header(k)).collect();
+        let later: Vec<String> = changed.iter().filter(|k| NEEDS_RESTART.contains(&k.as_str())).map(|k| header(k)).collect();
+        if applied.is_empty() {
+            println!("{}", tr!("daemon-reloaded-nothing"));
+        } else {
+            println!("{}", tr!("daemon-reloaded", sections = applied.join(", ")));
+        }
+        if !later.is_empty() {
+            println!("{}", tr!("daemon-restart-needed", sections = later.join(", ")));
+        }
+        notifications::reconfigure(config.notifications);
+        Some((settings, applied))
+    }
+}
+
+/// Do what `settings` say every `poll` until stopped, picking up config changes through
+/// `reload` and serving the daemon's state on `listen`. A dry run makes a single pass that
+/// moves nothing.
+pub async fn run(session: &Session, poll: Duration, mut settings: Settings, mut reload: Reload, listen: Option<SocketAddr>) -> anyhow::Result<()> {
+    // a dry run changes nothing, so it may run next to the real one
+    let _instance = match session.dry_run {
+        true => None,
+        false => match lock::try_exclusive(&lock::for_host("daemon", &session.host))? {
+            Some(held) => Some(held),
+            None => anyhow::bail!(tr!("daemon-running", host = session.host.as_str())),
+        },
+    };
+    let state = State::new(&session.host);
+    state.set_rules(settings.options.rules.len());
+    if let Some(listen) = listen {
+        daemon_state::serve(state.clone(), listen)?;
+    }
+    settings.announce(poll);
+    let mut pruned: Option<Instant> = None;
+    // what the schedule said when the server was last switched to it
+    let mut scheduled: Option<bool> = None;
+
+    let mut stop = Interrupt::listen();
+    let mut tracker = Tracker::default();
+    let mut holds = Holds::default();
+    let mut paused = false;
+    loop {
+        if let Some((new, changed)) = reload.check() {
+            // a new schedule is applied right away, and a new policy prunes on the next pass
+            scheduled = None;
+            pruned = None;
+            settings = new;
+            settings.announce(poll);
+            state.set_rules(settings.options.rules.len());
+            state.record("reload", "", changed.join(", "));
+        }
+        let Settings { watches, options, prune, schedule, .. } = &settings;
+        let guard_failure = match settings.guard.as_deref() {
+            Some(command) => guard::check(command).await.err(),
+            None => None,
+        };
+        match lock::try_exclusive(&lock::for_host("bulk", &session.host))? {
+            Some(_pass) => {
+                if paused {
+                    println!("{}", tr!("daemon-resuming"));
+                    paused = false;
+                    state.record("resumed", "", "");
+                }
+                // what is added while the guard fails would connect to peers before it is paused
+                for watch in watches.iter().filter(|_| guard_failure.is_none()) {
+                    if let Err(e) = scan(session, watch, options, &state).await {
+                        let path = watch.path().display().to_string();
+                        eprintln!("{} {}", tr!("warning"), tr!("daemon-watch-error", path = path, reason = format!("{:#}", e), every = duration_human(poll)));
+                    }
+                }
+                if let Some(policy) = prune.as_ref().filter(|_| pruned.is_none_or(|at| at.elapsed() >= PRUNE_EVERY)) {
+                    match prune::apply(session, policy).await {
+                        Ok(deleted) => {
+                            pruned = Some(Instant::now());
+                            for (name, why) in deleted {
+                                state.record("pruned", name, why);
+                            }
+                        }
+                        Err(e) => eprintln!("{} {}", tr!("warning"), tr!("daemon-prune-error", reason = format!("{:#}", e), every = duration_human(poll))),
+                    }
+                }
+                let (wanted, why) = schedule.wanted_now();
+                if !schedule.is_empty() && scheduled != Some(wanted) {
+                    match schedule::switch(session, wanted, &why).await {
+                        Ok(switched) => {
+                            scheduled = Some(wanted);
+                            if switched {
+                                state.record("schedule", if wanted { "on" } else { "off" }, why);
+                            }
+                        }
+                        Err(e) => eprintln!("{} {}", tr!("warning"), tr!("daemon-schedule-error", reason = format!("{:#}", e), every = duration_human(poll))),
+                    }
+                }
+            }
+            None if !paused => {
+                println!("{}", tr!("daemon-held", host = session.host.as_str()));
+                paused = true;
+                state.record("paused", "", "another rbit command changes torrents");
+            }
+            None => {}
+        }
+        if settings.tracks_torrents() && !session.dry_run {
+            if let Err(e) = check(session, &mut tracker, &mut holds, guard_failure.as_deref(), &settings, &state).await {
+                eprintln!("{} {}", tr!("warning"), tr!("daemon-check-error", reason = format!("{:#}", e)));
+            }
+        }
+        state.pass_done(paused);
+        if session.dry_run {
+            return Ok(());
+        }
+        tokio::select! {
+            _ = stop.recv() => {
+                if settings.record_history {
+                    history::record_now(session).await;
+                }
+                interrupt::wrap_up(session).await;
+                println!("{}", tr!("daemon-stopped"));
+                return Ok(());
+            }
+            _ = tokio::time::sleep(poll) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magnet_files_and_done_folder() {
+        let dir = std::env::temp_dir().join(format!("rbit-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("x.magnet");
+        fs::write(&file, "# from the tracker\nmagnet:?xt=urn:btih:aaaa\n\n").unwrap();
+        assert_eq!(inputs_of(&file).unwrap(), ["magnet:?xt=urn:btih:aaaa"]);
+        fs::create_dir_all(dir.join("done")).unwrap();
+        fs::write(dir.join("done/x.magnet"), "").unwrap();
+        let moved = move_into(&file, "done").unwrap();
+        assert!(!file.exists() && moved.exists() && moved != dir.join("done/x.magnet"));
+        fs::write(&file, "http://example.com/a.torrent").unwrap();
+        assert!(inputs_of(&file).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reloads_a_changed_config() {
+        let dir = std::env::temp_dir().join(format!("rbit-reload-{}", std::process::id()));
+        let (a, b) = (dir.join("a"), dir.join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let file = dir.join("config.toml");
+        let write = |text: String, age: u64| {
+            fs::write(&file, text).unwrap();
+            let file = fs::File::options().write(true).open(&file).unwrap();
+            file.set_modified(SystemTime::now() - Duration::from_secs(age)).unwrap();
+        };
+        let watching = |folder: &Path| format!("[[watch]]\ndir = {:?}\n", folder.display().to_string());
+        write(watching(&a), 60);
+        let config = read_config(Some(file.clone()), false).unwrap();
+        let mut reload = Reload::new(Some(file.clone()), false, &config, Vec::new());
+        assert!(reload.check().is_none());
+
+        write(watching(&b) + "[hooks]\non_complete = \"true\"\n", 30);
+        let (settings, changed) = reload.check().unwrap();
+        assert_eq!(changed, ["[hooks]", "[[watch]]"]);
+        assert_eq!(settings.watches.iter().map(|w| w.path()).collect::<Vec<_>>(), [b]);
+        assert_eq!(settings.on_complete.as_deref(), Some("true"));
+        assert!(reload.check().is_none());
+
+        // a folder that is not there keeps the old settings, and is reported once
+        write(watching(&dir.join("c")), 20);
+        assert!(reload.check().is_none());
+        assert!(reload.check().is_none());
+        let _ = fs::remove_dir_all(&dir);
+
+        let old = toml::Table::try_from(&config).unwrap();
+        assert!(old.contains_key("watch"));
+        let new: toml::Table = toml::from_str("watch = [{ dir = \"/x\" }]\n[http]\nretries = 1\n").unwrap();
+        assert_eq!(changed_keys(&old, &new), ["http", "watch"]);
+        assert!(changed_keys(&old, &old).is_empty());
+    }
+}
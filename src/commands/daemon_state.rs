@@ -0,0 +1,245 @@
+//! `rbit daemon --listen`: what the daemon has been doing, over HTTP, to check that it does its
+//! job. `/status` is a JSON summary (uptime, the last pass and sync, what the rules made of the
+//! files added), `/metrics` the same as Prometheus metrics and `/events` the recent actions.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::cache;
+use crate::i18n::tr;
+
+/// How many actions `/events` keeps, oldest dropped first.
+const KEEP_EVENTS: usize = 200;
+
+/// Something the daemon did, or that happened to a torrent it watches.
+#[derive(Serialize, Clone, Debug)]
+pub struct Action {
+    /// Unix time
+    pub time: u64,
+    /// `added`, `duplicate`, `failed`, `pruned`, `complete`, `error`, `stalled`, `hook`,
+    /// `hook-failed`, `schedule`, `low_space`, `reload`, `paused` or `resumed`
+    pub kind: &'static str,
+    /// The file or torrent it is about, if any
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub subject: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+#[derive(Default)]
+struct Inner {
+    started: u64,
+    host: String,
+    passes: u64,
+    last_pass: Option<u64>,
+    /// When the torrent list was last fetched from the server
+    last_sync: Option<u64>,
+    torrents: Option<usize>,
+    paused: bool,
+    /// Actions of each kind since the start
+    counts: BTreeMap<&'static str, u64>,
+    /// Files the rules were checked for, by the number of the rule that applied; 0 for none
+    rule_matches: BTreeMap<usize, u64>,
+    rules: usize,
+    events: VecDeque<Action>,
+}
+
+/// The daemon's state, shared with the HTTP endpoint.
+#[derive(Clone, Default)]
+pub struct State(Arc<Mutex<Inner>>);
+
+impl State {
+    pub fn new(host: &str) -> State {
+        State(Arc::new(Mutex::new(Inner { started: cache::now(), host: host.to_string(), ..Inner::default() })))
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Inner) -> T) -> T {
+        f(&mut self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn record(&self, kind: &'static str, subject: impl Into<String>, detail: impl Into<String>) {
+        let action = Action { time: cache::now(), kind, subject: subject.into(), detail: detail.into() };
+        self.with(|s| {
+            *s.counts.entry(kind).or_default() += 1;
+            if s.events.len() == KEEP_EVENTS {
+                s.events.pop_front();
+            }
+            s.events.push_back(action);
+        });
+    }
+
+    /// The rules applied to a file from a watch folder: rule number `rule` (from 1), or none.
+    pub fn rule_result(&self, rule: Option<usize>) {
+        self.with(|s| *s.rule_matches.entry(rule.unwrap_or(0)).or_default() += 1);
+    }
+
+    pub fn set_rules(&self, count: usize) {
+        self.with(|s| s.rules = count);
+    }
+
+    pub fn pass_done(&self, paused: bool) {
+        self.with(|s| {
+            s.passes += 1;
+            s.last_pass = Some(cache::now());
+            s.paused = paused;
+        });
+    }
+
+    pub fn synced(&self, torrents: usize) {
+        self.with(|s| {
+            s.last_sync = Some(cache::now());
+            s.torrents = Some(torrents);
+        });
+    }
+
+    fn status(&self) -> Value {
+        self.with(|s| {
+            let now = cache::now();
+            let rules: BTreeMap<String, u64> = s.rule_matches.iter().map(|(n, count)| (if *n == 0 { "none".to_string() } else { format!("#{}", n) }, *count)).collect();
+            json!({
+                "host": s.host,
+                "started": s.started,
+                "uptime_seconds": now.saturating_sub(s.started),
+                "passes": s.passes,
+                "last_pass": s.last_pass,
+                "last_sync": s.last_sync,
+                "torrents": s.torrents,
+                "paused": s.paused,
+                "rules": { "configured": s.rules, "matches": rules },
+                "actions": s.counts,
+                "last_action": s.events.back(),
+            })
+        })
+    }
+
+    fn events(&self, since: u64) -> Value {
+        self.with(|s| json!(s.events.iter().filter(|a| a.time >= since).collect::<Vec<_>>()))
+    }
+
+    /// The state in the Prometheus text exposition format.
+    fn prometheus(&self) -> String {
+        self.with(|s| {
+            let host = s.host.replace('\\', "\\\\").replace('"', "\\\"");
+            let mut out = String::new();
+            let mut gauge = |name: &str, help: &str, value: Option<u64>| {
+                if let Some(value) = value {
+                    out.push_str(&format!("# HELP rbit_daemon_{} {}\n# TYPE rbit_daemon_{} gauge\n", name, help, name));
+                    out.push_str(&format!("rbit_daemon_{}{{host=\"{}\"}} {}\n", name, host, value));
+                }
+            };
+            gauge("uptime_seconds", "Seconds since the daemon started", Some(cache::now().saturating_sub(s.started)));
+            gauge("passes", "Passes made since the start", Some(s.passes));
+            gauge("last_pass_timestamp_seconds", "When the last pass ended", s.last_pass);
+            gauge("last_sync_timestamp_seconds", "When the torrent list was last fetched from the server", s.last_sync);
+            gauge("torrents", "Torrents on the server at the last sync", s.torrents.map(|n| n as u64));
+            gauge("paused", "1 while another rbit command holds the daemon off", Some(s.paused as u64));
+            out.push_str("# HELP rbit_daemon_actions_total Actions since the start, by kind\n# TYPE rbit_daemon_actions_total counter\n");
+            for (kind, count) in &s.counts {
+                out.push_str(&format!("rbit_daemon_actions_total{{host=\"{}\",kind=\"{}\"}} {}\n", host, kind, count));
+            }
+            out.push_str("# HELP rbit_daemon_rule_matches_total Files added from watch folders, by the rule that applied\n# TYPE rbit_daemon_rule_matches_total counter\n");
+            for (rule, count) in &s.rule_matches {
+                let rule = if *rule == 0 { "none".to_string() } else { rule.to_string() };
+                out.push_str(&format!("rbit_daemon_rule_matches_total{{host=\"{}\",rule=\"{}\"}} {}\n", host, rule, count));
+            }
+            out
+        })
+    }
+}
+
+fn response(status: StatusCode, content_type: &str, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", content_type)
+        .body(Body::from(body))
+        .expect("static response parts are valid")
+}
+
+/// `since=<unix time>` from a query string; 0 without one.
+fn since(query: Option<&str>) -> Result<u64, String> {
+    let Some(value) = query.into_iter().flat_map(|q| q.split('&')).find_map(|pair| pair.strip_prefix("since=")) else {
+        return Ok(0);
+    };
+    value.parse().map_err(|_| format!("since must be a Unix time, not '{}'", value))
+}
+
+fn handle(state: &State, request: &Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET {
+        return response(StatusCode::METHOD_NOT_ALLOWED, "text/plain; charset=utf-8", "only GET is served\n".to_string());
+    }
+    match request.uri().path() {
+        "/status" => response(StatusCode::OK, "application/json", state.status().to_string()),
+        "/metrics" => response(StatusCode::OK, "text/plain; version=0.0.4", state.prometheus()),
+        "/events" => match since(request.uri().query()) {
+            Ok(since) => response(StatusCode::OK, "application/json", state.events(since).to_string()),
+            Err(e) => response(StatusCode::BAD_REQUEST, "text/plain; charset=utf-8", format!("{}\n", e)),
+        },
+        "/" => response(StatusCode::OK, "text/plain", "rbit daemon: see /status, /metrics and /events\n".to_string()),
+        _ => response(StatusCode::NOT_FOUND, "text/plain; charset=utf-8", "not found\n".to_string()),
+    }
+}
+
+/// Start serving `state` on `listen` in the background. Fails right away when the address is
+/// taken, so the daemon does not run without the endpoint it was asked for.
+pub fn serve(state: State, listen: SocketAddr) -> anyhow::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(handle(&state, &request)) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&listen).map_err(|e| anyhow::anyhow!("cannot listen on {}: {}", listen, e))?;
+    println!("{}", tr!("daemon-serving", address = listen.to_string()));
+    tokio::spawn(async move {
+        if let Err(e) = server.serve(make_service).await {
+            eprintln!("{} {}", tr!("warning"), tr!("daemon-serving-stopped", reason = e.to_string()));
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_metrics_and_events() {
+        let state = State::new("http://localhost:8080");
+        state.set_rules(2);
+        state.record("added", "a.torrent", "/srv/tv");
+        state.rule_result(Some(2));
+        state.record("failed", "b.magnet", "not a magnet link");
+        state.rule_result(None);
+        state.synced(7);
+        state.pass_done(false);
+
+        let status = state.status();
+        assert_eq!(status["passes"], 1);
+        assert_eq!(status["torrents"], 7);
+        assert_eq!(status["actions"]["added"], 1);
+        assert_eq!(status["rules"]["matches"]["#2"], 1);
+        assert_eq!(status["rules"]["matches"]["none"], 1);
+        assert_eq!(status["last_action"]["subject"], "b.magnet");
+
+        let metrics = state.prometheus();
+        assert!(metrics.contains("rbit_daemon_actions_total{host=\"http://localhost:8080\",kind=\"failed\"} 1\n"));
+        assert!(metrics.contains("rbit_daemon_rule_matches_total{host=\"http://localhost:8080\",rule=\"2\"} 1\n"));
+        assert!(metrics.contains("rbit_daemon_torrents{host=\"http://localhost:8080\"} 7\n"));
+
+        assert_eq!(state.events(0).as_array().unwrap().len(), 2);
+        assert!(state.events(u64::MAX).as_array().unwrap().is_empty());
+        assert_eq!(since(Some("since=12&x=1")), Ok(12));
+        assert_eq!(since(None), Ok(0));
+        assert!(since(Some("since=soon")).is_err());
+    }
+}
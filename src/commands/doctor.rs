@@ -0,0 +1,78 @@
+//! `rbit doctor`: check the way to the server one step at a time: the config, DNS, the TCP
+//! connection, the login, the API and reading the torrent list, and print what passed and
+//! what failed. The steps after a failed one are skipped, as they would fail for the same
+//! reason.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+use tokio::net::{lookup_host, TcpStream};
+
+use crate::api::ConnectOptions;
+use crate::client::TorrentClient;
+use crate::error::RbitError;
+use crate::i18n::tr;
+
+const TCP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Print a line of the checklist for `check`; when it failed, also the `skipped` checks after it.
+/// The reason for a failure is the error, printed last.
+fn checked(check: &str, result: anyhow::Result<String>, skipped: &[&str]) -> anyhow::Result<()> {
+    match result {
+        Ok(detail) => {
+            println!("[ ok ] {:<9} {}", check, detail);
+            Ok(())
+        }
+        Err(e) => {
+            println!("[FAIL] {}", check);
+            for step in skipped {
+                println!("[skip] {}", step);
+            }
+            Err(e)
+        }
+    }
+}
+
+pub async fn doctor(client: &impl TorrentClient, profile: &str, files: &[PathBuf], connect: &ConnectOptions) -> anyhow::Result<()> {
+    let found: Vec<String> = files.iter().filter(|f| f.exists()).map(|f| f.display().to_string()).collect();
+    let config = match found.is_empty() {
+        true => tr!("doctor-no-config", profile = profile, host = client.host()),
+        false => tr!("doctor-config", profile = profile, host = client.host(), files = found.join(", ")),
+    };
+    checked("config", Ok(config), &[])?;
+
+    let url = Url::parse(client.host())?;
+    let host = url.host_str().unwrap_or_default().to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+    if connect.proxy.is_some() {
+        println!("[skip] {:<9} {}", "dns, tcp", tr!("doctor-proxy"));
+    } else {
+        let addrs: Vec<_> = lookup_host((host.as_str(), port)).await.map(Iterator::collect).unwrap_or_default();
+        let shown: Vec<String> = addrs.iter().map(|a| a.ip().to_string()).collect();
+        let dns = match addrs.is_empty() {
+            false => Ok(tr!("doctor-dns", host = host.as_str(), addresses = shown.join(", "))),
+            true => Err(RbitError::Connection(tr!("doctor-dns-failed", host = host.as_str())).into()),
+        };
+        checked("dns", dns, &["tcp", "login", "version", "torrents"])?;
+
+        let started = Instant::now();
+        let tcp = match tokio::time::timeout(TCP_TIMEOUT, TcpStream::connect(&addrs[..])).await {
+            Ok(Ok(_)) => Ok(tr!("doctor-tcp", host = host.as_str(), port = port, ms = started.elapsed().as_millis() as u64)),
+            Ok(Err(e)) => Err(RbitError::Connection(tr!("doctor-tcp-failed", host = host.as_str(), port = port, reason = e.to_string())).into()),
+            Err(_) => Err(RbitError::Timeout(tr!("doctor-tcp-timeout", host = host.as_str(), port = port, seconds = TCP_TIMEOUT.as_secs())).into()),
+        };
+        checked("tcp", tcp, &["login", "version", "torrents"])?;
+    }
+
+    let login = client.login().await.map(|()| match client.username() {
+        Some(user) => tr!("doctor-login", user = user),
+        None => tr!("doctor-anonymous"),
+    });
+    checked("login", login, &["version", "torrents"])?;
+    checked("version", client.version().await, &["torrents"])?;
+    let torrents = client.torrents().await.map(|t| tr!("doctor-torrents", count = t.len()));
+    checked("torrents", torrents, &[])?;
+    println!("{}", tr!("doctor-ok"));
+    Ok(())
+}
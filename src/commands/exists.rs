@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use crate::api::Session;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::torrent::{info_hash_hex, is_torrent_url, magnet_info_hash, Metainfo};
+
+/// The info-hash of an info-hash, magnet link or local .torrent file.
+fn info_hash_of(input: &str) -> anyhow::Result<String> {
+    if input.starts_with("magnet:") {
+        return magnet_info_hash(input).ok_or_else(|| RbitError::InvalidInput(format!("no BitTorrent v1 info-hash in {}", input)).into());
+    }
+    if is_torrent_url(input) {
+        return Err(RbitError::InvalidInput(format!("{}: a torrent URL's info-hash is only known after downloading it", input)).into());
+    }
+    let path = Path::new(input);
+    match info_hash_hex(input) {
+        Some(hash) if !path.exists() => Ok(hash),
+        _ => Ok(Metainfo::from_file(path)?.info_hash),
+    }
+}
+
+/// Report whether the server has each input's torrent, computing info-hashes locally. Fails with
+/// [`RbitError::Absent`] (exit code 1) unless it has all of them.
+pub async fn exists(session: &Session, inputs: &[String], quiet: bool) -> anyhow::Result<()> {
+    let hashes = inputs.iter().map(|input| info_hash_of(input)).collect::<anyhow::Result<Vec<String>>>()?;
+    session.login().await?;
+    let found = session.find_torrents(&hashes).await?;
+    let mut missing = 0;
+    for hash in &hashes {
+        match found.iter().find(|t| t.hash.eq_ignore_ascii_case(hash)) {
+            Some(t) if !quiet => println!("{}  {}", hash, tr!("exists-found", name = t.name.as_str())),
+            Some(_) => {}
+            None => {
+                missing += 1;
+                if !quiet {
+                    println!("{}  {}", hash, tr!("exists-missing"));
+                }
+            }
+        }
+    }
+    if missing > 0 {
+        return Err(RbitError::Absent { missing, total: hashes.len() }.into());
+    }
+    Ok(())
+}
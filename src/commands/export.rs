@@ -0,0 +1,125 @@
+//! `rbit export`: download torrents' .torrent files from the server (`torrents/export`, Web API
+//! 2.8.14, qBittorrent 4.5), e.g. to back up a collection or cross-seed it elsewhere.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reqwest::StatusCode;
+
+use crate::api::{Session, TorrentInfo};
+use crate::commands::manage::short_id;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::select::Selector;
+use crate::torrent::Metainfo;
+
+/// Longest file name written, in bytes, well under the 255 most file systems allow.
+const MAX_NAME: usize = 200;
+
+/// A torrent's name as a file name: without characters Windows or Unix refuse in one, and
+/// without the trailing dots and spaces Windows drops.
+pub fn file_name_for(name: &str) -> String {
+    let mut clean: String = name.chars().map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c }).collect();
+    if clean.len() > MAX_NAME {
+        let mut end = MAX_NAME;
+        while !clean.is_char_boundary(end) {
+            end -= 1;
+        }
+        clean.truncate(end);
+    }
+    let clean = clean.trim_end_matches(['.', ' ']).trim_start();
+    if clean.is_empty() {
+        "torrent".to_string()
+    } else {
+        clean.to_string()
+    }
+}
+
+/// Where to write a torrent's file in `dir`: `<name>.torrent`, or `<name>-<id>.torrent` when
+/// that holds another torrent. `None` when the torrent is there already.
+fn target_for(dir: &Path, t: &TorrentInfo) -> Option<PathBuf> {
+    let base = file_name_for(&t.name);
+    for candidate in [format!("{}.torrent", base), format!("{}-{}.torrent", base, short_id(t))] {
+        let path = dir.join(candidate);
+        match Metainfo::from_file(&path) {
+            Ok(meta) if meta.info_hash.eq_ignore_ascii_case(&t.hash) => return None,
+            Ok(_) => continue,
+            Err(_) if path.exists() => continue,
+            Err(_) => return Some(path),
+        }
+    }
+    Some(dir.join(format!("{}-{}.torrent", base, t.hash.to_lowercase())))
+}
+
+/// Fetch one torrent's .torrent file.
+pub async fn fetch(session: &Session, t: &TorrentInfo) -> anyhow::Result<Vec<u8>> {
+    let (status, body) = session.get_bytes(&format!("torrents/export?hash={}", t.hash)).await?;
+    match status {
+        s if s.is_success() => Ok(body),
+        StatusCode::NOT_FOUND if body.is_empty() => {
+            Err(RbitError::InvalidInput("the server cannot export torrents (needs qBittorrent 4.5 or later)".to_string()).into())
+        }
+        StatusCode::CONFLICT => anyhow::bail!("qBittorrent does not have its metadata yet"),
+        s => anyhow::bail!("torrents/export failed with {}: {}", s, String::from_utf8_lossy(&body)),
+    }
+}
+
+/// Save the .torrent files of `ids` (or of every torrent, with `all`) that `selector` matches into `dir`.
+pub async fn export(session: &Session, ids: &[String], all: bool, selector: &Selector, dir: &Path) -> anyhow::Result<()> {
+    session.login().await?;
+    let torrents = selector.select(session, ids, all, session.dry_run).await?;
+    if !session.dry_run {
+        fs::create_dir_all(dir).map_err(|e| anyhow::anyhow!("{}: {}", dir.display(), e))?;
+    }
+    let (mut saved, mut skipped, mut failed) = (0, 0, 0);
+    for t in &torrents {
+        let Some(path) = target_for(dir, t) else {
+            skipped += 1;
+            println!("{}", tr!("export-skipped", name = t.name.as_str()));
+            continue;
+        };
+        if session.dry_run {
+            println!("[dry-run] would save {} to {}", t.name, path.display());
+            continue;
+        }
+        let written = match fetch(session, t).await {
+            Ok(data) => fs::write(&path, data).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e)),
+            Err(e) => Err(e),
+        };
+        match written {
+            Ok(()) => {
+                saved += 1;
+                println!("{}", tr!("export-saved", path = path.display().to_string()));
+            }
+            // an old server fails every torrent the same way
+            Err(e) if e.downcast_ref::<RbitError>().is_some() => return Err(e),
+            Err(e) => {
+                failed += 1;
+                println!("{}", tr!("export-failed", name = t.name.as_str(), id = short_id(t), reason = format!("{:#}", e)));
+            }
+        }
+    }
+    if torrents.len() > 1 {
+        let dir = dir.display().to_string();
+        println!("{}", tr!("export-summary", saved = saved, total = torrents.len(), dir = dir, skipped = skipped));
+    }
+    match failed {
+        0 => Ok(()),
+        _ if torrents.len() == 1 => anyhow::bail!("could not export the torrent"),
+        _ => Err(RbitError::PartialFailure { failed, total: torrents.len() }.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_file_names() {
+        assert_eq!(file_name_for("ubuntu-24.04-desktop-amd64.iso"), "ubuntu-24.04-desktop-amd64.iso");
+        assert_eq!(file_name_for("Show: S01/E02 \"final\"?"), "Show_ S01_E02 _final__");
+        assert_eq!(file_name_for("trailing dots... "), "trailing dots");
+        assert_eq!(file_name_for(".."), "torrent");
+        assert!(file_name_for(&"é".repeat(150)).len() <= MAX_NAME);
+    }
+}
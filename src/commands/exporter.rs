@@ -0,0 +1,327 @@
+//! `rbit exporter`: serve the stats history (see `rbit stats record`) over HTTP — as Prometheus
+//! metrics from the newest snapshot, or with `--json-api` as time series for Grafana's JSON
+//! datasource plugin.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::format::parse_date_utc;
+use crate::history::{self, Snapshot, TorrentSample};
+use crate::i18n::tr;
+
+/// Server-wide metrics: name and description.
+const GLOBAL_METRICS: [(&str, &str); 8] = [
+    ("dl_speed", "Download speed in bytes/s"),
+    ("up_speed", "Upload speed in bytes/s"),
+    ("session_dl", "Bytes downloaded since qBittorrent started"),
+    ("session_ul", "Bytes uploaded since qBittorrent started"),
+    ("alltime_dl", "Bytes downloaded in total"),
+    ("alltime_ul", "Bytes uploaded in total"),
+    ("free_space", "Free space on the default save path in bytes"),
+    ("peers", "Connected peers"),
+];
+
+/// Metrics over the torrents a query selects by hash and/or category.
+const TORRENT_METRICS: [(&str, &str); 6] = [
+    ("torrents", "Number of torrents"),
+    ("size", "Bytes selected for download"),
+    ("downloaded", "Bytes downloaded"),
+    ("uploaded", "Bytes uploaded"),
+    ("ratio", "Share ratio (uploaded over downloaded)"),
+    ("progress", "Download progress from 0 to 1, weighted by size"),
+];
+
+/// Which torrents a torrent metric covers; empty means all of them.
+#[derive(Default)]
+struct Filter {
+    /// Full hash or a prefix of it
+    hash: Option<String>,
+    category: Option<String>,
+}
+
+impl Filter {
+    fn from_payload(payload: &Value) -> Self {
+        let field = |key: &str| payload.get(key).and_then(Value::as_str).filter(|s| !s.is_empty()).map(str::to_string);
+        Filter { hash: field("hash").map(|h| h.to_lowercase()), category: field("category") }
+    }
+
+    fn matches(&self, torrent: &TorrentSample) -> bool {
+        self.hash.as_ref().is_none_or(|h| torrent.hash.starts_with(h.as_str()))
+            && self.category.as_ref().is_none_or(|c| torrent.category == *c)
+    }
+
+    /// Series name shown in Grafana, e.g. `uploaded (category=linux)`.
+    fn label(&self, metric: &str) -> String {
+        let mut parts = Vec::new();
+        if let Some(ref hash) = self.hash {
+            parts.push(format!("hash={}", hash));
+        }
+        if let Some(ref category) = self.category {
+            parts.push(format!("category={}", category));
+        }
+        if parts.is_empty() { metric.to_string() } else { format!("{} ({})", metric, parts.join(", ")) }
+    }
+}
+
+fn global_value(snapshot: &Snapshot, metric: &str) -> Option<f64> {
+    let value = match metric {
+        "dl_speed" => snapshot.dl_speed,
+        "up_speed" => snapshot.up_speed,
+        "session_dl" => snapshot.session_dl,
+        "session_ul" => snapshot.session_ul,
+        "alltime_dl" => snapshot.alltime_dl?,
+        "alltime_ul" => snapshot.alltime_ul?,
+        "free_space" => snapshot.free_space?,
+        "peers" => snapshot.peers?,
+        _ => return None,
+    };
+    Some(value as f64)
+}
+
+fn torrent_value(snapshot: &Snapshot, metric: &str, filter: &Filter) -> Option<f64> {
+    let matched: Vec<&TorrentSample> = snapshot.torrents.iter().filter(|t| filter.matches(t)).collect();
+    if matched.is_empty() && metric != "torrents" {
+        return None;
+    }
+    let sum = |f: fn(&TorrentSample) -> u64| matched.iter().map(|t| f(t)).sum::<u64>() as f64;
+    Some(match metric {
+        "torrents" => matched.len() as f64,
+        "size" => sum(|t| t.size),
+        "downloaded" => sum(|t| t.downloaded),
+        "uploaded" => sum(|t| t.uploaded),
+        "ratio" => {
+            let downloaded = sum(|t| t.downloaded);
+            if downloaded > 0.0 {
+                sum(|t| t.uploaded) / downloaded
+            } else {
+                matched.iter().map(|t| t.ratio).sum::<f64>() / matched.len() as f64
+            }
+        }
+        "progress" => {
+            let size = sum(|t| t.size);
+            if size > 0.0 {
+                matched.iter().map(|t| t.progress * t.size as f64).sum::<f64>() / size
+            } else {
+                matched.iter().map(|t| t.progress).sum::<f64>() / matched.len() as f64
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn is_torrent_metric(metric: &str) -> bool {
+    TORRENT_METRICS.iter().any(|(name, _)| *name == metric)
+}
+
+fn is_metric(metric: &str) -> bool {
+    is_torrent_metric(metric) || GLOBAL_METRICS.iter().any(|(name, _)| *name == metric)
+}
+
+#[derive(Deserialize)]
+struct QueryRange {
+    from: String,
+    to: String,
+}
+
+#[derive(Deserialize)]
+struct QueryTarget {
+    #[serde(default)]
+    target: String,
+    #[serde(default, rename = "refId")]
+    ref_id: String,
+    #[serde(default)]
+    payload: Value,
+    #[serde(default)]
+    hide: bool,
+}
+
+#[derive(Deserialize)]
+struct QueryRequest {
+    range: QueryRange,
+    targets: Vec<QueryTarget>,
+    #[serde(default, rename = "maxDataPoints")]
+    max_data_points: Option<usize>,
+}
+
+/// Answer a `/query`: one series of `[value, unix ms]` points per visible target.
+fn query(snapshots: &[Snapshot], request: &QueryRequest) -> anyhow::Result<Value> {
+    let from = parse_date_utc(&request.range.from)?;
+    let to = parse_date_utc(&request.range.to)?;
+    let window: Vec<&Snapshot> = snapshots.iter().filter(|s| s.time >= from && s.time <= to).collect();
+    // thin out long ranges so Grafana gets about as many points as it asked for
+    let step = match request.max_data_points {
+        Some(max) if max > 0 && window.len() > max => window.len().div_ceil(max),
+        _ => 1,
+    };
+    let mut series = Vec::new();
+    for target in request.targets.iter().filter(|t| !t.hide && !t.target.is_empty()) {
+        if !is_metric(&target.target) {
+            anyhow::bail!("unknown metric '{}'", target.target);
+        }
+        let filter = Filter::from_payload(&target.payload);
+        let points: Vec<Value> = window
+            .iter()
+            .step_by(step)
+            .filter_map(|s| {
+                let value = if is_torrent_metric(&target.target) { torrent_value(s, &target.target, &filter) } else { global_value(s, &target.target) };
+                value.map(|v| json!([v, s.time * 1000]))
+            })
+            .collect();
+        series.push(json!({ "target": filter.label(&target.target), "refId": target.ref_id, "datapoints": points }));
+    }
+    Ok(Value::Array(series))
+}
+
+/// The metric list for the JSON datasource's query editor; torrent metrics take a hash or
+/// category in their payload.
+fn metric_list() -> Value {
+    let payloads = json!([
+        { "label": "Hash (or prefix)", "name": "hash", "type": "input" },
+        { "label": "Category", "name": "category", "type": "input" },
+    ]);
+    let global = GLOBAL_METRICS.iter().map(|(name, help)| json!({ "label": help, "value": name }));
+    let torrent = TORRENT_METRICS.iter().map(|(name, help)| json!({ "label": help, "value": name, "payloads": payloads }));
+    Value::Array(global.chain(torrent).collect())
+}
+
+/// Escape a Prometheus label value.
+fn label_value(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// The newest snapshot in the Prometheus text exposition format.
+fn prometheus(snapshot: &Snapshot) -> String {
+    let host = label_value(&snapshot.host);
+    let mut out = String::new();
+    out.push_str("# HELP rbit_snapshot_timestamp_seconds When the newest snapshot was taken\n");
+    out.push_str("# TYPE rbit_snapshot_timestamp_seconds gauge\n");
+    out.push_str(&format!("rbit_snapshot_timestamp_seconds{{host=\"{}\"}} {}\n", host, snapshot.time));
+    for (name, help) in GLOBAL_METRICS {
+        if let Some(value) = global_value(snapshot, name) {
+            out.push_str(&format!("# HELP rbit_{} {}\n# TYPE rbit_{} gauge\n", name, help, name));
+            out.push_str(&format!("rbit_{}{{host=\"{}\"}} {}\n", name, host, value));
+        }
+    }
+    for (name, help) in TORRENT_METRICS.iter().filter(|(name, _)| *name != "torrents") {
+        out.push_str(&format!("# HELP rbit_torrent_{} {}\n# TYPE rbit_torrent_{} gauge\n", name, help, name));
+        for t in &snapshot.torrents {
+            let value = match *name {
+                "size" => t.size as f64,
+                "downloaded" => t.downloaded as f64,
+                "uploaded" => t.uploaded as f64,
+                "ratio" => t.ratio,
+                _ => t.progress,
+            };
+            out.push_str(&format!(
+                "rbit_torrent_{}{{host=\"{}\",hash=\"{}\",name=\"{}\",category=\"{}\",state=\"{}\"}} {}\n",
+                name,
+                host,
+                t.hash,
+                label_value(&t.name),
+                label_value(&t.category),
+                label_value(&t.state),
+                value
+            ));
+        }
+    }
+    out
+}
+
+struct Exporter {
+    file: PathBuf,
+    json_api: bool,
+}
+
+fn response(status: StatusCode, content_type: &str, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", content_type)
+        .body(Body::from(body))
+        .expect("static response parts are valid")
+}
+
+fn json_response(value: &Value) -> Response<Body> {
+    response(StatusCode::OK, "application/json", value.to_string())
+}
+
+fn error(status: StatusCode, message: impl std::fmt::Display) -> Response<Body> {
+    response(status, "text/plain; charset=utf-8", format!("{}\n", message))
+}
+
+impl Exporter {
+    /// The history is read again for every request, so new snapshots show up without a restart.
+    fn snapshots(&self) -> anyhow::Result<Vec<Snapshot>> {
+        history::read(&self.file)
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+        match (self.json_api, method, path.as_str()) {
+            // Grafana's "Save & test"
+            (true, Method::GET, "/") => response(StatusCode::OK, "text/plain", "OK\n".to_string()),
+            (true, Method::POST, "/metrics") => json_response(&metric_list()),
+            // the older SimpleJson datasource asks /search for plain metric names
+            (true, Method::POST, "/search") => {
+                let names: Vec<&str> = GLOBAL_METRICS.iter().chain(TORRENT_METRICS.iter()).map(|(name, _)| *name).collect();
+                json_response(&json!(names))
+            }
+            (true, Method::POST, "/metric-payload-options") => json_response(&json!([])),
+            (true, Method::POST, "/query") => {
+                let body = match hyper::body::to_bytes(request.into_body()).await {
+                    Ok(body) => body,
+                    Err(e) => return error(StatusCode::BAD_REQUEST, e),
+                };
+                let request: QueryRequest = match serde_json::from_slice(&body) {
+                    Ok(request) => request,
+                    Err(e) => return error(StatusCode::BAD_REQUEST, format!("invalid query: {}", e)),
+                };
+                match self.snapshots().and_then(|snapshots| query(&snapshots, &request)) {
+                    Ok(series) => json_response(&series),
+                    Err(e) => error(StatusCode::BAD_REQUEST, e),
+                }
+            }
+            (false, Method::GET, "/metrics") => match self.snapshots() {
+                Ok(snapshots) => match snapshots.last() {
+                    Some(newest) => response(StatusCode::OK, "text/plain; version=0.0.4", prometheus(newest)),
+                    None => error(StatusCode::SERVICE_UNAVAILABLE, "no snapshots recorded yet; run `rbit stats record`"),
+                },
+                Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+            },
+            (false, Method::GET, "/") => response(StatusCode::OK, "text/plain", "rbit exporter: metrics are at /metrics\n".to_string()),
+            _ => error(StatusCode::NOT_FOUND, "not found"),
+        }
+    }
+}
+
+/// Serve `file` on `listen` until the process is stopped.
+pub async fn serve(file: PathBuf, listen: SocketAddr, json_api: bool) -> anyhow::Result<()> {
+    if !file.exists() {
+        println!("{}", tr!("exporter-no-history", path = file.display().to_string()));
+    }
+    let exporter = Arc::new(Exporter { file, json_api });
+    let make_service = make_service_fn(move |_| {
+        let exporter = exporter.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let exporter = exporter.clone();
+                async move { Ok::<_, Infallible>(exporter.handle(request).await) }
+            }))
+        }
+    });
+    let server = Server::try_bind(&listen).map_err(|e| anyhow::anyhow!("cannot listen on {}: {}", listen, e))?;
+    if json_api {
+        println!("{}", tr!("exporter-grafana", address = listen.to_string()));
+    } else {
+        println!("{}", tr!("exporter-prometheus", address = listen.to_string()));
+    }
+    server.serve(make_service).await?;
+    Ok(())
+}
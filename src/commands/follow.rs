@@ -0,0 +1,162 @@
+//! `rbit add --follow`: watch the added torrents download, with a progress bar each, until they
+//! are complete, one fails, or Ctrl-C.
+
+use std::time::{Duration, Instant};
+
+use indicatif::{MultiProgress, ProgressDrawTarget};
+
+use crate::api::{Session, TorrentInfo};
+use crate::error::RbitError;
+use crate::hooks;
+use crate::format::{size_human, truncate};
+use crate::i18n::tr;
+use crate::interrupt::{self, Interrupt};
+use crate::notifications::{self, Event, EventKind};
+use rbit::progress::TransferBar;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a torrent may take to show up on the server after it was added; qBittorrent adds
+/// torrent URLs in the background.
+const APPEAR_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, PartialEq)]
+pub enum Progress {
+    Downloading,
+    Complete,
+    Failed,
+}
+
+pub fn progress_of(t: &TorrentInfo) -> Progress {
+    match t.state.as_str() {
+        "error" | "missingFiles" => Progress::Failed,
+        // a recheck can still find pieces missing
+        s if s.starts_with("checking") => Progress::Downloading,
+        _ if t.progress.unwrap_or(0.0) >= 1.0 => Progress::Complete,
+        _ => Progress::Downloading,
+    }
+}
+
+struct Followed {
+    hash: String,
+    bar: TransferBar,
+    done: Option<Progress>,
+}
+
+/// Run the on-complete hook for `t` with the bars in `multi` out of the way, as the hook's
+/// output goes straight to the terminal; they are drawn again once it is done.
+pub async fn run_hook(multi: &MultiProgress, command: &str, t: &TorrentInfo, host: &str, dry_run: bool) -> anyhow::Result<()> {
+    let _ = multi.clear();
+    multi.set_draw_target(ProgressDrawTarget::hidden());
+    let ran = hooks::on_complete(command, t, host, dry_run).await;
+    multi.set_draw_target(ProgressDrawTarget::stderr());
+    ran
+}
+
+/// Poll the torrents with these info-hashes until every one is complete or failed, running
+/// `on_complete` for each one that completes. Fails if any of them failed (or never showed up)
+/// or its hook did, and with [`RbitError::Interrupted`] on Ctrl-C; the torrents keep
+/// downloading either way.
+pub async fn follow(session: &Session, hashes: &[String], on_complete: Option<&str>) -> anyhow::Result<()> {
+    if hashes.is_empty() {
+        return Ok(());
+    }
+    if session.dry_run {
+        println!("[dry-run] would follow {} torrent(s) until they are complete", hashes.len());
+        return Ok(());
+    }
+    let multi = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
+    let mut followed: Vec<Followed> = hashes
+        .iter()
+        .map(|hash| {
+            // a length of 1 until the size is known: with 0 the bar would show 100%
+            let bar = TransferBar::with_target(&hash[..8.min(hash.len())], 1, ProgressDrawTarget::hidden());
+            multi.add(bar.bar().clone());
+            Followed { hash: hash.to_lowercase(), bar, done: None }
+        })
+        .collect();
+    let started = Instant::now();
+    let mut interrupt = Interrupt::listen();
+
+    loop {
+        let pending: Vec<String> = followed.iter().filter(|f| f.done.is_none()).map(|f| f.hash.clone()).collect();
+        let torrents = session.find_torrents(&pending).await?;
+        for f in followed.iter_mut().filter(|f| f.done.is_none()) {
+            let Some(t) = torrents.iter().find(|t| t.hash.eq_ignore_ascii_case(&f.hash)) else {
+                if started.elapsed() > APPEAR_TIMEOUT {
+                    f.bar.abandon(&tr!("follow-absent"));
+                    multi.suspend(|| println!("{}", tr!("follow-absent-failed", id = f.hash.as_str())));
+                    f.done = Some(Progress::Failed);
+                }
+                continue;
+            };
+            let total = t.size.unwrap_or(0);
+            if total == 0 && progress_of(t) == Progress::Downloading {
+                // a magnet link whose metadata qBittorrent is still fetching
+                f.bar.set_label(&tr!("follow-fetching", name = truncate(&t.name, 30)));
+                continue;
+            }
+            let done = (t.progress.unwrap_or(0.0) * total as f64) as u64;
+            f.bar.set_label(&truncate(&t.name, 30));
+            f.bar.set_server_eta(t.eta);
+            f.bar.update(done, total);
+            match progress_of(t) {
+                Progress::Downloading => {}
+                Progress::Complete => {
+                    f.bar.finish(&tr!("follow-complete"));
+                    multi.suspend(|| println!("{}", tr!("follow-completed", name = t.name.as_str(), size = size_human(total))));
+                    f.done = Some(Progress::Complete);
+                    notifications::notify(Event { kind: EventKind::Complete, torrent: t, host: &session.host }).await;
+                    if let Some(command) = on_complete {
+                        if let Err(e) = run_hook(&multi, command, t, &session.host, false).await {
+                            multi.suspend(|| println!("{}", tr!("follow-hook-failed", reason = format!("{:#}", e))));
+                            f.done = Some(Progress::Failed);
+                        }
+                    }
+                }
+                Progress::Failed => {
+                    f.bar.abandon(&t.state);
+                    multi.suspend(|| println!("{}", tr!("follow-failed", name = t.name.as_str(), state = t.state.as_str())));
+                    f.done = Some(Progress::Failed);
+                    notifications::notify(Event { kind: EventKind::Error, torrent: t, host: &session.host }).await;
+                }
+            }
+        }
+        if followed.iter().all(|f| f.done.is_some()) {
+            break;
+        }
+        tokio::select! {
+            _ = interrupt.recv() => {
+                for f in followed.iter().filter(|f| f.done.is_none()) {
+                    f.bar.abandon(&tr!("follow-stopped"));
+                }
+                multi.suspend(|| println!("{}", tr!("follow-interrupted")));
+                interrupt::wrap_up(session).await;
+                return Err(RbitError::Interrupted.into());
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+
+    let failed = followed.iter().filter(|f| f.done == Some(Progress::Failed)).count();
+    match failed {
+        0 => Ok(()),
+        _ if followed.len() == 1 => anyhow::bail!(tr!("follow-incomplete")),
+        _ => Err(RbitError::PartialFailure { failed, total: followed.len() }.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_once_fully_downloaded_and_not_checking() {
+        let torrent = |state: &str, progress: f64| -> TorrentInfo {
+            serde_json::from_value(serde_json::json!({ "name": "t", "hash": "aaaa", "state": state, "progress": progress })).unwrap()
+        };
+        assert_eq!(progress_of(&torrent("downloading", 0.5)), Progress::Downloading);
+        assert_eq!(progress_of(&torrent("stalledUP", 1.0)), Progress::Complete);
+        assert_eq!(progress_of(&torrent("checkingUP", 1.0)), Progress::Downloading);
+        assert_eq!(progress_of(&torrent("missingFiles", 1.0)), Progress::Failed);
+    }
+}
@@ -0,0 +1,260 @@
+//! `rbit forecast [<id>]`: when downloads will be done, from speeds measured over a window (10
+//! seconds by default) or over the stats history with `--from-history`, rather than the
+//! instantaneous speed behind qBittorrent's ETA.
+//!
+//! The whole queue is played forward: torrents download at their measured speeds, and with
+//! queueing on, a queued torrent starts when a slot frees up and takes over the speed of the
+//! torrent that had it. Forced downloads do not take a slot.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value};
+use tabled::Tabled;
+
+use crate::api::{Session, TorrentInfo};
+use crate::cache;
+use crate::error::RbitError;
+use crate::format::{date_utc, duration_human, eta_human, rate_human, size_human, truncate};
+use crate::history;
+use crate::i18n::tr;
+use crate::theme;
+
+/// How a torrent takes part in the queue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slot {
+    /// Downloading, or trying to, in one of the slots
+    Active,
+    /// Downloading without taking a slot
+    Forced,
+    /// Waiting for a slot, at this queue position
+    Queued(i64),
+    /// Paused, stopped or in error: not downloading until someone steps in
+    Held,
+}
+
+#[derive(Debug, Clone)]
+struct Job {
+    slot: Slot,
+    /// Bytes still to download
+    remaining: f64,
+    /// Measured speed in bytes/s
+    rate: f64,
+}
+
+fn slot_of(t: &TorrentInfo) -> Slot {
+    match t.state.as_str() {
+        "forcedDL" | "forcedMetaDL" => Slot::Forced,
+        "queuedDL" => Slot::Queued(t.priority.unwrap_or(i64::MAX)),
+        s if s.starts_with("paused") || s.starts_with("stopped") || s == "error" || s == "missingFiles" => Slot::Held,
+        _ => Slot::Active,
+    }
+}
+
+/// Seconds from now until each job is done, `None` for those that never are at these speeds.
+/// With `slots`, queued jobs wait for one of that many slots.
+fn play_forward(jobs: &[Job], slots: Option<usize>) -> Vec<Option<f64>> {
+    let mut done = vec![None; jobs.len()];
+    let mut remaining: Vec<f64> = jobs.iter().map(|j| j.remaining).collect();
+    let mut rate: Vec<f64> = jobs.iter().map(|j| j.rate).collect();
+    let mut running: Vec<usize> = (0..jobs.len()).filter(|&i| matches!(jobs[i].slot, Slot::Active | Slot::Forced)).collect();
+    let mut queued: Vec<(i64, usize)> = jobs.iter().enumerate().filter_map(|(i, j)| if let Slot::Queued(p) = j.slot { Some((p, i)) } else { None }).collect();
+    queued.sort_unstable();
+    let mut waiting: VecDeque<usize> = queued.into_iter().map(|(_, i)| i).collect();
+    // without a limit nothing waits, and with free slots the first in the queue start (slowly)
+    let in_slots = |running: &[usize]| running.iter().filter(|&&i| jobs[i].slot != Slot::Forced).count();
+    while slots.is_none_or(|n| in_slots(&running) < n) {
+        match waiting.pop_front() {
+            Some(i) => running.push(i),
+            None => break,
+        }
+    }
+
+    let mut now = 0.0;
+    loop {
+        let finishing = running.iter().copied().filter(|&i| rate[i] > 0.0).min_by(|&a, &b| (remaining[a] / rate[a]).total_cmp(&(remaining[b] / rate[b])));
+        let Some(i) = finishing else {
+            break;
+        };
+        let step = remaining[i] / rate[i];
+        now += step;
+        for &j in &running {
+            remaining[j] = (remaining[j] - rate[j] * step).max(0.0);
+        }
+        done[i] = Some(now);
+        running.retain(|&j| j != i);
+        if jobs[i].slot != Slot::Forced {
+            if let Some(next) = waiting.pop_front() {
+                rate[next] = rate[i];
+                running.push(next);
+            }
+        }
+    }
+    done
+}
+
+#[derive(Tabled)]
+struct ForecastRow {
+    name: String,
+    state: String,
+    progress: String,
+    left: String,
+    speed: String,
+    #[tabled(rename = "server ETA")]
+    server_eta: String,
+    forecast: String,
+    #[tabled(rename = "done at")]
+    done_at: String,
+}
+
+/// Speeds measured by listing the torrents twice, `window` apart.
+async fn sampled_rates(session: &Session, window: Duration) -> anyhow::Result<(Vec<TorrentInfo>, HashMap<String, f64>)> {
+    eprintln!("{}", tr!("forecast-measuring", limit = duration_human(window)));
+    let before = session.torrents("all").await?;
+    let started = Instant::now();
+    tokio::time::sleep(window).await;
+    let after = session.torrents("all").await?;
+    let elapsed = started.elapsed().as_secs_f64().max(1.0);
+    let earlier: HashMap<&str, u64> = before.iter().map(|t| (t.hash.as_str(), t.downloaded.unwrap_or(0))).collect();
+    let rates = after
+        .iter()
+        .filter_map(|t| earlier.get(t.hash.as_str()).map(|&d| (t.hash.clone(), history::growth([d, t.downloaded.unwrap_or(0)]) as f64 / elapsed)))
+        .collect();
+    Ok((after, rates))
+}
+
+/// Average speeds over the stats history of the last `span`.
+async fn history_rates(session: &Session, span: Duration) -> anyhow::Result<(Vec<TorrentInfo>, HashMap<String, f64>)> {
+    let path = history::history_path(&session.host).ok_or_else(|| anyhow::anyhow!("no data directory available"))?;
+    let snapshots = if path.exists() { history::read(&path)? } else { Vec::new() };
+    let recent = history::since(&snapshots, cache::now().saturating_sub(span.as_secs()));
+    if recent.len() < 2 {
+        return Err(RbitError::InvalidInput(format!(
+            "the stats history has {} snapshot(s) from the last {}; record more with `rbit stats record` (or `rbit daemon`), or leave out --from-history",
+            recent.len(),
+            duration_human(span)
+        ))
+        .into());
+    }
+    let mut samples: HashMap<&str, Vec<(u64, u64)>> = HashMap::new();
+    for snapshot in recent {
+        for t in &snapshot.torrents {
+            samples.entry(t.hash.as_str()).or_default().push((snapshot.time, t.downloaded));
+        }
+    }
+    let rates = samples
+        .into_iter()
+        .filter_map(|(hash, readings)| {
+            let elapsed = readings.last()?.0.checked_sub(readings.first()?.0).filter(|&e| e > 0)?;
+            Some((hash.to_lowercase(), history::growth(readings.iter().map(|r| r.1)) as f64 / elapsed as f64))
+        })
+        .collect();
+    Ok((session.torrents("all").await?, rates))
+}
+
+/// How many downloads the server runs at a time; `None` without queueing.
+async fn download_slots(session: &Session) -> anyhow::Result<Option<usize>> {
+    let prefs: Map<String, Value> = session.get_json("app/preferences").await?;
+    if prefs.get("queueing_enabled").and_then(Value::as_bool) != Some(true) {
+        return Ok(None);
+    }
+    // -1 is unlimited
+    Ok(prefs.get("max_active_downloads").and_then(Value::as_i64).and_then(|n| usize::try_from(n).ok()))
+}
+
+pub async fn forecast(session: &Session, id: Option<&str>, window: Duration, from_history: Option<Duration>) -> anyhow::Result<()> {
+    session.login().await?;
+    let only = match id {
+        Some(id) => Some(session.resolve(id).await?.hash),
+        None => None,
+    };
+    let (torrents, rates) = match from_history {
+        Some(span) => history_rates(session, span).await?,
+        None => sampled_rates(session, window).await?,
+    };
+    let slots = download_slots(session).await?;
+
+    let downloads: Vec<&TorrentInfo> = torrents.iter().filter(|t| t.progress.unwrap_or(0.0) < 1.0).collect();
+    let jobs: Vec<Job> = downloads
+        .iter()
+        .map(|t| Job {
+            slot: slot_of(t),
+            remaining: t.size.unwrap_or(0) as f64 * (1.0 - t.progress.unwrap_or(0.0)),
+            rate: rates.get(&t.hash.to_lowercase()).copied().unwrap_or(0.0),
+        })
+        .collect();
+    let done = play_forward(&jobs, slots);
+    let now = cache::now();
+
+    let mut order: Vec<usize> = (0..downloads.len()).filter(|&i| only.as_deref().is_none_or(|h| downloads[i].hash == h)).collect();
+    order.sort_by(|&a, &b| done[a].unwrap_or(f64::INFINITY).total_cmp(&done[b].unwrap_or(f64::INFINITY)));
+    if order.is_empty() {
+        println!("{}", if only.is_some() { tr!("forecast-complete") } else { tr!("forecast-nothing") });
+        return Ok(());
+    }
+    let rows = order.iter().map(|&i| {
+        let t = downloads[i];
+        let (forecast, done_at) = match (done[i], jobs[i].slot) {
+            (Some(secs), _) => (duration_human(Duration::from_secs_f64(secs)), date_utc(now + secs as u64)),
+            (None, Slot::Held) => ("held".to_string(), "-".to_string()),
+            (None, _) => ("∞".to_string(), "-".to_string()),
+        };
+        ForecastRow {
+            name: truncate(&t.name, 40),
+            state: t.state.clone(),
+            progress: format!("{:.1}%", t.progress.unwrap_or(0.0) * 100.0),
+            left: size_human(jobs[i].remaining as u64),
+            speed: rate_human(jobs[i].rate as u64),
+            server_eta: eta_human(t.eta.unwrap_or(-1)),
+            forecast,
+            done_at,
+        }
+    });
+    println!("{}", theme::table(rows));
+
+    if let Some(n) = slots {
+        println!("{}", tr!("forecast-queueing", count = n));
+    }
+    let never = done.iter().filter(|d| d.is_none()).count();
+    let last = done.iter().flatten().copied().fold(0.0, f64::max);
+    if never == 0 {
+        let after = duration_human(Duration::from_secs_f64(last));
+        println!("{}", tr!("forecast-all-done", count = jobs.len(), elapsed = after, time = date_utc(now + last as u64)));
+    } else {
+        println!("{}", tr!("forecast-never", failed = never, total = jobs.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(slot: Slot, remaining: f64, rate: f64) -> Job {
+        Job { slot, remaining, rate }
+    }
+
+    #[test]
+    fn the_queue_moves_up_as_slots_free() {
+        let jobs = [
+            job(Slot::Active, 100.0, 10.0),
+            job(Slot::Active, 300.0, 10.0),
+            job(Slot::Queued(2), 50.0, 0.0),
+            job(Slot::Queued(1), 100.0, 0.0),
+            job(Slot::Forced, 20.0, 1.0),
+            job(Slot::Held, 10.0, 0.0),
+        ];
+        let done = play_forward(&jobs, Some(2));
+        // the first in the queue gets the first free slot and its speed, at 10 s
+        assert_eq!(done[..5], [Some(10.0), Some(30.0), Some(25.0), Some(20.0), Some(20.0)]);
+        assert_eq!(done[5], None);
+
+        // without queueing, the queued ones are not waiting on anything, but have no speed
+        let done = play_forward(&jobs, None);
+        assert_eq!(done[..4], [Some(10.0), Some(30.0), None, None]);
+
+        // a stalled torrent holds its slot
+        let done = play_forward(&[job(Slot::Active, 100.0, 0.0), job(Slot::Queued(1), 10.0, 0.0)], Some(1));
+        assert_eq!(done, [None, None]);
+    }
+}
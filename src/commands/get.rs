@@ -0,0 +1,47 @@
+//! `rbit get <id> <field>`: one field of one torrent, as `torrents/info` reports it, printed bare
+//! for shell scripts: `dest=$(rbit get a1b2c3 save_path)`.
+
+use serde_json::{Map, Value};
+
+use crate::api::Session;
+use crate::error::RbitError;
+
+/// A field's value as a script wants it: strings without quotes, numbers and booleans as JSON
+/// writes them, and nothing for null.
+fn bare(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+pub async fn get(session: &Session, id: &str, field: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    // the raw listing, so fields rbit does not know about can be read too
+    let mut found: Vec<Map<String, Value>> = session.get_json(&format!("torrents/info?hashes={}", t.hash.to_lowercase())).await?;
+    let torrent = found.pop().ok_or_else(|| RbitError::NotFound(format!("{} was removed from the server", t.hash)))?;
+    let value = torrent.get(field).ok_or_else(|| {
+        let mut known: Vec<&str> = torrent.keys().map(String::as_str).collect();
+        known.sort_unstable();
+        RbitError::NotFound(format!("the server reports no field {} for torrents; it has: {}", field, known.join(", ")))
+    })?;
+    println!("{}", bare(value));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn values_are_printed_bare() {
+        assert_eq!(bare(&json!("/downloads/linux")), "/downloads/linux");
+        assert_eq!(bare(&json!(0.5)), "0.5");
+        assert_eq!(bare(&json!(1_000_000)), "1000000");
+        assert_eq!(bare(&json!(false)), "false");
+        assert_eq!(bare(&json!(null)), "");
+    }
+}
@@ -0,0 +1,109 @@
+//! `rbit history <id>`: a torrent's progress and transfers over time, from the stats history.
+
+use std::time::Duration;
+
+use tabled::Tabled;
+
+use crate::api::Session;
+use crate::format::{date_utc, duration_human, rate_human, size_human};
+use crate::history::{self, Snapshot, TorrentSample};
+use crate::i18n::tr;
+use crate::theme;
+
+#[derive(Tabled)]
+struct HistoryRow {
+    time: String,
+    state: String,
+    progress: String,
+    downloaded: String,
+    uploaded: String,
+    ratio: String,
+    /// Average speeds since the snapshot before
+    #[tabled(rename = "avg down")]
+    down: String,
+    #[tabled(rename = "avg up")]
+    up: String,
+}
+
+/// Average speed over `elapsed` seconds.
+fn rate(bytes: u64, elapsed: u64) -> String {
+    match elapsed {
+        0 => "-".to_string(),
+        _ => rate_human(bytes / elapsed),
+    }
+}
+
+/// The hash of the torrent `id` names: one on the server, or one the history has seen (so
+/// removed torrents keep their history).
+pub async fn hash_of(session: &Session, id: &str, snapshots: &[Snapshot]) -> anyhow::Result<String> {
+    let on_server = match session.resolve(id).await {
+        Ok(t) => return Ok(t.hash.to_lowercase()),
+        Err(e) => e,
+    };
+    let prefix = id.to_lowercase();
+    let mut seen: Vec<&str> = snapshots.iter().flat_map(|s| &s.torrents).map(|t| t.hash.as_str()).filter(|h| h.starts_with(&prefix)).collect();
+    seen.sort_unstable();
+    seen.dedup();
+    match seen[..] {
+        [hash] => Ok(hash.to_string()),
+        _ => Err(on_server),
+    }
+}
+
+pub async fn history(session: &Session, id: &str, since: Option<Duration>, limit: usize) -> anyhow::Result<()> {
+    let path = history::history_path(&session.host).ok_or_else(|| anyhow::anyhow!("no data directory available"))?;
+    let snapshots = if path.exists() { history::read(&path)? } else { Vec::new() };
+    session.login().await?;
+    let hash = hash_of(session, id, &snapshots).await?;
+    let from = since.map(|d| crate::cache::now().saturating_sub(d.as_secs())).unwrap_or(0);
+    let samples: Vec<(u64, &TorrentSample)> = history::since(&snapshots, from)
+        .iter()
+        .filter_map(|s| s.torrents.iter().find(|t| t.hash.eq_ignore_ascii_case(&hash)).map(|t| (s.time, t)))
+        .collect();
+    let (Some(&(first_time, first)), Some(&(last_time, last))) = (samples.first(), samples.last()) else {
+        println!("{}", tr!("history-none", id = id));
+        return Ok(());
+    };
+
+    let mut rows: Vec<HistoryRow> = Vec::new();
+    for (i, &(time, t)) in samples.iter().enumerate() {
+        let (down, up) = match i.checked_sub(1).map(|j| samples[j]) {
+            Some((before_time, before)) => {
+                let elapsed = time.saturating_sub(before_time);
+                let down = history::growth([before.downloaded, t.downloaded]);
+                let up = history::growth([before.uploaded, t.uploaded]);
+                (rate(down, elapsed), rate(up, elapsed))
+            }
+            None => ("-".to_string(), "-".to_string()),
+        };
+        rows.push(HistoryRow {
+            time: date_utc(time),
+            state: t.state.clone(),
+            progress: format!("{:.1}%", t.progress * 100.0),
+            downloaded: size_human(t.downloaded),
+            uploaded: size_human(t.uploaded),
+            ratio: format!("{:.2}", t.ratio),
+            down,
+            up,
+        });
+    }
+    let shown = rows.len().min(limit);
+    println!("{}", tr!("history-title", name = last.name.as_str(), id = &hash[..8.min(hash.len())], count = samples.len(), time = date_utc(first_time)));
+    println!("{}", theme::table(rows.into_iter().skip(samples.len() - shown)));
+
+    let elapsed = last_time - first_time;
+    let down = history::growth(samples.iter().map(|(_, t)| t.downloaded));
+    let up = history::growth(samples.iter().map(|(_, t)| t.uploaded));
+    println!(
+        "{}",
+        tr!(
+            "history-over",
+            elapsed = duration_human(Duration::from_secs(elapsed)),
+            down = tr!("stats-average", size = size_human(down), rate = rate(down, elapsed)),
+            up = tr!("stats-average", size = size_human(up), rate = rate(up, elapsed)),
+            old = format!("{:.2}", first.ratio),
+            new = format!("{:.2}", last.ratio)
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,123 @@
+//! `rbit import --from <source>`: move torrents from another client, or another qBittorrent, to
+//! this server. Each torrent's .torrent file (or magnet link, see [`Exported`]) is taken from the
+//! source and added here on the same data: complete torrents without checking it again, others
+//! with a check, which keeps the pieces already downloaded.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::api::{AddOptions, ContentLayout, Session, TorrentInfo};
+use crate::batch::{self, Outcome, Report};
+use crate::client::{Exported, TorrentClient};
+use crate::commands::export::file_name_for;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::select::Selector;
+use crate::theme::state_group;
+use crate::torrent::Metainfo;
+
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['/', '\\']) == b.trim_end_matches(['/', '\\'])
+}
+
+/// How `t` is added here: at its save path on the source, checked unless complete, and with
+/// `with_state` in its category, with its tags and share limits, and paused if it is paused.
+fn options_for(t: &TorrentInfo, meta: Option<&Metainfo>, with_state: bool) -> anyhow::Result<AddOptions> {
+    let save_path = t.save_path.clone().filter(|p| !p.is_empty()).ok_or_else(|| anyhow::anyhow!("the source does not tell where its data is"))?;
+    // a multi-file torrent added without its top folder has its files right in the save path
+    let layout = match (t.content_path.as_deref(), meta) {
+        (Some(content), Some(meta)) if meta.files.len() > 1 && same_path(content, &save_path) => ContentLayout::NoSubfolder,
+        _ => ContentLayout::Original,
+    };
+    let mut options = AddOptions {
+        save_path: Some(PathBuf::from(save_path)),
+        skip_checking: t.progress.unwrap_or(0.0) >= 1.0,
+        content_layout: Some(layout),
+        ..Default::default()
+    };
+    if with_state {
+        options.paused = state_group(&t.state) == Some("paused");
+        options.category = t.category.clone().filter(|c| !c.is_empty());
+        options.tags = t.tags.clone().filter(|tags| !tags.is_empty());
+        options.ratio_limit = t.ratio_limit;
+        options.seeding_time_limit = t.seeding_time_limit;
+    }
+    Ok(options)
+}
+
+async fn import_one(source: &impl TorrentClient, dest: &Session, t: &TorrentInfo, here: &HashSet<String>, with_state: bool) -> anyhow::Result<Outcome> {
+    if here.contains(&t.hash) {
+        return Ok(Outcome::Skipped("already on this server".to_string()));
+    }
+    let exported = source.export(t).await?;
+    let meta = match &exported {
+        Exported::File(data) => Some(Metainfo::parse(data)?),
+        Exported::Magnet(_) => None,
+    };
+    let options = options_for(t, meta.as_ref(), with_state)?;
+    let how = if options.skip_checking { "not checked" } else { "to be checked" };
+    match exported {
+        Exported::File(data) => {
+            dest.add_torrent_data(&[(format!("{}.torrent", file_name_for(&t.name)), data)], &options).await?;
+            Ok(Outcome::Done(format!("added, {}", how)))
+        }
+        Exported::Magnet(link) => {
+            dest.add_url(&link, &options).await?;
+            Ok(Outcome::Done(format!("added from a magnet link, {} once the metadata is in", how)))
+        }
+    }
+}
+
+/// Add the torrents of `source` that `ids` and `selector` pick (all of them by default) to `dest`.
+pub async fn import(source: &impl TorrentClient, dest: &Session, ids: &[String], selector: &Selector, with_state: bool) -> anyhow::Result<()> {
+    if same_path(source.host(), &dest.host) {
+        return Err(RbitError::InvalidInput(format!("{} is this server; --from names the one to import from", source.host())).into());
+    }
+    source.login().await?;
+    dest.login().await?;
+    let torrents = selector.select(source, ids, ids.is_empty(), dest.dry_run).await?;
+    if torrents.is_empty() {
+        println!("{}", tr!("import-nothing", host = source.host()));
+        return Ok(());
+    }
+    let here: HashSet<String> = dest.torrents("all").await?.into_iter().map(|t| t.hash.to_lowercase()).collect();
+
+    let results = batch::run(dest, "Importing", &torrents, |t| t.name.clone(), |t| import_one(source, dest, t, &here, with_state)).await;
+    let mut report = Report::default();
+    for (t, result) in torrents.iter().zip(results) {
+        report.push(t.name.clone(), result);
+    }
+    report.print();
+    if !dest.dry_run {
+        println!("{}", tr!("import-done", done = report.done(), total = torrents.len(), host = source.host(), skipped = report.skipped()));
+    }
+    report.result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_torrents_skip_the_check_and_state_is_optional() {
+        let t = TorrentInfo {
+            hash: "abc".to_string(),
+            name: "a".to_string(),
+            state: "pausedUP".to_string(),
+            progress: Some(1.0),
+            save_path: Some("/data/".to_string()),
+            category: Some("tv".to_string()),
+            tags: Some(String::new()),
+            ..Default::default()
+        };
+        let bare = options_for(&t, None, false).unwrap();
+        assert!(bare.skip_checking && !bare.paused && bare.category.is_none());
+        let kept = options_for(&t, None, true).unwrap();
+        assert!(kept.paused);
+        assert_eq!((kept.category.as_deref(), kept.tags), (Some("tv"), None));
+
+        let partial = TorrentInfo { progress: Some(0.4), state: "downloading".to_string(), ..t };
+        assert!(!options_for(&partial, None, true).unwrap().skip_checking);
+        assert!(options_for(&TorrentInfo { save_path: None, ..partial }, None, false).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use crate::format::size_human;
+use crate::i18n::{print_fields, tr};
+use crate::torrent::Metainfo;
+
+pub fn print_metainfo(meta: &Metainfo) {
+    let mut fields = vec![
+        (tr!("inspect-name"), meta.name.clone()),
+        (tr!("inspect-info-hash"), meta.info_hash.clone()),
+        (tr!("inspect-total-size"), tr!("inspect-bytes", size = size_human(meta.total_size()), count = meta.total_size())),
+        (tr!("inspect-piece-size"), tr!("inspect-pieces", size = size_human(meta.piece_length), count = meta.pieces.len())),
+        (tr!("inspect-private"), if meta.private { tr!("yes") } else { tr!("no") }),
+    ];
+    if let Some(ref c) = meta.comment {
+        fields.push((tr!("inspect-comment"), c.clone()));
+    }
+    print_fields(&fields);
+    println!("{}", tr!("inspect-trackers", count = meta.trackers.len()));
+    for t in &meta.trackers {
+        println!("  {}", t);
+    }
+    println!("{}", tr!("inspect-files", count = meta.files.len()));
+    for f in &meta.files {
+        println!("  {:>10}  {}", size_human(f.length), f.path);
+    }
+}
+
+/// Print the metadata of one or more local .torrent files without contacting the server.
+pub fn inspect(files: &[impl AsRef<Path>]) -> anyhow::Result<()> {
+    for (i, file) in files.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let meta = Metainfo::from_file(file.as_ref())?;
+        print_metainfo(&meta);
+    }
+    Ok(())
+}
+
+/// Print the magnet URI equivalent of each local .torrent file, one per line.
+pub fn magnet(files: &[impl AsRef<Path>]) -> anyhow::Result<()> {
+    for file in files {
+        println!("{}", Metainfo::from_file(file.as_ref())?.magnet());
+    }
+    Ok(())
+}
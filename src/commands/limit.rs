@@ -0,0 +1,191 @@
+//! `rbit limit`: global, alternative and per-torrent speed limits, and `rbit seedlimits`:
+//! per-torrent share ratio and seeding time limits.
+
+use tabled::Tabled;
+
+use crate::api::{Session, TransferInfo};
+use crate::commands::manage::short_id;
+use crate::format::{minutes_limit_human, ratio_limit_human, truncate, Rate};
+use crate::i18n::{print_fields, tr};
+use crate::select::Selector;
+use crate::theme;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum AltMode {
+    On,
+    Off,
+    Toggle,
+}
+
+#[derive(Tabled)]
+struct ShareLimitRow {
+    id: String,
+    name: String,
+    ratio: String,
+    seeding: String,
+    inactive: String,
+}
+
+#[derive(Tabled)]
+struct LimitRow {
+    id: String,
+    name: String,
+    dl: String,
+    up: String,
+}
+
+/// Set the global limits that were given; with neither, print the current ones.
+pub async fn global(session: &Session, dl: Option<Rate>, up: Option<Rate>) -> anyhow::Result<()> {
+    session.login().await?;
+    if dl.is_none() && up.is_none() {
+        let transfer: TransferInfo = session.get_json("transfer/info").await?;
+        print_fields(&[
+            (tr!("limit-download"), Rate::from_api(transfer.dl_rate_limit).to_string()),
+            (tr!("limit-upload"), Rate::from_api(transfer.up_rate_limit).to_string()),
+        ]);
+        return Ok(());
+    }
+    if let Some(rate) = dl {
+        session
+            .post_form("transfer/setDownloadLimit", &[("limit", &rate.to_api().to_string())])
+            .await?;
+        println!("{}", tr!("limit-download-set", rate = rate.to_string()));
+    }
+    if let Some(rate) = up {
+        session
+            .post_form("transfer/setUploadLimit", &[("limit", &rate.to_api().to_string())])
+            .await?;
+        println!("{}", tr!("limit-upload-set", rate = rate.to_string()));
+    }
+    Ok(())
+}
+
+/// Whether the alternative speed limits are on.
+pub async fn alt_enabled(session: &Session) -> anyhow::Result<bool> {
+    Ok(session.get_text("transfer/speedLimitsMode").await?.trim() == "1")
+}
+
+/// Switch the alternative speed limits on, off or over; with no mode, print whether they are on.
+/// qBittorrent only offers a toggle, so the current mode is read first.
+pub async fn alt(session: &Session, mode: Option<AltMode>) -> anyhow::Result<()> {
+    session.login().await?;
+    let current = alt_enabled(session).await?;
+    let wanted = match mode {
+        None => current,
+        Some(AltMode::On) => true,
+        Some(AltMode::Off) => false,
+        Some(AltMode::Toggle) => !current,
+    };
+    let state = if wanted { tr!("on") } else { tr!("off") };
+    if wanted == current {
+        match mode {
+            None => println!("{}", tr!("limit-alt-state", state = state)),
+            Some(_) => println!("{}", tr!("limit-alt-already", state = state)),
+        }
+        return Ok(());
+    }
+    session.post_form("transfer/toggleSpeedLimitsMode", &[]).await?;
+    println!("{}", tr!("limit-alt-turned", state = state));
+    Ok(())
+}
+
+/// Set per-torrent limits on every torrent in `ids` that `selector` matches; with neither limit
+/// given, print them.
+pub async fn torrent(session: &Session, ids: &[String], selector: &Selector, dl: Option<Rate>, up: Option<Rate>) -> anyhow::Result<()> {
+    session.login().await?;
+    let torrents = selector.select(session, ids, false, session.dry_run).await?;
+    if dl.is_none() && up.is_none() {
+        let rows: Vec<LimitRow> = torrents
+            .iter()
+            .map(|t| LimitRow {
+                id: short_id(t).to_string(),
+                name: truncate(&t.name, 40),
+                dl: Rate::from_api(t.dl_limit.unwrap_or(0)).to_string(),
+                up: Rate::from_api(t.up_limit.unwrap_or(0)).to_string(),
+            })
+            .collect();
+        println!("{}", theme::table(rows));
+        return Ok(());
+    }
+    let hashes: Vec<&str> = torrents.iter().map(|t| t.hash.as_str()).collect();
+    let hashes = hashes.join("|");
+    if let Some(rate) = dl {
+        session
+            .post_form("torrents/setDownloadLimit", &[("hashes", &hashes), ("limit", &rate.to_api().to_string())])
+            .await?;
+    }
+    if let Some(rate) = up {
+        session
+            .post_form("torrents/setUploadLimit", &[("hashes", &hashes), ("limit", &rate.to_api().to_string())])
+            .await?;
+    }
+    for t in &torrents {
+        let mut set = Vec::new();
+        if let Some(rate) = dl {
+            set.push(tr!("limit-download-rate", rate = rate.to_string()));
+        }
+        if let Some(rate) = up {
+            set.push(tr!("limit-upload-rate", rate = rate.to_string()));
+        }
+        println!("{}", tr!("limit-torrent-set", name = t.name.as_str(), id = short_id(t), what = set.join(", ")));
+    }
+    Ok(())
+}
+
+/// Share limits to set with `torrents/setShareLimits`; `None` keeps the torrent's current value.
+/// Ratios and minutes use qBittorrent's conventions: -2 global, -1 unlimited.
+#[derive(Debug, Default)]
+pub struct ShareLimits {
+    pub ratio: Option<f64>,
+    pub seeding_minutes: Option<i64>,
+    pub inactive_minutes: Option<i64>,
+}
+
+/// Set share limits on the torrents in `ids`, or print them when no limit is given.
+/// qBittorrent requires all three values, so each torrent keeps its own value for any left out.
+pub async fn share_limits(session: &Session, ids: &[String], limits: &ShareLimits) -> anyhow::Result<()> {
+    session.login().await?;
+    let torrents = session.resolve_many(ids).await?;
+    if limits.ratio.is_none() && limits.seeding_minutes.is_none() && limits.inactive_minutes.is_none() {
+        let rows: Vec<ShareLimitRow> = torrents
+            .iter()
+            .map(|t| ShareLimitRow {
+                id: short_id(t).to_string(),
+                name: truncate(&t.name, 40),
+                ratio: ratio_limit_human(t.ratio_limit.unwrap_or(-2.0)),
+                seeding: minutes_limit_human(t.seeding_time_limit.unwrap_or(-2)),
+                inactive: t.inactive_seeding_time_limit.map(minutes_limit_human).unwrap_or_else(|| "-".to_string()),
+            })
+            .collect();
+        println!("{}", theme::table(rows));
+        return Ok(());
+    }
+    for t in &torrents {
+        let ratio = limits.ratio.or(t.ratio_limit).unwrap_or(-2.0);
+        let seeding = limits.seeding_minutes.or(t.seeding_time_limit).unwrap_or(-2);
+        let inactive = limits.inactive_minutes.or(t.inactive_seeding_time_limit).unwrap_or(-2);
+        session
+            .post_form(
+                "torrents/setShareLimits",
+                &[
+                    ("hashes", &t.hash),
+                    ("ratioLimit", &ratio.to_string()),
+                    ("seedingTimeLimit", &seeding.to_string()),
+                    ("inactiveSeedingTimeLimit", &inactive.to_string()),
+                ],
+            )
+            .await?;
+        println!(
+            "{}",
+            tr!(
+                "limit-share-set",
+                name = t.name.as_str(),
+                id = short_id(t),
+                ratio = ratio_limit_human(ratio),
+                seeding = minutes_limit_human(seeding),
+                inactive = minutes_limit_human(inactive)
+            )
+        );
+    }
+    Ok(())
+}
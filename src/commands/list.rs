@@ -0,0 +1,630 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use tabled::builder::Builder;
+
+use crate::api::{Session, TorrentInfo};
+use crate::cache::{self, Snapshot};
+use crate::client::TorrentClient;
+use crate::commands::manage::short_id;
+use crate::error::RbitError;
+use crate::history;
+use crate::i18n::tr;
+use crate::notes;
+use crate::format::{date_utc, duration_human, eta_human, rate_human, size_human, truncate};
+use crate::porcelain::{self, field, opt, print_record};
+use crate::savepath::PathMap;
+use crate::theme;
+
+/// Which torrents `rbit list` shows without `--errors-only`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ListFilter {
+    All,
+    /// Not yet complete, or transferring data
+    #[default]
+    Active,
+    /// Not yet complete
+    Downloading,
+    /// Complete
+    Seeding,
+}
+
+impl ListFilter {
+    pub fn matches(self, t: &TorrentInfo) -> bool {
+        let complete = t.progress.unwrap_or(0.0) >= 1.0;
+        match self {
+            ListFilter::All => true,
+            ListFilter::Active => !complete || t.dlspeed.unwrap_or(0) > 0 || t.upspeed.unwrap_or(0) > 0,
+            ListFilter::Downloading => !complete,
+            ListFilter::Seeding => complete,
+        }
+    }
+
+    fn name(self) -> String {
+        match self {
+            ListFilter::All => tr!("filter-all"),
+            ListFilter::Active => tr!("filter-active"),
+            ListFilter::Downloading => tr!("filter-downloading"),
+            ListFilter::Seeding => tr!("filter-seeding"),
+        }
+    }
+}
+
+/// How many of `rows` are in each state group, e.g. `5 torrents: 1 downloading, 3 seeding, 1 error`,
+/// each count in its group's color.
+fn summary(rows: &[&TorrentInfo]) -> String {
+    let theme = theme::current();
+    let mut counts: Vec<String> = theme::STATE_GROUPS
+        .into_iter()
+        .filter_map(|group| {
+            let n = rows.iter().filter(|t| theme::state_group(&t.state) == Some(group)).count();
+            let text = tr!("list-summary-group", count = n, group = tr!(&format!("state-{}", group)));
+            (n > 0).then(|| theme.paint(group, &text))
+        })
+        .collect();
+    let other = rows.iter().filter(|t| theme::state_group(&t.state).is_none()).count();
+    if other > 0 {
+        counts.push(tr!("list-summary-group", count = other, group = tr!("state-other")));
+    }
+    tr!("list-summary", count = rows.len(), groups = counts.join(", "))
+}
+
+/// A column of the `rbit list` table.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Column {
+    /// Row number, for `%N` references in later commands
+    Row,
+    /// Hash prefix
+    Id,
+    Hash,
+    Name,
+    Status,
+    Progress,
+    /// Bytes selected for download
+    Size,
+    /// Bytes of all files
+    TotalSize,
+    Downloaded,
+    Uploaded,
+    Ratio,
+    /// Download speed
+    Dl,
+    /// Upload speed
+    Up,
+    Eta,
+    /// Seeds connected to
+    Seeds,
+    /// Leechers connected to
+    Peers,
+    Category,
+    Tags,
+    Tracker,
+    SavePath,
+    AddedOn,
+    CompletedOn,
+    LastActivity,
+    SeedingTime,
+    /// Queue position
+    Queue,
+    /// `*` for torrents with a note
+    Note,
+}
+
+/// The columns `rbit list` shows unless told otherwise. Queue positions only appear when
+/// queueing is enabled on the server, categories when some torrent has one, and the note
+/// marker when some torrent has a note.
+const DEFAULT_COLUMNS: [Column; 13] = [
+    Column::Row,
+    Column::Id,
+    Column::Queue,
+    Column::Name,
+    Column::Size,
+    Column::Status,
+    Column::Progress,
+    Column::Dl,
+    Column::Up,
+    Column::Eta,
+    Column::Ratio,
+    Column::Category,
+    Column::Note,
+];
+
+/// Names, save paths and trackers are cut to this many characters unless `--wide` is given.
+const TRUNCATE_AT: usize = 40;
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Column::Row => "#",
+            Column::Id => "id",
+            Column::Hash => "hash",
+            Column::Name => "name",
+            Column::Status => "status",
+            Column::Progress => "progress",
+            Column::Size => "size",
+            Column::TotalSize => "total size",
+            Column::Downloaded => "downloaded",
+            Column::Uploaded => "uploaded",
+            Column::Ratio => "ratio",
+            Column::Dl => "dl",
+            Column::Up => "up",
+            Column::Eta => "eta",
+            Column::Seeds => "seeds",
+            Column::Peers => "peers",
+            Column::Category => "category",
+            Column::Tags => "tags",
+            Column::Tracker => "tracker",
+            Column::SavePath => "save path",
+            Column::AddedOn => "added on",
+            Column::CompletedOn => "completed on",
+            Column::LastActivity => "last activity",
+            Column::SeedingTime => "seeding time",
+            Column::Queue => "queue",
+            Column::Note => "note",
+        }
+    }
+
+    /// The cell of this column for `t`, shown in row `row`; `-` when the server does not say.
+    fn cell(self, row: usize, t: &TorrentInfo, noted: bool, wide: bool) -> String {
+        let long = |s: &str| if wide { s.to_string() } else { truncate(s, TRUNCATE_AT) };
+        let or_dash = |v: Option<String>| v.unwrap_or_else(|| "-".to_string());
+        let time = |at: Option<i64>| or_dash(at.filter(|&at| at > 0).map(|at| date_utc(at as u64)));
+        let count = |n: Option<i64>| or_dash(n.map(|n| n.to_string()));
+        match self {
+            Column::Row => row.to_string(),
+            Column::Id => short_id(t).to_string(),
+            Column::Hash => t.hash.clone(),
+            Column::Name => long(&t.name),
+            Column::Status => theme::state_label(&t.state).to_string(),
+            Column::Progress => or_dash(t.progress.map(|p| format!("{:.1}%", p * 100.0))),
+            Column::Size => or_dash(t.size.map(size_human)),
+            Column::TotalSize => or_dash(t.total_size.map(size_human)),
+            Column::Downloaded => or_dash(t.downloaded.map(size_human)),
+            Column::Uploaded => or_dash(t.uploaded.map(size_human)),
+            Column::Ratio => or_dash(t.ratio.map(|r| format!("{:.2}", r))),
+            Column::Dl => rate_human(t.dlspeed.unwrap_or(0)),
+            Column::Up => rate_human(t.upspeed.unwrap_or(0)),
+            Column::Eta => match t.eta {
+                _ if t.progress.unwrap_or(0.0) >= 1.0 => "-".to_string(),
+                Some(eta) => eta_human(eta),
+                None => "-".to_string(),
+            },
+            Column::Seeds => count(t.num_seeds),
+            Column::Peers => count(t.num_leechs),
+            Column::Category => t.category.clone().unwrap_or_default(),
+            Column::Tags => t.tags.clone().unwrap_or_default(),
+            Column::Tracker => long(t.tracker.as_deref().unwrap_or("")),
+            Column::SavePath => long(t.save_path.as_deref().unwrap_or("")),
+            Column::AddedOn => time(t.added_on),
+            Column::CompletedOn => time(t.completion_on),
+            Column::LastActivity => time(t.last_activity),
+            Column::SeedingTime => or_dash(t.seeding_time.map(|s| duration_human(Duration::from_secs(s.max(0) as u64)))),
+            Column::Queue => match t.priority {
+                Some(p) if p > 0 => p.to_string(),
+                _ => "-".to_string(),
+            },
+            Column::Note => if noted { "*" } else { "" }.to_string(),
+        }
+    }
+}
+
+/// `[list]`: defaults for `rbit list`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ListConfig {
+    /// Torrents shown when no filter flag is given (default `active`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<ListFilter>,
+    /// Columns shown when `--columns` is not given, e.g. `["name", "ratio", "eta"]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Vec<Column>>,
+}
+
+impl ListConfig {
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.columns.is_none()
+    }
+}
+
+/// How `rbit list` lays out its table
+#[derive(clap::Args, Debug)]
+pub struct Layout {
+    /// Columns to show, in order, e.g. `name,ratio,eta,category,added_on,size` (overrides
+    /// `[list] columns`)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Option<Vec<Column>>,
+
+    /// Do not cut long names, save paths and trackers short
+    #[arg(long, visible_alias = "no-truncate")]
+    pub wide: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[group(id = "filter", multiple = false)]
+pub struct ListOptions {
+    /// Show all torrents
+    #[arg(long)]
+    pub all: bool,
+
+    /// Show torrents that are not complete yet or are transferring data (the default unless
+    /// `[list] default` says otherwise)
+    #[arg(long)]
+    pub active: bool,
+
+    /// Show torrents that are not complete yet
+    #[arg(long)]
+    pub downloading: bool,
+
+    /// Show complete torrents
+    #[arg(long)]
+    pub seeding: bool,
+
+    /// Show only torrents that need attention: errored, missing files, or unregistered on a tracker
+    #[arg(long)]
+    pub errors_only: bool,
+}
+
+impl ListOptions {
+    /// The filter given on the command line, otherwise the configured one.
+    pub fn filter(&self, configured: Option<ListFilter>) -> ListFilter {
+        match (self.all, self.active, self.downloading, self.seeding) {
+            (true, ..) => ListFilter::All,
+            (_, true, ..) => ListFilter::Active,
+            (_, _, true, _) => ListFilter::Downloading,
+            (.., true) => ListFilter::Seeding,
+            _ if self.errors_only => ListFilter::All,
+            _ => configured.unwrap_or_default(),
+        }
+    }
+
+    fn explicit(&self) -> bool {
+        self.all || self.active || self.downloading || self.seeding || self.errors_only
+    }
+}
+
+/// States qBittorrent uses for torrents that cannot make progress on their own.
+pub fn is_error_state(state: &str) -> bool {
+    matches!(state, "error" | "missingFiles")
+}
+
+/// Keep torrents in an error state, plus those a tracker reports as unregistered.
+/// The latter needs one `torrents/trackers` call per torrent, run concurrently.
+async fn errored(session: &Session, torrents: Vec<TorrentInfo>) -> anyhow::Result<Vec<TorrentInfo>> {
+    let checked: Vec<anyhow::Result<Option<TorrentInfo>>> = stream::iter(torrents)
+        .map(|mut t| async move {
+            if is_error_state(&t.state) {
+                return Ok(Some(t));
+            }
+            let trackers = session.trackers(&t.hash).await?;
+            if trackers.iter().any(|tr| tr.status == 4 && tr.msg.to_lowercase().contains("unregistered")) {
+                t.state = "unregistered".to_string();
+                return Ok(Some(t));
+            }
+            Ok(None)
+        })
+        .buffered(session.concurrency.max(1))
+        .collect()
+        .await;
+    let mut out = Vec::new();
+    for t in checked {
+        out.extend(t?);
+    }
+    Ok(out)
+}
+
+/// `--porcelain=v1`: hash, state, progress (0 to 1), size, downloaded and uploaded bytes,
+/// download and upload speed, ratio, category, tags (comma-separated), name; and with
+/// `servers`, the profile each torrent is on.
+fn print_porcelain(rows: &[&TorrentInfo], servers: Option<&[&str]>) {
+    for (index, t) in rows.iter().enumerate() {
+        let tags: Vec<&str> = t.tags.as_deref().unwrap_or("").split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
+        let mut record = vec![
+            t.hash.clone(),
+            field(&t.state),
+            opt(t.progress),
+            opt(t.size),
+            opt(t.downloaded),
+            opt(t.uploaded),
+            opt(t.dlspeed),
+            opt(t.upspeed),
+            opt(t.ratio),
+            field(t.category.as_deref().unwrap_or("")),
+            field(&tags.join(",")),
+            field(&t.name),
+        ];
+        if let Some(servers) = servers {
+            record.push(field(servers[index]));
+        }
+        print_record(&record);
+    }
+}
+
+/// The torrents of a qBittorrent server and whether it has queueing enabled.
+async fn fetch(session: &Session) -> anyhow::Result<(Vec<TorrentInfo>, bool)> {
+    session.login().await?;
+    let (torrents, prefs) = tokio::try_join!(session.torrents("all"), session.get_json::<serde_json::Value>("app/preferences"))?;
+    Ok((torrents, prefs["queueing_enabled"].as_bool().unwrap_or(false)))
+}
+
+pub async fn list_torrents(
+    session: &Session,
+    options: &ListOptions,
+    layout: &Layout,
+    config: &ListConfig,
+    porcelain: Option<porcelain::Version>,
+    record_history: bool,
+    cached: bool,
+) -> anyhow::Result<()> {
+    let paths = &session.path_map;
+    if cached {
+        return show_cached(&session.host, paths, options, layout, config, porcelain);
+    }
+    let (mut torrents, queueing) = match fetch(session).await {
+        Ok(fetched) => fetched,
+        Err(e) => return fall_back(&session.host, paths, e, options, layout, config, porcelain),
+    };
+    if record_history {
+        history::record_due(session, &torrents).await;
+    }
+    save_snapshot(&session.host, &torrents, queueing);
+    if options.errors_only {
+        torrents = errored(session, torrents).await?;
+    }
+    localize(&mut torrents, paths);
+
+    show(&session.host, &torrents, queueing, options, layout, config, porcelain);
+    Ok(())
+}
+
+/// `list --everywhere`: the torrents of every `(profile name, session)` pair in one table with a
+/// `server` column, fetched concurrently. A server that cannot be reached is shown from its saved
+/// listing, as `list` does, or else left out with a warning; this fails only if all of them are.
+pub async fn list_everywhere(
+    servers: &[(String, Session)],
+    options: &ListOptions,
+    layout: &Layout,
+    config: &ListConfig,
+    porcelain: Option<porcelain::Version>,
+    record_history: bool,
+    cached: bool,
+) -> anyhow::Result<()> {
+    let listings = join_all(servers.iter().map(|(name, session)| async move {
+        if cached {
+            let snapshot = cache::load_snapshot(&session.host).ok_or_else(|| anyhow::anyhow!(tr!("list-no-snapshot", host = session.host.as_str())))?;
+            return Ok(stale(name, snapshot, &session.path_map, options));
+        }
+        let (mut torrents, queueing) = match fetch(session).await {
+            Ok(fetched) => fetched,
+            Err(e) => match cache::load_snapshot(&session.host).filter(|_| porcelain.is_none() && unreachable(&e)) {
+                Some(snapshot) => {
+                    eprintln!("{} {}: {:#}", tr!("warning"), name, e);
+                    return Ok(stale(name, snapshot, &session.path_map, options));
+                }
+                None => return Err(e),
+            },
+        };
+        if record_history {
+            history::record_due(session, &torrents).await;
+        }
+        save_snapshot(&session.host, &torrents, queueing);
+        if options.errors_only {
+            torrents = errored(session, torrents).await?;
+        }
+        localize(&mut torrents, &session.path_map);
+        Ok((torrents, queueing))
+    }))
+    .await;
+
+    let mut failed = 0;
+    let mut queueing = false;
+    let mut listed = Vec::new();
+    for ((name, _), listing) in servers.iter().zip(&listings) {
+        match listing {
+            Ok((torrents, queues)) => {
+                queueing |= queues;
+                listed.extend(torrents.iter().map(|t| (name.as_str(), t)));
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("{} {}: {:#}", tr!("warning"), name, e);
+            }
+        }
+    }
+    if failed == servers.len() {
+        return Err(anyhow::anyhow!(tr!("status-unreachable")));
+    }
+
+    let filter = options.filter(config.default);
+    let total = listed.len();
+    listed.retain(|(_, t)| filter.matches(t));
+    let (names, rows): (Vec<&str>, Vec<&TorrentInfo>) = listed.into_iter().unzip();
+    if let Some(porcelain::Version::V1) = porcelain {
+        print_porcelain(&rows, Some(&names));
+        return Ok(());
+    }
+    print_table(&rows, Some(&names), queueing, layout, config);
+    if total > rows.len() && !options.explicit() {
+        println!("{}", tr!("list-hidden", hidden = total - rows.len(), filter = filter.name()));
+    }
+    Ok(())
+}
+
+/// A saved listing standing in for the server of profile `name`, saying so on stderr.
+fn stale(name: &str, snapshot: Snapshot, paths: &PathMap, options: &ListOptions) -> (Vec<TorrentInfo>, bool) {
+    let age = duration_human(Duration::from_secs(cache::now().saturating_sub(snapshot.saved_at)));
+    eprintln!("{}: {}", name, tr!("list-stale", age = age));
+    let mut torrents = snapshot.torrents;
+    if options.errors_only {
+        torrents.retain(|t| is_error_state(&t.state));
+    }
+    localize(&mut torrents, paths);
+    (torrents, snapshot.queueing)
+}
+
+/// List the torrents of a server that is not qBittorrent. `--errors-only` keeps the torrents
+/// in an error state; trackers are not asked.
+pub async fn list_from(
+    client: &impl TorrentClient,
+    options: &ListOptions,
+    layout: &Layout,
+    config: &ListConfig,
+    porcelain: Option<porcelain::Version>,
+    cached: bool,
+) -> anyhow::Result<()> {
+    let paths = &PathMap::default();
+    if cached {
+        return show_cached(client.host(), paths, options, layout, config, porcelain);
+    }
+    let fetched = async {
+        client.login().await?;
+        client.torrents().await
+    };
+    let mut torrents = match fetched.await {
+        Ok(torrents) => torrents,
+        Err(e) => return fall_back(client.host(), paths, e, options, layout, config, porcelain),
+    };
+    save_snapshot(client.host(), &torrents, false);
+    if options.errors_only {
+        torrents.retain(|t| is_error_state(&t.state));
+    }
+    show(client.host(), &torrents, false, options, layout, config, porcelain);
+    Ok(())
+}
+
+/// The save paths of `torrents` where they are on this machine, for showing them.
+fn localize(torrents: &mut [TorrentInfo], paths: &PathMap) {
+    for t in torrents {
+        t.save_path = t.save_path.as_deref().map(|p| paths.show(p));
+        t.content_path = t.content_path.as_deref().map(|p| paths.show(p));
+    }
+}
+
+fn save_snapshot(host: &str, torrents: &[TorrentInfo], queueing: bool) {
+    if let Err(e) = cache::save_snapshot(host, torrents, queueing) {
+        tracing::debug!("could not save the listing for --cached: {:#}", e);
+    }
+}
+
+/// Whether `e` says the server could not be reached, rather than that it refused.
+fn unreachable(e: &anyhow::Error) -> bool {
+    e.chain().any(|c| matches!(c.downcast_ref::<RbitError>(), Some(RbitError::Connection(_))))
+}
+
+/// Show a saved listing, saying on stderr how old it is. Only error states say a torrent
+/// needs attention here, as the trackers cannot be asked.
+fn show_snapshot(snapshot: Snapshot, paths: &PathMap, options: &ListOptions, layout: &Layout, config: &ListConfig, porcelain: Option<porcelain::Version>) {
+    let age = duration_human(Duration::from_secs(cache::now().saturating_sub(snapshot.saved_at)));
+    eprintln!("{}", tr!("list-stale", age = age));
+    let mut torrents = snapshot.torrents;
+    if options.errors_only {
+        torrents.retain(|t| is_error_state(&t.state));
+    }
+    localize(&mut torrents, paths);
+    show(&snapshot.host, &torrents, snapshot.queueing, options, layout, config, porcelain);
+}
+
+/// `--cached`: the listing saved the last time `host` was reached.
+fn show_cached(host: &str, paths: &PathMap, options: &ListOptions, layout: &Layout, config: &ListConfig, porcelain: Option<porcelain::Version>) -> anyhow::Result<()> {
+    let snapshot = cache::load_snapshot(host).ok_or_else(|| anyhow::anyhow!(tr!("list-no-snapshot", host = host)))?;
+    show_snapshot(snapshot, paths, options, layout, config, porcelain);
+    Ok(())
+}
+
+/// When `e` is that the server cannot be reached, show the listing saved the last time it was
+/// instead. Not for `--porcelain`, where a script would take it for the current state.
+fn fall_back(
+    host: &str,
+    paths: &PathMap,
+    e: anyhow::Error,
+    options: &ListOptions,
+    layout: &Layout,
+    config: &ListConfig,
+    porcelain: Option<porcelain::Version>,
+) -> anyhow::Result<()> {
+    match cache::load_snapshot(host).filter(|_| porcelain.is_none() && unreachable(&e)) {
+        Some(snapshot) => {
+            eprintln!("{} {:#}", tr!("warning"), e);
+            show_snapshot(snapshot, paths, options, layout, config, porcelain);
+            Ok(())
+        }
+        None => Err(e),
+    }
+}
+
+/// Print the torrents the options select, as a table or porcelain records, and remember the
+/// rows for `%N` references.
+fn show(
+    host: &str,
+    torrents: &[TorrentInfo],
+    queueing: bool,
+    options: &ListOptions,
+    layout: &Layout,
+    config: &ListConfig,
+    porcelain: Option<porcelain::Version>,
+) {
+    let filter = options.filter(config.default);
+    let rows: Vec<&TorrentInfo> = torrents.iter().filter(|t| filter.matches(t)).collect();
+    if let Some(porcelain::Version::V1) = porcelain {
+        print_porcelain(&rows, None);
+        return;
+    }
+
+    let hidden = torrents.len() - rows.len();
+    if let Err(e) = cache::save_listing(host, rows.iter().map(|t| t.hash.clone()).collect()) {
+        tracing::debug!("could not save the listing for %N references: {:#}", e);
+    }
+    print_table(&rows, None, queueing, layout, config);
+    // without a filter flag, say what the default left out
+    if hidden > 0 && !options.explicit() {
+        println!("{}", tr!("list-hidden", hidden = hidden, filter = filter.name()));
+    }
+}
+
+/// The table of `rows` with the summary under it. With `servers`, the profile of each row comes
+/// first, and there are no row numbers, since `%N` references are kept per server.
+fn print_table(rows: &[&TorrentInfo], servers: Option<&[&str]>, queueing: bool, layout: &Layout, config: &ListConfig) {
+    let saved_notes = notes::load().unwrap_or_else(|e| {
+        eprintln!("{} notes: {:#}", tr!("warning"), e);
+        Default::default()
+    });
+    let noted: Vec<bool> = rows
+        .iter()
+        .map(|t| saved_notes.contains_key(&t.hash.to_lowercase()) || notes::from_tags(t.tags.as_deref().unwrap_or("")).is_some())
+        .collect();
+    let mut columns: Vec<Column> = match layout.columns.as_ref().or(config.columns.as_ref()) {
+        Some(columns) => columns.clone(),
+        None => {
+            let any_note = noted.contains(&true);
+            let any_category = rows.iter().any(|t| t.category.as_deref().is_some_and(|c| !c.is_empty()));
+            DEFAULT_COLUMNS
+                .into_iter()
+                .filter(|&c| match c {
+                    Column::Queue => queueing,
+                    Column::Category => any_category,
+                    Column::Note => any_note,
+                    _ => true,
+                })
+                .collect()
+        }
+    };
+
+    if servers.is_some() {
+        columns.retain(|&c| c != Column::Row);
+    }
+
+    let theme = theme::current();
+    let server_header = servers.map(|_| "server");
+    let mut builder = Builder::new().set_header(server_header.into_iter().chain(columns.iter().map(|c| c.header())));
+    for (index, (t, noted)) in rows.iter().zip(noted).enumerate() {
+        let server = servers.map(|names| theme.by_state(&t.state, names[index]));
+        builder = builder.add_row(server.into_iter().chain(columns.iter().map(|c| theme.by_state(&t.state, &c.cell(index + 1, t, noted, layout.wide)))));
+    }
+    println!("{}", theme::styled(builder.build()));
+    if !rows.is_empty() {
+        println!("{}", summary(rows));
+    }
+}
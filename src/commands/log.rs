@@ -0,0 +1,156 @@
+//! `rbit log`: qBittorrent's own log (`log/main`), or its log of blocked and banned peers
+//! (`log/peers`), with `--follow` to keep printing new entries as the server logs them. New
+//! entries are asked for with `last_known_id`, so following costs one small request per poll.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::api::Session;
+use crate::format::time_utc;
+use crate::i18n::tr;
+use crate::interrupt::{self, Interrupt};
+use crate::theme;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Severities of `log/main`, least severe first.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Normal,
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Level {
+    /// The severity qBittorrent sends as `type`, a bit each.
+    fn of(kind: u32) -> Level {
+        match kind {
+            8 => Level::Critical,
+            4 => Level::Warning,
+            2 => Level::Info,
+            _ => Level::Normal,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Normal => "normal",
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Critical => "critical",
+        }
+    }
+
+    /// The theme color the level is shown in.
+    fn color(self) -> Option<&'static str> {
+        match self {
+            Level::Critical => Some("error"),
+            Level::Warning => Some("stalled"),
+            Level::Info => Some("accent"),
+            Level::Normal => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Entry {
+    id: i64,
+    #[serde(default)]
+    message: String,
+    timestamp: i64,
+    #[serde(rename = "type", default)]
+    kind: u32,
+}
+
+#[derive(Deserialize, Debug)]
+struct PeerEntry {
+    id: i64,
+    #[serde(default)]
+    ip: String,
+    timestamp: i64,
+    #[serde(default)]
+    blocked: bool,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Log timestamps are seconds, or milliseconds from some servers.
+fn when(timestamp: i64) -> String {
+    let secs = if timestamp > 100_000_000_000 { timestamp / 1000 } else { timestamp };
+    time_utc(secs.max(0) as u64)
+}
+
+/// The query for `log/main`: the levels from `min` up, and only entries after `after`.
+fn main_query(min: Level, after: i64) -> String {
+    let levels = [Level::Normal, Level::Info, Level::Warning, Level::Critical];
+    let flags: Vec<String> = levels.iter().map(|&l| format!("{}={}", l.label(), l >= min)).collect();
+    format!("log/main?{}&last_known_id={}", flags.join("&"), after)
+}
+
+/// Print the newest `lines` entries at `min` or above, or of the peer log with `peers`; with
+/// `follow`, keep printing new ones until Ctrl-C.
+pub async fn log(session: &Session, min: Level, peers: bool, lines: usize, follow: bool) -> anyhow::Result<()> {
+    session.login().await?;
+    let theme = theme::current();
+    let mut last = -1;
+    let mut first = true;
+    let mut interrupt = Interrupt::listen();
+    loop {
+        let mut printed: Vec<String> = Vec::new();
+        if peers {
+            let entries: Vec<PeerEntry> = session.get_json(&format!("log/peers?last_known_id={}", last)).await?;
+            for e in entries.iter().filter(|e| e.id > last) {
+                let what = if e.blocked { "blocked" } else { "banned" };
+                let reason = if e.reason.is_empty() { String::new() } else { format!(": {}", e.reason) };
+                printed.push(format!("{}  {}  {}{}", when(e.timestamp), theme.paint("error", &format!("{:<8}", what)), e.ip, reason));
+            }
+            last = entries.iter().map(|e| e.id).max().unwrap_or(last);
+        } else {
+            let entries: Vec<Entry> = session.get_json(&main_query(min, last)).await?;
+            // the server filters already; this keeps a follow from repeating entries if it did not
+            for e in entries.iter().filter(|e| e.id > last && Level::of(e.kind) >= min) {
+                let level = Level::of(e.kind);
+                let label = format!("{:<8}", level.label().to_uppercase());
+                let label = match level.color() {
+                    Some(color) => theme.paint(color, &label),
+                    None => label,
+                };
+                printed.push(format!("{}  {}  {}", when(e.timestamp), label, e.message));
+            }
+            last = entries.iter().map(|e| e.id).max().unwrap_or(last);
+        }
+        // at first only the tail; a follow prints everything that came in since
+        let skip = if first { printed.len().saturating_sub(lines) } else { 0 };
+        for line in &printed[skip..] {
+            println!("{}", line);
+        }
+        if first && printed.is_empty() && !follow {
+            println!("{}", if peers { tr!("log-empty") } else { tr!("log-empty-at-level") });
+        }
+        first = false;
+        if !follow {
+            return Ok(());
+        }
+        tokio::select! {
+            _ = interrupt.recv() => {
+                interrupt::wrap_up(session).await;
+                return Ok(());
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asks_for_the_levels_from_the_minimum() {
+        assert_eq!(main_query(Level::Warning, 41), "log/main?normal=false&info=false&warning=true&critical=true&last_known_id=41");
+        assert_eq!(Level::of(4), Level::Warning);
+        assert_eq!(when(1_700_000_005_123), when(1_700_000_005));
+    }
+}
@@ -0,0 +1,462 @@
+//! `rbit man`: man pages generated from the command-line definition, one per subcommand, and
+//! `rbit.toml(5)` for the config file, whose keys come from the config structs themselves (see
+//! [`crate::schema`]). The examples shown here are also added to each subcommand's `--help`.
+
+use std::fs;
+use std::path::Path;
+
+use clap::{Arg, Command};
+
+use crate::config::Config;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::schema::{probe, Kind};
+
+/// Examples per subcommand, by path (`limit torrent`): a comment and the command line.
+const EXAMPLES: &[(&str, &[(&str, &str)])] = &[
+    ("add", &[
+        ("Add a magnet link, saving to /downloads", "rbit add 'magnet:?xt=urn:btih:...' --dest=/downloads"),
+        ("Add every .torrent file of a folder", "rbit add './downloads/*.torrent'"),
+        ("Add a .torrent from a URL", "rbit add 'https://tracker.example.org/download/1234.torrent'"),
+    ]),
+    ("inspect", &[("Show the info-hash, files and trackers of a .torrent file", "rbit inspect ./some.torrent")]),
+    ("exists", &[("Add a torrent only if the server does not have it yet", "rbit exists -q ubuntu.torrent || rbit add ubuntu.torrent")]),
+    ("get", &[("Go to where a torrent is saved", "cd \"$(rbit get a1b2c3 save_path)\"")]),
+    ("export", &[
+        ("Save the .torrent files of two torrents", "rbit export a1b2c3d4 e5f6a7b8 --dir ./backup"),
+        ("Back up the .torrent files of every torrent", "rbit export --all --dir ./backup"),
+    ]),
+    ("import", &[("Move everything from a Transmission profile, with categories and paused states", "rbit import --from old-box --with-state")]),
+    ("cross-seed", &[("Seed a torrent's data on a second tracker", "rbit cross-seed a1b2c3d4 --tracker 'https://tracker.example.org/announce/PASSKEY' --source EXM")]),
+    ("verify", &[
+        ("Hash a torrent's files where this machine can read them", "rbit verify a1b2c3d4 --deep"),
+        ("Hash them on a share mounted here, against a local .torrent file", "rbit verify a1b2c3d4 --deep --data-root /mnt/seedbox/tv --torrent-file show.torrent"),
+    ]),
+    ("wait", &[("Post-process a torrent once it is done, giving up after six hours", "rbit wait 3f2a --timeout 6h && ./post-process.sh")]),
+    ("list", &[
+        ("Active torrents", "rbit list"),
+        ("All torrents, with chosen columns", "rbit list --all --columns name,ratio,eta,category,added_on,size"),
+        ("What the server listed last time, without contacting it", "rbit list --cached --all"),
+        ("The torrents of every server profile in one table", "rbit list --everywhere"),
+    ]),
+    ("magnet", &[("Print the magnet link of a .torrent file", "rbit magnet ./some.torrent")]),
+    ("auth", &[("Store the password of a server profile in the OS keyring", "rbit -s seedbox auth login")]),
+    ("category sync", &[("Show what syncing the [categories] table would change, then do it", "rbit --dry-run category sync --from-config\nrbit category sync --from-config")]),
+    ("apply-rules", &[("Show how the [[rules]] would file the torrents already on the server, then do it", "rbit --dry-run apply-rules --all\nrbit apply-rules --all")]),
+    ("cleanup", &[("Show which tagged torrents the [[retention]] policies delete and why", "rbit --dry-run cleanup")]),
+    ("prune", &[
+        ("Show what a policy would delete", "rbit --dry-run prune --ratio-over 2.0 --seeded-for 14d --category sonarr"),
+        ("Delete those torrents and their data without asking, e.g. from cron", "rbit prune --ratio-over 2.0 --seeded-for 14d --category sonarr --delete-data --yes"),
+    ]),
+    ("schedule", &[("Switch the alternative speed limits to what the schedule says, e.g. from cron", "rbit schedule --apply")]),
+    ("completions", &[
+        ("Completion for bash", "rbit completions bash > ~/.local/share/bash-completion/completions/rbit"),
+        ("Completion for zsh", "echo 'source <(rbit completions zsh)' >> ~/.zshrc"),
+    ]),
+    ("self-update", &[("See whether a newer release is out", "rbit self-update --check")]),
+    ("man", &[
+        ("Read the manual of `rbit add`", "rbit man add | man -l -"),
+        ("Install every page", "rbit man --output /usr/local/share/man"),
+    ]),
+    ("config init", &[("Write a starting config, asking for the server and credentials", "rbit config init")]),
+    ("config show", &[("Show the settings in effect for a server profile and where each comes from", "rbit -s seedbox config show")]),
+    ("config export", &[("Export the server profiles without their secrets", "rbit config export --redact -o rbit-profiles.toml")]),
+    ("note set", &[("Note why a torrent is kept, and tag it", "rbit note set %2 'cross-seeded with the BD remux, keep until 2.0' --tag")]),
+    ("move", &[("Move a torrent's data", "rbit move a1b2c3d4 /archive/linux")]),
+    ("rename", &[("Rename a torrent", "rbit rename a1b2c3d4 'Ubuntu 24.04 Desktop'")]),
+    ("rename-file", &[
+        ("Rename a file inside a torrent", "rbit rename-file a1b2c3d4 'ubuntu/old.iso' 'ubuntu/new.iso'"),
+        ("Rename a folder", "rbit rename-file a1b2c3d4 'Bad.Folder.Name' 'Good Folder Name' --folder"),
+    ]),
+    ("reannounce", &[("Announce stalled torrents of a category", "rbit reannounce --state stalled --category tv")]),
+    ("recheck", &[("Recheck every torrent", "rbit recheck --all")]),
+    ("pause", &[
+        ("Pause the seeding torrents of a category", "rbit pause --category tv --state seeding"),
+        ("Pause the stalled torrents on every server profile", "rbit --server all pause --state stalled"),
+    ]),
+    ("resume", &[("Resume two torrents", "rbit resume a1b2c3d4 e5f6a7b8")]),
+    ("peers", &[
+        ("Peers of a torrent", "rbit peers a1b2c3d4"),
+        ("Ban a peer", "rbit peers a1b2c3d4 --ban 203.0.113.7"),
+    ]),
+    ("pieces", &[("A larger map of a torrent's pieces", "rbit pieces a1b2c3d4 --width 100 --lines 20")]),
+    ("queue", &[("Move a torrent to the top of the queue", "rbit queue top a1b2c3d4")]),
+    ("sequential", &[("Download a torrent in order, e.g. to watch it while it downloads", "rbit sequential a1b2c3d4 on")]),
+    ("stream", &[
+        ("Watch the largest file of a torrent while it downloads", "rbit stream a1b2c3d4"),
+        ("Play the second file with VLC from a seedbox mounted at /mnt/seedbox", "rbit stream a1b2c3d4 --file 1 --player 'vlc --fullscreen' --data-root /mnt/seedbox"),
+    ]),
+    ("webseeds", &[("Add a web seed", "rbit webseeds a1b2c3d4 --add https://mirror.example.org/ubuntu.iso")]),
+    ("trackers", &[
+        ("Add a tracker", "rbit trackers a1b2c3d4 --add udp://tracker.example.org:1337/announce"),
+        ("Replace an announce URL, e.g. after a passkey change", "rbit trackers a1b2c3d4 --replace https://old.example.net/abc/announce https://new.example.net/abc/announce"),
+    ]),
+    ("limit global", &[("Limit downloads to 5 MiB/s and uploads to 1 MiB/s", "rbit limit global --dl 5M --up 1M")]),
+    ("limit alt", &[("Turn the alternative speed limits on", "rbit limit alt on")]),
+    ("limit torrent", &[("Slow down uploads of old torrents past a ratio of 2", "rbit limit torrent --tag old --ratio '>2.0' --up 100k")]),
+    ("prefs set", &[("Change several preferences at once", "rbit prefs set listen_port=51413 dht=off max_connections=unlimited alt_dl_limit=2M")]),
+    ("port test", &[("Check whether peers can reach the listening port", "rbit port test")]),
+    ("report", &[("Usage per tracker as a Markdown table", "rbit report trackers --format markdown > trackers.md")]),
+    ("rss add", &[("Subscribe to a feed in a folder", "rbit rss add https://ubuntu.example/releases.rss --path 'Linux\\Distros\\Ubuntu releases'")]),
+    ("search", &[
+        ("The first ten results", "rbit search 'ubuntu 24.04' --limit 10"),
+        ("Search some plugins only, in a category", "rbit search 'big buck bunny' --category movies --plugin piratebay --plugin eztv"),
+    ]),
+    ("seedlimits", &[("Stop seeding two torrents at a ratio of 2 or after two weeks", "rbit seedlimits a1b2c3d4 e5f6a7b8 --ratio 2.0 --seeding-time 14d")]),
+    ("stats", &[("Transfers of the last week, from the stats history", "rbit stats --since 7d")]),
+    ("history", &[("A torrent's progress over the last two days", "rbit history 3f2a --since 2d")]),
+    ("timeline", &[("When a torrent downloaded, as a chart over the last week", "rbit timeline 3f2a --since 7d")]),
+    ("forecast", &[("When the downloads will be done, from the speeds of the last hour", "rbit forecast --from-history 1h")]),
+    ("log", &[("Keep printing the server's warnings and errors as they are logged", "rbit log --level warning --follow")]),
+    ("exporter", &[("Serve a Grafana JSON datasource for a server profile", "rbit --server seedbox exporter --json-api --listen 0.0.0.0:9187")]),
+    ("run-once", &[("Unpack each torrent once, from qBittorrent's \"Run external program\"", "rbit run-once --hash \"%I\" -- /usr/local/bin/unpack.sh \"%F\" \"%N\"")]),
+    ("doctor", &[("Find out why a server profile cannot be reached", "rbit -s seedbox doctor")]),
+    ("status", &[("A health summary of every server profile", "rbit status --all-profiles")]),
+];
+
+/// What `rbit` exits with, for EXIT STATUS.
+const EXIT_CODES: &[(u8, &str)] = &[
+    (0, "Success."),
+    (1, "Any other error; for exists, a torrent that is not on the server."),
+    (2, "Invalid arguments, host or config file."),
+    (3, "Authentication failed."),
+    (4, "The server could not be reached or did not answer in time."),
+    (5, "A torrent id matched no torrent."),
+    (6, "Some items of a batch failed; the others went through."),
+    (7, "wait --timeout ran out."),
+    (130, "Interrupted with Ctrl-C."),
+];
+
+fn examples(path: &str) -> &'static [(&'static str, &'static str)] {
+    EXAMPLES.iter().find(|(p, _)| *p == path).map_or(&[], |(_, examples)| examples)
+}
+
+/// Add the examples to the `--help` of the subcommands that have some.
+pub fn with_examples(cmd: Command) -> Command {
+    fn add(cmd: Command, path: &str) -> Command {
+        let names: Vec<String> = cmd.get_subcommands().map(|s| s.get_name().to_string()).collect();
+        let mut cmd = cmd;
+        for name in names {
+            let sub_path = if path.is_empty() { name.clone() } else { format!("{} {}", path, name) };
+            cmd = cmd.mut_subcommand(name, |sub| {
+                let sub = add(sub, &sub_path);
+                match examples(&sub_path) {
+                    [] => sub,
+                    examples => {
+                        let text: Vec<String> = examples.iter().map(|(what, line)| format!("  # {}\n  {}", what, line.replace('\n', "\n  "))).collect();
+                        sub.after_long_help(format!("Examples:\n{}", text.join("\n\n")))
+                    }
+                }
+            });
+        }
+        cmd
+    }
+    add(cmd, "")
+}
+
+/// `text` safe to put into a roff document.
+fn roff(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A help text as roff paragraphs, with `code` in bold.
+fn paragraphs(text: &str) -> String {
+    let text = text.trim().split("\n\n").map(roff).collect::<Vec<_>>().join("\n.sp\n");
+    let parts: Vec<&str> = text.split('`').collect();
+    if parts.len().is_multiple_of(2) {
+        // an unpaired backtick: leave them all as they are
+        return text;
+    }
+    parts.iter().enumerate().map(|(i, part)| if i % 2 == 1 { format!("\\fB{}\\fR", part) } else { part.to_string() }).collect()
+}
+
+fn header(out: &mut String, title: &str, section: u8) {
+    out.push_str(&format!(".TH \"{}\" \"{}\" \"\" \"rbit {}\" \"rbit manual\"\n", title.to_uppercase(), section, env!("CARGO_PKG_VERSION")));
+}
+
+fn page_name(path: &[&str]) -> String {
+    std::iter::once("rbit").chain(path.iter().copied()).collect::<Vec<_>>().join("-")
+}
+
+/// How `arg` is written on the command line, e.g. `-c, --config <CONFIG>`.
+fn arg_usage(arg: &Arg) -> String {
+    let value = || match arg.get_value_names() {
+        Some(names) => names.iter().map(|n| format!("<{}>", n)).collect::<Vec<_>>().join(" "),
+        None => format!("<{}>", arg.get_id().as_str().to_uppercase()),
+    };
+    if arg.is_positional() {
+        return format!("\\fI{}\\fR", roff(&value()));
+    }
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("\\fB\\-{}\\fR", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("\\fB\\-\\-{}\\fR", roff(long)));
+    }
+    let mut usage = names.join(", ");
+    if arg.get_action().takes_values() {
+        usage.push_str(&format!(" \\fI{}\\fR", roff(&value())));
+    }
+    usage
+}
+
+fn arg_item(out: &mut String, arg: &Arg) {
+    out.push_str(&format!(".TP\n{}\n", arg_usage(arg)));
+    if let Some(help) = arg.get_long_help().or(arg.get_help()) {
+        out.push_str(&format!("{}\n", paragraphs(&help.to_string())));
+    }
+    let mut notes = Vec::new();
+    if arg.get_action().takes_values() {
+        let values: Vec<String> = arg.get_possible_values().iter().filter(|v| !v.is_hide_set()).map(|v| v.get_name().to_string()).collect();
+        if !values.is_empty() {
+            notes.push(format!("Possible values: {}.", values.join(", ")));
+        }
+        let defaults: Vec<String> = arg.get_default_values().iter().map(|v| v.to_string_lossy().into_owned()).collect();
+        if !defaults.is_empty() {
+            notes.push(format!("Default: {}.", defaults.join(", ")));
+        }
+    }
+    if let Some(env) = arg.get_env() {
+        notes.push(format!("Also read from ${}.", env.to_string_lossy()));
+    }
+    if !notes.is_empty() {
+        out.push_str(&format!(".sp\n{}\n", roff(&notes.join(" "))));
+    }
+}
+
+/// Whether `arg` is listed on the page of the command it belongs to.
+fn listed(arg: &Arg) -> bool {
+    !arg.is_hide_set() && !matches!(arg.get_id().as_str(), "help" | "version")
+}
+
+/// The page of `cmd`, found at `path` below `rbit`. `cmd` must have been built.
+fn command_page(cmd: &Command, path: &[&str]) -> String {
+    let name = page_name(path);
+    let root = path.is_empty();
+    let mut out = String::new();
+    header(&mut out, &name, 1);
+    let about = cmd.get_about().map(|a| a.to_string()).unwrap_or_default();
+    out.push_str(&format!(".SH NAME\n{} \\- {}\n", roff(&name), roff(about.lines().next().unwrap_or_default())));
+
+    let usage = cmd.clone().render_usage().to_string();
+    let usage = usage.trim().trim_start_matches("Usage:").trim();
+    out.push_str(".SH SYNOPSIS\n.nf\n");
+    for line in usage.lines() {
+        out.push_str(&format!("\\fB{}\\fR\n", roff(line.trim())));
+    }
+    out.push_str(".fi\n");
+
+    out.push_str(".SH DESCRIPTION\n");
+    let long = cmd.get_long_about().map(|a| a.to_string()).unwrap_or_else(|| about.clone());
+    out.push_str(&format!("{}\n", paragraphs(&long)));
+    let aliases: Vec<&str> = cmd.get_visible_aliases().collect();
+    if !aliases.is_empty() {
+        out.push_str(&format!(".sp\nAlso available as {}.\n", roff(&aliases.join(", "))));
+    }
+    if root {
+        out.push_str(".sp\nEach command has its own page, e.g. \\fBrbit\\-list\\fR(1); \\fBrbit.toml\\fR(5) describes the config file.\n");
+    }
+
+    let positionals: Vec<&Arg> = cmd.get_positionals().filter(|a| listed(a)).collect();
+    if !positionals.is_empty() {
+        out.push_str(".SH ARGUMENTS\n");
+        positionals.iter().for_each(|a| arg_item(&mut out, a));
+    }
+    // global options are described once, on rbit(1)
+    let options: Vec<&Arg> = cmd.get_arguments().filter(|a| !a.is_positional() && listed(a) && (root || !a.is_global_set())).collect();
+    if !options.is_empty() {
+        out.push_str(".SH OPTIONS\n");
+        options.iter().for_each(|a| arg_item(&mut out, a));
+    }
+
+    let subcommands: Vec<&Command> = cmd.get_subcommands().filter(|s| !s.is_hide_set() && s.get_name() != "help").collect();
+    if !subcommands.is_empty() {
+        out.push_str(if root { ".SH COMMANDS\n" } else { ".SH SUBCOMMANDS\n" });
+        for sub in subcommands {
+            let mut sub_path = path.to_vec();
+            sub_path.push(sub.get_name());
+            let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+            out.push_str(&format!(".TP\n\\fB{}\\fR(1)\n{}\n", roff(&page_name(&sub_path)), paragraphs(&about)));
+        }
+    }
+
+    let examples = examples(&path.join(" "));
+    if !examples.is_empty() {
+        out.push_str(".SH EXAMPLES\n");
+        for (what, line) in examples {
+            out.push_str(&format!(".PP\n{}\n.RS 4\n.nf\n{}\n.fi\n.RE\n", roff(what), roff(line)));
+        }
+    }
+
+    if root {
+        out.push_str(".SH EXIT STATUS\n");
+        for (code, meaning) in EXIT_CODES {
+            out.push_str(&format!(".TP\n{}\n{}\n", code, roff(meaning)));
+        }
+        out.push_str(".SH FILES\n.TP\n\\fI~/.config/rbit/config.toml\\fR\n");
+        out.push_str("The config file, or the platform's equivalent; a \\fIrbit.toml\\fR in the current folder overrides it. See \\fBrbit.toml\\fR(5).\n");
+    }
+
+    out.push_str(".SH SEE ALSO\n");
+    let mut see_also = Vec::new();
+    if !root {
+        see_also.push(format!("\\fB{}\\fR(1)", roff(&page_name(&path[..path.len() - 1]))));
+    }
+    see_also.push("\\fBrbit.toml\\fR(5)".to_string());
+    out.push_str(&format!("{}\n", see_also.join(", ")));
+    out
+}
+
+/// A value's type, e.g. `string` or `"binary" or "si"`.
+fn type_name(kind: &Kind) -> String {
+    match kind {
+        Kind::Bool => "true or false".to_string(),
+        Kind::Integer => "integer".to_string(),
+        Kind::Float => "number".to_string(),
+        Kind::String => "string".to_string(),
+        Kind::Choice(values) => {
+            let quoted: Vec<String> = values.iter().map(|v| format!("\"{}\"", v)).collect();
+            match quoted.split_last() {
+                Some((last, [])) => last.clone(),
+                Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+                None => "string".to_string(),
+            }
+        }
+        Kind::List(item) => format!("array of {}", type_name(item)),
+        Kind::Map(value) => format!("table of {}", type_name(value)),
+        Kind::Table(_) => "table".to_string(),
+        Kind::Any => "any value".to_string(),
+    }
+}
+
+/// Whether a value of `kind` is a table of its own rather than a key of its table.
+fn own_section(kind: &Kind) -> Option<(&Vec<(&'static str, Kind)>, &'static str)> {
+    match kind {
+        Kind::Table(keys) => Some((keys, "")),
+        Kind::Map(value) => match &**value {
+            Kind::Table(keys) => Some((keys, ".<name>")),
+            _ => None,
+        },
+        Kind::List(item) => match &**item {
+            Kind::Table(keys) => Some((keys, "[]")),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The keys of a table, then a section for each of its tables. Tables with the same keys as one
+/// described before refer to it.
+fn config_section<'a>(out: &mut String, title: &str, keys: &'a [(&'static str, Kind)], seen: &mut Vec<(&'a [(&'static str, Kind)], String)>) {
+    if !title.is_empty() {
+        out.push_str(&format!(".SS {}\n", roff(title)));
+        if let Some((_, first)) = seen.iter().find(|(k, _)| *k == keys) {
+            out.push_str(&format!("The same keys as {}.\n", roff(first)));
+            return;
+        }
+        seen.push((keys, title.to_string()));
+    }
+    for (name, kind) in keys.iter().filter(|(_, kind)| own_section(kind).is_none()) {
+        let key = match kind {
+            Kind::Map(value) => format!("\\fB{}.\\fR\\fI<name>\\fR = {}", roff(name), roff(&type_name(value))),
+            kind => format!("\\fB{}\\fR = {}", roff(name), roff(&type_name(kind))),
+        };
+        out.push_str(&format!(".TP\n{}\n", key));
+    }
+    let prefix = title.trim_matches(|c| c == '[' || c == ']');
+    for (name, kind) in keys {
+        if let Some((keys, shape)) = own_section(kind) {
+            let path = if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+            let title = match shape {
+                "[]" => format!("[[{}]]", path),
+                shape => format!("[{}{}]", path, shape),
+            };
+            config_section(out, &title, keys, seen);
+        }
+    }
+}
+
+/// `rbit.toml(5)`: every key of the config file, as the config structs define them.
+fn config_page() -> String {
+    let mut out = String::new();
+    header(&mut out, "rbit.toml", 5);
+    out.push_str(".SH NAME\nrbit.toml \\- the config file of rbit\n");
+    out.push_str(".SH DESCRIPTION\n");
+    out.push_str("rbit reads \\fI~/.config/rbit/config.toml\\fR (or the platform's equivalent), then \\fIrbit.toml\\fR in the current folder, or only the file given with \\fB\\-\\-config\\fR. ");
+    out.push_str("The files are TOML, and every key is optional. \\fBrbit config init\\fR writes a starting file; \\fBrbit config show\\fR shows the settings in effect and where each comes from.\n");
+    out.push_str(".sp\nThis list is generated from the definitions rbit reads the file with, so it names every key rbit knows. Keys it does not know are warned about, or rejected with \\fB\\-\\-strict\\-config\\fR.\n");
+    if let Kind::Table(keys) = probe::<Config>() {
+        out.push_str(".SH KEYS\n");
+        config_section(&mut out, "", &keys, &mut Vec::new());
+    }
+    out.push_str(".SH SEE ALSO\n\\fBrbit\\fR(1), \\fBrbit\\-config\\fR(1)\n");
+    out
+}
+
+/// Every page, as (file name, text), with the subcommands of `cmd` below `path`.
+fn pages<'a>(cmd: &'a Command, path: &mut Vec<&'a str>, found: &mut Vec<(String, String)>) {
+    found.push((format!("{}.1", page_name(path)), command_page(cmd, path)));
+    for sub in cmd.get_subcommands().filter(|s| !s.is_hide_set() && s.get_name() != "help") {
+        path.push(sub.get_name());
+        pages(sub, path, found);
+        path.pop();
+    }
+}
+
+/// Print the page of `command` (a subcommand path; `rbit` itself when empty) or of the config
+/// file, or with `output`, write every page into its `man1` and `man5` folders.
+pub fn man(cmd: Command, command: &[String], config_file: bool, output: Option<&Path>) -> anyhow::Result<()> {
+    let mut cmd = with_examples(cmd);
+    cmd.build();
+    if let Some(dir) = output {
+        let mut found = Vec::new();
+        pages(&cmd, &mut Vec::new(), &mut found);
+        found.push(("rbit.toml.5".to_string(), config_page()));
+        for (file, text) in &found {
+            let section = dir.join(format!("man{}", &file[file.len() - 1..]));
+            fs::create_dir_all(&section).map_err(|e| anyhow::anyhow!("{}: {}", section.display(), e))?;
+            fs::write(section.join(file), text).map_err(|e| anyhow::anyhow!("{}: {}", section.join(file).display(), e))?;
+        }
+        println!("{}", tr!("man-written", count = found.len(), path = dir.display().to_string()));
+        return Ok(());
+    }
+    if config_file {
+        print!("{}", config_page());
+        return Ok(());
+    }
+    let mut page = &cmd;
+    for name in command {
+        page = page.find_subcommand(name).ok_or_else(|| RbitError::InvalidInput(format!("no such command: {}", command.join(" "))))?;
+    }
+    let path: Vec<&str> = command.iter().map(String::as_str).collect();
+    print!("{}", command_page(page, &path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples_name_real_commands() {
+        let cmd = <crate::Cli as clap::CommandFactory>::command();
+        for (path, _) in EXAMPLES {
+            let found = path.split(' ').try_fold(&cmd, |cmd, name| cmd.find_subcommand(name));
+            assert!(found.is_some(), "examples for a command that does not exist: {}", path);
+        }
+        let page = config_page();
+        assert!(page.contains(".SS [servers.<name>]\nThe same keys as [qbittorrent]."));
+        assert!(page.contains(".SS [[schedule]]"));
+    }
+}
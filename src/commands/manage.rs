@@ -0,0 +1,211 @@
+//! Commands that change the state of existing torrents.
+
+use crate::api::{Session, TorrentInfo};
+use crate::batch::{self, Outcome, Report};
+use crate::confirm::confirm;
+use crate::i18n::tr;
+use crate::select::Selector;
+
+/// Short form of a torrent's hash as shown in `list`.
+pub fn short_id(t: &TorrentInfo) -> &str {
+    &t.hash[..8.min(t.hash.len())]
+}
+
+/// Move a torrent's data to a new location on the server (`torrents/setLocation`).
+pub async fn move_torrent(session: &Session, id: &str, location: &str) -> anyhow::Result<()> {
+    let location = location.trim();
+    if location.is_empty() {
+        anyhow::bail!("new location must not be empty");
+    }
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let location = session.server_path(location).await?;
+    session
+        .post_form("torrents/setLocation", &[("hashes", &t.hash), ("location", &location)])
+        .await?;
+    println!("{}", tr!("move-done", name = t.name.as_str(), id = short_id(&t), path = location));
+    Ok(())
+}
+
+/// Rename a torrent as shown by qBittorrent (`torrents/rename`); the data on disk is unaffected.
+pub async fn rename(session: &Session, id: &str, name: &str) -> anyhow::Result<()> {
+    if name.trim().is_empty() {
+        anyhow::bail!("new name must not be empty");
+    }
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    session
+        .post_form("torrents/rename", &[("hash", &t.hash), ("name", name)])
+        .await?;
+    println!("{}", tr!("rename-done", name = t.name.as_str(), id = short_id(&t), new = name));
+    Ok(())
+}
+
+/// Rename a file (`torrents/renameFile`) or, with `folder`, a folder (`torrents/renameFolder`)
+/// inside a torrent. Paths are relative to the torrent's content root, as shown in the WebUI.
+pub async fn rename_file(session: &Session, id: &str, old_path: &str, new_path: &str, folder: bool) -> anyhow::Result<()> {
+    if old_path.is_empty() || new_path.is_empty() {
+        anyhow::bail!("old and new paths must not be empty");
+    }
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let endpoint = if folder { "torrents/renameFolder" } else { "torrents/renameFile" };
+    session
+        .post_form(endpoint, &[("hash", &t.hash), ("oldPath", old_path), ("newPath", new_path)])
+        .await
+        .map_err(|e| anyhow::anyhow!("could not rename '{}' in {}: {}", old_path, t.name, e))?;
+    println!("{}", tr!("rename-file-done", old = old_path, new = new_path, name = t.name.as_str(), id = short_id(&t)));
+    Ok(())
+}
+
+/// Ask qBittorrent to act on several torrents at once through an endpoint taking `hashes`
+/// (`|`-separated, or `all`). `what` describes the action for the summary line. With `ask`,
+/// e.g. `pause`, acting on more than one torrent is confirmed first with the `<ask>-confirm`
+/// message.
+async fn bulk(session: &Session, endpoint: &str, what: &str, ask: Option<&str>, ids: &[String], all: bool, selector: &Selector) -> anyhow::Result<()> {
+    session.login().await?;
+    let confirmed = |count: usize| match ask.filter(|_| count > 1 && !session.dry_run) {
+        Some(ask) => confirm(
+            &tr!(&format!("{}-confirm", ask), count = count, host = session.host.as_str()),
+            &tr!(&format!("{}-what", ask), count = count),
+        ),
+        None => Ok(true),
+    };
+    if all {
+        let count = session.torrents("all").await?.len();
+        if !confirmed(count)? {
+            println!("{}", tr!("nothing-done"));
+            return Ok(());
+        }
+        session.post_form(endpoint, &[("hashes", "all")]).await?;
+        println!("{}", tr!("bulk-all", what = what, count = count));
+        return Ok(());
+    }
+    let torrents = selector.select(session, ids, false, session.dry_run).await?;
+    if ask.is_some() && torrents.len() > 1 && !session.dry_run {
+        for t in &torrents {
+            println!("  {} {}", short_id(t), t.name);
+        }
+    }
+    if !confirmed(torrents.len())? {
+        println!("{}", tr!("nothing-done"));
+        return Ok(());
+    }
+    let hashes: Vec<&str> = torrents.iter().map(|t| t.hash.as_str()).collect();
+    session.post_form(endpoint, &[("hashes", &hashes.join("|"))]).await?;
+    for t in &torrents {
+        println!("{}", tr!("bulk-done", what = what, name = t.name.as_str(), id = short_id(t)));
+    }
+    Ok(())
+}
+
+/// Re-verify the given torrents' data against their piece hashes (`torrents/recheck`). Several
+/// torrents are asked one request each, so one that fails does not hold up the others.
+pub async fn recheck(session: &Session, ids: &[String], all: bool, selector: &Selector) -> anyhow::Result<()> {
+    if all {
+        return bulk(session, "torrents/recheck", &tr!("recheck-doing"), None, ids, all, selector).await;
+    }
+    session.login().await?;
+    let torrents = selector.select(session, ids, false, session.dry_run).await?;
+    if let [t] = torrents.as_slice() {
+        session.post_form("torrents/recheck", &[("hashes", &t.hash)]).await?;
+        println!("{}", tr!("bulk-done", what = tr!("recheck-doing"), name = t.name.as_str(), id = short_id(t)));
+        return Ok(());
+    }
+    let results = batch::run(session, &tr!("recheck-doing"), &torrents, |t| t.name.clone(), |t| async move {
+        session.post_form("torrents/recheck", &[("hashes", &t.hash)]).await?;
+        Ok(Outcome::Done("rechecking".to_string()))
+    })
+    .await;
+    let mut report = Report::default();
+    for (t, result) in torrents.iter().zip(results) {
+        report.push(format!("{} {}", short_id(t), t.name), result);
+    }
+    report.print();
+    println!("{}", tr!("recheck-done", done = report.done(), total = torrents.len()));
+    report.result()
+}
+
+/// Whether the server speaks Web API 2.11 (qBittorrent 5) or later, which renamed
+/// `torrents/pause` and `torrents/resume` to `torrents/stop` and `torrents/start`.
+pub async fn stop_start_api(session: &Session) -> anyhow::Result<bool> {
+    let version = session.get_text("app/webapiVersion").await?;
+    let mut parts = version.trim().split('.').map(|p| p.parse::<u32>().unwrap_or(0));
+    Ok((parts.next().unwrap_or(0), parts.next().unwrap_or(0)) >= (2, 11))
+}
+
+/// Pause the given torrents, or all of them (`torrents/stop`, `torrents/pause` before qBittorrent 5).
+pub async fn pause(session: &Session, ids: &[String], all: bool, selector: &Selector) -> anyhow::Result<()> {
+    session.login().await?;
+    let endpoint = if stop_start_api(session).await? { "torrents/stop" } else { "torrents/pause" };
+    bulk(session, endpoint, &tr!("pause-done"), Some("pause"), ids, all, selector).await
+}
+
+/// Resume the given torrents, or all of them (`torrents/start`, `torrents/resume` before qBittorrent 5).
+pub async fn resume(session: &Session, ids: &[String], all: bool, selector: &Selector) -> anyhow::Result<()> {
+    session.login().await?;
+    let endpoint = if stop_start_api(session).await? { "torrents/start" } else { "torrents/resume" };
+    bulk(session, endpoint, &tr!("resume-done"), None, ids, all, selector).await
+}
+
+/// Directions for `rbit queue`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum QueueMove {
+    Top,
+    Bottom,
+    Up,
+    Down,
+}
+
+/// Move torrents in qBittorrent's download/upload queue. Fails with a 409 when queueing is
+/// disabled in the server settings.
+pub async fn queue(session: &Session, direction: QueueMove, ids: &[String], selector: &Selector) -> anyhow::Result<()> {
+    let (endpoint, what) = match direction {
+        QueueMove::Top => ("torrents/topPrio", tr!("queue-top")),
+        QueueMove::Bottom => ("torrents/bottomPrio", tr!("queue-bottom")),
+        QueueMove::Up => ("torrents/increasePrio", tr!("queue-up")),
+        QueueMove::Down => ("torrents/decreasePrio", tr!("queue-down")),
+    };
+    bulk(session, endpoint, &what, None, ids, false, selector)
+        .await
+        .map_err(|e| anyhow::anyhow!("{} {}", e, tr!("queue-disabled")))
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Switch {
+    On,
+    Off,
+}
+
+/// Set one of qBittorrent's per-torrent on/off switches, which the API only exposes as toggles:
+/// the current value is read from `torrents/info` and toggled only when it differs.
+async fn switch(
+    session: &Session,
+    id: &str,
+    wanted: Switch,
+    current: fn(&TorrentInfo) -> Option<bool>,
+    endpoint: &str,
+    what: &str,
+) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let wanted = wanted == Switch::On;
+    let state = if wanted { tr!("on") } else { tr!("off") };
+    if current(&t) == Some(wanted) {
+        println!("{}", tr!("switch-already", what = tr!(what), state = state, name = t.name.as_str(), id = short_id(&t)));
+        return Ok(());
+    }
+    session.post_form(endpoint, &[("hashes", &t.hash)]).await?;
+    println!("{}", tr!("switch-done", what = tr!(what), state = state, name = t.name.as_str(), id = short_id(&t)));
+    Ok(())
+}
+
+/// Download a torrent's pieces in order, e.g. to play a video while it downloads.
+pub async fn sequential(session: &Session, id: &str, wanted: Switch) -> anyhow::Result<()> {
+    switch(session, id, wanted, |t| t.seq_dl, "torrents/toggleSequentialDownload", "switch-sequential").await
+}
+
+/// Prioritise the first and last piece of each file so media players can read headers and indexes early.
+pub async fn first_last(session: &Session, id: &str, wanted: Switch) -> anyhow::Result<()> {
+    switch(session, id, wanted, |t| t.f_l_piece_prio, "torrents/toggleFirstLastPiecePrio", "switch-first-last").await
+}
@@ -0,0 +1,48 @@
+pub mod add;
+pub mod apply_rules;
+pub mod auth;
+pub mod category;
+pub mod cleanup;
+pub mod completions;
+pub mod config;
+pub mod cross_seed;
+pub mod daemon;
+pub mod daemon_state;
+pub mod doctor;
+pub mod exists;
+pub mod export;
+pub mod exporter;
+pub mod follow;
+pub mod forecast;
+pub mod get;
+pub mod history;
+pub mod import;
+pub mod inspect;
+pub mod limit;
+pub mod list;
+pub mod log;
+pub mod man;
+pub mod manage;
+pub mod note;
+pub mod peers;
+pub mod pieces;
+pub mod port;
+pub mod prefs;
+pub mod prune;
+pub mod reannounce;
+pub mod report;
+pub mod rss;
+pub mod run_once;
+pub mod schedule;
+pub mod search;
+pub mod self_update;
+pub mod shutdown;
+pub mod stats;
+pub mod status;
+pub mod stream;
+pub mod timeline;
+pub mod trackers;
+pub mod tui;
+pub mod verify;
+pub mod wait;
+pub mod webseeds;
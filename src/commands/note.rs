@@ -0,0 +1,99 @@
+//! `rbit note`: free-form notes on torrents, see [`crate::notes`].
+
+use tabled::Tabled;
+
+use crate::api::{Session, TorrentInfo};
+use crate::commands::manage::short_id;
+use crate::format::{date_utc, truncate};
+use crate::i18n::tr;
+use crate::notes;
+use crate::theme;
+
+/// Replace the `note:` tags of a torrent with `tag`, or just remove them.
+async fn retag(session: &Session, t: &TorrentInfo, tag: Option<&str>) -> anyhow::Result<()> {
+    let old = notes::note_tags(t.tags.as_deref().unwrap_or(""));
+    if !old.is_empty() {
+        session.post_form("torrents/removeTags", &[("hashes", &t.hash), ("tags", &old.join(","))]).await?;
+    }
+    if let Some(tag) = tag {
+        session.post_form("torrents/addTags", &[("hashes", &t.hash), ("tags", tag)]).await?;
+    }
+    Ok(())
+}
+
+/// Set a torrent's note, with `tag` also as a `note:` tag on the server.
+pub async fn set(session: &Session, id: &str, text: &str, tag: bool) -> anyhow::Result<()> {
+    let text = text.trim();
+    if text.is_empty() {
+        anyhow::bail!("the note is empty; `rbit note clear` removes a note");
+    }
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    if tag {
+        retag(session, &t, Some(&notes::tag_for(text))).await?;
+    }
+    if session.dry_run {
+        println!("[dry-run] would save the note for {} in {}", t.name, notes::notes_path().unwrap_or_default().display());
+        return Ok(());
+    }
+    notes::set(&t.hash, text)?;
+    println!("{}", tr!("note-saved", name = t.name.as_str(), id = short_id(&t)));
+    Ok(())
+}
+
+/// Print a torrent's note: the local one, otherwise the one in its `note:` tag.
+pub async fn show(session: &Session, id: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let local = notes::load()?.remove(&t.hash.to_lowercase());
+    match (local, notes::from_tags(t.tags.as_deref().unwrap_or(""))) {
+        (Some(note), _) => println!("{}\n{}", tr!("note-noted", name = t.name.as_str(), id = short_id(&t), time = date_utc(note.updated_at)), note.text),
+        (None, Some(text)) => println!("{}\n{}", tr!("note-from-tag", name = t.name.as_str(), id = short_id(&t)), text),
+        (None, None) => println!("{}", tr!("note-none", name = t.name.as_str(), id = short_id(&t))),
+    }
+    Ok(())
+}
+
+/// Remove a torrent's note, and its `note:` tags.
+pub async fn clear(session: &Session, id: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    retag(session, &t, None).await?;
+    if session.dry_run {
+        println!("[dry-run] would remove the note for {}", t.name);
+        return Ok(());
+    }
+    if notes::remove(&t.hash)? {
+        println!("{}", tr!("note-removed", name = t.name.as_str(), id = short_id(&t)));
+    } else {
+        println!("{}", tr!("note-no-local", name = t.name.as_str(), id = short_id(&t)));
+    }
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct NoteRow {
+    id: String,
+    name: String,
+    note: String,
+}
+
+/// Show the torrents on the server that have a note, local or tagged.
+pub async fn list(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    let saved = notes::load()?;
+    let mut rows = Vec::new();
+    for t in session.torrents("all").await? {
+        let tagged = notes::from_tags(t.tags.as_deref().unwrap_or(""));
+        let Some(text) = saved.get(&t.hash.to_lowercase()).map(|n| n.text.clone()).or(tagged) else {
+            continue;
+        };
+        rows.push(NoteRow { id: short_id(&t).to_string(), name: truncate(&t.name, 40), note: truncate(&text, 60) });
+    }
+    if rows.is_empty() {
+        println!("{}", tr!("note-nothing"));
+        return Ok(());
+    }
+    println!("{}", theme::table(rows));
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! `rbit peers`: connected peers of one torrent, and banning misbehaving ones.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use tabled::Tabled;
+
+use crate::api::{PeerInfo, Session, TorrentInfo};
+use crate::commands::manage::short_id;
+use crate::format::{rate_human, truncate};
+use crate::i18n::{print_fields, tr};
+use crate::theme;
+
+#[derive(Tabled)]
+struct PeerRow {
+    address: String,
+    client: String,
+    flags: String,
+    conn: String,
+    country: String,
+    progress: String,
+    dl: String,
+    up: String,
+}
+
+fn address(p: &PeerInfo) -> String {
+    if p.ip.contains(':') {
+        format!("[{}]:{}", p.ip, p.port)
+    } else {
+        format!("{}:{}", p.ip, p.port)
+    }
+}
+
+/// `1.2.3.4:5555` or `[::1]:5555`, as opposed to a bare address.
+fn has_port(addr: &str) -> bool {
+    let (host, port) = match addr.rsplit_once(':') {
+        Some(split) => split,
+        None => return false,
+    };
+    let bracketed = host.starts_with('[') && host.ends_with(']');
+    (bracketed || !host.contains(':')) && port.parse::<u16>().is_ok()
+}
+
+/// Turn `--ban` arguments into the `host:port` form `transfer/banPeers` expects. A bare IP is
+/// matched against the torrent's connected peers, since qBittorrent needs the port as well.
+fn ban_targets(bans: &[String], peers: &[PeerInfo]) -> anyhow::Result<Vec<String>> {
+    let mut targets = Vec::new();
+    for ban in bans {
+        let ip = ban.trim_start_matches('[').trim_end_matches(']');
+        let matching: Vec<String> = peers.iter().filter(|p| p.ip == ip).map(address).collect();
+        if !matching.is_empty() {
+            targets.extend(matching);
+        } else if has_port(ban) {
+            targets.push(ban.clone());
+        } else {
+            anyhow::bail!("{} is not connected to this torrent; pass it as ip:port to ban it anyway", ban);
+        }
+    }
+    Ok(targets)
+}
+
+/// The client's name without its version: `qBittorrent/4.6.2` and `Transmission 4.0` become
+/// `qBittorrent` and `Transmission`.
+fn client_family(client: &str) -> String {
+    let name = client.split(['/', ' ']).next().unwrap_or("");
+    if name.is_empty() { "unknown".to_string() } else { name.to_string() }
+}
+
+/// `value count` pairs, most common first, with the tail folded into `+N more`.
+fn top(counts: HashMap<String, usize>, total: usize) -> String {
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let mut parts: Vec<String> =
+        counts.iter().take(6).map(|(name, n)| format!("{} {} ({:.0}%)", name, n, *n as f64 * 100.0 / total as f64)).collect();
+    if counts.len() > 6 {
+        parts.push(tr!("peers-more", count = counts.len() - 6));
+    }
+    parts.join(", ")
+}
+
+fn count_by(peers: &[PeerInfo], key: impl Fn(&PeerInfo) -> String) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for p in peers {
+        *counts.entry(key(p)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Print what the connected peers are, instead of listing each one.
+fn summary(t: &TorrentInfo, peers: &[PeerInfo]) {
+    let total = peers.len();
+    let seeds = peers.iter().filter(|p| p.progress >= 1.0).count();
+    let swarm = match (t.num_complete, t.num_incomplete) {
+        (Some(s), Some(l)) if s >= 0 && l >= 0 => tr!("peers-swarm", total = s + l, seeds = s, leechers = l),
+        _ => String::new(),
+    };
+    let mut fields = vec![(tr!("peers-peers"), tr!("peers-connected", count = total, seeds = seeds, leechers = total - seeds, what = swarm))];
+    let downloading = peers.iter().filter(|p| p.flags.contains('D')).count();
+    let uploading = peers.iter().filter(|p| p.flags.contains('U')).count();
+    let dl: u64 = peers.iter().map(|p| p.dl_speed).sum();
+    let up: u64 = peers.iter().map(|p| p.up_speed).sum();
+    fields.push((
+        tr!("peers-transfer"),
+        tr!("peers-transferring", downloading = downloading, uploading = uploading, down = rate_human(dl), up = rate_human(up)),
+    ));
+    if total == 0 {
+        print_fields(&fields);
+        return;
+    }
+    // `E` marks encrypted traffic, `e` an encrypted handshake only
+    let encrypted = peers.iter().filter(|p| p.flags.contains('E') || p.flags.contains('e')).count();
+    let percent = format!("{:.0}", encrypted as f64 * 100.0 / total as f64);
+    fields.extend([
+        (tr!("peers-encryption"), tr!("peers-encrypted", count = encrypted, total = total, percent = percent)),
+        (tr!("peers-connection"), top(count_by(peers, |p| if p.connection.is_empty() { "?".to_string() } else { p.connection.clone() }), total)),
+        (tr!("peers-clients"), top(count_by(peers, |p| client_family(&p.client)), total)),
+        (
+            tr!("peers-countries"),
+            top(count_by(peers, |p| if p.country_code.is_empty() { "??".to_string() } else { p.country_code.to_uppercase() }), total),
+        ),
+    ]);
+    print_fields(&fields);
+}
+
+/// Show the peers connected to a torrent, fastest first (or summarised), after banning any given
+/// in `ban`.
+pub async fn peers(session: &Session, id: &str, ban: &[String], summarise: bool) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let mut peers = session.peers(&t.hash).await?;
+
+    if !ban.is_empty() {
+        let targets = ban_targets(ban, &peers)?;
+        session.post_form("transfer/banPeers", &[("peers", &targets.join("|"))]).await?;
+        println!("{}", tr!("peers-banned", addresses = targets.join(", ")));
+        if session.dry_run {
+            return Ok(());
+        }
+        peers = session.peers(&t.hash).await?;
+    }
+
+    if summarise {
+        summary(&t, &peers);
+        return Ok(());
+    }
+    if peers.is_empty() {
+        println!("{}", tr!("peers-none", name = t.name.as_str(), id = short_id(&t)));
+        return Ok(());
+    }
+    peers.sort_by_key(|p| Reverse(p.dl_speed + p.up_speed));
+    let rows: Vec<PeerRow> = peers
+        .iter()
+        .map(|p| PeerRow {
+            address: address(p),
+            client: truncate(&p.client, 24),
+            flags: p.flags.clone(),
+            conn: p.connection.clone(),
+            country: p.country_code.to_uppercase(),
+            progress: format!("{:.1}%", p.progress * 100.0),
+            dl: rate_human(p.dl_speed),
+            up: rate_human(p.up_speed),
+        })
+        .collect();
+    println!("{}", theme::table(rows));
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! `rbit pieces`: a torrent's pieces as a block map, from `torrents/pieceStates`, with the
+//! swarm's availability. A torrent stuck below 100% with an availability under 1 has pieces no
+//! connected peer has, and will not finish until a peer with them shows up.
+
+use crate::api::Session;
+use crate::commands::manage::short_id;
+use crate::i18n::tr;
+use crate::theme;
+
+/// States qBittorrent reports per piece.
+const MISSING: u8 = 0;
+const DOWNLOADING: u8 = 1;
+const HAVE: u8 = 2;
+
+/// How a cell of the map is drawn: the color group and the character.
+fn cell(pieces: &[u8]) -> (Option<&'static str>, char) {
+    if pieces.contains(&DOWNLOADING) {
+        (Some("downloading"), '▓')
+    } else if pieces.iter().all(|&p| p == HAVE) {
+        (Some("seeding"), '█')
+    } else if pieces.contains(&HAVE) {
+        (Some("seeding"), '▒')
+    } else {
+        (None, '·')
+    }
+}
+
+/// Pieces per cell for `pieces` pieces to fit in `lines` lines of `width` cells.
+fn per_cell(pieces: usize, width: usize, lines: usize) -> usize {
+    pieces.div_ceil(width.max(1) * lines.max(1)).max(1)
+}
+
+/// The map of `states` in lines of `width` cells, each cell standing for `per_cell` pieces.
+fn block_map(states: &[u8], width: usize, per_cell: usize) -> Vec<Vec<(Option<&'static str>, char)>> {
+    let cells: Vec<_> = states.chunks(per_cell).map(cell).collect();
+    cells.chunks(width).map(<[_]>::to_vec).collect()
+}
+
+pub async fn pieces(session: &Session, id: &str, width: usize, lines: usize) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let states: Vec<u8> = session.get_json(&format!("torrents/pieceStates?hash={}", t.hash)).await?;
+    if states.is_empty() {
+        println!("{}", tr!("pieces-no-metadata", name = t.name.as_str(), id = short_id(&t)));
+        return Ok(());
+    }
+
+    let theme = theme::current();
+    let per_cell = per_cell(states.len(), width, lines);
+    println!("{} ({})", t.name, short_id(&t));
+    for line in block_map(&states, width.max(1), per_cell) {
+        let line: String = line
+            .into_iter()
+            .map(|(group, c)| match group {
+                Some(group) => theme.paint(group, &c.to_string()),
+                None => c.to_string(),
+            })
+            .collect();
+        println!("{}", line);
+    }
+    if per_cell > 1 {
+        println!("{}", tr!("pieces-legend-cells", count = per_cell));
+    } else {
+        println!("{}", tr!("pieces-legend"));
+    }
+
+    let count = |state: u8| states.iter().filter(|&&s| s == state).count();
+    let (have, downloading, missing) = (count(HAVE), count(DOWNLOADING), count(MISSING));
+    println!(
+        "{}",
+        tr!(
+            "pieces-counts",
+            count = states.len(),
+            done = have,
+            share = format!("{:.1}", have as f64 * 100.0 / states.len() as f64),
+            downloading = downloading,
+            missing = missing
+        )
+    );
+    let availability = match t.availability {
+        _ if missing == 0 && downloading == 0 => tr!("pieces-complete"),
+        Some(a) if a < 0.0 => tr!("pieces-unknown"),
+        Some(a) if a < 1.0 => tr!("pieces-short", copies = format!("{:.2}", a), share = format!("{:.0}", a * 100.0)),
+        Some(a) => tr!("pieces-available", copies = format!("{:.2}", a)),
+        None => tr!("pieces-not-reported"),
+    };
+    println!("{}", tr!("pieces-availability", availability = availability));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_summarise_runs_of_pieces() {
+        let states = [2, 2, 2, 1, 2, 0, 0, 0];
+        let chars = |map: Vec<Vec<(Option<&str>, char)>>| map.into_iter().map(|l| l.into_iter().map(|(_, c)| c).collect::<String>()).collect::<Vec<_>>();
+        assert_eq!(chars(block_map(&states, 4, per_cell(8, 4, 2))), ["███▓", "█···"]);
+        assert_eq!(chars(block_map(&states, 4, per_cell(8, 4, 1))), ["█▓▒·"]);
+        assert_eq!(chars(block_map(&states, 2, per_cell(8, 2, 1))), ["▓▒"]);
+        assert_eq!(per_cell(1200, 64, 8), 3);
+    }
+}
@@ -0,0 +1,232 @@
+//! `rbit port`: the port qBittorrent listens on for incoming peer connections (the `listen_port`
+//! preference), and `rbit port test` to see whether peers can reach it. A port closed to the
+//! outside is the most common cause of slow torrents: peers behind a firewall themselves can then
+//! never connect, and the server only reaches the peers that accept incoming connections.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::net::{IpAddr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::api::{MainData, Session, TransferInfo};
+use crate::i18n::tr;
+use crate::theme;
+
+const DIAL_TIMEOUT: Duration = Duration::from_secs(5);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Ports `rbit port random` picks from: the dynamic range, which no service registers.
+const RANDOM_PORTS: RangeInclusive<u16> = 49152..=65535;
+
+/// `[port]`: settings for `rbit port test`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PortConfig {
+    /// A port-check service to ask whether the port is open from the internet, with `{ip}`
+    /// and `{port}` filled in; its answer should say `open` or `closed` (or `true`/`false`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_url: Option<String>,
+}
+
+impl PortConfig {
+    pub fn is_empty(&self) -> bool {
+        self.check_url.is_none()
+    }
+}
+
+async fn listen_port(session: &Session) -> anyhow::Result<u16> {
+    let prefs: Map<String, Value> = session.get_json("app/preferences").await?;
+    prefs
+        .get("listen_port")
+        .and_then(Value::as_u64)
+        .and_then(|p| u16::try_from(p).ok())
+        .ok_or_else(|| anyhow::anyhow!("the server's preferences have no listen_port"))
+}
+
+/// Print the listening port, bare for scripts.
+pub async fn get(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    println!("{}", listen_port(session).await?);
+    Ok(())
+}
+
+/// Make the server listen on `port`.
+pub async fn set(session: &Session, port: u16) -> anyhow::Result<()> {
+    session.login().await?;
+    let old = listen_port(session).await?;
+    if old == port {
+        println!("listen_port: {}", tr!("port-already", port = port));
+        return Ok(());
+    }
+    println!("listen_port: {} -> {}", old, port);
+    session.post_form("app/setPreferences", &[("json", &json!({ "listen_port": port }).to_string())]).await?;
+    Ok(())
+}
+
+/// A port of [`RANDOM_PORTS`] from `seed`.
+fn pick(seed: u64) -> u16 {
+    let (first, last) = (*RANDOM_PORTS.start(), *RANDOM_PORTS.end());
+    first + (seed % (u64::from(last - first) + 1)) as u16
+}
+
+/// Move the server to a random port of the dynamic range, e.g. when the current one is blocked.
+pub async fn random(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    let old = listen_port(session).await?;
+    let hasher = RandomState::new();
+    let port = (0u64..).map(|n| pick(hasher.hash_one((SystemTime::now(), n)))).find(|&p| p != old).expect("the range has other ports");
+    println!("listen_port: {} -> {}", old, port);
+    session.post_form("app/setPreferences", &[("json", &json!({ "listen_port": port }).to_string())]).await?;
+    Ok(())
+}
+
+/// What a port-check service answered: `Some(true)` for open, `None` when it cannot be told.
+fn verdict(answer: &str) -> Option<bool> {
+    let answer = answer.to_ascii_lowercase();
+    // the closed words first: "not open" and "unreachable" contain the open ones
+    if ["closed", "false", "not open", "unreachable", "blocked"].iter().any(|w| answer.contains(w)) {
+        Some(false)
+    } else if ["open", "true", "reachable"].iter().any(|w| answer.contains(w)) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Whether `addr` is on this machine or a private network, from where a dial says nothing
+/// about the internet.
+fn is_local(addr: &IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(v4) => v4.is_loopback() || v4.is_private() || v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_loopback() || (v6.segments()[0] & 0xfe00) == 0xfc00,
+    }
+}
+
+/// Dial the listening port on the server's host; `Ok` with the address that answered.
+async fn dial(host: &str, port: u16) -> Result<SocketAddr, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await.map_err(|e| format!("could not resolve {}: {}", host, e))?.collect();
+    let mut last = format!("{} has no address", host);
+    for addr in addrs {
+        match tokio::time::timeout(DIAL_TIMEOUT, tokio::net::TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => return Ok(addr),
+            Ok(Err(e)) => last = format!("{}: {}", addr, e),
+            Err(_) => last = format!("{}: no answer within {}s", addr, DIAL_TIMEOUT.as_secs()),
+        }
+    }
+    Err(last)
+}
+
+/// Ask the service at `template` about `ip`:`port`.
+async fn ask_service(template: &str, ip: &str, port: u16) -> anyhow::Result<String> {
+    let url = template.replace("{ip}", ip).replace("{port}", &port.to_string());
+    let client = reqwest::Client::builder().timeout(CHECK_TIMEOUT).user_agent(concat!("rbit/", env!("CARGO_PKG_VERSION"))).build()?;
+    let res = client.get(&url).send().await.map_err(|e| anyhow::anyhow!("could not reach {}: {}", url, e))?;
+    if !res.status().is_success() {
+        anyhow::bail!("{} answered {}", url, res.status());
+    }
+    Ok(res.text().await?.trim().to_string())
+}
+
+/// Check whether the listening port can be reached: what qBittorrent itself reports, a TCP dial
+/// to the server's host, and with `[port] check_url` a port-check service on the internet.
+/// Fails when the port looks closed.
+pub async fn test(session: &Session, config: &PortConfig) -> anyhow::Result<()> {
+    session.login().await?;
+    let theme = theme::current();
+    let port = listen_port(session).await?;
+    let (info, maindata) = tokio::try_join!(session.get_json::<TransferInfo>("transfer/info"), session.get_json::<MainData>("sync/maindata"))?;
+    println!("{}: {}", tr!("port-listening"), port);
+
+    // qBittorrent knows its port is open once a peer has connected to it
+    let connected = info.connection_status == "connected";
+    let status = match info.connection_status.as_str() {
+        "connected" => tr!("port-connected", state = theme.paint("seeding", "connected")),
+        "firewalled" => tr!("port-firewalled", state = theme.paint("stalled", "firewalled")),
+        other => tr!("port-disconnected", state = theme.paint("error", other)),
+    };
+    println!("{}: {}", tr!("port-server-reports"), status);
+
+    let url = reqwest::Url::parse(&session.host)?;
+    let host = url.host_str().unwrap_or("127.0.0.1").trim_matches(['[', ']']).to_string();
+    let dialed = dial(&host, port).await;
+    let local = match &dialed {
+        Ok(addr) => {
+            let local = is_local(&addr.ip());
+            let open = theme.paint("seeding", &tr!("port-open"));
+            let dial = match local {
+                true => tr!("port-dial-open-local", state = open, address = addr.to_string()),
+                false => tr!("port-dial-open", state = open, address = addr.to_string()),
+            };
+            println!("{}: {}", tr!("port-direct-dial"), dial);
+            local
+        }
+        Err(e) => {
+            println!("{}: {} ({})", tr!("port-direct-dial"), theme.paint("error", &tr!("port-closed")), e);
+            false
+        }
+    };
+
+    let external = maindata.server_state.and_then(|s| s.last_external_address_v4).filter(|ip| !ip.is_empty());
+    let service = match &config.check_url {
+        None => {
+            println!("{}: {}", tr!("port-from-internet"), tr!("port-no-check-url"));
+            None
+        }
+        Some(template) if template.contains("{ip}") && external.is_none() => {
+            println!("{}: {}", tr!("port-from-internet"), tr!("port-no-external-address"));
+            None
+        }
+        Some(template) => {
+            let ip = external.as_deref().unwrap_or(&host);
+            match ask_service(template, ip, port).await {
+                Ok(answer) => {
+                    let found = verdict(&answer);
+                    let shown = match found {
+                        Some(true) => theme.paint("seeding", &tr!("port-open")),
+                        Some(false) => theme.paint("error", &tr!("port-closed")),
+                        None => tr!("port-unclear", reason = crate::format::truncate(&answer, 80)),
+                    };
+                    println!("{}: {} ({}:{})", tr!("port-from-internet"), shown, ip, port);
+                    found
+                }
+                Err(e) => {
+                    println!("{}: {}", tr!("port-from-internet"), tr!("port-not-checked", reason = format!("{:#}", e)));
+                    None
+                }
+            }
+        }
+    };
+
+    // the service is the only check from outside; without it, a failed dial the server's own
+    // status does not contradict is as close as this gets
+    let closed = match service {
+        Some(open) => !open,
+        None => dialed.is_err() && !connected,
+    };
+    if closed {
+        anyhow::bail!(tr!("port-looks-closed", port = port));
+    }
+    if service.is_none() && !connected && local {
+        println!("{}", tr!("port-local-only"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_port_checks_and_picks_ports() {
+        assert_eq!(verdict("Port 51413 is open"), Some(true));
+        assert_eq!(verdict(r#"{"open": false}"#), Some(false));
+        assert_eq!(verdict("Error: port is not open"), Some(false));
+        assert_eq!(verdict("rate limited"), None);
+        assert!(is_local(&"192.168.1.20".parse().unwrap()));
+        assert!(!is_local(&"203.0.113.7".parse().unwrap()));
+        assert!((0..1000).map(pick).all(|p| RANDOM_PORTS.contains(&p)));
+    }
+}
@@ -0,0 +1,246 @@
+//! `rbit prefs`: read and change the server's application preferences (`app/preferences` and
+//! `app/setPreferences`). Well-known keys get typed values (`on`, `5M`, `36h`); anything else
+//! goes through `--json`.
+
+use serde_json::{Map, Value};
+use tabled::Tabled;
+
+use crate::api::Session;
+use crate::format::{minutes_limit_human, parse_minutes_limit, Rate};
+use crate::i18n::tr;
+use crate::theme;
+
+/// How a well-known preference's value is written on the command line and shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Bool,
+    Port,
+    /// A count where -1 means unlimited
+    Count,
+    /// Bytes per second, 0 for unlimited
+    Rate,
+    Ratio,
+    Minutes,
+    Text,
+    /// Named values of an integer setting
+    Choice(&'static [(&'static str, i64)]),
+}
+
+const ENCRYPTION: &[(&str, i64)] = &[("prefer", 0), ("require", 1), ("disable", 2)];
+
+/// Preferences rbit knows the type of, by their qBittorrent name.
+const KNOWN: &[(&str, Kind)] = &[
+    ("listen_port", Kind::Port),
+    ("random_port", Kind::Bool),
+    ("upnp", Kind::Bool),
+    ("dht", Kind::Bool),
+    ("pex", Kind::Bool),
+    ("lsd", Kind::Bool),
+    ("encryption", Kind::Choice(ENCRYPTION)),
+    ("anonymous_mode", Kind::Bool),
+    ("max_connec", Kind::Count),
+    ("max_connec_per_torrent", Kind::Count),
+    ("max_uploads", Kind::Count),
+    ("max_uploads_per_torrent", Kind::Count),
+    ("dl_limit", Kind::Rate),
+    ("up_limit", Kind::Rate),
+    ("alt_dl_limit", Kind::Rate),
+    ("alt_up_limit", Kind::Rate),
+    ("queueing_enabled", Kind::Bool),
+    ("max_active_downloads", Kind::Count),
+    ("max_active_uploads", Kind::Count),
+    ("max_active_torrents", Kind::Count),
+    ("max_ratio_enabled", Kind::Bool),
+    ("max_ratio", Kind::Ratio),
+    ("max_seeding_time_enabled", Kind::Bool),
+    ("max_seeding_time", Kind::Minutes),
+    ("save_path", Kind::Text),
+    ("temp_path_enabled", Kind::Bool),
+    ("temp_path", Kind::Text),
+    ("auto_tmm_enabled", Kind::Bool),
+    ("export_dir", Kind::Text),
+    ("scheduler_enabled", Kind::Bool),
+    ("web_ui_port", Kind::Port),
+];
+
+/// Friendlier names accepted for some keys.
+const ALIASES: &[(&str, &str)] = &[
+    ("port", "listen_port"),
+    ("max_connections", "max_connec"),
+    ("max_connections_per_torrent", "max_connec_per_torrent"),
+];
+
+fn canonical(key: &str) -> &str {
+    ALIASES.iter().find(|(alias, _)| *alias == key).map(|(_, name)| *name).unwrap_or(key)
+}
+
+fn kind(key: &str) -> Option<Kind> {
+    KNOWN.iter().find(|(name, _)| *name == key).map(|(_, kind)| *kind)
+}
+
+/// Parse a command-line value for a preference of the given kind into the JSON qBittorrent takes.
+fn parse_value(kind: Kind, input: &str) -> anyhow::Result<Value> {
+    let s = input.trim().to_ascii_lowercase();
+    let value = match kind {
+        Kind::Bool => match s.as_str() {
+            "true" | "on" | "yes" | "1" => Value::from(true),
+            "false" | "off" | "no" | "0" => Value::from(false),
+            _ => anyhow::bail!("invalid value '{}': expected on or off", input),
+        },
+        Kind::Port => match s.parse::<u16>() {
+            Ok(port) if port > 0 => Value::from(port),
+            _ => anyhow::bail!("invalid port '{}': expected 1-65535", input),
+        },
+        Kind::Count => match s.as_str() {
+            "unlimited" | "none" | "off" | "-1" => Value::from(-1),
+            _ => match s.parse::<u32>() {
+                Ok(n) => Value::from(n),
+                Err(_) => anyhow::bail!("invalid number '{}': expected a count or 'unlimited'", input),
+            },
+        },
+        Kind::Rate => Value::from(input.parse::<Rate>()?.to_api()),
+        Kind::Ratio => match s.as_str() {
+            "unlimited" | "none" | "off" => Value::from(-1),
+            _ => match s.parse::<f64>() {
+                Ok(r) if r >= 0.0 && r.is_finite() => Value::from(r),
+                _ => anyhow::bail!("invalid ratio '{}': expected a number like 2.0", input),
+            },
+        },
+        Kind::Minutes => match parse_minutes_limit(input)? {
+            -2 => anyhow::bail!("'global' does not apply here: this is the global limit"),
+            minutes => Value::from(minutes),
+        },
+        Kind::Text => Value::from(input),
+        Kind::Choice(choices) => match choices.iter().find(|(name, _)| *name == s) {
+            Some((_, n)) => Value::from(*n),
+            None => {
+                let names: Vec<&str> = choices.iter().map(|(name, _)| *name).collect();
+                anyhow::bail!("invalid value '{}': expected one of {}", input, names.join(", "))
+            }
+        },
+    };
+    Ok(value)
+}
+
+/// Show a preference value, in the typed form where the key is known.
+fn show_value(key: &str, value: &Value) -> String {
+    let number = value.as_i64();
+    match (kind(key), number) {
+        (Some(Kind::Rate), Some(n)) => Rate::from_api(n).to_string(),
+        (Some(Kind::Count), Some(n)) if n < 0 => "unlimited".to_string(),
+        (Some(Kind::Minutes), Some(n)) => minutes_limit_human(n),
+        (Some(Kind::Choice(choices)), Some(n)) => match choices.iter().find(|(_, v)| *v == n) {
+            Some((name, _)) => name.to_string(),
+            None => n.to_string(),
+        },
+        _ => match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        },
+    }
+}
+
+#[derive(Tabled)]
+struct PrefRow {
+    key: String,
+    value: String,
+}
+
+async fn preferences(session: &Session) -> anyhow::Result<Map<String, Value>> {
+    session.get_json("app/preferences").await
+}
+
+/// Print the preferences named in `keys` (every preference if empty); one bare value for a
+/// single key, so it can be used in scripts.
+pub async fn get(session: &Session, keys: &[String], json: bool) -> anyhow::Result<()> {
+    session.login().await?;
+    let prefs = preferences(session).await?;
+    let selected: Vec<(String, &Value)> = if keys.is_empty() {
+        prefs.iter().map(|(k, v)| (k.clone(), v)).collect()
+    } else {
+        keys.iter()
+            .map(|key| {
+                let name = canonical(key);
+                prefs
+                    .get(name)
+                    .map(|v| (name.to_string(), v))
+                    .ok_or_else(|| anyhow::anyhow!("the server has no preference '{}'", key))
+            })
+            .collect::<anyhow::Result<_>>()?
+    };
+    if json {
+        let object: Map<String, Value> = selected.into_iter().map(|(k, v)| (k, v.clone())).collect();
+        println!("{}", serde_json::to_string_pretty(&object)?);
+    } else if keys.len() == 1 {
+        println!("{}", show_value(&selected[0].0, selected[0].1));
+    } else {
+        let mut rows: Vec<PrefRow> = selected.iter().map(|(k, v)| PrefRow { key: k.clone(), value: show_value(k, v) }).collect();
+        rows.sort_by(|a, b| a.key.cmp(&b.key));
+        println!("{}", theme::table(rows));
+    }
+    Ok(())
+}
+
+/// Set `key=value` assignments of well-known keys, plus the raw keys of `json`, in one request.
+pub async fn set(session: &Session, assignments: &[String], json: Option<&str>) -> anyhow::Result<()> {
+    let mut changes = Map::new();
+    for assignment in assignments {
+        let (key, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected KEY=VALUE, got '{}'", assignment))?;
+        let name = canonical(key.trim());
+        let kind = kind(name).ok_or_else(|| {
+            anyhow::anyhow!("'{}' is not a preference rbit knows the type of; set it with --json '{{\"{}\": ...}}'", key, name)
+        })?;
+        let value = parse_value(kind, value).map_err(|e| anyhow::anyhow!("{}: {}", name, e))?;
+        changes.insert(name.to_string(), value);
+    }
+    if let Some(json) = json {
+        match serde_json::from_str(json) {
+            Ok(Value::Object(raw)) => changes.extend(raw),
+            Ok(_) => anyhow::bail!("--json must be an object, e.g. '{{\"dht\": false}}'"),
+            Err(e) => anyhow::bail!("invalid --json: {}", e),
+        }
+    }
+
+    session.login().await?;
+    let current = preferences(session).await?;
+    let mut send = Map::new();
+    for (key, value) in changes {
+        match current.get(&key) {
+            Some(old) if *old == value => println!("{}: {}", key, tr!("prefs-already", what = show_value(&key, old))),
+            Some(old) => {
+                println!("{}: {} -> {}", key, show_value(&key, old), show_value(&key, &value));
+                send.insert(key, value);
+            }
+            None => {
+                eprintln!("{} {}", tr!("warning"), tr!("prefs-unknown", name = key.as_str()));
+                send.insert(key, value);
+            }
+        }
+    }
+    if send.is_empty() {
+        println!("{}", tr!("prefs-nothing"));
+        return Ok(());
+    }
+    session.post_form("app/setPreferences", &[("json", &Value::Object(send).to_string())]).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_typed_values() {
+        assert_eq!(parse_value(Kind::Bool, "off").unwrap(), Value::from(false));
+        assert_eq!(parse_value(Kind::Port, "51413").unwrap(), Value::from(51413));
+        assert!(parse_value(Kind::Port, "0").is_err());
+        assert_eq!(parse_value(Kind::Count, "unlimited").unwrap(), Value::from(-1));
+        assert_eq!(parse_value(Kind::Rate, "5M").unwrap(), Value::from(5 * 1024 * 1024));
+        assert_eq!(parse_value(Kind::Minutes, "36h").unwrap(), Value::from(2160));
+        assert_eq!(parse_value(Kind::Choice(ENCRYPTION), "require").unwrap(), Value::from(1));
+        assert!(parse_value(Kind::Choice(ENCRYPTION), "maybe").is_err());
+        assert_eq!(canonical("max_connections"), "max_connec");
+    }
+}
@@ -0,0 +1,188 @@
+//! `rbit prune`: delete complete torrents that have seeded enough, by share ratio or by time
+//! since they completed, optionally only those of one category. The `[prune]` policy is what
+//! `rbit daemon` prunes by on its own, and what `rbit prune` uses when no limits are given.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+use crate::api::{Session, TorrentInfo};
+use crate::batch::{self, Outcome, Report};
+use crate::cache;
+use crate::commands::manage::short_id;
+use crate::confirm::confirm;
+use crate::format::{minutes_limit_human, parse_duration, truncate};
+use crate::i18n::tr;
+use crate::plan::{Action, Plan};
+use crate::theme;
+
+/// `[prune]`: the policy `rbit daemon` applies by itself
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PruneConfig {
+    /// Share ratio above which a complete torrent is deleted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratio_over: Option<f64>,
+    /// Time after completion after which a torrent is deleted, e.g. `14d`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seeded_for: Option<String>,
+    /// Only prune torrents in this category
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Delete the downloaded data too
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub delete_data: bool,
+}
+
+impl PruneConfig {
+    pub fn is_empty(&self) -> bool {
+        self.ratio_over.is_none() && self.seeded_for.is_none() && self.category.is_none() && !self.delete_data
+    }
+
+    /// The configured policy; `None` when it sets no limit.
+    pub fn policy(&self) -> anyhow::Result<Option<Policy>> {
+        let seeded_for = match self.seeded_for {
+            Some(ref v) => Some(parse_duration(v).map_err(|e| anyhow::anyhow!("[prune] seeded_for: {}", e))?),
+            None => None,
+        };
+        if self.ratio_over.is_none() && seeded_for.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Policy { ratio_over: self.ratio_over, seeded_for, category: self.category.clone(), delete_data: self.delete_data }))
+    }
+}
+
+/// Which torrents go: complete ones, in `category` if one is given, that reached either limit.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    pub ratio_over: Option<f64>,
+    pub seeded_for: Option<Duration>,
+    pub category: Option<String>,
+    pub delete_data: bool,
+}
+
+impl Policy {
+    /// Why `t` is deleted, if it is.
+    fn reason(&self, t: &TorrentInfo, now: u64) -> Option<String> {
+        let completed = t.completion_on.unwrap_or(-1);
+        if t.progress.unwrap_or(0.0) < 1.0 || completed <= 0 {
+            return None;
+        }
+        if self.category.as_ref().is_some_and(|c| t.category.as_deref() != Some(c.as_str())) {
+            return None;
+        }
+        let ratio = t.ratio.unwrap_or(0.0);
+        if let Some(limit) = self.ratio_over.filter(|&limit| ratio > limit) {
+            return Some(format!("ratio {:.2} (limit {})", ratio, limit));
+        }
+        let seeded = Duration::from_secs(now.saturating_sub(completed as u64));
+        let limit = self.seeded_for.filter(|&limit| seeded >= limit)?;
+        let minutes = |d: Duration| minutes_limit_human((d.as_secs() / 60) as i64);
+        Some(format!("seeded for {} (limit {})", minutes(seeded), minutes(limit)))
+    }
+
+    /// The torrents of `torrents` the policy deletes, with the reason, and the plan to delete them.
+    fn plan<'a>(&self, torrents: &'a [TorrentInfo], now: u64) -> (Vec<(&'a TorrentInfo, String)>, Plan) {
+        let due: Vec<(&TorrentInfo, String)> = torrents.iter().filter_map(|t| self.reason(t, now).map(|why| (t, why))).collect();
+        let mut plan = Plan::new("prune");
+        for (t, why) in &due {
+            let detail = if self.delete_data { format!("{}, with its data", why) } else { why.clone() };
+            plan.change(Action::Delete, "torrent", &format!("{} {}", short_id(t), t.name), detail).size = t.size;
+        }
+        if !due.is_empty() {
+            let hashes: Vec<&str> = due.iter().map(|(t, _)| t.hash.as_str()).collect();
+            plan.request("torrents/delete", &[("hashes", &hashes.join("|")), ("deleteFiles", if self.delete_data { "true" } else { "false" })]);
+        }
+        (due, plan)
+    }
+}
+
+#[derive(Tabled)]
+struct PruneRow {
+    id: String,
+    name: String,
+    category: String,
+    reason: String,
+}
+
+/// Show the torrents `policy` deletes and delete them once confirmed.
+pub async fn prune(session: &Session, policy: &Policy, json: bool) -> anyhow::Result<()> {
+    session.login().await?;
+    let torrents = session.torrents("all").await?;
+    let (due, plan) = policy.plan(&torrents, cache::now());
+    if session.dry_run {
+        return plan.print(json);
+    }
+    if due.is_empty() {
+        println!("{}", tr!("prune-none"));
+        return Ok(());
+    }
+    let rows = due.iter().map(|(t, why)| PruneRow {
+        id: short_id(t).to_string(),
+        name: truncate(&t.name, 40),
+        category: t.category.clone().unwrap_or_default(),
+        reason: why.clone(),
+    });
+    println!("{}", theme::table(rows));
+    let question = match policy.delete_data {
+        true => tr!("prune-confirm-data", count = due.len()),
+        false => tr!("prune-confirm", count = due.len()),
+    };
+    if !confirm(&question, &tr!("delete-what", count = due.len()))? {
+        println!("{}", tr!("nothing-deleted"));
+        return Ok(());
+    }
+    // one request per torrent, so one that fails is reported and the rest still go
+    let delete_files = if policy.delete_data { "true" } else { "false" };
+    let results = batch::run(session, &tr!("prune-deleting"), &due, |(t, _)| t.name.clone(), |(t, why)| async move {
+        session.post_form("torrents/delete", &[("hashes", &t.hash), ("deleteFiles", delete_files)]).await?;
+        Ok(Outcome::Done(why.clone()))
+    })
+    .await;
+    let mut report = Report::default();
+    for ((t, _), result) in due.iter().zip(results) {
+        report.push(format!("{} {}", short_id(t), t.name), result);
+    }
+    report.print();
+    let deleted = if policy.delete_data { "prune-deleted-data" } else { "prune-deleted" };
+    println!("{}", tr!(deleted, done = report.done(), total = due.len()));
+    report.result()
+}
+
+/// One pass of `rbit daemon`: delete what the configured policy says, without asking. Returns
+/// the names of the torrents deleted, with the reason.
+pub async fn apply(session: &Session, policy: &Policy) -> anyhow::Result<Vec<(String, String)>> {
+    session.login().await?;
+    let torrents = session.torrents("all").await?;
+    let (due, plan) = policy.plan(&torrents, cache::now());
+    plan.apply(session).await?;
+    for (t, why) in &due {
+        println!("{}", tr!("daemon-pruned", name = t.name.as_str(), id = short_id(t), reason = why.as_str()));
+    }
+    Ok(due.into_iter().map(|(t, why)| (t.name.clone(), why)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(category: &str, ratio: f64, completion_on: i64) -> TorrentInfo {
+        TorrentInfo { category: Some(category.to_string()), ratio: Some(ratio), completion_on: Some(completion_on), progress: Some(1.0), ..Default::default() }
+    }
+
+    #[test]
+    fn either_limit_prunes_complete_torrents() {
+        let policy = Policy { ratio_over: Some(2.0), seeded_for: Some(Duration::from_secs(14 * 86400)), category: Some("sonarr".to_string()), delete_data: true };
+        let now = 100 * 86400;
+        let day = |d: u64| (now - d * 86400) as i64;
+        assert!(policy.reason(&torrent("sonarr", 2.5, day(1)), now).is_some_and(|why| why.starts_with("ratio 2.50")));
+        assert!(policy.reason(&torrent("sonarr", 0.3, day(20)), now).is_some_and(|why| why.starts_with("seeded for 20d")));
+        assert!(policy.reason(&torrent("sonarr", 1.0, day(3)), now).is_none());
+        assert!(policy.reason(&torrent("radarr", 5.0, day(30)), now).is_none());
+        assert!(policy.reason(&torrent("sonarr", 5.0, -1), now).is_none());
+
+        let config = PruneConfig { category: Some("tv".to_string()), ..Default::default() };
+        assert!(config.policy().unwrap().is_none());
+        assert!(PruneConfig { seeded_for: Some("soon".to_string()), ..Default::default() }.policy().is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! `rbit reannounce`: make torrents announce to their trackers now, without hammering the
+//! trackers. A torrent rbit made announce less than the minimum interval ago, or whose tracker
+//! reports it does not accept another announce yet, is skipped. The rest are spaced out per
+//! tracker: each round asks for one announce per tracker, and rounds are `spacing` apart.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{Session, TorrentInfo, TrackerInfo};
+use crate::cache;
+use crate::commands::manage::short_id;
+use crate::format::{duration_human, parse_duration};
+use crate::i18n::tr;
+use crate::select::Selector;
+
+/// `[announce]`: how `rbit reannounce` spares the trackers
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AnnounceConfig {
+    /// Least time between two announces rbit asks of a torrent, e.g. `30m` (the default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_interval: Option<String>,
+    /// Pause between two announces to the same tracker, e.g. `1s` (the default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<String>,
+}
+
+impl AnnounceConfig {
+    pub fn is_empty(&self) -> bool {
+        self.min_interval.is_none() && self.spacing.is_none()
+    }
+
+    fn duration(value: &Option<String>, key: &str, default: u64) -> anyhow::Result<Duration> {
+        match value {
+            Some(v) => parse_duration(v).map_err(|e| anyhow::anyhow!("[announce] {}: {}", key, e)),
+            None => Ok(Duration::from_secs(default)),
+        }
+    }
+}
+
+/// The host a torrent announces to: its first real tracker, for grouping.
+fn tracker_host(t: &TorrentInfo, trackers: &[TrackerInfo]) -> String {
+    let url = trackers.iter().find(|tr| tr.tier >= 0).map(|tr| tr.url.as_str()).or(t.tracker.as_deref()).unwrap_or("");
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| "(no tracker)".to_string())
+}
+
+/// Why a torrent should not announce yet, if it should not.
+fn too_soon(last: Option<u64>, now: u64, min_interval: Duration, trackers: &[TrackerInfo]) -> Option<String> {
+    if let Some(ago) = last.map(|at| now.saturating_sub(at)).filter(|&ago| ago < min_interval.as_secs()) {
+        return Some(tr!("reannounce-too-recent", age = duration_human(Duration::from_secs(ago))));
+    }
+    let wait = trackers.iter().filter(|tr| tr.tier >= 0).filter_map(|tr| tr.min_announce).max().filter(|&w| w > 0)?;
+    Some(tr!("reannounce-tracker-wait", limit = duration_human(Duration::from_secs(wait as u64))))
+}
+
+/// Split `(tracker, torrent)` pairs into rounds with at most one torrent per tracker, keeping
+/// their order.
+fn rounds<T>(due: Vec<(String, T)>) -> Vec<Vec<T>> {
+    let mut rounds: Vec<Vec<T>> = Vec::new();
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    for (tracker, item) in due {
+        let round = seen.entry(tracker).or_insert(0);
+        if *round == rounds.len() {
+            rounds.push(Vec::new());
+        }
+        rounds[*round].push(item);
+        *round += 1;
+    }
+    rounds
+}
+
+pub async fn reannounce(session: &Session, ids: &[String], all: bool, selector: &Selector, force: bool, config: &AnnounceConfig) -> anyhow::Result<()> {
+    let min_interval = AnnounceConfig::duration(&config.min_interval, "min_interval", 30 * 60)?;
+    let spacing = AnnounceConfig::duration(&config.spacing, "spacing", 1)?;
+    session.login().await?;
+    let torrents = selector.select(session, ids, all, session.dry_run).await?;
+    let trackers: Vec<Vec<TrackerInfo>> = stream::iter(&torrents)
+        .map(|t| async move { session.trackers(&t.hash).await.unwrap_or_default() })
+        .buffered(session.concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut announces = cache::load_announces(&session.host);
+    let now = cache::now();
+    announces.torrents.retain(|_, &mut at| now.saturating_sub(at) < min_interval.as_secs().max(86400));
+    let mut due = Vec::new();
+    let mut skipped = 0;
+    for (t, trackers) in torrents.iter().zip(&trackers) {
+        let last = announces.torrents.get(&t.hash.to_lowercase()).copied();
+        match too_soon(last, now, min_interval, trackers) {
+            Some(why) if !force => {
+                skipped += 1;
+                println!("{}", tr!("reannounce-skipping", name = t.name.as_str(), id = short_id(t), why = why));
+            }
+            _ => due.push((tracker_host(t, trackers), t)),
+        }
+    }
+    let hosts = due.iter().map(|(host, _)| host.as_str()).collect::<std::collections::BTreeSet<_>>().len();
+    let count = due.len();
+
+    for (i, round) in rounds(due).into_iter().enumerate() {
+        if i > 0 && !session.dry_run {
+            tokio::time::sleep(spacing).await;
+        }
+        let hashes: Vec<&str> = round.iter().map(|t| t.hash.as_str()).collect();
+        session.post_form("torrents/reannounce", &[("hashes", &hashes.join("|"))]).await?;
+        for t in &round {
+            println!("{}", tr!("reannounce-doing", name = t.name.as_str(), id = short_id(t)));
+            announces.torrents.insert(t.hash.to_lowercase(), cache::now());
+        }
+        if !session.dry_run {
+            if let Err(e) = cache::save_announces(&announces) {
+                tracing::debug!("could not save the announce times: {:#}", e);
+            }
+        }
+    }
+    if torrents.len() > 1 {
+        let mut summary = tr!("reannounce-done", count = count, tracker = hosts);
+        if skipped > 0 {
+            summary.push_str(&tr!("reannounce-skipped", skipped = skipped));
+        }
+        println!("{}", summary);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_announce_per_tracker_per_round() {
+        let due = vec![("a".to_string(), 1), ("a".to_string(), 2), ("b".to_string(), 3), ("a".to_string(), 4), ("c".to_string(), 5)];
+        assert_eq!(rounds(due), vec![vec![1, 3, 5], vec![2], vec![4]]);
+
+        let min = Duration::from_secs(1800);
+        assert!(too_soon(Some(1000), 1600, min, &[]).is_some());
+        assert!(too_soon(Some(1000), 3000, min, &[]).is_none());
+        assert!(too_soon(None, 3000, min, &[]).is_none());
+    }
+}
@@ -0,0 +1,186 @@
+//! `rbit report`: summaries over the whole library (ratios, trackers, storage) as terminal
+//! output or as a markdown document for wikis and digests.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use tabled::{Table, Tabled};
+
+use crate::api::{MainData, Session, TorrentInfo};
+use crate::cache;
+use crate::format::{date_utc, size_human, truncate};
+use crate::i18n::tr;
+use crate::theme::{self, TableStyle};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ReportKind {
+    /// Share ratio per torrent, lowest first
+    Ratio,
+    /// Torrents, size and upload per tracker
+    Trackers,
+    /// Disk usage per save path
+    Storage,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Tables in the configured style
+    #[default]
+    Text,
+    /// A markdown document with a heading, summary list and table
+    Markdown,
+}
+
+#[derive(Tabled)]
+struct RatioRow {
+    name: String,
+    ratio: String,
+    uploaded: String,
+    downloaded: String,
+    size: String,
+}
+
+#[derive(Tabled)]
+struct TrackerRow {
+    tracker: String,
+    torrents: usize,
+    size: String,
+    uploaded: String,
+    #[tabled(rename = "avg ratio")]
+    avg_ratio: String,
+}
+
+#[derive(Tabled)]
+struct StorageRow {
+    path: String,
+    torrents: usize,
+    size: String,
+    share: String,
+}
+
+/// A finished report before it is formatted. The title and the summary labels are message ids.
+struct Report {
+    title: &'static str,
+    summary: Vec<(&'static str, String)>,
+    table: Table,
+}
+
+fn tracker_host(url: &str) -> String {
+    if url.is_empty() {
+        return "(none)".to_string();
+    }
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| url.to_string())
+}
+
+fn ratio_report(torrents: &[TorrentInfo]) -> Report {
+    let mut sorted: Vec<&TorrentInfo> = torrents.iter().collect();
+    sorted.sort_by(|a, b| a.ratio.unwrap_or(0.0).total_cmp(&b.ratio.unwrap_or(0.0)));
+    let uploaded: u64 = torrents.iter().filter_map(|t| t.uploaded).sum();
+    let downloaded: u64 = torrents.iter().filter_map(|t| t.downloaded).sum();
+    let below_one = torrents.iter().filter(|t| t.ratio.unwrap_or(0.0) < 1.0).count();
+    let rows = sorted.iter().map(|t| RatioRow {
+        name: truncate(&t.name, 50),
+        ratio: format!("{:.2}", t.ratio.unwrap_or(0.0)),
+        uploaded: size_human(t.uploaded.unwrap_or(0)),
+        downloaded: size_human(t.downloaded.unwrap_or(0)),
+        size: size_human(t.size.unwrap_or(0)),
+    });
+    let overall = if downloaded > 0 { format!("{:.2}", uploaded as f64 / downloaded as f64) } else { "-".to_string() };
+    Report {
+        title: "report-ratio",
+        summary: vec![
+            ("report-torrents", torrents.len().to_string()),
+            ("report-uploaded", size_human(uploaded)),
+            ("report-downloaded", size_human(downloaded)),
+            ("report-overall-ratio", overall),
+            ("report-below-one", below_one.to_string()),
+        ],
+        table: Table::new(rows),
+    }
+}
+
+fn trackers_report(torrents: &[TorrentInfo]) -> Report {
+    let mut groups: BTreeMap<String, Vec<&TorrentInfo>> = BTreeMap::new();
+    for t in torrents {
+        groups.entry(tracker_host(t.tracker.as_deref().unwrap_or(""))).or_default().push(t);
+    }
+    let rows = groups.iter().map(|(host, ts)| TrackerRow {
+        tracker: host.clone(),
+        torrents: ts.len(),
+        size: size_human(ts.iter().filter_map(|t| t.size).sum()),
+        uploaded: size_human(ts.iter().filter_map(|t| t.uploaded).sum()),
+        avg_ratio: format!("{:.2}", ts.iter().map(|t| t.ratio.unwrap_or(0.0)).sum::<f64>() / ts.len() as f64),
+    });
+    Report {
+        title: "report-trackers",
+        summary: vec![("report-torrents", torrents.len().to_string()), ("report-trackers", groups.len().to_string())],
+        table: Table::new(rows),
+    }
+}
+
+fn storage_report(torrents: &[TorrentInfo], free: Option<u64>) -> Report {
+    let mut groups: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for t in torrents {
+        let entry = groups.entry(t.save_path.clone().unwrap_or_default()).or_default();
+        entry.0 += 1;
+        entry.1 += t.size.unwrap_or(0);
+    }
+    let total: u64 = groups.values().map(|(_, size)| size).sum();
+    let mut by_size: Vec<(String, (usize, u64))> = groups.into_iter().collect();
+    by_size.sort_by_key(|(_, (_, size))| Reverse(*size));
+    let rows = by_size.iter().map(|(path, (count, size))| StorageRow {
+        path: path.clone(),
+        torrents: *count,
+        size: size_human(*size),
+        share: if total > 0 { format!("{:.1}%", *size as f64 * 100.0 / total as f64) } else { "-".to_string() },
+    });
+    Report {
+        title: "report-storage",
+        summary: vec![
+            ("report-torrents", torrents.len().to_string()),
+            ("report-total-size", size_human(total)),
+            ("report-free-space", free.map(size_human).unwrap_or_else(|| "-".to_string())),
+        ],
+        table: Table::new(rows),
+    }
+}
+
+/// Build the requested report from one `torrents/info` listing and print it.
+pub async fn report(session: &Session, kind: ReportKind, format: ReportFormat) -> anyhow::Result<()> {
+    session.login().await?;
+    let torrents = session.torrents("all").await?;
+    let report = match kind {
+        ReportKind::Ratio => ratio_report(&torrents),
+        ReportKind::Trackers => trackers_report(&torrents),
+        ReportKind::Storage => {
+            let maindata: MainData = session.get_json("sync/maindata").await?;
+            storage_report(&torrents, maindata.server_state.and_then(|s| s.free_space_on_disk))
+        }
+    };
+
+    match format {
+        ReportFormat::Text => {
+            println!("{}", tr!(report.title));
+            for (label, value) in &report.summary {
+                println!("  {}: {}", tr!(label), value);
+            }
+            println!();
+            print!("{}", theme::styled(report.table));
+        }
+        ReportFormat::Markdown => {
+            println!("# {}", tr!(report.title));
+            println!();
+            println!("{}", tr!("report-generated", host = session.host.as_str(), time = date_utc(cache::now())));
+            println!();
+            for (label, value) in &report.summary {
+                println!("- **{}:** {}", tr!(label), value);
+            }
+            println!();
+            print!("{}", theme::render(report.table, TableStyle::Markdown));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,538 @@
+//! `rbit rss`: qBittorrent's RSS feeds, folders, articles and auto-download rules (`rss/*`).
+//!
+//! Items are addressed by their path in the feed tree, with `\` between folder names as the
+//! Web UI does, e.g. `Linux\Ubuntu`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tabled::Tabled;
+
+use crate::api::Session;
+use crate::config::RssRule;
+use crate::format::truncate;
+use crate::i18n::{print_fields, tr};
+use crate::plan::{Action, Plan};
+use crate::theme;
+
+/// Auto-download rules by name, as `rss/rules` returns them and the Web UI exports them.
+/// Definitions are kept as raw JSON so fields from newer servers survive a round trip.
+type Rules = BTreeMap<String, serde_json::Value>;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Article {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    date: String,
+    #[serde(default, rename = "torrentURL")]
+    torrent_url: String,
+    #[serde(default)]
+    is_read: bool,
+}
+
+/// A feed found in the `rss/items` tree.
+struct Feed {
+    path: String,
+    url: String,
+    articles: Vec<Article>,
+}
+
+#[derive(Tabled)]
+struct FeedRow {
+    path: String,
+    url: String,
+    articles: String,
+    unread: String,
+}
+
+#[derive(Tabled)]
+struct ArticleRow {
+    feed: String,
+    title: String,
+    date: String,
+    torrent: String,
+}
+
+#[derive(Tabled)]
+struct RuleRow {
+    name: String,
+    enabled: String,
+    #[tabled(rename = "must contain")]
+    must_contain: String,
+    #[tabled(rename = "must not contain")]
+    must_not_contain: String,
+    episodes: String,
+    category: String,
+    #[tabled(rename = "save path")]
+    save_path: String,
+    feeds: usize,
+}
+
+/// `rbit rss rule add` flags; each one given overwrites that setting of the rule.
+#[derive(clap::Args, Debug)]
+pub struct RuleFlags {
+    /// Turn the rule on or off (`--enabled false`)
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    pub enabled: Option<bool>,
+
+    /// Words (or a regex with --regex) an article title must contain; `|` separates alternatives
+    #[arg(long, value_name = "PATTERN")]
+    pub must_contain: Option<String>,
+
+    /// Words (or a regex) that exclude an article
+    #[arg(long, value_name = "PATTERN")]
+    pub must_not_contain: Option<String>,
+
+    /// Treat the contain patterns as regular expressions
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    pub regex: Option<bool>,
+
+    /// Episode filter, e.g. `1x01-;` for series 1 from episode 1 on
+    #[arg(long, value_name = "FILTER")]
+    pub episode_filter: Option<String>,
+
+    /// Skip episodes that were already downloaded
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    pub smart_filter: Option<bool>,
+
+    /// Category for matched torrents
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Save path for matched torrents
+    #[arg(long)]
+    pub save_path: Option<String>,
+
+    /// Feed the rule applies to, by URL or feed path (repeatable; replaces the list)
+    #[arg(long = "feed", value_name = "FEED")]
+    pub feeds: Vec<String>,
+}
+
+impl RuleFlags {
+    pub fn into_rule(self) -> RssRule {
+        RssRule {
+            enabled: self.enabled,
+            must_contain: self.must_contain,
+            must_not_contain: self.must_not_contain,
+            use_regex: self.regex,
+            episode_filter: self.episode_filter,
+            smart_filter: self.smart_filter,
+            category: self.category,
+            save_path: self.save_path,
+            feeds: if self.feeds.is_empty() { None } else { Some(self.feeds) },
+        }
+    }
+}
+
+/// Walk the `rss/items` tree. Feeds are objects with a `url` string; any other object is a
+/// folder. Folder paths are collected in `folders`.
+fn walk(prefix: &str, node: &Map<String, Value>, feeds: &mut Vec<Feed>, folders: &mut Vec<String>) {
+    for (name, value) in node {
+        let path = if prefix.is_empty() { name.clone() } else { format!("{}\\{}", prefix, name) };
+        let Some(object) = value.as_object() else { continue };
+        match object.get("url").and_then(Value::as_str) {
+            Some(url) => {
+                let articles = object
+                    .get("articles")
+                    .and_then(|a| serde_json::from_value(a.clone()).ok())
+                    .unwrap_or_default();
+                feeds.push(Feed { path, url: url.to_string(), articles });
+            }
+            None => {
+                folders.push(path.clone());
+                walk(&path, object, feeds, folders);
+            }
+        }
+    }
+}
+
+async fn feed_tree(session: &Session) -> anyhow::Result<(Vec<Feed>, Vec<String>)> {
+    let tree: Map<String, Value> = session.get_json("rss/items?withData=true").await?;
+    let (mut feeds, mut folders) = (Vec::new(), Vec::new());
+    walk("", &tree, &mut feeds, &mut folders);
+    Ok((feeds, folders))
+}
+
+/// Whether `path` is `item` itself or lies inside the folder `item`.
+fn under(path: &str, item: &str) -> bool {
+    path == item || path.starts_with(&format!("{}\\", item))
+}
+
+/// Last component of an item path.
+fn item_name(path: &str) -> &str {
+    path.rsplit('\\').next().unwrap_or(path)
+}
+
+/// Print every folder and feed with its article counts, in tree order.
+pub async fn list(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    let (feeds, folders) = feed_tree(session).await?;
+    if feeds.is_empty() && folders.is_empty() {
+        println!("{}", tr!("rss-no-feeds"));
+        return Ok(());
+    }
+    let mut rows: Vec<FeedRow> = folders
+        .iter()
+        .map(|path| FeedRow { path: format!("{}\\", path), url: String::new(), articles: String::new(), unread: String::new() })
+        .chain(feeds.iter().map(|f| FeedRow {
+            path: f.path.clone(),
+            url: truncate(&f.url, 60),
+            articles: f.articles.len().to_string(),
+            unread: f.articles.iter().filter(|a| !a.is_read).count().to_string(),
+        }))
+        .collect();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    print!("{}", theme::table(rows));
+    Ok(())
+}
+
+/// Subscribe to a feed, optionally at a path such as `Linux\Ubuntu` (the folder must exist).
+pub async fn add_feed(session: &Session, url: &str, path: Option<&str>) -> anyhow::Result<()> {
+    session.login().await?;
+    let mut params = vec![("url", url)];
+    if let Some(path) = path {
+        params.push(("path", path));
+    }
+    session.post_form("rss/addFeed", &params).await?;
+    println!("{}", tr!("rss-feed-added", path = path.unwrap_or(url)));
+    Ok(())
+}
+
+/// Remove a feed, or a folder together with everything in it.
+pub async fn remove(session: &Session, path: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    session.post_form("rss/removeItem", &[("path", path)]).await?;
+    println!("{}", tr!("rss-removed", path = path));
+    Ok(())
+}
+
+/// Ask the server to fetch a feed (or every feed in a folder) now; with no path, all of them.
+pub async fn refresh(session: &Session, path: Option<&str>) -> anyhow::Result<()> {
+    session.login().await?;
+    let targets: Vec<String> = match path {
+        Some(path) => vec![path.to_string()],
+        None => {
+            let tree: Map<String, Value> = session.get_json("rss/items").await?;
+            tree.keys().cloned().collect()
+        }
+    };
+    for target in &targets {
+        session.post_form("rss/refreshItem", &[("itemPath", target)]).await?;
+    }
+    match path {
+        Some(path) => println!("{}", tr!("rss-refreshing", path = path)),
+        None => println!("{}", tr!("rss-refreshing-all", count = targets.len())),
+    }
+    Ok(())
+}
+
+/// Show the articles of one feed, of the feeds in a folder, or of all feeds.
+pub async fn items(session: &Session, path: Option<&str>, unread: bool) -> anyhow::Result<()> {
+    session.login().await?;
+    let (feeds, folders) = feed_tree(session).await?;
+    if let Some(path) = path {
+        if !feeds.iter().any(|f| f.path == path) && !folders.iter().any(|f| f == path) {
+            anyhow::bail!("no RSS feed or folder named '{}' (see `rbit rss list`)", path);
+        }
+    }
+    let rows: Vec<ArticleRow> = feeds
+        .iter()
+        .filter(|f| path.is_none_or(|p| under(&f.path, p)))
+        .flat_map(|f| {
+            f.articles.iter().filter(|a| !unread || !a.is_read).map(|a| ArticleRow {
+                feed: truncate(item_name(&f.path), 30),
+                title: truncate(&a.title, 60),
+                date: a.date.clone(),
+                torrent: truncate(&a.torrent_url, 50),
+            })
+        })
+        .collect();
+    if rows.is_empty() {
+        println!("{}", tr!("rss-no-articles"));
+        return Ok(());
+    }
+    print!("{}", theme::table(rows));
+    Ok(())
+}
+
+/// Create a folder; nested paths need their parent folder to exist.
+pub async fn add_folder(session: &Session, path: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    session.post_form("rss/addFolder", &[("path", path)]).await?;
+    println!("{}", tr!("rss-folder-created", path = path));
+    Ok(())
+}
+
+/// Move a feed or folder into `folder`, keeping its name. An empty folder means the top level.
+pub async fn move_item(session: &Session, item: &str, folder: &str) -> anyhow::Result<()> {
+    let folder = folder.trim_matches('\\');
+    let dest = if folder.is_empty() {
+        item_name(item).to_string()
+    } else {
+        format!("{}\\{}", folder, item_name(item))
+    };
+    if dest == item {
+        println!("{}", tr!("rss-already-there", name = item));
+        return Ok(());
+    }
+    session.login().await?;
+    session.post_form("rss/moveItem", &[("itemPath", item), ("destPath", &dest)]).await?;
+    println!("{}", tr!("rss-moved", name = item, destination = dest.as_str()));
+    Ok(())
+}
+
+/// Write every auto-download rule as pretty JSON, sorted by name so the file diffs cleanly.
+pub async fn export_rules(session: &Session, output: Option<&Path>) -> anyhow::Result<()> {
+    session.login().await?;
+    let rules: Rules = session.get_json("rss/rules").await?;
+    let text = serde_json::to_string_pretty(&rules)? + "\n";
+    match output {
+        Some(path) => {
+            fs::write(path, text)?;
+            println!("{}", tr!("rss-rules-written", count = rules.len(), path = path.display().to_string()));
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Create the rules from an exported file. Rules the server already has are skipped unless
+/// `overwrite` is set.
+pub async fn import_rules(session: &Session, file: &Path, overwrite: bool) -> anyhow::Result<()> {
+    let incoming: Rules = serde_json::from_str(&fs::read_to_string(file)?)
+        .map_err(|e| anyhow::anyhow!("{}: {}", file.display(), e))?;
+    session.login().await?;
+    let existing: Rules = session.get_json("rss/rules").await?;
+    let mut imported = 0;
+    let mut skipped = 0;
+    for (name, rule) in &incoming {
+        if !rule.is_object() {
+            anyhow::bail!("{}: rule '{}' is not an object", file.display(), name);
+        }
+        if existing.contains_key(name) && !overwrite {
+            println!("{}", tr!("rss-import-skipped", name = name.as_str()));
+            skipped += 1;
+            continue;
+        }
+        session
+            .post_form("rss/setRule", &[("ruleName", name), ("ruleDef", &rule.to_string())])
+            .await?;
+        println!("{}", tr!("rss-import-ok", name = name.as_str()));
+        imported += 1;
+    }
+    println!("{}", tr!("rss-imported", done = imported, total = incoming.len(), skipped = skipped));
+    Ok(())
+}
+
+/// What qBittorrent fills in for a rule created in the Web UI.
+fn new_rule() -> Map<String, Value> {
+    let Value::Object(def) = serde_json::json!({
+        "enabled": true,
+        "mustContain": "",
+        "mustNotContain": "",
+        "useRegex": false,
+        "episodeFilter": "",
+        "smartFilter": false,
+        "previouslyMatchedEpisodes": [],
+        "affectedFeeds": [],
+        "ignoreDays": 0,
+        "lastMatch": "",
+        "addPaused": null,
+        "assignedCategory": "",
+        "savePath": "",
+    }) else {
+        unreachable!()
+    };
+    def
+}
+
+/// Rules refer to feeds by URL; a feed path such as `Shows\Daily` is looked up in the tree.
+fn feed_url(feeds: &[Feed], entry: &str) -> anyhow::Result<String> {
+    if entry.contains("://") {
+        return Ok(entry.to_string());
+    }
+    match feeds.iter().find(|f| f.path == entry) {
+        Some(feed) => Ok(feed.url.clone()),
+        None => anyhow::bail!("no RSS feed named '{}' (give its URL or a path from `rbit rss list`)", entry),
+    }
+}
+
+/// Write the settings given in `rule` into a rule definition, leaving the others alone.
+fn apply_rule(def: &mut Map<String, Value>, rule: &RssRule, feeds: &[Feed]) -> anyhow::Result<()> {
+    let mut set = |key: &str, value: Value| {
+        def.insert(key.to_string(), value);
+    };
+    if let Some(enabled) = rule.enabled {
+        set("enabled", enabled.into());
+    }
+    if let Some(ref pattern) = rule.must_contain {
+        set("mustContain", pattern.as_str().into());
+    }
+    if let Some(ref pattern) = rule.must_not_contain {
+        set("mustNotContain", pattern.as_str().into());
+    }
+    if let Some(regex) = rule.use_regex {
+        set("useRegex", regex.into());
+    }
+    if let Some(ref filter) = rule.episode_filter {
+        set("episodeFilter", filter.as_str().into());
+    }
+    if let Some(smart) = rule.smart_filter {
+        set("smartFilter", smart.into());
+    }
+    if let Some(ref category) = rule.category {
+        set("assignedCategory", category.as_str().into());
+    }
+    if let Some(ref path) = rule.save_path {
+        set("savePath", path.as_str().into());
+    }
+    if let Some(ref entries) = rule.feeds {
+        let urls = entries.iter().map(|e| feed_url(feeds, e)).collect::<anyhow::Result<Vec<String>>>()?;
+        set("affectedFeeds", urls.into());
+    }
+    Ok(())
+}
+
+fn text(def: &Value, key: &str) -> String {
+    def.get(key).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn flag(def: &Value, key: &str) -> bool {
+    def.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn affected_feeds(def: &Value) -> Vec<String> {
+    def.get("affectedFeeds")
+        .and_then(Value::as_array)
+        .map(|feeds| feeds.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Print every auto-download rule, one row each.
+pub async fn list_rules(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    let rules: Rules = session.get_json("rss/rules").await?;
+    if rules.is_empty() {
+        println!("{}", tr!("rss-no-rules"));
+        return Ok(());
+    }
+    let rows = rules.iter().map(|(name, def)| RuleRow {
+        name: name.clone(),
+        enabled: if flag(def, "enabled") { "yes" } else { "no" }.to_string(),
+        must_contain: truncate(&text(def, "mustContain"), 30),
+        must_not_contain: truncate(&text(def, "mustNotContain"), 30),
+        episodes: text(def, "episodeFilter"),
+        category: text(def, "assignedCategory"),
+        save_path: text(def, "savePath"),
+        feeds: affected_feeds(def).len(),
+    });
+    print!("{}", theme::table(rows));
+    Ok(())
+}
+
+/// Print one rule in full, naming its feeds by path where they are subscribed.
+pub async fn show_rule(session: &Session, name: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    let rules: Rules = session.get_json("rss/rules").await?;
+    let Some(def) = rules.get(name) else {
+        anyhow::bail!("no RSS rule named '{}'", name);
+    };
+    let (feeds, _) = feed_tree(session).await?;
+    let matcher = if flag(def, "useRegex") { " (regex)" } else { "" };
+    let yes_no = |key| if flag(def, key) { tr!("yes") } else { tr!("no") };
+    print_fields(&[
+        (tr!("rss-rule"), name.to_string()),
+        (tr!("rss-rule-enabled"), yes_no("enabled")),
+        (tr!("rss-rule-must-contain"), format!("{}{}", text(def, "mustContain"), matcher)),
+        (tr!("rss-rule-must-not-contain"), text(def, "mustNotContain")),
+        (tr!("rss-rule-episode-filter"), text(def, "episodeFilter")),
+        (tr!("rss-rule-smart-filter"), yes_no("smartFilter")),
+        (tr!("rss-rule-category"), text(def, "assignedCategory")),
+        (tr!("rss-rule-save-path"), text(def, "savePath")),
+        (tr!("rss-rule-last-match"), text(def, "lastMatch")),
+    ]);
+    println!("{}:", tr!("rss-rule-feeds"));
+    for url in affected_feeds(def) {
+        match feeds.iter().find(|f| f.url == url) {
+            Some(feed) => println!("  {} ({})", feed.path, url),
+            None => println!("  {} ({})", url, tr!("rss-not-subscribed")),
+        }
+    }
+    Ok(())
+}
+
+/// Create a rule or change the given settings of an existing one.
+pub async fn add_rule(session: &Session, name: &str, rule: &RssRule) -> anyhow::Result<()> {
+    session.login().await?;
+    let rules: Rules = session.get_json("rss/rules").await?;
+    let (feeds, _) = feed_tree(session).await?;
+    let existing = rules.get(name).and_then(Value::as_object).cloned();
+    let created = existing.is_none();
+    let mut def = existing.unwrap_or_else(new_rule);
+    apply_rule(&mut def, rule, &feeds)?;
+    session
+        .post_form("rss/setRule", &[("ruleName", name), ("ruleDef", &Value::Object(def).to_string())])
+        .await?;
+    println!("{}", if created { tr!("rss-rule-created", name = name) } else { tr!("rss-rule-updated", name = name) });
+    Ok(())
+}
+
+pub async fn remove_rule(session: &Session, name: &str) -> anyhow::Result<()> {
+    session.login().await?;
+    let rules: Rules = session.get_json("rss/rules").await?;
+    if !rules.contains_key(name) {
+        anyhow::bail!("no RSS rule named '{}'", name);
+    }
+    session.post_form("rss/removeRule", &[("ruleName", name)]).await?;
+    println!("{}", tr!("rss-rule-removed", name = name));
+    Ok(())
+}
+
+/// Push the `[rss.rules]` from the config to the server. Settings a rule does not mention keep
+/// their server value; with `prune`, server rules missing from the config are removed.
+pub async fn sync_rules(session: &Session, wanted: &BTreeMap<String, RssRule>, prune: bool, json: bool) -> anyhow::Result<()> {
+    if wanted.is_empty() && !prune {
+        println!("{}", tr!("rss-no-config-rules"));
+        return Ok(());
+    }
+    session.login().await?;
+    let rules: Rules = session.get_json("rss/rules").await?;
+    let (feeds, _) = feed_tree(session).await?;
+    let mut plan = Plan::new("rss rule sync");
+    for (name, rule) in wanted {
+        let existing = rules.get(name).and_then(Value::as_object).cloned();
+        let mut def = existing.clone().unwrap_or_else(new_rule);
+        apply_rule(&mut def, rule, &feeds).map_err(|e| anyhow::anyhow!("[rss.rules.{}]: {}", name, e))?;
+        match existing {
+            Some(ref current) if *current == def => {
+                plan.unchanged += 1;
+                continue;
+            }
+            Some(ref current) => {
+                let changed: Vec<&str> = def.iter().filter(|(k, v)| current.get(*k) != Some(v)).map(|(k, _)| k.as_str()).collect();
+                plan.change(Action::Update, "rule", name, format!("changes {}", changed.join(", ")));
+            }
+            None => {
+                plan.change(Action::Create, "rule", name, String::new());
+            }
+        }
+        plan.request("rss/setRule", &[("ruleName", name), ("ruleDef", &Value::Object(def).to_string())]);
+    }
+    if prune {
+        for name in rules.keys().filter(|name| !wanted.contains_key(*name)) {
+            plan.change(Action::Delete, "rule", name, String::new());
+            plan.request("rss/removeRule", &[("ruleName", name)]);
+        }
+    }
+    if session.dry_run {
+        return plan.print(json);
+    }
+    plan.apply(session).await?;
+    plan.print_applied();
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! `rbit run-once`: run a post-processing command for a torrent unless it already ran (or is
+//! running) for that torrent, e.g. as qBittorrent's "Run external program on torrent finished".
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::format::date_utc;
+use crate::i18n::tr;
+use crate::postprocess::{self, Claim};
+use crate::torrent::info_hash_hex;
+
+/// The step name used for markers when `--name` is not given: the program's file name.
+fn default_step(command: &[String]) -> String {
+    let program = command.first().map(String::as_str).unwrap_or("command");
+    Path::new(program).file_name().and_then(|n| n.to_str()).unwrap_or(program).to_string()
+}
+
+pub fn run_once(hash: &str, name: Option<&str>, forget: bool, command: &[String], dry_run: bool) -> anyhow::Result<()> {
+    let hash = info_hash_hex(hash)
+        .or_else(|| (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_lowercase()))
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not an info-hash", hash))?;
+    let step = name.map(str::to_string).unwrap_or_else(|| default_step(command));
+    let dir = postprocess::markers_dir().ok_or_else(|| anyhow::anyhow!("no data directory available"))?;
+
+    if forget {
+        if postprocess::forget(&dir, &hash, &step)? {
+            println!("{}", tr!("run-once-forgot", name = step.as_str(), id = hash.as_str()));
+        } else {
+            println!("{}", tr!("run-once-not-run", name = step.as_str(), id = hash.as_str()));
+        }
+        return Ok(());
+    }
+    if dry_run {
+        println!("[dry-run] would run `{}` for {} unless '{}' already ran", command.join(" "), hash, step);
+        return Ok(());
+    }
+
+    let guard = match postprocess::claim(&dir, &hash, &step)? {
+        Claim::Acquired(guard) => guard,
+        Claim::Done(at) => {
+            println!("{}", tr!("run-once-done", name = step.as_str(), id = hash.as_str(), time = date_utc(at)));
+            return Ok(());
+        }
+        Claim::Running(pid, since) => {
+            println!("{}", tr!("run-once-running", name = step.as_str(), id = hash.as_str(), pid = pid, time = date_utc(since)));
+            return Ok(());
+        }
+    };
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .env("RBIT_HASH", &hash)
+        .status()
+        .map_err(|e| anyhow::anyhow!("could not run {}: {}", command[0], e))?;
+    if !status.success() {
+        // the guard is dropped here, releasing the lock so the step can be retried
+        anyhow::bail!(tr!("run-once-failed", name = step.as_str(), id = hash.as_str(), reason = status.to_string()));
+    }
+    guard.finish()
+}
@@ -0,0 +1,224 @@
+//! `rbit schedule`: turn qBittorrent's alternative speed limits on during the `[[schedule]]`
+//! windows of the config and off outside them. qBittorrent itself offers a single window; here
+//! there can be any number, per day of the week and across midnight. `rbit daemon` follows the
+//! schedule by itself; `rbit schedule --apply` switches once, e.g. from cron.
+//!
+//! Times are the local time of the machine rbit runs on.
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::Session;
+use crate::cache;
+use crate::commands::limit::alt_enabled;
+use crate::error::RbitError;
+use crate::i18n::tr;
+
+const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// `[[schedule]]`: a window in which the alternative speed limits are on
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduleWindow {
+    /// Days the window starts on: `mon-fri`, `sat,sun`, `weekdays`, `weekends` or `daily`
+    #[serde(default = "every_day")]
+    pub days: String,
+    /// Start, e.g. `09:00`
+    pub from: String,
+    /// End, e.g. `18:00`; before `from` the window runs into the next day
+    pub to: String,
+}
+
+fn every_day() -> String {
+    "daily".to_string()
+}
+
+/// A parsed [`ScheduleWindow`], in minutes since midnight.
+#[derive(Debug, Clone, PartialEq)]
+struct Window {
+    /// Indexed from Monday
+    days: [bool; 7],
+    from: u32,
+    to: u32,
+    /// As written in the config, for messages
+    label: String,
+}
+
+impl Window {
+    /// Whether the window covers `minute` of `weekday` (0 for Monday).
+    fn covers(&self, weekday: usize, minute: u32) -> bool {
+        if self.from <= self.to {
+            return self.days[weekday] && (self.from..self.to).contains(&minute);
+        }
+        // overnight: the evening of a listed day, or the morning after one
+        (self.days[weekday] && minute >= self.from) || (self.days[(weekday + 6) % 7] && minute < self.to)
+    }
+}
+
+fn parse_days(input: &str) -> anyhow::Result<[bool; 7]> {
+    let bad = || RbitError::InvalidInput(format!("[[schedule]] days '{}': expected e.g. mon-fri, sat,sun, weekdays, weekends or daily", input));
+    let day = |name: &str| DAYS.iter().position(|d| name.trim().to_ascii_lowercase().starts_with(d)).ok_or_else(bad);
+    let mut days = [false; 7];
+    for part in input.split(',') {
+        match part.trim().to_ascii_lowercase().as_str() {
+            "daily" | "all" | "every day" => days = [true; 7],
+            "weekdays" => days[..5].fill(true),
+            "weekends" => days[5..].fill(true),
+            range => match range.split_once('-') {
+                Some((first, last)) => {
+                    let (first, last) = (day(first)?, day(last)?);
+                    let mut d = first;
+                    loop {
+                        days[d] = true;
+                        if d == last {
+                            break;
+                        }
+                        d = (d + 1) % 7;
+                    }
+                }
+                None => days[day(range)?] = true,
+            },
+        }
+    }
+    Ok(days)
+}
+
+fn parse_time(input: &str) -> anyhow::Result<u32> {
+    let bad = || RbitError::InvalidInput(format!("[[schedule]] time '{}': expected HH:MM, e.g. 09:00", input));
+    let (hour, minute) = input.trim().split_once(':').ok_or_else(bad)?;
+    let (hour, minute): (u32, u32) = (hour.parse().map_err(|_| bad())?, minute.parse().map_err(|_| bad())?);
+    if minute > 59 || hour > 24 || (hour == 24 && minute > 0) {
+        return Err(bad().into());
+    }
+    Ok(hour * 60 + minute)
+}
+
+/// The `[[schedule]]` windows of the config.
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    windows: Vec<Window>,
+}
+
+impl Schedule {
+    pub fn from_config(windows: &[ScheduleWindow]) -> anyhow::Result<Schedule> {
+        let windows = windows
+            .iter()
+            .map(|w| {
+                Ok(Window {
+                    days: parse_days(&w.days)?,
+                    from: parse_time(&w.from)?,
+                    to: parse_time(&w.to)?,
+                    label: format!("{} {}-{}", w.days, w.from.trim(), w.to.trim()),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Schedule { windows })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    /// The window that turns the alternative limits on at `minute` of `weekday`, if any.
+    fn active(&self, weekday: usize, minute: u32) -> Option<&Window> {
+        self.windows.iter().find(|w| w.covers(weekday, minute))
+    }
+
+    /// Whether the alternative limits should be on now, and why, e.g. `schedule: mon-fri 09:00-18:00`.
+    pub fn wanted_now(&self) -> (bool, String) {
+        let (weekday, minute) = local_now();
+        match self.active(weekday, minute) {
+            Some(w) => (true, format!("schedule: {}", w.label)),
+            None => (false, "schedule: outside every window".to_string()),
+        }
+    }
+}
+
+/// Today's weekday (0 for Monday) and the minute of the day, in local time.
+#[cfg(unix)]
+fn local_now() -> (usize, u32) {
+    let now = cache::now() as libc::time_t;
+    // SAFETY: `tm` is plain integers and a pointer, for which all zeroes is a valid value
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    // SAFETY: localtime_r only reads `now` and writes `tm`, both of which live through the call
+    if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+        return utc_now();
+    }
+    ((tm.tm_wday as usize + 6) % 7, (tm.tm_hour * 60 + tm.tm_min) as u32)
+}
+
+/// Without a portable way to read the time zone there, other systems follow the schedule in UTC.
+#[cfg(not(unix))]
+fn local_now() -> (usize, u32) {
+    utc_now()
+}
+
+fn utc_now() -> (usize, u32) {
+    let now = cache::now();
+    // 1970-01-01 was a Thursday
+    (((now / 86400 + 3) % 7) as usize, (now % 86400 / 60) as u32)
+}
+
+/// Switch the alternative limits on or off, and say so with `why`; `false` when they already were.
+pub async fn switch(session: &Session, on: bool, why: &str) -> anyhow::Result<bool> {
+    let state = if on { tr!("on") } else { tr!("off") };
+    if alt_enabled(session).await? == on {
+        return Ok(false);
+    }
+    session.post_form("transfer/toggleSpeedLimitsMode", &[]).await?;
+    println!("{} ({})", tr!("limit-alt-turned", state = state), why);
+    Ok(true)
+}
+
+/// Print the schedule and what it says for now; with `apply`, switch the server to that.
+pub async fn schedule(session: &Session, schedule: &Schedule, apply: bool) -> anyhow::Result<()> {
+    if schedule.is_empty() {
+        return Err(RbitError::InvalidInput("no schedule: add [[schedule]] tables with days, from and to to the config".to_string()).into());
+    }
+    session.login().await?;
+    println!("{}", tr!("schedule-windows"));
+    for w in &schedule.windows {
+        println!("  {}", w.label);
+    }
+    let (wanted, why) = schedule.wanted_now();
+    let state = if wanted { tr!("on") } else { tr!("off") };
+    if apply {
+        if !switch(session, wanted, &why).await? {
+            println!("{} ({})", tr!("limit-alt-already", state = state), why);
+        }
+        return Ok(());
+    }
+    let current = if alt_enabled(session).await? { tr!("on") } else { tr!("off") };
+    println!("{}", tr!("schedule-now", state = state.as_str(), why = why.as_str(), limit = current.as_str()));
+    if current != state {
+        println!("{}", tr!("schedule-apply-hint"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(days: &str, from: &str, to: &str) -> ScheduleWindow {
+        ScheduleWindow { days: days.to_string(), from: from.to_string(), to: to.to_string() }
+    }
+
+    #[test]
+    fn windows_by_day_and_across_midnight() {
+        let schedule = Schedule::from_config(&[window("mon-fri", "09:00", "18:00"), window("fri,sat", "22:00", "06:30")]).unwrap();
+        let at = |day: usize, hh: u32, mm: u32| schedule.active(day, hh * 60 + mm).map(|w| w.label.as_str());
+        assert_eq!(at(0, 9, 0), Some("mon-fri 09:00-18:00"));
+        assert_eq!(at(4, 17, 59), Some("mon-fri 09:00-18:00"));
+        assert_eq!(at(2, 18, 0), None);
+        assert_eq!(at(5, 12, 0), None);
+        assert_eq!(at(4, 23, 0), Some("fri,sat 22:00-06:30"));
+        assert_eq!(at(6, 6, 0), Some("fri,sat 22:00-06:30"));
+        assert_eq!(at(0, 6, 0), None);
+
+        assert_eq!(parse_days("sat-mon").unwrap(), [true, false, false, false, false, true, true]);
+        assert_eq!(parse_days("weekdays").unwrap(), parse_days("Monday-Friday").unwrap());
+        assert!(parse_days("someday").is_err());
+        assert_eq!(parse_time("24:00").unwrap(), 1440);
+        assert!(parse_time("9am").is_err());
+        assert!(parse_time("25:00").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! `rbit search`: run a search through qBittorrent's search plugins (`search/*`) and show the
+//! results with releases found by several plugins merged into one row. The rows shown last are
+//! kept in the cache so `rbit search --add <#>` can add one of them.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tabled::Tabled;
+
+use crate::api::{AddOptions, Session};
+use crate::cache;
+use crate::commands::add;
+use crate::config::SavedSearch;
+use crate::format::{size_human, truncate};
+use crate::i18n::tr;
+use crate::theme;
+
+#[derive(Deserialize)]
+struct SearchJob {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct JobStatus {
+    status: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SearchResult {
+    file_name: String,
+    /// Magnet link or torrent URL
+    #[serde(default)]
+    file_url: String,
+    /// -1 when the plugin does not know
+    #[serde(default)]
+    file_size: i64,
+    #[serde(default)]
+    nb_seeders: i64,
+    #[serde(default)]
+    nb_leechers: i64,
+}
+
+#[derive(Deserialize)]
+struct SearchResults {
+    results: Vec<SearchResult>,
+}
+
+/// One release, possibly reported by several plugins.
+struct Release {
+    best: SearchResult,
+    sources: usize,
+}
+
+#[derive(Tabled)]
+struct ResultRow {
+    #[tabled(rename = "#")]
+    index: usize,
+    name: String,
+    size: String,
+    seeders: i64,
+    leechers: i64,
+    sources: usize,
+}
+
+/// A row of the last results table, as kept in the cache for `--add`.
+#[derive(Serialize, Deserialize)]
+struct ShownResult {
+    name: String,
+    url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LastSearch {
+    host: String,
+    query: String,
+    results: Vec<ShownResult>,
+}
+
+fn last_search_file(host: &str) -> String {
+    format!("search-{}.json", cache::file_key(host))
+}
+
+/// Lowercase, with punctuation turned into spaces and runs of spaces collapsed, so
+/// `Ubuntu.24.04-ISO` and `ubuntu 24.04 iso` compare equal.
+fn normalize_name(name: &str) -> String {
+    let spaced: String = name.chars().map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { ' ' }).collect();
+    spaced.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Merge results with the same normalized name and size, keeping the best-seeded copy of each.
+fn dedup(results: Vec<SearchResult>) -> Vec<Release> {
+    let mut releases: Vec<Release> = Vec::new();
+    let mut by_key: HashMap<(String, i64), usize> = HashMap::new();
+    for result in results {
+        let key = (normalize_name(&result.file_name), result.file_size);
+        match by_key.get(&key) {
+            Some(&i) => {
+                let release = &mut releases[i];
+                release.sources += 1;
+                if result.nb_seeders > release.best.nb_seeders {
+                    release.best = result;
+                }
+            }
+            None => {
+                by_key.insert(key, releases.len());
+                releases.push(Release { best: result, sources: 1 });
+            }
+        }
+    }
+    releases
+}
+
+/// Start a search job, wait for it to finish (or `timeout`), and return its results.
+/// The job is deleted afterwards so it does not pile up on the server.
+async fn run_search(session: &Session, search: &SavedSearch, timeout: Duration) -> anyhow::Result<Vec<SearchResult>> {
+    let plugins = if search.plugins.is_empty() { "enabled".to_string() } else { search.plugins.join("|") };
+    let category = search.category.as_deref().unwrap_or("all");
+    let body = session
+        .post_form("search/start", &[("pattern", &search.query), ("plugins", &plugins), ("category", category)])
+        .await?;
+    if session.dry_run {
+        return Ok(Vec::new());
+    }
+    let job: SearchJob = serde_json::from_str(&body)
+        .map_err(|_| anyhow::anyhow!("could not start a search: {} (is the search engine enabled and a plugin installed?)", body.trim()))?;
+    let id = job.id.to_string();
+
+    let started = Instant::now();
+    loop {
+        let status: Vec<JobStatus> = session.get_json(&format!("search/status?id={}", id)).await?;
+        if status.first().is_none_or(|s| s.status != "Running") {
+            break;
+        }
+        if started.elapsed() >= timeout {
+            session.post_form("search/stop", &[("id", &id)]).await?;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let results: SearchResults = session.get_json(&format!("search/results?id={}", id)).await?;
+    session.post_form("search/delete", &[("id", &id)]).await?;
+    Ok(results.results)
+}
+
+/// Store `search` as `[searches.<name>]` in the config file at `target`, replacing a saved
+/// search of the same name.
+pub fn save(target: &Path, name: &str, search: &SavedSearch) -> anyhow::Result<()> {
+    let mut table: toml::Table = if target.exists() {
+        toml::from_str(&fs::read_to_string(target)?).map_err(|e| anyhow::anyhow!("{}: {}", target.display(), e))?
+    } else {
+        toml::Table::new()
+    };
+    let searches = table
+        .entry("searches")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| anyhow::anyhow!("{}: `searches` is not a table", target.display()))?;
+    searches.insert(name.to_lowercase(), toml::Value::try_from(search)?);
+    if let Some(dir) = target.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(target, toml::to_string_pretty(&table)?)?;
+    println!("{}", tr!("search-saved", name = name.to_lowercase(), path = target.display().to_string()));
+    Ok(())
+}
+
+/// Run a search and print the results ranked by seeders.
+pub async fn search(session: &Session, search: &SavedSearch, limit: usize, timeout: Duration, no_dedup: bool) -> anyhow::Result<()> {
+    session.login().await?;
+    let query = search.query.as_str();
+    let results = run_search(session, search, timeout).await?;
+    if session.dry_run {
+        return Ok(());
+    }
+    let total = results.len();
+    let mut releases = if no_dedup {
+        results.into_iter().map(|r| Release { best: r, sources: 1 }).collect()
+    } else {
+        dedup(results)
+    };
+    if releases.is_empty() {
+        println!("{}", tr!("search-no-results", what = query));
+        return Ok(());
+    }
+    releases.sort_by_key(|r| Reverse(r.best.nb_seeders));
+
+    let shown = releases.len().min(limit);
+    let last = LastSearch {
+        host: session.host.clone(),
+        query: query.to_string(),
+        results: releases
+            .iter()
+            .take(limit)
+            .map(|r| ShownResult { name: r.best.file_name.clone(), url: r.best.file_url.clone() })
+            .collect(),
+    };
+    if let Err(e) = cache::write(&last_search_file(&session.host), &last) {
+        eprintln!("{} {}", tr!("warning"), tr!("search-not-remembered", reason = e.to_string()));
+    }
+    let rows = releases.iter().take(limit).enumerate().map(|(i, r)| ResultRow {
+        index: i + 1,
+        name: truncate(&r.best.file_name, 60),
+        size: if r.best.file_size < 0 { "-".to_string() } else { size_human(r.best.file_size as u64) },
+        seeders: r.best.nb_seeders.max(0),
+        leechers: r.best.nb_leechers.max(0),
+        sources: r.sources,
+    });
+    print!("{}", theme::table(rows));
+    println!();
+    println!("{}", tr!("search-summary", total = total, count = releases.len(), shown = shown));
+    println!("{}", tr!("search-add-hint"));
+    Ok(())
+}
+
+/// Add result number `index` (as numbered in the last results table for this server).
+pub async fn add_result(session: &Session, index: usize, options: &AddOptions) -> anyhow::Result<()> {
+    let last: LastSearch = cache::read(&last_search_file(&session.host))
+        .filter(|l: &LastSearch| l.host == session.host)
+        .ok_or_else(|| anyhow::anyhow!("no search results to add from; run `rbit search <query>` first"))?;
+    let result = index
+        .checked_sub(1)
+        .and_then(|i| last.results.get(i))
+        .ok_or_else(|| anyhow::anyhow!("no result #{} in the last search for '{}' ({} shown)", index, last.query, last.results.len()))?;
+    if result.url.is_empty() {
+        anyhow::bail!("the plugin gave no download link for '{}'", result.name);
+    }
+    println!("{}", tr!("search-adding", name = result.name.as_str()));
+    add::add_inputs(session, std::slice::from_ref(&result.url), options, false, false).await
+}
@@ -0,0 +1,191 @@
+//! `rbit self-update`: replace this binary with the latest release from GitHub, for machines
+//! without cargo. Releases carry one binary per platform, named `rbit-<os>-<arch>` (`.exe` on
+//! Windows), a `SHA256SUMS` file and its minisign signature, `SHA256SUMS.minisig`. A download is
+//! only installed when the signature checks out against [`PUBLIC_KEY`] and the checksum matches,
+//! so someone who can change the release files cannot ship a binary of their own.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::RbitError;
+use crate::i18n::tr;
+
+const RELEASES: &str = "https://api.github.com/repos/fugazister/rbit/releases/latest";
+const CHECKSUMS: &str = "SHA256SUMS";
+const SIGNATURE: &str = "SHA256SUMS.minisig";
+
+/// The minisign key releases are signed with (`minisign -S -m SHA256SUMS`).
+const PUBLIC_KEY: &str = "RWRGmxi+rJ9RAimPnv8jyDeMDgnR+sYpfbkRJzctMFxucQ13PTxTq0Oz";
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> anyhow::Result<&Asset> {
+        self.assets.iter().find(|a| a.name == name).ok_or_else(|| {
+            RbitError::NotFound(format!("release {} has no {} (there is no build for this platform, or it is still being uploaded)", self.tag_name, name)).into()
+        })
+    }
+}
+
+/// The release asset built for the platform this binary runs on.
+fn asset_name() -> String {
+    let exe = if cfg!(windows) { ".exe" } else { "" };
+    format!("rbit-{}-{}{}", std::env::consts::OS, std::env::consts::ARCH, exe)
+}
+
+/// `1.2.3` (with or without a leading `v`) as numbers to compare; missing parts count as 0.
+fn version(tag: &str) -> Vec<u64> {
+    let tag = tag.trim().trim_start_matches('v');
+    let core = tag.split(['-', '+']).next().unwrap_or(tag);
+    core.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut latest, mut current) = (version(latest), version(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+/// The checksum `SHA256SUMS` lists for `file`, in the `sha256sum` format (`<hex>  <name>`).
+fn listed_checksum(sums: &str, file: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == file).then(|| hash.to_ascii_lowercase())
+    })
+}
+
+/// Check that `signature` (a `.minisig` file) is `key`'s signature of `sums`.
+fn verify_signature(sums: &str, signature: &str, key: &str) -> anyhow::Result<()> {
+    let key = PublicKey::from_base64(key).map_err(|e| anyhow::anyhow!("the release key built into rbit is broken: {}", e))?;
+    let signature = Signature::decode(signature).map_err(|e| anyhow::anyhow!("{} is not a minisign signature: {}", SIGNATURE, e))?;
+    key.verify(sums.as_bytes(), &signature, false).map_err(|e| anyhow::anyhow!("{} is not signed with rbit's release key: {}", CHECKSUMS, e))
+}
+
+async fn get(client: &reqwest::Client, url: &str) -> anyhow::Result<reqwest::Response> {
+    let res = client.get(url).send().await.map_err(|e| RbitError::Connection(format!("could not reach GitHub: {}", e)))?;
+    if !res.status().is_success() {
+        anyhow::bail!("GET {} failed with {}", url, res.status());
+    }
+    Ok(res)
+}
+
+/// Put `binary` in place of the executable at `target`. The new file is written next to it
+/// and renamed over it, so a failed update leaves the old binary working. Windows does not let
+/// a running executable be replaced, only renamed, so there it is moved aside first.
+fn install(target: &Path, binary: &[u8]) -> anyhow::Result<()> {
+    let dir = target.parent().ok_or_else(|| anyhow::anyhow!("{} has no parent folder", target.display()))?;
+    let staged: PathBuf = dir.join(format!(".rbit-update-{}", std::process::id()));
+    fs::write(&staged, binary).map_err(|e| anyhow::anyhow!("could not write to {}: {} (is it writable by this user?)", dir.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(target).map(|m| m.permissions().mode()).unwrap_or(0o755);
+        fs::set_permissions(&staged, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(windows)]
+    let old = {
+        let old = target.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(target, &old)?;
+        old
+    };
+    fs::rename(&staged, target).inspect_err(|_| {
+        let _ = fs::remove_file(&staged);
+        // put the old binary back, or there would be none
+        #[cfg(windows)]
+        let _ = fs::rename(&old, target);
+    })?;
+    Ok(())
+}
+
+/// Check for a newer release and, unless `check`, install it over this binary.
+pub async fn self_update(check: bool, disabled: bool, dry_run: bool) -> anyhow::Result<()> {
+    if disabled && !check {
+        return Err(RbitError::InvalidInput(
+            "self-update is turned off by no_self_update in the config; update rbit the way it was installed".to_string(),
+        )
+        .into());
+    }
+    let current = env!("CARGO_PKG_VERSION");
+    let client = reqwest::Client::builder().user_agent(concat!("rbit/", env!("CARGO_PKG_VERSION"))).build()?;
+    let release: Release = get(&client, RELEASES).await?.json().await?;
+    if !is_newer(&release.tag_name, current) {
+        println!("{}", tr!("self-update-current", version = current, latest = release.tag_name.as_str()));
+        return Ok(());
+    }
+    let name = asset_name();
+    let asset = release.asset(&name)?;
+    if check {
+        println!("{}", tr!("self-update-available", latest = release.tag_name.as_str(), version = current));
+        return Ok(());
+    }
+
+    let target = std::env::current_exe()?.canonicalize()?;
+    if dry_run {
+        println!("[dry-run] would replace {} ({}) with {} from {}", target.display(), current, release.tag_name, asset.browser_download_url);
+        return Ok(());
+    }
+    let sums = get(&client, &release.asset(CHECKSUMS)?.browser_download_url).await?.text().await?;
+    let signature = get(&client, &release.asset(SIGNATURE)?.browser_download_url).await?.text().await?;
+    verify_signature(&sums, &signature, PUBLIC_KEY).map_err(|e| anyhow::anyhow!("{} of {}: {:#}; nothing was replaced", CHECKSUMS, release.tag_name, e))?;
+    let expected = listed_checksum(&sums, &name).ok_or_else(|| RbitError::NotFound(format!("{} of {} does not list {}", CHECKSUMS, release.tag_name, name)))?;
+    println!("{}", tr!("self-update-downloading", name = name.as_str(), latest = release.tag_name.as_str()));
+    let binary = get(&client, &asset.browser_download_url).await?.bytes().await?;
+    let actual = format!("{:x}", Sha256::digest(&binary));
+    if actual != expected {
+        anyhow::bail!("the download of {} does not match its checksum ({} instead of {}); nothing was replaced", name, actual, expected);
+    }
+    install(&target, &binary)?;
+    println!("{}", tr!("self-update-done", path = target.display().to_string(), version = current, latest = release.tag_name.as_str()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_and_checksums() {
+        assert!(is_newer("v0.2.0", "0.1.9"));
+        assert!(is_newer("v1.0", "0.9.12"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("0.1.0-rc.1", "0.1.0"));
+        let sums = "ab12  rbit-linux-x86_64\nCD34 *rbit-windows-x86_64.exe\n";
+        assert_eq!(listed_checksum(sums, "rbit-linux-x86_64").as_deref(), Some("ab12"));
+        assert_eq!(listed_checksum(sums, "rbit-windows-x86_64.exe").as_deref(), Some("cd34"));
+        assert_eq!(listed_checksum(sums, "rbit-macos-aarch64"), None);
+    }
+
+    #[test]
+    fn only_signed_checksums_pass() {
+        let key = "RWTuVhpvlDcNYN3tzn+XxwlyexFStFK3Iyqzv6BD1ng41IdGsrDJh/op";
+        let signature = "untrusted comment: signature from rbit secret key
+RUTuVhpvlDcNYAHnOfW702RTUKs4gsISW6SZR8z6TLDi36IOMMvR89r/oGg2VCE3JqPntW+3T2s1HU+C0wO7cEfyYyZT/izJMQA=
+trusted comment: timestamp:1700000000\tfile:SHA256SUMS
+Nr8e8/TT9ZW3l3Ru/1gRoIYua4CcStSbxPT8idE/QAagLSkxDyvdqeRzF320kBZUAqs9nszm0Zb5uRUA/V2KBQ==
+";
+        let sums = "ab12  rbit-linux-x86_64\n";
+        verify_signature(sums, signature, key).unwrap();
+        assert!(verify_signature("ab13  rbit-linux-x86_64\n", signature, key).is_err());
+        // signed, but not with the release key
+        assert!(verify_signature(sums, signature, PUBLIC_KEY).is_err());
+        assert!(verify_signature(sums, "not a signature", key).is_err());
+    }
+}
@@ -0,0 +1,23 @@
+//! `rbit shutdown`: stop the qBittorrent application itself through `app/shutdown`. Nothing
+//! can start it again from here, so rbit says which server it is about to stop and asks first.
+
+use crate::api::Session;
+use crate::confirm::confirm;
+use crate::i18n::tr;
+
+pub async fn shutdown(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    if !session.dry_run {
+        let version = session.get_text("app/version").await?;
+        let question = tr!("shutdown-confirm", version = version.trim(), host = session.host.as_str());
+        if !confirm(&question, &tr!("shutdown-what"))? {
+            println!("{}", tr!("nothing-done"));
+            return Ok(());
+        }
+    }
+    session.post_form("app/shutdown", &[]).await?;
+    if !session.dry_run {
+        println!("{}", tr!("shutdown-done", host = session.host.as_str()));
+    }
+    Ok(())
+}
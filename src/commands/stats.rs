@@ -0,0 +1,111 @@
+//! `rbit stats`: global transfer statistics and session state of the server, and
+//! `rbit stats record`: append a snapshot to the stats history, and `rbit stats --since`:
+//! totals and speeds over time from that history.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::api::{MainData, Session, TransferInfo};
+use crate::cache;
+use crate::format::{date_utc, duration_human, rate_human, size_human, Rate};
+use crate::history;
+use crate::i18n::print_fields;
+use crate::i18n::tr;
+
+fn opt<T: ToString>(v: Option<T>) -> String {
+    v.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string())
+}
+
+/// Print `transfer/info` together with the `server_state` part of `sync/maindata`.
+pub async fn stats(session: &Session) -> anyhow::Result<()> {
+    session.login().await?;
+    let (transfer, maindata, save_path) = tokio::try_join!(
+        session.get_json::<TransferInfo>("transfer/info"),
+        session.get_json::<MainData>("sync/maindata"),
+        session.get_text("app/defaultSavePath"),
+    )?;
+    let state = maindata.server_state.unwrap_or_default();
+    let alt = match state.use_alt_speed_limits {
+        Some(true) => tr!("on"),
+        Some(false) => tr!("off"),
+        None => "-".to_string(),
+    };
+    let limited = |rate: u64, limit: i64| tr!("stats-limited", rate = rate_human(rate), limit = Rate::from_api(limit).to_string());
+    let both = |down: String, up: String| tr!("stats-down-up", down = down, up = up);
+
+    print_fields(&[
+        (tr!("stats-connection"), transfer.connection_status.clone()),
+        (tr!("stats-dht-nodes"), transfer.dht_nodes.to_string()),
+        (tr!("stats-peers"), opt(state.total_peer_connections)),
+        (tr!("stats-download"), limited(transfer.dl_info_speed, transfer.dl_rate_limit)),
+        (tr!("stats-upload"), limited(transfer.up_info_speed, transfer.up_rate_limit)),
+        (tr!("stats-alt-limits"), alt),
+        (tr!("stats-session-data"), both(size_human(transfer.dl_info_data), size_human(transfer.up_info_data))),
+        (tr!("stats-all-time-data"), both(opt(state.alltime_dl.map(size_human)), opt(state.alltime_ul.map(size_human)))),
+        (tr!("stats-global-ratio"), opt(state.global_ratio)),
+        (tr!("stats-free-space"), tr!("stats-free-on", size = opt(state.free_space_on_disk.map(size_human)), path = save_path.trim())),
+    ]);
+    Ok(())
+}
+
+/// Take one snapshot and append it to `file`, or to the server's default history file.
+/// Meant to be run from cron.
+pub async fn record(session: &Session, file: Option<&Path>) -> anyhow::Result<()> {
+    let path = match file {
+        Some(path) => path.to_path_buf(),
+        None => history::history_path(&session.host).ok_or_else(|| anyhow::anyhow!("no data directory available; pass --file"))?,
+    };
+    session.login().await?;
+    let snapshot = history::snapshot(session).await?;
+    if session.dry_run {
+        println!("[dry-run] would append a snapshot of {} torrents to {}", snapshot.torrents.len(), path.display());
+        return Ok(());
+    }
+    history::append(&path, &snapshot)?;
+    println!("{}", tr!("stats-recorded", count = snapshot.torrents.len(), time = date_utc(snapshot.time), path = path.display().to_string()));
+    Ok(())
+}
+
+/// Transfer totals, average and peak speeds over the last `since`, from the stats history.
+pub fn over_time(host: &str, since: Duration) -> anyhow::Result<()> {
+    let path = history::history_path(host).ok_or_else(|| anyhow::anyhow!("no data directory available"))?;
+    let snapshots = if path.exists() { history::read(&path)? } else { Vec::new() };
+    let from = cache::now().saturating_sub(since.as_secs());
+    let window = history::since(&snapshots, from);
+    let (Some(first), Some(last)) = (window.first(), window.last()) else {
+        anyhow::bail!("no snapshots since {}; they are recorded by `rbit stats record`, `rbit list` and `rbit daemon`", date_utc(from));
+    };
+    let elapsed = last.time - first.time;
+    if elapsed == 0 {
+        anyhow::bail!("only one snapshot since {}; averages need at least two", date_utc(from));
+    }
+    let down = history::growth(window.iter().map(|s| s.session_dl));
+    let up = history::growth(window.iter().map(|s| s.session_ul));
+    let peak_down = window.iter().max_by_key(|s| s.dl_speed).expect("a snapshot");
+    let peak_up = window.iter().max_by_key(|s| s.up_speed).expect("a snapshot");
+
+    println!("{}", tr!("stats-since", time = date_utc(first.time), elapsed = duration_human(Duration::from_secs(elapsed)), count = window.len()));
+    let mut fields = vec![
+        (tr!("stats-downloaded"), tr!("stats-average", size = size_human(down), rate = rate_human(down / elapsed))),
+        (tr!("stats-uploaded"), tr!("stats-average", size = size_human(up), rate = rate_human(up / elapsed))),
+        (tr!("stats-peak-download"), tr!("stats-peak", rate = rate_human(peak_down.dl_speed), time = date_utc(peak_down.time))),
+        (tr!("stats-peak-upload"), tr!("stats-peak", rate = rate_human(peak_up.up_speed), time = date_utc(peak_up.time))),
+    ];
+
+    // per torrent, the sum over the window of what each one uploaded
+    let mut uploaded: BTreeMap<&str, (&str, Vec<u64>)> = BTreeMap::new();
+    for snapshot in window {
+        for t in &snapshot.torrents {
+            uploaded.entry(t.hash.as_str()).or_insert((t.name.as_str(), Vec::new())).1.push(t.uploaded);
+        }
+    }
+    let mut top: Vec<(u64, &str)> = uploaded.into_values().map(|(name, readings)| (history::growth(readings), name)).filter(|(up, _)| *up > 0).collect();
+    top.sort_by_key(|&(up, _)| std::cmp::Reverse(up));
+    for (i, (up, name)) in top.iter().take(3).enumerate() {
+        let label = if i == 0 { tr!("stats-most-uploaded") } else { String::new() };
+        fields.push((label, format!("{} ({})", name, size_human(*up))));
+    }
+    print_fields(&fields);
+    Ok(())
+}
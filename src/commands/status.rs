@@ -0,0 +1,129 @@
+use futures::future::join_all;
+use tabled::Tabled;
+
+use crate::api::{MainData, Session, TransferInfo};
+use crate::error::RbitError;
+use crate::format::{rate_human, size_human, truncate};
+use crate::i18n::tr;
+use crate::porcelain::{self, field, opt, print_record};
+use crate::theme;
+
+/// One server's answers to the health probe.
+struct Health {
+    version: String,
+    active: usize,
+    dl: u64,
+    up: u64,
+    free_space: Option<u64>,
+    connection: String,
+}
+
+#[derive(Tabled)]
+struct StatusRow {
+    server: String,
+    host: String,
+    reachable: String,
+    version: String,
+    active: String,
+    dl: String,
+    up: String,
+    free: String,
+}
+
+async fn probe(session: &Session) -> anyhow::Result<Health> {
+    session.login().await?;
+    let (version, active, transfer, maindata) = tokio::try_join!(
+        session.get_text("app/version"),
+        session.torrents("active"),
+        session.get_json::<TransferInfo>("transfer/info"),
+        session.get_json::<MainData>("sync/maindata"),
+    )?;
+    Ok(Health {
+        version: version.trim().to_string(),
+        active: active.len(),
+        dl: transfer.dl_info_speed,
+        up: transfer.up_info_speed,
+        free_space: maindata.server_state.and_then(|s| s.free_space_on_disk),
+        connection: transfer.connection_status,
+    })
+}
+
+fn row(name: &str, session: &Session, health: anyhow::Result<Health>) -> StatusRow {
+    match health {
+        Ok(h) => StatusRow {
+            server: name.to_string(),
+            host: session.host.clone(),
+            reachable: tr!("status-yes", connection = h.connection.as_str()),
+            version: h.version,
+            active: h.active.to_string(),
+            dl: rate_human(h.dl),
+            up: rate_human(h.up),
+            free: h.free_space.map(size_human).unwrap_or_else(|| "-".to_string()),
+        },
+        Err(e) => StatusRow {
+            server: name.to_string(),
+            host: session.host.clone(),
+            reachable: tr!("status-no", reason = truncate(&e.to_string(), 50)),
+            version: "-".to_string(),
+            active: "-".to_string(),
+            dl: "-".to_string(),
+            up: "-".to_string(),
+            free: "-".to_string(),
+        },
+    }
+}
+
+/// `--porcelain=v1`: profile, host, `yes` or `no`, connection status, version, active torrents,
+/// download and upload speed, free space in bytes, the error when not reachable.
+fn porcelain_record(name: &str, session: &Session, health: &anyhow::Result<Health>) -> Vec<String> {
+    match health {
+        Ok(h) => vec![
+            field(name),
+            field(&session.host),
+            "yes".to_string(),
+            field(&h.connection),
+            field(&h.version),
+            h.active.to_string(),
+            h.dl.to_string(),
+            h.up.to_string(),
+            opt(h.free_space),
+            "-".to_string(),
+        ],
+        Err(e) => {
+            let mut record = vec![field(name), field(&session.host), "no".to_string()];
+            record.extend(std::iter::repeat_n("-".to_string(), 6));
+            record.push(field(&format!("{:#}", e)));
+            record
+        }
+    }
+}
+
+/// Probe every `(profile name, session)` pair concurrently and print one summary row per server.
+/// Fails only if none of the servers could be reached.
+pub async fn status(servers: &[(String, Session)], porcelain: Option<porcelain::Version>) -> anyhow::Result<()> {
+    let results = join_all(servers.iter().map(|(_, session)| probe(session))).await;
+
+    let reachable = results.iter().filter(|r| r.is_ok()).count();
+    if let Some(porcelain::Version::V1) = porcelain {
+        for ((name, session), health) in servers.iter().zip(&results) {
+            print_record(&porcelain_record(name, session, health));
+        }
+        if reachable == 0 {
+            return Err(RbitError::Connection(tr!("status-unreachable")).into());
+        }
+        return Ok(());
+    }
+    let rows: Vec<StatusRow> = servers
+        .iter()
+        .zip(results)
+        .map(|((name, session), health)| row(name, session, health))
+        .collect();
+
+    let table = theme::table(rows);
+    println!("{}", table);
+
+    if reachable == 0 {
+        return Err(RbitError::Connection(tr!("status-unreachable")).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,203 @@
+//! `rbit stream <id>`: play a file of a torrent while it downloads. The torrent is switched to
+//! sequential download with the first and last pieces of its files first, the file gets the
+//! highest priority, and once the start of the file and its last piece (where many containers
+//! keep their index) are there, the media player is started on it.
+//!
+//! The player reads the file where the server keeps it, so this works when qBittorrent runs on
+//! this machine, with a `path_map` in the server's profile, or with `--data-root` naming the
+//! folder where the server's save path is mounted here.
+
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::{FileInfo, Session};
+use crate::commands::manage::{short_id, stop_start_api};
+use crate::error::RbitError;
+use crate::format::{duration_human, parse_size, size_human};
+use crate::i18n::tr;
+
+/// The highest of qBittorrent's file priorities
+const MAXIMAL_PRIORITY: i64 = 7;
+const DEFAULT_PLAYER: &str = "mpv";
+const DEFAULT_BUFFER: u64 = 20 << 20;
+/// The state of a downloaded piece in `torrents/pieceStates`
+const HAVE: u8 = 2;
+
+/// `[stream]`: how `rbit stream` plays files
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StreamConfig {
+    /// Media player command, given the file as its last argument, e.g. `vlc --fullscreen`
+    /// (default: mpv)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub player: Option<String>,
+    /// How much of the start of the file to have before playing, e.g. `50MiB` (default 20 MiB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer: Option<String>,
+}
+
+impl StreamConfig {
+    pub fn is_empty(&self) -> bool {
+        self.player.is_none() && self.buffer.is_none()
+    }
+}
+
+/// The file to play: the one asked for, or else the largest, which is the video for most
+/// torrents.
+fn choose(files: &[FileInfo], index: Option<usize>) -> Result<usize, RbitError> {
+    match index {
+        Some(i) if i < files.len() => Ok(i),
+        Some(i) => Err(RbitError::NotFound(format!("there is no file {}: the torrent has {} files, numbered from 0", i, files.len()))),
+        None => Ok(files.iter().enumerate().max_by_key(|(_, f)| f.size).map_or(0, |(i, _)| i)),
+    }
+}
+
+/// Where the file `name` (as the server lists it, under the save path) is on this machine.
+fn local_path(save_path: &str, name: &str, data_root: Option<&Path>) -> PathBuf {
+    let mut path = data_root.map_or_else(|| PathBuf::from(save_path), Path::to_path_buf);
+    path.extend(name.split(['/', '\\']).filter(|part| !part.is_empty()));
+    path
+}
+
+/// The pieces to have before playing: the file's first `buffer` bytes and its last piece.
+fn needed_pieces([first, last]: [usize; 2], piece_size: u64, buffer: u64) -> Vec<usize> {
+    let count = buffer.div_ceil(piece_size.max(1)).max(1) as usize;
+    let mut pieces: Vec<usize> = (first..=last).take(count).collect();
+    if !pieces.contains(&last) {
+        pieces.push(last);
+    }
+    pieces
+}
+
+/// Poll until the pieces needed to start playing file `index` are downloaded.
+async fn wait_for_buffer(session: &Session, hash: &str, index: usize, buffer: u64) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let terminal = std::io::stderr().is_terminal();
+    loop {
+        let t = session.find_torrent(hash).await?.ok_or_else(|| RbitError::NotFound(format!("{} was removed from the server", hash)))?;
+        if matches!(t.state.as_str(), "error" | "missingFiles") {
+            anyhow::bail!("{} is in state {}; see `rbit list --errors-only`", t.name, t.state);
+        }
+        let files = session.files(hash).await?;
+        let file = files.get(index).ok_or_else(|| anyhow::anyhow!("{} no longer has file {}", t.name, index))?;
+        let (have, needed) = match file.piece_range {
+            Some(range) => {
+                let states: Vec<u8> = session.get_json(&format!("torrents/pieceStates?hash={}", hash)).await?;
+                let piece_size = t.total_size.or(t.size).unwrap_or(0).div_ceil(states.len().max(1) as u64);
+                let needed = needed_pieces(range, piece_size, buffer);
+                (needed.iter().filter(|&&i| states.get(i) == Some(&HAVE)).count(), needed.len())
+            }
+            // servers without piece ranges only tell how much of the file is there
+            None => ((file.progress * file.size as f64) as usize, buffer.min(file.size) as usize),
+        };
+        if have >= needed {
+            if terminal {
+                eprintln!();
+            }
+            return Ok(());
+        }
+        let line = tr!("stream-buffering", percent = have * 100 / needed.max(1), elapsed = duration_human(started.elapsed()));
+        if terminal {
+            eprint!("\r{}", line);
+        } else if started.elapsed() < Duration::from_secs(1) {
+            eprintln!("{}", line);
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+pub async fn stream(
+    session: &Session,
+    config: &StreamConfig,
+    id: &str,
+    file: Option<usize>,
+    player: Option<&str>,
+    data_root: Option<&Path>,
+    buffer: Option<u64>,
+) -> anyhow::Result<()> {
+    let buffer = match (buffer, config.buffer.as_deref()) {
+        (Some(bytes), _) => bytes,
+        (None, Some(text)) => parse_size(text).map_err(|e| RbitError::InvalidInput(format!("[stream] buffer: {:#}", e)))?,
+        (None, None) => DEFAULT_BUFFER,
+    };
+    let player = player.or(config.player.as_deref()).unwrap_or(DEFAULT_PLAYER);
+    let mut words = player.split_whitespace();
+    let program = words.next().ok_or_else(|| RbitError::InvalidInput("the player command is empty".to_string()))?;
+
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let files = session.files(&t.hash).await?;
+    if files.is_empty() {
+        anyhow::bail!("{} ({}) has no metadata yet, so its files are not known; try again in a moment", t.name, short_id(&t));
+    }
+    let index = choose(&files, file)?;
+    let chosen = &files[index];
+    if file.is_none() && files.len() > 1 {
+        println!("{}", tr!("stream-largest", count = files.len(), name = chosen.name.as_str(), size = size_human(chosen.size)));
+    }
+
+    if t.seq_dl != Some(true) {
+        session.post_form("torrents/toggleSequentialDownload", &[("hashes", &t.hash)]).await?;
+    }
+    if t.f_l_piece_prio != Some(true) {
+        session.post_form("torrents/toggleFirstLastPiecePrio", &[("hashes", &t.hash)]).await?;
+    }
+    if chosen.priority < MAXIMAL_PRIORITY {
+        session.post_form("torrents/filePrio", &[("hash", &t.hash), ("id", &index.to_string()), ("priority", &MAXIMAL_PRIORITY.to_string())]).await?;
+    }
+    if t.state.starts_with("paused") || t.state.starts_with("stopped") {
+        let endpoint = if stop_start_api(session).await? { "torrents/start" } else { "torrents/resume" };
+        session.post_form(endpoint, &[("hashes", &t.hash)]).await?;
+    } else if t.state.starts_with("queued") {
+        eprintln!("{}", tr!("stream-queued", name = t.name.as_str(), id = short_id(&t)));
+    }
+
+    let save_path = session.path_map.show(t.save_path.as_deref().unwrap_or_default());
+    let path = local_path(&save_path, &chosen.name, data_root);
+    if session.dry_run {
+        println!("[dry-run] would wait for the first {} of {} and run: {} {}", size_human(buffer), chosen.name, player, path.display());
+        return Ok(());
+    }
+    if chosen.progress < 1.0 {
+        wait_for_buffer(session, &t.hash, index, buffer).await?;
+    }
+    if !path.exists() {
+        let hint = match data_root {
+            Some(root) => format!("check that {} is where the server's {} is mounted", root.display(), t.save_path.as_deref().unwrap_or_default()),
+            None => "give --data-root with the folder where the server's save path is mounted here".to_string(),
+        };
+        return Err(RbitError::NotFound(format!("{} is not on this machine; {}", path.display(), hint)).into());
+    }
+
+    println!("{}", tr!("stream-playing", path = path.display().to_string(), command = program));
+    let status = Command::new(program).args(words).arg(&path).status().map_err(|e| anyhow::anyhow!("could not start the player {}: {}", program, e))?;
+    if !status.success() {
+        anyhow::bail!("the player exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_and_pieces_to_wait_for() {
+        let file = |name: &str, size| FileInfo { name: name.to_string(), size, priority: 1, progress: 0.0, piece_range: None };
+        let files = [file("Show/sample.mkv", 10), file("Show/show.mkv", 900), file("Show/show.nfo", 1)];
+        assert_eq!(choose(&files, None).unwrap(), 1);
+        assert_eq!(choose(&files, Some(2)).unwrap(), 2);
+        assert!(choose(&files, Some(3)).is_err());
+
+        assert_eq!(local_path("/downloads", "Show/show.mkv", None), Path::new("/downloads/Show/show.mkv"));
+        assert_eq!(local_path("/downloads", "Show/show.mkv", Some(Path::new("/mnt/seedbox"))), Path::new("/mnt/seedbox/Show/show.mkv"));
+
+        // 20 MiB of 4 MiB pieces, then the last one
+        assert_eq!(needed_pieces([10, 100], 4 << 20, DEFAULT_BUFFER), [10, 11, 12, 13, 14, 100]);
+        assert_eq!(needed_pieces([3, 4], 4 << 20, DEFAULT_BUFFER), [3, 4]);
+        assert_eq!(needed_pieces([7, 7], 4 << 20, 0), [7]);
+    }
+}
@@ -0,0 +1,157 @@
+//! `rbit timeline <id>`: a torrent's progress, speeds and ratio over time as a terminal chart,
+//! from the stats history, to see when it actually downloaded.
+
+use std::time::Duration;
+
+use crate::api::Session;
+use crate::commands::history::hash_of;
+use crate::format::{date_utc, rate_human, size_human};
+use crate::history::{self, TorrentSample};
+use crate::i18n::tr;
+use crate::theme;
+
+/// Bars from low to high; a column without samples is left blank and one at zero gets a dot.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Width of the labels left of the chart.
+const LABEL: usize = 10;
+
+/// One column of the chart: the samples taken in its stretch of time.
+#[derive(Default, Clone, Debug, PartialEq)]
+struct Column {
+    /// The last reading in the column
+    progress: Option<f64>,
+    ratio: Option<f64>,
+    /// Average speeds over the intervals ending in the column, in bytes/s
+    down: Option<u64>,
+    up: Option<u64>,
+}
+
+/// Spread `samples` (time and reading, oldest first) over `width` columns, evenly in time.
+fn columns(samples: &[(u64, &TorrentSample)], width: usize) -> Vec<Column> {
+    let width = width.max(1);
+    let (Some(&(first, _)), Some(&(last, _))) = (samples.first(), samples.last()) else {
+        return Vec::new();
+    };
+    let span = last - first;
+    let column_of = |time: u64| if span == 0 { 0 } else { ((time - first) as u128 * (width as u128 - 1) / span as u128) as usize };
+    let mut columns = vec![Column::default(); if span == 0 { 1 } else { width }];
+    // bytes and seconds of the intervals ending in each column
+    let mut moved = vec![(0u64, 0u64, 0u64); columns.len()];
+    for (i, &(time, t)) in samples.iter().enumerate() {
+        let c = column_of(time);
+        columns[c].progress = Some(t.progress);
+        columns[c].ratio = Some(t.ratio);
+        if let Some(&(before_time, before)) = i.checked_sub(1).map(|j| &samples[j]) {
+            moved[c].0 += history::growth([before.downloaded, t.downloaded]);
+            moved[c].1 += history::growth([before.uploaded, t.uploaded]);
+            moved[c].2 += time - before_time;
+        }
+    }
+    for (column, (down, up, elapsed)) in columns.iter_mut().zip(moved) {
+        column.down = down.checked_div(elapsed);
+        column.up = up.checked_div(elapsed);
+    }
+    columns
+}
+
+/// A row of bars for `values` scaled to `max`.
+fn spark(values: impl Iterator<Item = Option<f64>>, max: f64) -> String {
+    values
+        .map(|value| match value {
+            None => ' ',
+            Some(v) if v <= 0.0 || max <= 0.0 => '·',
+            Some(v) => BARS[((v / max * BARS.len() as f64).ceil() as usize).clamp(1, BARS.len()) - 1],
+        })
+        .collect()
+}
+
+/// When the torrent downloaded: from the sample before the first that shows more downloaded to
+/// the last that does.
+fn downloading_between(samples: &[(u64, &TorrentSample)]) -> Option<(u64, u64)> {
+    let grew: Vec<usize> = (1..samples.len()).filter(|&i| samples[i].1.downloaded > samples[i - 1].1.downloaded).collect();
+    Some((samples[*grew.first()? - 1].0, samples[*grew.last()?].0))
+}
+
+pub async fn timeline(session: &Session, id: &str, since: Option<Duration>, width: usize) -> anyhow::Result<()> {
+    let path = history::history_path(&session.host).ok_or_else(|| anyhow::anyhow!("no data directory available"))?;
+    let snapshots = if path.exists() { history::read(&path)? } else { Vec::new() };
+    session.login().await?;
+    let hash = hash_of(session, id, &snapshots).await?;
+    let from = since.map(|d| crate::cache::now().saturating_sub(d.as_secs())).unwrap_or(0);
+    let samples: Vec<(u64, &TorrentSample)> = history::since(&snapshots, from)
+        .iter()
+        .filter_map(|s| s.torrents.iter().find(|t| t.hash.eq_ignore_ascii_case(&hash)).map(|t| (s.time, t)))
+        .collect();
+    let (Some(&(first_time, first)), Some(&(last_time, last))) = (samples.first(), samples.last()) else {
+        println!("{}", tr!("history-none", id = id));
+        return Ok(());
+    };
+
+    let chart = columns(&samples, width);
+    let peak_down = chart.iter().filter_map(|c| c.down).max().unwrap_or(0);
+    let peak_up = chart.iter().filter_map(|c| c.up).max().unwrap_or(0);
+    let peak_ratio = chart.iter().filter_map(|c| c.ratio).fold(0.0, f64::max);
+    let theme = theme::current();
+    let row = |label: &str, key: &str, bars: String, note: String| println!("{:<LABEL$}{}  {}", label, theme.paint(key, &bars), note);
+
+    println!("{}", tr!("timeline-title", name = last.name.as_str(), id = &hash[..8.min(hash.len())], count = samples.len()));
+    row("progress", "downloading", spark(chart.iter().map(|c| c.progress), 1.0), format!("{:.1}% -> {:.1}%", first.progress * 100.0, last.progress * 100.0));
+    row("down", "downloading", spark(chart.iter().map(|c| c.down.map(|v| v as f64)), peak_down as f64), format!("peak {}", rate_human(peak_down)));
+    row("up", "seeding", spark(chart.iter().map(|c| c.up.map(|v| v as f64)), peak_up as f64), format!("peak {}", rate_human(peak_up)));
+    row("ratio", "seeding", spark(chart.iter().map(|c| c.ratio), peak_ratio), format!("{:.2} -> {:.2}", first.ratio, last.ratio));
+    let (start, end) = (date_utc(first_time), date_utc(last_time));
+    if chart.len() >= start.len() + end.len() + 2 {
+        println!("{:<LABEL$}{}{:>w$}", "", start, end, w = chart.len() - start.len());
+    } else {
+        println!("{:<LABEL$}{}", "", tr!("timeline-from-to", from = start, to = end));
+    }
+
+    match downloading_between(&samples) {
+        Some((from, to)) => {
+            let downloaded = history::growth(samples.iter().map(|(_, t)| t.downloaded));
+            println!("{}", tr!("timeline-downloaded", size = size_human(downloaded), from = date_utc(from), to = date_utc(to)));
+        }
+        None => println!("{}", tr!("timeline-nothing-downloaded")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(downloaded: u64, uploaded: u64, progress: f64) -> TorrentSample {
+        TorrentSample {
+            hash: "aaaa".to_string(),
+            name: "t".to_string(),
+            state: "downloading".to_string(),
+            category: String::new(),
+            progress,
+            size: 1000,
+            downloaded,
+            uploaded,
+            ratio: uploaded as f64 / 1000.0,
+        }
+    }
+
+    #[test]
+    fn samples_spread_over_columns() {
+        let readings = [sample(0, 0, 0.0), sample(600, 0, 0.6), sample(1000, 0, 1.0), sample(1000, 500, 1.0)];
+        let samples: Vec<(u64, &TorrentSample)> = [0, 10, 20, 40].into_iter().zip(readings.iter()).collect();
+        let chart = columns(&samples, 5);
+        assert_eq!(chart.len(), 5);
+        // 0, 10 and 20 land in columns 0, 1 and 2, and 40 in the last; column 3 has nothing
+        assert_eq!(chart.iter().map(|c| c.down).collect::<Vec<_>>(), [None, Some(60), Some(40), None, Some(0)]);
+        assert_eq!(chart[4].up, Some(25));
+        assert_eq!(chart[3], Column::default());
+        assert_eq!(spark(chart.iter().map(|c| c.down.map(|v| v as f64)), 60.0), " █▆ ·");
+        assert_eq!(spark(chart.iter().map(|c| c.progress), 1.0), "·▅█ █");
+        assert_eq!(downloading_between(&samples), Some((0, 20)));
+
+        let one = [(7, &readings[0])];
+        assert_eq!(columns(&one, 5).len(), 1);
+        assert_eq!(downloading_between(&one), None);
+        assert!(columns(&[], 5).is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+//! `rbit trackers`: show and edit a torrent's tracker list.
+
+use tabled::Tabled;
+
+use crate::api::{Session, TrackerInfo};
+use crate::commands::manage::short_id;
+use crate::format::truncate;
+use crate::i18n::tr;
+use crate::theme;
+
+#[derive(Tabled)]
+struct TrackerRow {
+    tier: String,
+    url: String,
+    status: String,
+    peers: String,
+    seeds: String,
+    leeches: String,
+    message: String,
+}
+
+fn status_name(status: i64) -> &'static str {
+    match status {
+        0 => "disabled",
+        1 => "not contacted",
+        2 => "working",
+        3 => "updating",
+        4 => "not working",
+        _ => "unknown",
+    }
+}
+
+/// qBittorrent reports -1 for counts it does not know yet.
+fn count(n: i64) -> String {
+    if n < 0 { "-".to_string() } else { n.to_string() }
+}
+
+fn print_trackers(trackers: &[TrackerInfo]) {
+    let rows: Vec<TrackerRow> = trackers
+        .iter()
+        .map(|t| TrackerRow {
+            tier: if t.tier < 0 { "-".to_string() } else { t.tier.to_string() },
+            url: t.url.clone(),
+            status: status_name(t.status).to_string(),
+            peers: count(t.num_peers),
+            seeds: count(t.num_seeds),
+            leeches: count(t.num_leeches),
+            message: truncate(&t.msg, 40),
+        })
+        .collect();
+    println!("{}", theme::table(rows));
+}
+
+/// What `rbit trackers` should change before showing the list.
+#[derive(Debug, Default)]
+pub struct TrackerEdits {
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+    /// `(old, new)` announce URL pairs
+    pub replace: Vec<(String, String)>,
+}
+
+impl TrackerEdits {
+    fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty() && self.replace.is_empty()
+    }
+}
+
+/// Apply `edits` to a torrent's trackers (`editTracker`, `removeTrackers`, `addTrackers`, in that
+/// order) and print the resulting list from `torrents/trackers`.
+pub async fn trackers(session: &Session, id: &str, edits: &TrackerEdits) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+
+    for (old, new) in &edits.replace {
+        session
+            .post_form("torrents/editTracker", &[("hash", &t.hash), ("origUrl", old), ("newUrl", new)])
+            .await
+            .map_err(|e| anyhow::anyhow!("could not replace {} in {}: {}", old, t.name, e))?;
+        println!("{}", tr!("trackers-replaced", old = old.as_str(), new = new.as_str(), name = t.name.as_str(), id = short_id(&t)));
+    }
+    if !edits.remove.is_empty() {
+        session
+            .post_form("torrents/removeTrackers", &[("hash", &t.hash), ("urls", &edits.remove.join("|"))])
+            .await
+            .map_err(|e| anyhow::anyhow!("could not remove trackers from {}: {}", t.name, e))?;
+        println!("{}", tr!("trackers-removed", count = edits.remove.len(), name = t.name.as_str(), id = short_id(&t)));
+    }
+    if !edits.add.is_empty() {
+        session
+            .post_form("torrents/addTrackers", &[("hash", &t.hash), ("urls", &edits.add.join("\n"))])
+            .await?;
+        println!("{}", tr!("trackers-added", count = edits.add.len(), name = t.name.as_str(), id = short_id(&t)));
+    }
+
+    if session.dry_run && !edits.is_empty() {
+        return Ok(());
+    }
+    print_trackers(&session.trackers(&t.hash).await?);
+    Ok(())
+}
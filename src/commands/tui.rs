@@ -0,0 +1,896 @@
+//! `rbit tui`: a full-screen torrent list that keeps itself up to date. Torrents are marked
+//! with space, or a range of them with `v`, and then paused, resumed, deleted or moved into a
+//! category together, after a confirmation that shows what the action applies to. `/` filters
+//! the list as you type, by name and with `state:` and `category:` words, the way the
+//! `--name`, `--state` and `--category` selectors do. `a` adds a torrent, starting from the
+//! magnet link on the clipboard when there is one.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, IsTerminal, Stdout};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste, Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use futures::StreamExt;
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+use ratatui::{Frame, Terminal};
+
+use crate::api::{AddOptions, Session, TorrentInfo};
+use crate::cache;
+use crate::commands::manage::stop_start_api;
+use crate::error::RbitError;
+use crate::format::{rate_human, size_human};
+use crate::i18n::tr;
+use crate::interrupt::{self, Interrupt};
+use crate::select::Selector;
+use crate::theme::{self, state_group, state_label};
+use crate::torrent::magnet_info_hash;
+
+/// Names the confirmation lists before summing up the rest
+const LISTED: usize = 8;
+
+/// How long a clipboard tool gets to answer
+const PASTE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What can be done to the selected torrents.
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    Pause,
+    Resume,
+    Delete { files: bool },
+    /// An empty name takes the torrents out of their category
+    SetCategory(String),
+}
+
+/// An action the user confirmed, for the torrents it was confirmed for.
+#[derive(Debug, PartialEq)]
+struct Request {
+    action: Action,
+    hashes: Vec<String>,
+}
+
+/// What a key press leaves for the event loop to do, since that needs the server.
+#[derive(Debug, PartialEq)]
+enum Effect {
+    /// Carry out a confirmed action
+    Apply(Request),
+    /// Read the clipboard and open the add dialog
+    OpenAdd,
+    /// Add the torrent the dialog was filled in for
+    Add(AddForm),
+}
+
+/// The add dialog's fields.
+#[derive(Debug, Clone, PartialEq)]
+struct AddForm {
+    /// A magnet link, a torrent URL or a .torrent file on this machine
+    link: String,
+    /// Empty for the server's default
+    destination: String,
+    category: String,
+    paused: bool,
+    /// The field being edited: link, destination, category or paused
+    field: usize,
+}
+
+impl AddForm {
+    const FIELDS: usize = 4;
+
+    fn text(&mut self) -> Option<&mut String> {
+        match self.field {
+            0 => Some(&mut self.link),
+            1 => Some(&mut self.destination),
+            2 => Some(&mut self.category),
+            _ => None,
+        }
+    }
+
+    /// What to add the torrent with: `base` (the config's rules), with the dialog's choices.
+    fn options(&self, base: &AddOptions) -> AddOptions {
+        let destination = self.destination.trim();
+        let category = self.category.trim();
+        AddOptions {
+            save_path: (!destination.is_empty()).then(|| PathBuf::from(destination)),
+            // as with `rbit add`, rules may still choose where the torrent goes unless a place
+            // other than `default_save_path` is given
+            fixed_save_path: !destination.is_empty() && base.save_path.as_deref() != Some(Path::new(destination)),
+            category: (!category.is_empty()).then(|| category.to_string()),
+            paused: self.paused,
+            rules: base.rules.clone(),
+            ..AddOptions::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Mode {
+    List,
+    /// Typing in the filter bar
+    Filter,
+    /// Typing the name of a category
+    Category(String),
+    /// Asking whether to go ahead with an action on these torrents
+    Confirm(Action, Vec<TorrentInfo>),
+    /// Filling in the add dialog
+    Add(AddForm),
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Info(String),
+    Error(String),
+}
+
+/// Everything on the screen, apart from talking to the server.
+struct App {
+    host: String,
+    /// Where the add dialog says torrents go until it is changed: `default_save_path`
+    destination: String,
+    /// Every torrent on the server
+    all: Vec<TorrentInfo>,
+    /// The ones the filter lets through, as listed
+    torrents: Vec<TorrentInfo>,
+    /// What the filter bar holds, and the selectors it stands for
+    filter: String,
+    selector: Selector,
+    cursor: usize,
+    /// Hashes of the marked torrents, which stay marked across refreshes
+    marked: BTreeSet<String>,
+    /// Where a `v` range starts; it ends at the cursor
+    anchor: Option<usize>,
+    mode: Mode,
+    status: Option<Status>,
+    table: TableState,
+    /// Rows of torrents the last frame had room for, for page up and down
+    page: usize,
+    quit: bool,
+}
+
+impl App {
+    fn new(host: &str, destination: &str) -> App {
+        App {
+            host: host.to_string(),
+            destination: destination.to_string(),
+            all: Vec::new(),
+            torrents: Vec::new(),
+            filter: String::new(),
+            selector: Selector::default(),
+            cursor: 0,
+            marked: BTreeSet::new(),
+            anchor: None,
+            mode: Mode::List,
+            status: None,
+            table: TableState::default(),
+            page: 10,
+            quit: false,
+        }
+    }
+
+    /// Take in a fresh list from the server.
+    fn refresh(&mut self, mut torrents: Vec<TorrentInfo>) {
+        torrents.sort_by_cached_key(|t| (t.name.to_lowercase(), t.hash.clone()));
+        self.marked.retain(|hash| torrents.iter().any(|t| &t.hash == hash));
+        self.all = torrents;
+        self.show();
+    }
+
+    /// List the torrents the filter lets through, keeping the cursor on the torrent it was on.
+    /// Marked torrents the filter hides stay marked, but actions leave them alone.
+    fn show(&mut self) {
+        let now = cache::now();
+        let torrents: Vec<TorrentInfo> = self.all.iter().filter(|t| self.selector.matches(t, now)).cloned().collect();
+        let current = self.torrents.get(self.cursor).map(|t| t.hash.clone());
+        self.cursor = current.and_then(|hash| torrents.iter().position(|t| t.hash == hash)).unwrap_or(self.cursor);
+        self.cursor = self.cursor.min(torrents.len().saturating_sub(1));
+        self.anchor = self.anchor.map(|a| a.min(torrents.len().saturating_sub(1)));
+        self.torrents = torrents;
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        self.selector = filter_selector(&filter);
+        self.filter = filter;
+        self.show();
+    }
+
+    fn in_range(&self, index: usize) -> bool {
+        self.anchor.is_some_and(|a| (a.min(self.cursor)..=a.max(self.cursor)).contains(&index))
+    }
+
+    fn is_selected(&self, index: usize) -> bool {
+        self.in_range(index) || self.marked.contains(&self.torrents[index].hash)
+    }
+
+    /// The torrents an action applies to: the marked ones and the `v` range, or else the one
+    /// under the cursor.
+    fn selection(&self) -> Vec<TorrentInfo> {
+        let chosen: Vec<TorrentInfo> = (0..self.torrents.len()).filter(|&i| self.is_selected(i)).map(|i| self.torrents[i].clone()).collect();
+        match chosen.is_empty() {
+            true => self.torrents.get(self.cursor).cloned().into_iter().collect(),
+            false => chosen,
+        }
+    }
+
+    fn move_to(&mut self, index: usize) {
+        self.cursor = index.min(self.torrents.len().saturating_sub(1));
+    }
+
+    /// Handle a key press; returns what is left to do, such as an action once it is confirmed.
+    fn key(&mut self, key: KeyEvent) -> Option<Effect> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return None;
+        }
+        match std::mem::replace(&mut self.mode, Mode::List) {
+            Mode::List => self.list_key(key.code),
+            Mode::Filter => self.filter_key(key.code),
+            Mode::Category(input) => self.category_key(input, key.code),
+            Mode::Confirm(action, torrents) => self.confirm_key(action, torrents, key.code),
+            Mode::Add(form) => self.add_key(form, key.code),
+        }
+    }
+
+    /// Text pasted into the terminal goes into the field being typed in.
+    fn paste(&mut self, text: &str) {
+        let text = text.trim();
+        match &mut self.mode {
+            Mode::Filter => self.set_filter(format!("{}{}", self.filter, text)),
+            Mode::Category(input) => input.push_str(text),
+            Mode::Add(form) => {
+                if let Some(field) = form.text() {
+                    field.push_str(text);
+                }
+            }
+            Mode::List | Mode::Confirm(..) => {}
+        }
+    }
+
+    /// Open the add dialog, with the link from `clipboard` if it holds a magnet link.
+    fn open_add(&mut self, clipboard: Option<String>) {
+        let link = clipboard.map(|text| text.trim().to_string()).filter(|text| text.starts_with("magnet:")).unwrap_or_default();
+        self.mode = Mode::Add(AddForm { link, destination: self.destination.clone(), category: String::new(), paused: false, field: 0 });
+    }
+
+    fn list_key(&mut self, code: KeyCode) -> Option<Effect> {
+        match code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.move_to(self.cursor.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.move_to(self.cursor + 1),
+            KeyCode::PageUp => self.move_to(self.cursor.saturating_sub(self.page)),
+            KeyCode::PageDown => self.move_to(self.cursor + self.page),
+            KeyCode::Home | KeyCode::Char('g') => self.move_to(0),
+            KeyCode::End | KeyCode::Char('G') => self.move_to(usize::MAX),
+            KeyCode::Char(' ') if self.anchor.is_some() => {
+                let range: Vec<String> = (0..self.torrents.len()).filter(|&i| self.in_range(i)).map(|i| self.torrents[i].hash.clone()).collect();
+                self.marked.extend(range);
+                self.anchor = None;
+            }
+            KeyCode::Char(' ') => {
+                if let Some(t) = self.torrents.get(self.cursor) {
+                    if !self.marked.remove(&t.hash) {
+                        self.marked.insert(t.hash.clone());
+                    }
+                    self.move_to(self.cursor + 1);
+                }
+            }
+            KeyCode::Char('v') => self.anchor = if self.anchor.is_some() { None } else { Some(self.cursor) },
+            KeyCode::Char('*') if self.torrents.iter().all(|t| self.marked.contains(&t.hash)) => {
+                for t in &self.torrents {
+                    self.marked.remove(&t.hash);
+                }
+            }
+            KeyCode::Char('*') => self.marked.extend(self.torrents.iter().map(|t| t.hash.clone())),
+            KeyCode::Char('/') => self.mode = Mode::Filter,
+            KeyCode::Esc if !self.filter.is_empty() => self.set_filter(String::new()),
+            KeyCode::Esc => {
+                self.marked.clear();
+                self.anchor = None;
+                self.status = None;
+            }
+            KeyCode::Char('p') => self.ask(Action::Pause),
+            KeyCode::Char('r') => self.ask(Action::Resume),
+            KeyCode::Char('d') => self.ask(Action::Delete { files: false }),
+            KeyCode::Char('c') if !self.torrents.is_empty() => self.mode = Mode::Category(String::new()),
+            KeyCode::Char('a') => return Some(Effect::OpenAdd),
+            _ => {}
+        }
+        None
+    }
+
+    fn ask(&mut self, action: Action) {
+        let torrents = self.selection();
+        if !torrents.is_empty() {
+            self.mode = Mode::Confirm(action, torrents);
+        }
+    }
+
+    fn filter_key(&mut self, code: KeyCode) -> Option<Effect> {
+        match code {
+            KeyCode::Enter => {}
+            KeyCode::Esc => self.set_filter(String::new()),
+            KeyCode::Backspace => {
+                let mut filter = self.filter.clone();
+                filter.pop();
+                self.set_filter(filter);
+                self.mode = Mode::Filter;
+            }
+            KeyCode::Char(c) => {
+                self.set_filter(format!("{}{}", self.filter, c));
+                self.mode = Mode::Filter;
+            }
+            _ => self.mode = Mode::Filter,
+        }
+        None
+    }
+
+    fn category_key(&mut self, mut input: String, code: KeyCode) -> Option<Effect> {
+        match code {
+            KeyCode::Esc => {}
+            KeyCode::Enter => self.ask(Action::SetCategory(input.trim().to_string())),
+            KeyCode::Backspace => {
+                input.pop();
+                self.mode = Mode::Category(input);
+            }
+            KeyCode::Char(c) => {
+                input.push(c);
+                self.mode = Mode::Category(input);
+            }
+            _ => self.mode = Mode::Category(input),
+        }
+        None
+    }
+
+    fn confirm_key(&mut self, action: Action, torrents: Vec<TorrentInfo>, code: KeyCode) -> Option<Effect> {
+        match (code, action) {
+            (KeyCode::Char('y') | KeyCode::Enter, action) => {
+                self.marked.clear();
+                self.anchor = None;
+                return Some(Effect::Apply(Request { action, hashes: torrents.into_iter().map(|t| t.hash).collect() }));
+            }
+            (KeyCode::Char('f'), Action::Delete { files }) => self.mode = Mode::Confirm(Action::Delete { files: !files }, torrents),
+            (KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q'), _) => {}
+            (_, action) => self.mode = Mode::Confirm(action, torrents),
+        }
+        None
+    }
+
+    fn add_key(&mut self, mut form: AddForm, code: KeyCode) -> Option<Effect> {
+        match code {
+            KeyCode::Esc => return None,
+            KeyCode::Enter if !form.link.trim().is_empty() => {
+                // a torrent the server has is not added again, like `rbit add` does
+                let existing = magnet_info_hash(form.link.trim()).and_then(|hash| self.all.iter().find(|t| t.hash.eq_ignore_ascii_case(&hash)));
+                if let Some(t) = existing {
+                    self.status = Some(Status::Info(tr!("tui-duplicate", existing = t.name.as_str())));
+                    return None;
+                }
+                return Some(Effect::Add(form));
+            }
+            KeyCode::Tab | KeyCode::Down => form.field = (form.field + 1) % AddForm::FIELDS,
+            KeyCode::BackTab | KeyCode::Up => form.field = (form.field + AddForm::FIELDS - 1) % AddForm::FIELDS,
+            KeyCode::Backspace => {
+                form.text().map(String::pop);
+            }
+            KeyCode::Char(' ') if form.text().is_none() => form.paused = !form.paused,
+            KeyCode::Char(c) => {
+                if let Some(field) = form.text() {
+                    field.push(c);
+                }
+            }
+            _ => {}
+        }
+        self.mode = Mode::Add(form);
+        None
+    }
+}
+
+/// The selectors a filter like `ubuntu state:seeding category:linux` stands for. The words
+/// besides `state:` and `category:` are a name pattern, which matches anywhere in the name
+/// unless it has a `*` or `?` of its own; `category:` alone means no category.
+fn filter_selector(filter: &str) -> Selector {
+    let mut selector = Selector::default();
+    let mut words = Vec::new();
+    for word in filter.split_whitespace() {
+        if let Some(state) = word.strip_prefix("state:") {
+            selector.state = Some(state.to_string());
+        } else if let Some(category) = word.strip_prefix("category:") {
+            selector.category = Some(category.to_string());
+        } else {
+            words.push(word);
+        }
+    }
+    if !words.is_empty() {
+        let name = words.join(" ");
+        selector.name = Some(if name.contains(['*', '?']) { name } else { format!("*{}*", name) });
+    }
+    selector
+}
+
+/// The question the confirmation asks.
+fn question(action: &Action, count: usize) -> String {
+    match action {
+        Action::Pause => tr!("tui-confirm-pause", count = count),
+        Action::Resume => tr!("tui-confirm-resume", count = count),
+        Action::Delete { files: false } => tr!("prune-confirm", count = count),
+        Action::Delete { files: true } => tr!("prune-confirm-data", count = count),
+        Action::SetCategory(name) if name.is_empty() => tr!("tui-confirm-no-category", count = count),
+        Action::SetCategory(name) => tr!("tui-confirm-category", count = count, name = name.as_str()),
+    }
+}
+
+/// An SGR color from the theme as a terminal color.
+fn color(sgr: &str) -> Option<Color> {
+    if let Some(index) = sgr.strip_prefix("38;5;") {
+        return index.parse().ok().map(Color::Indexed);
+    }
+    match sgr.parse::<u8>().ok()? {
+        c @ 30..=37 => Some(Color::Indexed(c - 30)),
+        c @ 90..=97 => Some(Color::Indexed(c - 90 + 8)),
+        _ => None,
+    }
+}
+
+fn style_of(key: &str) -> Style {
+    match theme::current().sgr(key).and_then(color) {
+        Some(c) => Style::default().fg(c),
+        None => Style::default(),
+    }
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [top, list, bottom] = Layout::vertical([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let mut title = match app.filter.is_empty() {
+        true => tr!("tui-title", host = app.host.as_str(), count = app.torrents.len()),
+        false => tr!("tui-title-filtered", host = app.host.as_str(), count = app.torrents.len(), total = app.all.len()),
+    };
+    let selected = (0..app.torrents.len()).filter(|&i| app.is_selected(i)).count();
+    if selected > 0 {
+        title = format!("{}  {}", title, tr!("tui-marked", count = selected));
+    }
+    if !app.filter.is_empty() && !matches!(app.mode, Mode::Filter) {
+        title = format!("{}  /{}", title, app.filter);
+    }
+    frame.render_widget(Paragraph::new(title).style(style_of("accent").add_modifier(Modifier::BOLD)), top);
+
+    let rows = app.torrents.iter().enumerate().map(|(i, t)| {
+        let group = state_group(&t.state).unwrap_or("");
+        let mut style = style_of(group);
+        if app.is_selected(i) {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        let category = t.category.clone().unwrap_or_default();
+        Row::new(vec![
+            if app.is_selected(i) { "*".to_string() } else { String::new() },
+            t.name.clone(),
+            state_label(&t.state).to_string(),
+            format!("{:.0}%", t.progress.unwrap_or(0.0) * 100.0),
+            size_human(t.size.unwrap_or(0)),
+            rate_human(t.dlspeed.unwrap_or(0)),
+            rate_human(t.upspeed.unwrap_or(0)),
+            format!("{:.2}", t.ratio.unwrap_or(0.0)),
+            category,
+        ])
+        .style(style)
+    });
+    let widths = [
+        Constraint::Length(1),
+        Constraint::Fill(1),
+        Constraint::Length(20),
+        Constraint::Length(5),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(6),
+        Constraint::Length(14),
+    ];
+    let header = Row::new(["", "Name", "State", "Done", "Size", "Down", "Up", "Ratio", "Category"]).style(style_of("accent").add_modifier(Modifier::BOLD));
+    let table = Table::new(rows, widths).header(header).row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    app.page = list.height.saturating_sub(1).max(1) as usize;
+    app.table.select((!app.torrents.is_empty()).then_some(app.cursor));
+    frame.render_stateful_widget(table, list, &mut app.table);
+
+    let footer = match (&app.mode, &app.status) {
+        (Mode::Filter, _) => Paragraph::new(format!("/{}_", app.filter)),
+        (Mode::Category(input), _) => Paragraph::new(format!("{} {}_", tr!("tui-category-prompt"), input)),
+        (_, Some(Status::Error(e))) => Paragraph::new(e.as_str()).style(style_of("error")),
+        (_, Some(Status::Info(s))) => Paragraph::new(s.as_str()),
+        (_, None) => Paragraph::new(tr!("tui-keys")).style(Style::default().add_modifier(Modifier::DIM)),
+    };
+    frame.render_widget(footer, bottom);
+
+    match &app.mode {
+        Mode::Confirm(action, torrents) => draw_confirm(frame, list, action, torrents),
+        Mode::Add(form) => draw_add(frame, list, form),
+        _ => {}
+    }
+}
+
+/// A box of `lines` in the middle of `area`, over what is drawn there.
+fn draw_box(frame: &mut Frame, area: Rect, lines: Vec<Line>, title: Option<String>) {
+    let width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+    let mut block = Block::default().borders(Borders::ALL);
+    if let Some(title) = title {
+        block = block.title(title);
+    }
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(Paragraph::new(lines).block(block), overlay);
+}
+
+/// The confirmation overlay: the question, the torrents it is about and the keys to answer with.
+fn draw_confirm(frame: &mut Frame, area: Rect, action: &Action, torrents: &[TorrentInfo]) {
+    let mut lines = vec![Line::from(question(action, torrents.len())), Line::from("")];
+    let listed = if torrents.len() > LISTED { LISTED - 1 } else { torrents.len() };
+    lines.extend(torrents[..listed].iter().map(|t| Line::from(format!("  {}", t.name))));
+    if listed < torrents.len() {
+        lines.push(Line::from(format!("  {}", tr!("tui-more", count = torrents.len() - listed))));
+    }
+    lines.push(Line::from(""));
+    lines.push(match action {
+        Action::Delete { files } => Line::from(tr!("tui-answer-delete", with_files = *files as u8)),
+        _ => Line::from(tr!("tui-answer")),
+    });
+    draw_box(frame, area, lines, None);
+}
+
+/// The add dialog: a line per field, the one being edited highlighted.
+fn draw_add(frame: &mut Frame, area: Rect, form: &AddForm) {
+    let labels = [tr!("tui-add-link"), tr!("tui-add-destination"), tr!("tui-add-category"), tr!("tui-add-paused")];
+    let pad = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    // long magnet links show their end, where the typing is
+    let room = (area.width as usize).saturating_sub(pad + 8).clamp(10, 70);
+    let values = [form.link.as_str(), form.destination.as_str(), form.category.as_str()].map(|v| {
+        let skip = v.chars().count().saturating_sub(room);
+        v.chars().skip(skip).collect::<String>()
+    });
+    let mut lines = Vec::new();
+    for (i, label) in labels.iter().enumerate() {
+        let value = match i {
+            3 if form.paused => "[x]".to_string(),
+            3 => "[ ]".to_string(),
+            1 if values[1].is_empty() && form.field != 1 => tr!("tui-add-server-default"),
+            _ => values[i].clone(),
+        };
+        let cursor = if form.field == i && i < 3 { "_" } else { "" };
+        let style = if form.field == i { Style::default().add_modifier(Modifier::REVERSED) } else { Style::default() };
+        lines.push(Line::from(vec![Span::styled(format!("{:<pad$}", label, pad = pad), style), Span::raw(format!("  {}{}", value, cursor))]));
+    }
+    // keep the box as wide when the link is typed in as when it is pasted
+    lines.push(Line::from(" ".repeat(pad + room + 3)));
+    lines.push(Line::from(tr!("tui-add-keys")));
+    draw_box(frame, area, lines, Some(tr!("tui-add-title")));
+}
+
+/// The terminal in raw mode on the alternate screen, given back as it was when dropped.
+struct Screen(Terminal<CrosstermBackend<Stdout>>);
+
+impl Screen {
+    fn enter() -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+        Ok(Screen(Terminal::new(CrosstermBackend::new(io::stdout()))?))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+        let _ = crossterm::execute!(self.0.backend_mut(), DisableBracketedPaste, LeaveAlternateScreen);
+        let _ = self.0.show_cursor();
+    }
+}
+
+/// Ask the server to carry out `request`; returns what was done, for the status line.
+async fn apply(session: &Session, request: &Request, stop_start: bool) -> anyhow::Result<String> {
+    let hashes = request.hashes.join("|");
+    let count = request.hashes.len();
+    let (endpoint, params, done) = match &request.action {
+        Action::Pause => (if stop_start { "torrents/stop" } else { "torrents/pause" }, vec![("hashes", hashes.as_str())], tr!("tui-done", what = tr!("pause-done"), count = count)),
+        Action::Resume => (if stop_start { "torrents/start" } else { "torrents/resume" }, vec![("hashes", hashes.as_str())], tr!("tui-done", what = tr!("resume-done"), count = count)),
+        Action::Delete { files } => {
+            let done = if *files { tr!("tui-deleted-data", count = count) } else { tr!("tui-deleted", count = count) };
+            ("torrents/delete", vec![("hashes", hashes.as_str()), ("deleteFiles", if *files { "true" } else { "false" })], done)
+        }
+        Action::SetCategory(name) => ("torrents/setCategory", vec![("hashes", hashes.as_str()), ("category", name.as_str())], tr!("tui-category-done", count = count, name = name.as_str())),
+    };
+    // posting would print the requests over the screen
+    if session.dry_run {
+        return Ok(format!("[dry-run] POST {} for {} torrent(s)", endpoint, count));
+    }
+    if let Action::SetCategory(name) = &request.action {
+        let existing: BTreeMap<String, serde_json::Value> = session.get_json("torrents/categories").await?;
+        if !name.is_empty() && !existing.contains_key(name) {
+            session.post_form("torrents/createCategory", &[("category", name.as_str()), ("savePath", "")]).await?;
+        }
+    }
+    session.post_form(endpoint, &params).await?;
+    Ok(done)
+}
+
+/// What the clipboard holds, from the first of the platform's paste tools that answers; `None`
+/// when none does.
+async fn clipboard() -> Option<String> {
+    let tools: &[&[&str]] = if cfg!(target_os = "macos") {
+        &[&["pbpaste"]]
+    } else if cfg!(windows) {
+        &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]]
+    } else {
+        &[&["wl-paste", "--no-newline"], &["xclip", "-selection", "clipboard", "-out"], &["xsel", "--clipboard", "--output"]]
+    };
+    for tool in tools {
+        let output = tokio::process::Command::new(tool[0]).args(&tool[1..]).stdin(Stdio::null()).stderr(Stdio::null()).kill_on_drop(true).output();
+        match tokio::time::timeout(PASTE_TIMEOUT, output).await {
+            Ok(Ok(output)) if output.status.success() => return Some(String::from_utf8_lossy(&output.stdout).into_owned()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Add the torrent the dialog was filled in for; returns what was done, for the status line.
+async fn add(session: &Session, form: &AddForm, defaults: &AddOptions) -> anyhow::Result<String> {
+    let link = form.link.trim();
+    if session.dry_run {
+        return Ok(format!("[dry-run] POST torrents/add for {}", link));
+    }
+    session.add(link, &form.options(defaults)).await?;
+    Ok(tr!("tui-added"))
+}
+
+/// Run the TUI until `q`, Ctrl-C or a stop signal, fetching the torrent list every `interval`.
+/// Torrents added from it take the save path, when none is given, and the rules from
+/// `defaults`.
+pub async fn tui(session: &Session, interval: Duration, defaults: &AddOptions) -> anyhow::Result<()> {
+    if !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+        return Err(RbitError::InvalidInput(tr!("tui-needs-terminal")).into());
+    }
+    session.login().await?;
+    let stop_start = stop_start_api(session).await?;
+    let destination = defaults.save_path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+    let mut app = App::new(session.host.as_str(), &destination);
+    app.refresh(session.torrents("all").await?);
+
+    let mut interrupt = Interrupt::listen();
+    let mut events = EventStream::new();
+    let mut tick = tokio::time::interval(interval);
+    tick.reset();
+    let mut screen = Screen::enter()?;
+    while !app.quit {
+        screen.0.draw(|frame| draw(frame, &mut app))?;
+        tokio::select! {
+            _ = tick.tick() => match session.torrents("all").await {
+                Ok(torrents) => app.refresh(torrents),
+                Err(e) => app.status = Some(Status::Error(format!("{:#}", e))),
+            },
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    let done = match app.key(key) {
+                        Some(Effect::Apply(request)) => apply(session, &request, stop_start).await,
+                        Some(Effect::Add(form)) => add(session, &form, defaults).await,
+                        Some(Effect::OpenAdd) => {
+                            app.open_add(clipboard().await);
+                            continue;
+                        }
+                        None => continue,
+                    };
+                    app.status = Some(match done {
+                        Ok(done) => Status::Info(done),
+                        Err(e) => Status::Error(format!("{:#}", e)),
+                    });
+                    tick.reset_immediately();
+                }
+                Some(Ok(Event::Paste(text))) => app.paste(&text),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+                None => break,
+            },
+            _ = interrupt.recv() => break,
+        }
+    }
+    drop(screen);
+    interrupt::wrap_up(session).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn press(app: &mut App, keys: &str) -> Option<Effect> {
+        let mut request = None;
+        for c in keys.chars() {
+            let code = match c {
+                '\x1b' => KeyCode::Esc,
+                '\n' => KeyCode::Enter,
+                '\x08' => KeyCode::Backspace,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            request = app.key(KeyEvent::new(code, KeyModifiers::NONE)).or(request);
+        }
+        request
+    }
+
+    fn app(names: &[&str]) -> App {
+        let mut app = App::new("http://localhost:8080", "/downloads");
+        app.refresh(names.iter().enumerate().map(|(i, name)| TorrentInfo { name: name.to_string(), hash: format!("{:040}", i), state: "uploading".to_string(), ..Default::default() }).collect());
+        app
+    }
+
+    fn names(torrents: &[TorrentInfo]) -> Vec<&str> {
+        torrents.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn marks_and_ranges_make_the_selection() {
+        let mut app = app(&["a", "b", "c", "d", "e"]);
+        // nothing marked: the torrent under the cursor
+        assert_eq!(names(&app.selection()), ["a"]);
+        // space marks and moves on; again unmarks
+        press(&mut app, "  k ");
+        assert_eq!(names(&app.selection()), ["a"]);
+        // a range from the anchor to the cursor, kept by space
+        press(&mut app, "vjj");
+        assert_eq!(names(&app.selection()), ["a", "c", "d", "e"]);
+        press(&mut app, " g");
+        assert_eq!(names(&app.selection()), ["a", "c", "d", "e"]);
+        press(&mut app, "\x1b");
+        assert_eq!(names(&app.selection()), ["a"]);
+        press(&mut app, "*");
+        assert_eq!(app.selection().len(), 5);
+        press(&mut app, "*");
+        assert_eq!(app.selection().len(), 1);
+    }
+
+    #[test]
+    fn refreshing_keeps_the_cursor_and_the_marks() {
+        let mut app = app(&["b", "c"]);
+        press(&mut app, " ");
+        assert_eq!(app.cursor, 1);
+        let mut torrents = app.torrents.clone();
+        torrents.insert(0, TorrentInfo { name: "a".to_string(), hash: "f".repeat(40), ..Default::default() });
+        app.refresh(torrents);
+        assert_eq!(app.torrents[app.cursor].name, "c");
+        assert_eq!(names(&app.selection()), ["b"]);
+        // a marked torrent that is gone is no longer marked
+        app.refresh(app.torrents.iter().filter(|t| t.name != "b").cloned().collect());
+        assert_eq!(names(&app.selection()), ["c"]);
+    }
+
+    #[test]
+    fn the_filter_narrows_the_list_as_it_is_typed() {
+        let mut app = app(&["debian", "ubuntu-desktop", "ubuntu-server"]);
+        for (t, category) in app.all.iter_mut().zip(["linux", "", ""]) {
+            t.category = Some(category.to_string());
+        }
+        app.all[2].state = "pausedUP".to_string();
+        press(&mut app, "/ubu");
+        assert!(matches!(app.mode, Mode::Filter));
+        assert_eq!(names(&app.torrents), ["ubuntu-desktop", "ubuntu-server"]);
+        press(&mut app, " state:paused");
+        assert_eq!(names(&app.torrents), ["ubuntu-server"]);
+        // Enter keeps the filter, and the keys are the list's again
+        press(&mut app, "\n*");
+        assert_eq!(names(&app.selection()), ["ubuntu-server"]);
+        press(&mut app, "\x1b");
+        assert_eq!(app.torrents.len(), 3);
+        // the marked torrent the filter hid is still marked
+        assert_eq!(names(&app.selection()), ["ubuntu-server"]);
+
+        press(&mut app, "/category:linux\n");
+        assert_eq!(names(&app.torrents), ["debian"]);
+        press(&mut app, "/\x08\x08\x08\x08\x08\n");
+        assert_eq!(names(&app.torrents), ["ubuntu-desktop", "ubuntu-server"]);
+        press(&mut app, "/\x1b");
+        assert_eq!(app.filter, "");
+    }
+
+    #[test]
+    fn filters_are_selectors() {
+        let selector = filter_selector("  ubuntu  24 state:seeding category:linux");
+        assert_eq!(selector.name.as_deref(), Some("*ubuntu 24*"));
+        assert_eq!(selector.state.as_deref(), Some("seeding"));
+        assert_eq!(selector.category.as_deref(), Some("linux"));
+        assert_eq!(filter_selector("ubuntu-*.iso").name.as_deref(), Some("ubuntu-*.iso"));
+        assert_eq!(filter_selector("category:").category.as_deref(), Some(""));
+        assert!(filter_selector(" ").is_empty());
+    }
+
+    #[test]
+    fn actions_wait_for_a_confirmation() {
+        let mut app = app(&["a", "b", "c"]);
+        assert_eq!(press(&mut app, " p"), None);
+        assert!(matches!(app.mode, Mode::Confirm(Action::Pause, _)));
+        // anything but yes or no leaves the question up
+        assert_eq!(press(&mut app, "x"), None);
+        assert_eq!(press(&mut app, "n"), None);
+        assert!(matches!(app.mode, Mode::List));
+        assert_eq!(app.selection().len(), 1);
+
+        let request = press(&mut app, "dfy");
+        assert_eq!(request, Some(Effect::Apply(Request { action: Action::Delete { files: true }, hashes: vec![format!("{:040}", 0)] })));
+        // done with the marks
+        assert_eq!(names(&app.selection()), ["b"]);
+
+        let request = press(&mut app, " cmoviesx\x08\n");
+        assert_eq!(request, None);
+        assert!(matches!(app.mode, Mode::Confirm(Action::SetCategory(ref name), _) if name == "movies"));
+        let request = press(&mut app, "\n");
+        assert_eq!(request, Some(Effect::Apply(Request { action: Action::SetCategory("movies".to_string()), hashes: vec![format!("{:040}", 1)] })));
+    }
+
+    #[test]
+    fn the_add_dialog_starts_from_a_magnet_on_the_clipboard() {
+        let magnet = format!("magnet:?xt=urn:btih:{}&dn=x", "ab".repeat(20));
+        let mut app = app(&["a"]);
+        assert_eq!(press(&mut app, "a"), Some(Effect::OpenAdd));
+        app.open_add(Some("https://example.org/not-a-magnet".to_string()));
+        assert!(matches!(app.mode, Mode::Add(ref form) if form.link.is_empty() && form.destination == "/downloads"));
+        press(&mut app, "\x1b");
+        assert!(matches!(app.mode, Mode::List));
+
+        app.open_add(Some(format!("  {}\n", magnet)));
+        // the destination is edited, the category typed and paused switched on; space types
+        // a space in a text field
+        let request = press(&mut app, "\t\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08/tv\tmy shows\t \n");
+        let form = AddForm { link: magnet.clone(), destination: "/tv".to_string(), category: "my shows".to_string(), paused: true, field: 3 };
+        assert_eq!(request, Some(Effect::Add(form.clone())));
+
+        let options = form.options(&AddOptions { save_path: Some(PathBuf::from("/downloads")), ..AddOptions::default() });
+        assert_eq!(options.save_path, Some(PathBuf::from("/tv")));
+        assert!(options.fixed_save_path);
+        assert_eq!(options.category.as_deref(), Some("my shows"));
+        assert!(options.paused);
+        // the default save path leaves the rules free to choose, and no path is the server's
+        let kept = AddForm { destination: "/downloads".to_string(), ..form.clone() }.options(&AddOptions { save_path: Some(PathBuf::from("/downloads")), ..AddOptions::default() });
+        assert!(!kept.fixed_save_path);
+        let none = AddForm { destination: " ".to_string(), category: String::new(), ..form }.options(&AddOptions::default());
+        assert_eq!((none.save_path, none.category, none.fixed_save_path), (None, None, false));
+    }
+
+    #[test]
+    fn the_add_dialog_does_not_add_a_torrent_twice() {
+        let mut app = app(&["a", "b"]);
+        app.open_add(Some(format!("magnet:?xt=urn:btih:{:040}", 1)));
+        assert_eq!(press(&mut app, "\n"), None);
+        assert_eq!(app.status, Some(Status::Info("Already on the server as b".to_string())));
+        // nor one without a link
+        app.open_add(None);
+        assert_eq!(press(&mut app, "\n"), None);
+        assert!(matches!(app.mode, Mode::Add(_)));
+        app.paste("magnet:?xt=urn:btih:0123\n");
+        assert!(matches!(app.mode, Mode::Add(ref form) if form.link == "magnet:?xt=urn:btih:0123"));
+    }
+
+    #[test]
+    fn the_confirmation_lists_what_it_applies_to() {
+        let mut app = app(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        press(&mut app, "*r");
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+        let screen: Vec<String> = terminal.backend().buffer().content.chunks(60).map(|row| row.iter().map(|c| c.symbol()).collect()).collect();
+        let screen = screen.join("\n");
+        assert!(screen.contains("Resume 10 torrents?"), "{}", screen);
+        assert!(screen.contains("  g "), "{}", screen);
+        assert!(!screen.contains("  h "), "{}", screen);
+        assert!(screen.contains("… and 3 more"), "{}", screen);
+        assert!(screen.contains("10 marked"), "{}", screen);
+
+        press(&mut app, "na");
+        app.open_add(Some("magnet:?xt=urn:btih:0123".to_string()));
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+        let screen: Vec<String> = terminal.backend().buffer().content.chunks(60).map(|row| row.iter().map(|c| c.symbol()).collect()).collect();
+        let screen = screen.join("\n");
+        assert!(screen.contains("Add a torrent"), "{}", screen);
+        assert!(screen.contains("magnet:?xt=urn:btih:0123_"), "{}", screen);
+        assert!(screen.contains("/downloads"), "{}", screen);
+        assert!(screen.contains("[ ]"), "{}", screen);
+    }
+}
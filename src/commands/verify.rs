@@ -0,0 +1,319 @@
+//! `rbit verify <id>`: check a complete torrent's data from this machine, without trusting the
+//! server: the files' sizes, and with `--deep` their contents against the piece hashes of the
+//! torrent's exported .torrent file (or a local copy given with `--torrent-file`).
+//!
+//! The data is read where the server keeps it, so this works when qBittorrent runs on this
+//! machine or its download folders are mounted at the same paths here; otherwise `--data-root`
+//! names the folder where the torrent's save path is mounted.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use sha1::{Digest, Sha1};
+
+use crate::api::{FileInfo, Session};
+use crate::commands::export;
+use crate::commands::manage::short_id;
+use crate::error::RbitError;
+use crate::i18n::tr;
+use crate::format::{duration_human, size_human};
+use crate::torrent::{Metainfo, TorrentFile};
+use rbit::progress::TransferBar;
+
+/// How much of a file is read at a time.
+const CHUNK: usize = 1 << 20;
+
+/// One of the torrent's files, where it is on this machine.
+#[derive(Debug)]
+struct LocalFile {
+    path: PathBuf,
+    length: u64,
+    /// A padding file, all zeros and never on disk
+    pad: bool,
+    /// Not downloaded (priority 0), so its pieces cannot be checked
+    skipped: bool,
+}
+
+/// What was found wrong with a file.
+#[derive(Debug, Default)]
+struct FileCheck {
+    /// Missing, unreadable or the wrong size
+    problem: Option<String>,
+    /// Pieces overlapping the file whose hash does not match
+    bad_pieces: usize,
+}
+
+/// The torrent's files under `root`, named as the server names them (files can be renamed), or
+/// as in the metainfo when the server's list does not line up with it.
+fn local_files(root: &Path, files: &[TorrentFile], on_server: &[FileInfo]) -> Vec<LocalFile> {
+    let real = files.iter().filter(|f| !f.pad).count();
+    let lines_up = |with_pad: bool| {
+        let sizes = files.iter().filter(|f| with_pad || !f.pad).map(|f| f.length);
+        on_server.len() == if with_pad { files.len() } else { real } && sizes.eq(on_server.iter().map(|s| s.size))
+    };
+    // qBittorrent leaves padding files out of its list, older versions list them
+    let on_server = if lines_up(false) || lines_up(true) { on_server } else { &[] };
+    let mut listed = on_server.iter();
+    files
+        .iter()
+        .map(|f| {
+            let server = if f.pad && on_server.len() == real { None } else { listed.next() };
+            LocalFile {
+                path: root.join(server.map_or(f.path.as_str(), |s| s.name.as_str())),
+                length: f.length,
+                pad: f.pad,
+                skipped: server.is_some_and(|s| s.priority == 0),
+            }
+        })
+        .collect()
+}
+
+fn open_error(e: &io::Error) -> String {
+    match e.kind() {
+        io::ErrorKind::NotFound => tr!("verify-missing"),
+        _ => e.to_string(),
+    }
+}
+
+/// Whether each file is there with the right size.
+fn check_sizes(files: &[LocalFile]) -> Vec<FileCheck> {
+    files
+        .iter()
+        .map(|f| {
+            let problem = match std::fs::metadata(&f.path) {
+                _ if f.pad || f.skipped => None,
+                Ok(m) if m.len() != f.length => Some(tr!("verify-size", size = size_human(m.len()), expected = size_human(f.length))),
+                Ok(_) => None,
+                Err(e) => Some(open_error(&e)),
+            };
+            FileCheck { problem, bad_pieces: 0 }
+        })
+        .collect()
+}
+
+/// The result of hashing all pieces.
+#[derive(Debug)]
+struct Hashed {
+    files: Vec<FileCheck>,
+    /// Pieces whose hash does not match, by index
+    bad: Vec<usize>,
+    /// Pieces overlapping files that are not downloaded
+    unchecked: usize,
+}
+
+/// The piece being hashed.
+#[derive(Default)]
+struct Piece {
+    hasher: Sha1,
+    len: u64,
+    /// Files it overlaps
+    files: Vec<usize>,
+    /// Some of its bytes could not be read
+    unreadable: bool,
+    /// Some of its bytes are in a file that is not downloaded
+    not_downloaded: bool,
+}
+
+impl Piece {
+    /// Compare piece `index` with `expected`, count it in `tally` and start the next one.
+    fn finish(&mut self, index: usize, expected: Option<&[u8; 20]>, tally: &mut Hashed) {
+        let piece = std::mem::take(self);
+        let digest = piece.hasher.finalize();
+        if piece.not_downloaded && !piece.unreadable {
+            tally.unchecked += 1;
+        } else if piece.unreadable || expected.is_none_or(|h| h[..] != digest[..]) {
+            tally.bad.push(index);
+            for i in piece.files {
+                tally.files[i].bad_pieces += 1;
+            }
+        }
+    }
+}
+
+/// Read the files in torrent order and compare each piece with its hash, calling `progress`
+/// with the bytes read so far. The hashes must cover the files in pieces of `piece_length`,
+/// as [`Metainfo::parse`] makes sure.
+fn hash_pieces(files: &[LocalFile], piece_length: u64, pieces: &[[u8; 20]], mut progress: impl FnMut(u64)) -> Hashed {
+    debug_assert!(piece_length > 0 && pieces.len() as u64 == files.iter().map(|f| f.length).sum::<u64>().div_ceil(piece_length));
+    let mut tally = Hashed { files: check_sizes(files), bad: Vec::new(), unchecked: 0 };
+    let mut piece = Piece::default();
+    let (mut index, mut done) = (0, 0u64);
+    let mut buf = vec![0u8; CHUNK];
+    for (i, f) in files.iter().enumerate() {
+        let mut source = match File::open(&f.path) {
+            Ok(file) if !f.pad && !f.skipped => Some(file),
+            _ => None,
+        };
+        let mut left = f.length;
+        while left > 0 {
+            if !f.pad && !piece.files.contains(&i) {
+                piece.files.push(i);
+            }
+            let n = left.min(piece_length - piece.len).min(CHUNK as u64) as usize;
+            let chunk = &mut buf[..n];
+            if f.pad {
+                chunk.fill(0);
+            } else if f.skipped {
+                piece.not_downloaded = true;
+            } else if let Some(ref mut file) = source {
+                if let Err(e) = file.read_exact(chunk) {
+                    tally.files[i].problem.get_or_insert_with(|| e.to_string());
+                    source = None;
+                    piece.unreadable = true;
+                }
+            } else {
+                piece.unreadable = true;
+            }
+            piece.hasher.update(&*chunk);
+            piece.len += n as u64;
+            left -= n as u64;
+            done += n as u64;
+            progress(done);
+            if piece.len == piece_length {
+                piece.finish(index, pieces.get(index), &mut tally);
+                index += 1;
+            }
+        }
+    }
+    if piece.len > 0 {
+        piece.finish(index, pieces.get(index), &mut tally);
+    }
+    tally
+}
+
+/// Piece indices as runs, e.g. `3, 7-9`.
+fn runs(pieces: &[usize]) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < pieces.len() {
+        let start = pieces[i];
+        while i + 1 < pieces.len() && pieces[i + 1] == pieces[i] + 1 {
+            i += 1;
+        }
+        out.push(if pieces[i] == start { start.to_string() } else { format!("{}-{}", start, pieces[i]) });
+        i += 1;
+    }
+    out.join(", ")
+}
+
+/// Print the files with problems; how many there were.
+fn report(files: &[LocalFile], checks: &[FileCheck]) -> usize {
+    let mut failed = 0;
+    for (f, check) in files.iter().zip(checks) {
+        let mut why: Vec<String> = check.problem.iter().cloned().collect();
+        if check.bad_pieces > 0 {
+            why.push(tr!("verify-bad-pieces", count = check.bad_pieces));
+        }
+        if !why.is_empty() {
+            failed += 1;
+            println!("{}", tr!("verify-file-failed", path = f.path.display().to_string(), reason = why.join(", ")));
+        }
+    }
+    failed
+}
+
+pub async fn verify(session: &Session, id: &str, deep: bool, data_root: Option<&Path>, torrent_file: Option<&Path>) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+    let progress = t.progress.unwrap_or(0.0);
+    if progress < 1.0 {
+        let percent = format!("{:.1}", progress * 100.0);
+        return Err(RbitError::InvalidInput(tr!("torrent-incomplete", name = t.name.as_str(), percent = percent)).into());
+    }
+    let save_path = t.save_path.clone().unwrap_or_default();
+    // the server's save path, or where path_map says it is here
+    let root = data_root.map_or_else(|| PathBuf::from(session.path_map.show(&save_path)), Path::to_path_buf);
+    if !root.is_dir() {
+        anyhow::bail!(match data_root {
+            Some(root) => tr!("verify-no-data-root", path = root.display().to_string(), save_path = save_path.as_str()),
+            None => tr!("verify-unreachable", path = root.display().to_string()),
+        });
+    }
+    let meta = match torrent_file {
+        Some(path) => {
+            let meta = Metainfo::from_file(path)?;
+            if !meta.info_hash.eq_ignore_ascii_case(&t.hash) {
+                return Err(RbitError::InvalidInput(tr!("verify-other-torrent", path = path.display().to_string(), name = t.name.as_str())).into());
+            }
+            meta
+        }
+        None => Metainfo::parse(&export::fetch(session, &t).await?)?,
+    };
+    let files = local_files(&root, &meta.files, &session.files(&t.hash).await?);
+
+    if !deep {
+        let failed = report(&files, &check_sizes(&files));
+        if failed > 0 {
+            anyhow::bail!(tr!("verify-files-missing", failed = failed, total = files.len()));
+        }
+        println!("{}", tr!("verify-sizes-ok", total = files.len(), name = t.name.as_str()));
+        return Ok(());
+    }
+
+    let total = meta.total_size();
+    let pieces = meta.pieces.len();
+    let started = Instant::now();
+    let piece_length = meta.piece_length;
+    let (files, hashed) = tokio::task::spawn_blocking(move || {
+        let mut bar = TransferBar::new(&tr!("verify-progress"), total);
+        let hashed = hash_pieces(&files, piece_length, &meta.pieces, |done| bar.update(done, total));
+        bar.finish("");
+        (files, hashed)
+    })
+    .await?;
+
+    let failed = report(&files, &hashed.files);
+    let elapsed = duration_human(Duration::from_secs(started.elapsed().as_secs()));
+    let ok = pieces.saturating_sub(hashed.bad.len() + hashed.unchecked);
+    let mut summary = tr!("verify-summary", name = t.name.as_str(), ok = ok, total = pieces, size = size_human(total), elapsed = elapsed);
+    if hashed.unchecked > 0 {
+        summary.push_str(&tr!("verify-unchecked", unchecked = hashed.unchecked));
+    }
+    println!("{}", summary);
+    if !hashed.bad.is_empty() {
+        println!("{}", tr!("verify-bad-list", pieces = runs(&hashed.bad)));
+        anyhow::bail!(tr!("verify-bad", bad = hashed.bad.len(), total = pieces, id = short_id(&t)));
+    }
+    if failed > 0 {
+        anyhow::bail!(tr!("verify-wrong-size", failed = failed, total = files.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_pieces_are_blamed_on_their_files() {
+        let dir = std::env::temp_dir().join(format!("rbit-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (b"hello".to_vec(), b"0123456789".to_vec());
+        std::fs::write(dir.join("a"), &a).unwrap();
+        std::fs::write(dir.join("b"), &b).unwrap();
+        let file = |name: &str, length, pad| LocalFile { path: dir.join(name), length, pad, skipped: false };
+        // a padding file lines `b` up on a piece boundary
+        let files = vec![file("a", 5, false), file("pad", 3, true), file("b", 10, false)];
+        let data = [a, vec![0; 3], b].concat();
+        let pieces: Vec<[u8; 20]> = data.chunks(8).map(|c| Sha1::digest(c).into()).collect();
+
+        let hashed = hash_pieces(&files, 8, &pieces, |_| ());
+        assert_eq!((hashed.bad.len(), hashed.unchecked), (0, 0));
+
+        std::fs::write(dir.join("b"), b"0123456780").unwrap();
+        let hashed = hash_pieces(&files, 8, &pieces, |_| ());
+        assert_eq!(hashed.bad, [2]);
+        assert_eq!((hashed.files[0].bad_pieces, hashed.files[2].bad_pieces), (0, 1));
+
+        std::fs::remove_file(dir.join("a")).unwrap();
+        let hashed = hash_pieces(&files, 8, &pieces, |_| ());
+        assert_eq!(hashed.bad, [0, 2]);
+        assert_eq!(hashed.files[0].problem.as_deref(), Some("missing"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(runs(&[0, 2]), "0, 2");
+        assert_eq!(runs(&[3, 7, 8, 9, 12]), "3, 7-9, 12");
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::{Duration, Instant};
+
+use indicatif::{MultiProgress, ProgressDrawTarget};
+use rbit::progress::TransferBar;
+
+use crate::api::{Session, TorrentInfo};
+use crate::commands::follow::{progress_of, run_hook, Progress};
+use crate::error::RbitError;
+use crate::format::{duration_human, truncate};
+use crate::i18n::tr;
+use crate::interrupt::{self, Interrupt};
+use crate::notifications::{self, Event, EventKind};
+
+/// The state `rbit wait` waits for.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitState {
+    /// Fully downloaded (and not being rechecked)
+    Completed,
+    /// Fully downloaded and seeding: not paused or queued
+    Seeding,
+}
+
+impl WaitState {
+    fn reached(self, t: &TorrentInfo) -> bool {
+        let complete = progress_of(t) == Progress::Complete;
+        match self {
+            WaitState::Completed => complete,
+            WaitState::Seeding => complete && matches!(t.state.as_str(), "uploading" | "stalledUP" | "forcedUP"),
+        }
+    }
+}
+
+/// Poll until every torrent in `ids` has reached `state`, running `on_complete` for each as it
+/// gets there. Fails when one goes into an error state or is removed, and with
+/// [`RbitError::Timeout`] after `timeout`; a failed hook fails the command once all are done.
+pub async fn wait(
+    session: &Session,
+    ids: &[String],
+    state: WaitState,
+    timeout: Option<Duration>,
+    interval: Duration,
+    on_complete: Option<&str>,
+) -> anyhow::Result<()> {
+    session.login().await?;
+    let mut pending: Vec<TorrentInfo> = session.resolve_many(ids).await?;
+    let total = pending.len();
+    let started = Instant::now();
+    let mut hook_failures = 0;
+    // a bar per torrent with its progress, smoothed ETA and qBittorrent's, hidden off a terminal
+    let multi = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
+    let mut bars: Vec<(String, TransferBar)> = pending
+        .iter()
+        .map(|t| {
+            let bar = TransferBar::with_target(&truncate(&t.name, 30), 1, ProgressDrawTarget::hidden());
+            multi.add(bar.bar().clone());
+            (t.hash.to_lowercase(), bar)
+        })
+        .collect();
+    let mut interrupt = Interrupt::listen();
+    loop {
+        let hashes: Vec<String> = pending.iter().map(|t| t.hash.clone()).collect();
+        let current = session.find_torrents(&hashes).await?;
+        let mut still = Vec::new();
+        for t in pending {
+            let Some(now) = current.iter().find(|c| c.hash.eq_ignore_ascii_case(&t.hash)) else {
+                return Err(RbitError::NotFound(tr!("wait-removed", name = t.name.as_str())).into());
+            };
+            let mut bar = bars.iter_mut().find(|(hash, _)| hash.eq_ignore_ascii_case(&now.hash)).map(|(_, bar)| bar);
+            if let Some(bar) = bar.as_deref_mut() {
+                let total = now.size.unwrap_or(0);
+                bar.set_server_eta(now.eta);
+                bar.update((now.progress.unwrap_or(0.0) * total as f64) as u64, total.max(1));
+            }
+            if progress_of(now) == Progress::Failed {
+                if let Some(bar) = bar {
+                    bar.abandon(&now.state);
+                }
+                notifications::notify(Event { kind: EventKind::Error, torrent: now, host: &session.host }).await;
+                anyhow::bail!(tr!("wait-failed", name = now.name.as_str(), state = now.state.as_str()));
+            }
+            if state.reached(now) {
+                if let Some(bar) = bar {
+                    bar.finish(&now.state);
+                }
+                multi.suspend(|| println!("{}: {}", now.name, now.state));
+                notifications::notify(Event { kind: EventKind::Complete, torrent: now, host: &session.host }).await;
+                if let Some(command) = on_complete {
+                    if let Err(e) = run_hook(&multi, command, now, &session.host, session.dry_run).await {
+                        hook_failures += 1;
+                        multi.suspend(|| eprintln!("{:#}", e));
+                    }
+                }
+            } else {
+                still.push(now.clone());
+            }
+        }
+        pending = still;
+        if pending.is_empty() {
+            if hook_failures > 0 {
+                anyhow::bail!(tr!("wait-hook-failed", failed = hook_failures, total = total));
+            }
+            return Ok(());
+        }
+        let waited = started.elapsed();
+        let mut pause = interval;
+        if let Some(timeout) = timeout {
+            if waited >= timeout {
+                for (_, bar) in &bars {
+                    bar.abandon(&tr!("wait-gave-up"));
+                }
+                let names: Vec<&str> = pending.iter().map(|t| t.name.as_str()).collect();
+                let message = tr!("wait-timeout", limit = duration_human(timeout), what = names.join(", "));
+                return Err(RbitError::Timeout(message).into());
+            }
+            pause = interval.min(timeout - waited);
+        }
+        tokio::select! {
+            _ = interrupt.recv() => {
+                for (_, bar) in &bars {
+                    bar.abandon(&tr!("wait-stopped"));
+                }
+                multi.suspend(|| println!("{}", tr!("wait-interrupted")));
+                interrupt::wrap_up(session).await;
+                return Err(RbitError::Interrupted.into());
+            }
+            _ = tokio::time::sleep(pause) => {}
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! `rbit webseeds`: show a torrent's web seeds (HTTP/FTP mirrors of its files), and add or
+//! remove them on servers with the Web API for it.
+
+use crate::api::Session;
+use crate::commands::manage::short_id;
+use crate::i18n::tr;
+
+/// Older qBittorrent only lists web seeds; changing them needs the endpoints qBittorrent 5.1
+/// added, and a missing endpoint fails with a bare 404.
+fn unsupported(what: &str, name: &str, e: anyhow::Error) -> anyhow::Error {
+    match e.to_string().contains("404") {
+        true => anyhow::anyhow!("could not {} web seeds of {}: the server does not support it (needs qBittorrent 5.1 or later)", what, name),
+        false => e.context(format!("could not {} web seeds of {}", what, name)),
+    }
+}
+
+/// Remove then add web seeds of a torrent, and print the resulting list.
+pub async fn webseeds(session: &Session, id: &str, add: &[String], remove: &[String]) -> anyhow::Result<()> {
+    session.login().await?;
+    let t = session.resolve(id).await?;
+
+    if !remove.is_empty() {
+        session
+            .post_form("torrents/removeWebSeeds", &[("hash", &t.hash), ("urls", &remove.join("|"))])
+            .await
+            .map_err(|e| unsupported("remove", &t.name, e))?;
+        println!("{}", tr!("webseeds-removed", count = remove.len(), name = t.name.as_str(), id = short_id(&t)));
+    }
+    if !add.is_empty() {
+        session
+            .post_form("torrents/addWebSeeds", &[("hash", &t.hash), ("urls", &add.join("|"))])
+            .await
+            .map_err(|e| unsupported("add", &t.name, e))?;
+        println!("{}", tr!("webseeds-added", count = add.len(), name = t.name.as_str(), id = short_id(&t)));
+    }
+
+    if session.dry_run && !(add.is_empty() && remove.is_empty()) {
+        return Ok(());
+    }
+    let seeds = session.webseeds(&t.hash).await?;
+    if seeds.is_empty() {
+        println!("{}", tr!("webseeds-none", name = t.name.as_str(), id = short_id(&t)));
+        return Ok(());
+    }
+    println!("{}", tr!("webseeds-list", name = t.name.as_str(), id = short_id(&t)));
+    for seed in &seeds {
+        println!("  {}", seed.url);
+    }
+    Ok(())
+}
@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use config::{Config as ConfigLoader, File as ConfigFile, FileFormat};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::api::ConnectOptions;
+use crate::client::Backend;
+use crate::commands::daemon::WatchDir;
+use crate::commands::list::ListConfig;
+use crate::commands::port::PortConfig;
+use crate::commands::prune::PruneConfig;
+use crate::commands::reannounce::AnnounceConfig;
+use crate::commands::schedule::ScheduleWindow;
+use crate::commands::stream::StreamConfig;
+use crate::error::RbitError;
+use crate::format::Units;
+use crate::guard::GuardConfig;
+use crate::history::HistoryConfig;
+use crate::hooks::HooksConfig;
+use crate::i18n::tr;
+use crate::notifications::NotificationsConfig;
+use crate::rules::Rule;
+use crate::savepath::PathMap;
+use crate::space::DiskConfig;
+use crate::theme::ThemeConfig;
+
+/// Host used when neither the CLI nor the config names one.
+pub const DEFAULT_HOST: &str = "http://127.0.0.1:8080";
+
+/// Name under which the top-level `[qbittorrent]` table is listed next to `[servers.*]` profiles.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// `--server all`: every configured profile
+pub const ALL_PROFILES: &str = "all";
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Config {
+    /// Where `rbit add` saves torrents without `--dest`; may be a template like `/media/{category}/{yyyy}`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_save_path: Option<String>,
+    /// Language of rbit's messages, e.g. `de` (default: from `LANG`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Go ahead with destructive actions without asking, as with `--yes`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_confirm: bool,
+    /// Turn off `rbit self-update`, e.g. where rbit comes from a package manager
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_self_update: bool,
+    /// How sizes and rates are shown: `binary` (KiB, MiB, ...; the default) or `si` (kB, MB, ...)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<Units>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qbittorrent: Option<QBConfig>,
+    /// Additional named server profiles, selected with `--server <name>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub servers: BTreeMap<String, QBConfig>,
+    #[serde(default, skip_serializing_if = "HttpConfig::is_empty")]
+    pub http: HttpConfig,
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_empty")]
+    pub theme: ThemeConfig,
+    #[serde(default, skip_serializing_if = "ListConfig::is_empty")]
+    pub list: ListConfig,
+    #[serde(default, skip_serializing_if = "HooksConfig::is_empty")]
+    pub hooks: HooksConfig,
+    #[serde(default, skip_serializing_if = "NotificationsConfig::is_empty")]
+    pub notifications: NotificationsConfig,
+    #[serde(default, skip_serializing_if = "HistoryConfig::is_empty")]
+    pub history: HistoryConfig,
+    #[serde(default, skip_serializing_if = "AnnounceConfig::is_empty")]
+    pub announce: AnnounceConfig,
+    /// Named search queries, run with `rbit search --saved <name>`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub searches: BTreeMap<String, SavedSearch>,
+    #[serde(default, skip_serializing_if = "RssConfig::is_empty")]
+    pub rss: RssConfig,
+    /// Server categories kept in line by `rbit category sync --from-config`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, CategoryConfig>,
+    /// Category, tags and save path by tracker or name, for `rbit add` and `rbit apply-rules`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+    /// Tag policies applied by `rbit cleanup`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retention: Vec<RetentionPolicy>,
+    #[serde(default, skip_serializing_if = "PruneConfig::is_empty")]
+    pub prune: PruneConfig,
+    #[serde(default, skip_serializing_if = "DiskConfig::is_empty")]
+    pub disk: DiskConfig,
+    #[serde(default, skip_serializing_if = "GuardConfig::is_empty")]
+    pub guard: GuardConfig,
+    #[serde(default, skip_serializing_if = "PortConfig::is_empty")]
+    pub port: PortConfig,
+    #[serde(default, skip_serializing_if = "StreamConfig::is_empty")]
+    pub stream: StreamConfig,
+    /// Windows in which the alternative speed limits are on, for `rbit schedule` and `rbit daemon`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<ScheduleWindow>,
+    /// Folders `rbit daemon` adds torrents from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watch: Vec<WatchDir>,
+}
+
+/// `[[retention]]`: what `rbit cleanup` does with torrents that carry a tag. A list rather than
+/// a table keyed by tag, because the config loader lowercases keys and tags are case-sensitive.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RetentionPolicy {
+    pub tag: String,
+    /// Time after completion until the torrent is deleted, e.g. `7d` or `36h`; `never` protects
+    /// it from every policy
+    pub delete_after: String,
+    /// Delete the downloaded data too
+    #[serde(default)]
+    pub delete_files: bool,
+}
+
+/// `[categories.<name>]`: a category and where its torrents are saved.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CategoryConfig {
+    /// The name on the server, for names that are not all lowercase (the config loader
+    /// lowercases keys)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Empty uses qBittorrent's default save path
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub save_path: String,
+}
+
+/// `[rss]`: RSS settings kept on this machine
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RssConfig {
+    /// Auto-download rules pushed to the server by `rbit rss rule sync`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<String, RssRule>,
+}
+
+impl RssConfig {
+    fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// `[rss.rules.<name>]`, or the flags of `rbit rss rule add`. Unset fields keep the value
+/// the server has (or qBittorrent's default for a new rule).
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RssRule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub must_contain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub must_not_contain: Option<String>,
+    /// Treat the contain filters as regular expressions instead of wildcards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_regex: Option<bool>,
+    /// Episode filter such as `1x01-;` (series 1 from episode 1 on)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episode_filter: Option<String>,
+    /// Skip episodes that were already downloaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smart_filter: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+    /// Feeds the rule applies to, by URL or by feed path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feeds: Option<Vec<String>>,
+}
+
+/// `[searches.<name>]`: a search query with its filters
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedSearch {
+    pub query: String,
+    /// Plugin category such as `movies` or `software` (default: all)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Plugins to ask (default: all enabled ones)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<String>,
+}
+
+/// `[http]`: behaviour of the HTTP client
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HttpConfig {
+    /// Retries for requests that fail to connect, time out, or get a 502/503/504 (default 2)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Delay before the first retry in milliseconds, doubled on each further retry (default 500)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_backoff_ms: Option<u64>,
+    /// Requests run in parallel by batch operations (default 4)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// Requests per second at most, e.g. 5 or 0.5 (default: no limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct QBConfig {
+    pub host: String,
+    /// The client running there (default: qBittorrent)
+    #[serde(rename = "type", default, skip_serializing_if = "Backend::is_default")]
+    pub kind: Backend,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Read the password from the OS keyring (stored with `rbit auth login`) instead of `password`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keyring: bool,
+    /// Accept any TLS certificate from this server, e.g. a self-signed one
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool,
+    /// PEM file with a CA, or the server's self-signed certificate, to trust
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<String>,
+    /// PEM client certificate for servers that ask for one, and its key if in a separate file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<String>,
+    /// Proxy for this server, e.g. `socks5h://127.0.0.1:1080`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Reach `host` through an SSH port forward to this destination, e.g. `me@seedbox`; `host`
+    /// is then the address as seen from that machine
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssh_tunnel: Option<String>,
+    /// Local end of the SSH port forward (default: a port picked from the destination)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tunnel_port: Option<u16>,
+    /// The server's folders and where they are on this machine, e.g. `["/downloads -> Z:\\downloads"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path_map: Vec<String>,
+    /// Log out when `rbit daemon`, `wait` or a `--follow` is stopped with Ctrl-C or SIGTERM
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub logout_on_stop: bool,
+}
+
+impl HttpConfig {
+    fn is_empty(&self) -> bool {
+        self.retries.is_none() && self.retry_backoff_ms.is_none() && self.concurrency.is_none() && self.rate_limit.is_none()
+    }
+}
+
+impl QBConfig {
+    pub fn connect_options(&self) -> ConnectOptions {
+        ConnectOptions {
+            proxy: self.proxy.clone(),
+            insecure: self.insecure,
+            ca_cert: self.ca_cert.as_ref().map(PathBuf::from),
+            client_cert: self.client_cert.as_ref().map(PathBuf::from),
+            client_key: self.client_key.as_ref().map(PathBuf::from),
+        }
+    }
+
+    pub fn path_map(&self) -> Result<PathMap, RbitError> {
+        PathMap::parse(&self.path_map)
+    }
+
+    /// Password for this profile, from `password` or, with `keyring = true`, the OS keyring.
+    pub fn resolve_password(&self, profile: &str) -> Option<String> {
+        self.password.clone().or_else(|| if self.keyring { crate::credentials::keyring_password(profile) } else { None })
+    }
+}
+
+impl Config {
+    /// Look up a server profile by name. `default` refers to the `[qbittorrent]` table.
+    pub fn profile(&self, name: &str) -> anyhow::Result<Option<&QBConfig>> {
+        if let Some(qb) = self.servers.get(name) {
+            return Ok(Some(qb));
+        }
+        if name == DEFAULT_PROFILE {
+            return Ok(self.qbittorrent.as_ref());
+        }
+        Err(RbitError::InvalidInput(format!("unknown server profile '{}' (configured: {})", name, self.profile_names().join(", "))).into())
+    }
+
+    /// Every configured server: `[qbittorrent]` first (as `default`), then `[servers.*]` by name.
+    pub fn profiles(&self) -> Vec<(String, QBConfig)> {
+        let mut out = Vec::new();
+        if let Some(ref qb) = self.qbittorrent {
+            if !self.servers.contains_key(DEFAULT_PROFILE) {
+                out.push((DEFAULT_PROFILE.to_string(), qb.clone()));
+            }
+        }
+        for (name, qb) in &self.servers {
+            out.push((name.clone(), qb.clone()));
+        }
+        out
+    }
+
+    /// Look up a saved search. Names are case-insensitive, since the config loader lowercases keys.
+    pub fn saved_search(&self, name: &str) -> anyhow::Result<&SavedSearch> {
+        match self.searches.get(&name.to_lowercase()) {
+            Some(search) => Ok(search),
+            None if self.searches.is_empty() => anyhow::bail!("no saved search '{}' (none are configured; save one with --save)", name),
+            None => {
+                let names: Vec<&str> = self.searches.keys().map(String::as_str).collect();
+                anyhow::bail!("no saved search '{}' (configured: {})", name, names.join(", "))
+            }
+        }
+    }
+
+    fn profile_names(&self) -> Vec<String> {
+        self.profiles().into_iter().map(|(name, _)| name).collect()
+    }
+}
+
+/// The per-user config file: `$XDG_CONFIG_HOME/rbit/config.toml` or the platform equivalent
+/// (`%APPDATA%\rbit\config.toml` on Windows, which roams with the profile).
+pub fn user_config_path() -> Option<PathBuf> {
+    BaseDirs::new().map(|b| b.config_dir().join("rbit").join("config.toml"))
+}
+
+/// Config files that are read, later ones overriding earlier ones: the explicit `--config`
+/// path alone if given, otherwise the user config and then a local `./rbit.toml` (for
+/// repo-level config). They may not exist.
+pub fn config_sources(path: Option<PathBuf>) -> Vec<PathBuf> {
+    match path {
+        Some(p) => vec![p],
+        None => user_config_path().into_iter().chain([PathBuf::from("rbit.toml")]).collect(),
+    }
+}
+
+/// The file among `files` whose value for `key` (e.g. `["http", "retries"]`) is the one used:
+/// the last that sets it. Keys compare case-insensitively, as the loader lowercases them.
+pub fn defined_in<'a>(files: &'a [PathBuf], key: &[&str]) -> Option<&'a Path> {
+    files.iter().rev().map(PathBuf::as_path).find(|file| {
+        let Ok(text) = fs::read_to_string(file) else { return false };
+        let Ok(table) = toml::from_str::<toml::Table>(&text) else { return false };
+        let mut value = Some(&toml::Value::Table(table));
+        for part in key {
+            value = value
+                .and_then(toml::Value::as_table)
+                .and_then(|t| t.iter().find(|(k, _)| k.eq_ignore_ascii_case(part)).map(|(_, v)| v));
+        }
+        value.is_some()
+    })
+}
+
+/// The same table with every key lowercased, as the config loader sees it.
+fn lowercase_keys(value: toml::Value) -> toml::Value {
+    match value {
+        toml::Value::Table(table) => toml::Value::Table(table.into_iter().map(|(k, v)| (k.to_lowercase(), lowercase_keys(v))).collect()),
+        toml::Value::Array(items) => toml::Value::Array(items.into_iter().map(lowercase_keys).collect()),
+        other => other,
+    }
+}
+
+/// Check one config file on its own: TOML syntax and value types (reported with line and
+/// column), and keys rbit does not know, which are returned as dotted paths.
+fn check_file(file: &Path, text: &str) -> anyhow::Result<Vec<String>> {
+    let table: toml::Table = toml::from_str(text).map_err(|e| anyhow::anyhow!("{}: {}", file.display(), e))?;
+    let mut unknown = Vec::new();
+    let checked: Result<Config, _> = serde_ignored::deserialize(lowercase_keys(toml::Value::Table(table)), |path| {
+        // optional sections show up as `?` in the path
+        unknown.push(path.to_string().replace("?.", ""))
+    });
+    if let Err(e) = checked {
+        // deserializing the text itself gives the error a position, unless it only fails
+        // because of upper-case keys
+        let located = toml::from_str::<Config>(text).err().map(|e| e.to_string()).unwrap_or_else(|| e.to_string());
+        anyhow::bail!("{}: {}", file.display(), located.trim_end());
+    }
+    Ok(unknown)
+}
+
+/// Load the configuration. Files that do not parse, or have values of the wrong type, are
+/// errors, as is a `--config` file that does not exist. Unknown keys (usually typos) are
+/// warned about, or with `strict` rejected.
+pub fn read_config(path: Option<PathBuf>, strict: bool) -> anyhow::Result<Config> {
+    if let Some(ref p) = path {
+        if !p.exists() {
+            anyhow::bail!("config file {} does not exist", p.display());
+        }
+    }
+    let mut builder = ConfigLoader::builder();
+    for file in config_sources(path) {
+        let text = match fs::read_to_string(&file) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => anyhow::bail!("{}: {}", file.display(), e),
+        };
+        let unknown = check_file(&file, &text)?;
+        if !unknown.is_empty() {
+            if strict {
+                anyhow::bail!("{}: unknown key(s) {}", file.display(), unknown.join(", "));
+            }
+            for key in &unknown {
+                eprintln!("{} {}", tr!("warning"), tr!("config-unknown-key", path = file.display().to_string(), name = key.as_str()));
+            }
+        }
+        builder = builder.add_source(ConfigFile::from(file).format(FileFormat::Toml));
+    }
+    Ok(builder.build()?.try_deserialize::<Config>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_bad_values_and_unknown_keys() {
+        let file = Path::new("rbit.toml");
+        let text = "[qbittorrent]\nhost = \"http://box\"\nPassWord = \"x\"\nhots = 1\n[htp]\nretries = 1\n";
+        assert_eq!(check_file(file, text).unwrap(), ["htp", "qbittorrent.hots"]);
+        let err = check_file(file, "[http]\nretries = \"five\"\n").unwrap_err().to_string();
+        assert!(err.contains("line 2"), "{}", err);
+        assert!(check_file(file, "[qbittorrent\n").is_err());
+    }
+}
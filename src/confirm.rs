@@ -0,0 +1,32 @@
+//! Confirmation before destructive actions (deleting torrents or their data, shutting the
+//! server down). Commands show what they are about to change and then ask; `--yes` or
+//! `no_confirm = true` in the config answers for them. Off a terminal nobody can answer, so the
+//! action is refused instead.
+
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+use crate::credentials;
+use crate::error::RbitError;
+use crate::i18n::tr;
+
+static SKIP: OnceLock<bool> = OnceLock::new();
+
+/// Skip the questions for this run, from `--yes` or `no_confirm`; later calls are ignored.
+pub fn init(skip: bool) {
+    let _ = SKIP.set(skip);
+}
+
+/// Ask `question` with a `[y/N]` prompt and return the answer. `what` names the action for the
+/// error when there is no terminal to ask on, e.g. `delete 3 torrent(s)`.
+pub fn confirm(question: &str, what: &str) -> anyhow::Result<bool> {
+    if SKIP.get().copied().unwrap_or(false) {
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(RbitError::InvalidInput(tr!("confirm-refused", what = what)).into());
+    }
+    let answer = credentials::prompt(&tr!("confirm-prompt", question = question), None)?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer.starts_with('y') || answer.starts_with(&tr!("confirm-yes")))
+}
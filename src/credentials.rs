@@ -0,0 +1,58 @@
+//! Passwords kept outside the config file: the OS keyring and interactive prompts.
+
+use std::io::{self, BufRead, Write};
+
+/// Keyring service name; entries are keyed by server profile name.
+const KEYRING_SERVICE: &str = "rbit";
+
+/// Overrides any configured password; the way to go on headless boxes without a secret service.
+pub const PASSWORD_ENV: &str = "RBIT_PASSWORD";
+
+fn entry(profile: &str) -> anyhow::Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, profile)?)
+}
+
+/// Password stored for a profile that has `keyring = true`.
+pub fn keyring_password(profile: &str) -> Option<String> {
+    match entry(profile).and_then(|e| Ok(e.get_password()?)) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            tracing::debug!("keyring lookup for '{}' failed: {}", profile, e);
+            None
+        }
+    }
+}
+
+pub fn store_password(profile: &str, password: &str) -> anyhow::Result<()> {
+    entry(profile)?
+        .set_password(password)
+        .map_err(|e| anyhow::anyhow!("could not store password in the OS keyring: {} (set ${} instead)", e, PASSWORD_ENV))
+}
+
+pub fn delete_password(profile: &str) -> anyhow::Result<()> {
+    entry(profile)?
+        .delete_credential()
+        .map_err(|e| anyhow::anyhow!("could not remove keyring entry for '{}': {}", profile, e))
+}
+
+/// Prompt on the terminal for a line of input, returning `default` if the answer is empty.
+pub fn prompt(label: &str, default: Option<&str>) -> anyhow::Result<String> {
+    match default {
+        Some(d) => print!("{} [{}]: ", label, d),
+        None => print!("{}: ", label),
+    }
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let line = line.trim();
+    if line.is_empty() {
+        Ok(default.unwrap_or_default().to_string())
+    } else {
+        Ok(line.to_string())
+    }
+}
+
+/// Prompt on the terminal for a password without echoing it.
+pub fn prompt_password(label: &str) -> anyhow::Result<String> {
+    Ok(rpassword::prompt_password(format!("{}: ", label))?)
+}
@@ -0,0 +1,79 @@
+//! The failures scripts may want to tell apart, each with its own exit code. Everything else
+//! stays a plain `anyhow` error and exits with 1.
+
+use std::fmt;
+
+use crate::i18n::tr;
+
+#[derive(Debug)]
+pub enum RbitError {
+    /// Bad arguments, host or config file
+    InvalidInput(String),
+    /// The server rejected the credentials or the session
+    Auth(String),
+    /// The server could not be reached or did not answer in time
+    Connection(String),
+    /// An id matched no torrent
+    NotFound(String),
+    /// Some items of a batch failed; the others went through
+    PartialFailure { failed: usize, total: usize },
+    /// Gave up waiting, e.g. `wait --timeout`
+    Timeout(String),
+    /// Stopped with Ctrl-C
+    Interrupted,
+    /// `exists`: some of the torrents are not on the server. Already reported on stdout, so
+    /// nothing more is printed for it.
+    Absent { missing: usize, total: usize },
+}
+
+impl RbitError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            // the code clap uses for usage errors
+            RbitError::InvalidInput(_) => 2,
+            RbitError::Auth(_) => 3,
+            RbitError::Connection(_) => 4,
+            RbitError::NotFound(_) => 5,
+            RbitError::PartialFailure { .. } => 6,
+            RbitError::Timeout(_) => 7,
+            // as a shell reports a process killed by SIGINT
+            RbitError::Interrupted => 130,
+            RbitError::Absent { .. } => 1,
+        }
+    }
+}
+
+impl fmt::Display for RbitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RbitError::InvalidInput(msg)
+            | RbitError::Auth(msg)
+            | RbitError::Connection(msg)
+            | RbitError::NotFound(msg)
+            | RbitError::Timeout(msg) => write!(f, "{}", msg),
+            RbitError::PartialFailure { failed, total } => write!(f, "{}", tr!("error-partial-failure", failed = *failed, total = *total)),
+            RbitError::Interrupted => write!(f, "{}", tr!("error-interrupted")),
+            RbitError::Absent { missing, total } => write!(f, "{}", tr!("error-absent", missing = *missing, total = *total)),
+        }
+    }
+}
+
+impl std::error::Error for RbitError {}
+
+/// The exit code for an error: that of the first [`RbitError`] in its chain, otherwise 1.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    err.chain().find_map(|e| e.downcast_ref::<RbitError>()).map_or(1, RbitError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_looks_through_context() {
+        let err = anyhow::Error::from(RbitError::NotFound("no torrent matches id 'ab'".into())).context("move failed");
+        assert_eq!(exit_code(&err), 5);
+        assert_eq!(exit_code(&anyhow::anyhow!("something else")), 1);
+        assert_eq!(RbitError::PartialFailure { failed: 2, total: 5 }.to_string(), "2 of 5 items failed");
+    }
+}
@@ -0,0 +1,416 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+/// How sizes and rates are shown: in 1024s as KiB, MiB, ... (what qBittorrent counts in), or
+/// in 1000s as kB, MB, ... (what disk vendors and some trackers use).
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Binary,
+    Si,
+}
+
+static SI: AtomicBool = AtomicBool::new(false);
+
+/// Show sizes and rates in `units` from now on.
+pub fn set_units(units: Units) {
+    SI.store(units == Units::Si, Ordering::Relaxed);
+}
+
+/// The units sizes and rates are shown in.
+pub fn units() -> Units {
+    match SI.load(Ordering::Relaxed) {
+        true => Units::Si,
+        false => Units::Binary,
+    }
+}
+
+/// Format a byte count in the current [`units`], e.g. `1.50 GiB`, or `1.61 GB` with [`Units::Si`].
+pub fn size_human(b: u64) -> String {
+    size_in(b, units())
+}
+
+fn size_in(b: u64, units: Units) -> String {
+    let (base, labels) = match units {
+        Units::Binary => (1024.0, ["KiB", "MiB", "GiB", "TiB"]),
+        Units::Si => (1000.0, ["kB", "MB", "GB", "TB"]),
+    };
+    let mut value = b as f64;
+    let mut label = None;
+    for l in labels {
+        if value < base {
+            break;
+        }
+        value /= base;
+        label = Some(l);
+    }
+    match label {
+        Some(l) => format!("{:.2} {}", value, l),
+        None => format!("{} B", b),
+    }
+}
+
+/// Format a transfer rate in bytes per second, e.g. `1.50 MiB/s`.
+pub fn rate_human(b: u64) -> String {
+    format!("{}/s", size_human(b))
+}
+
+/// Format a duration with its two largest units, e.g. `1d 4h`, `3m 05s`, `42s`.
+pub fn duration_human(d: Duration) -> String {
+    match d.as_secs() {
+        s if s >= 86400 => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+        s if s >= 3600 => format!("{}h {:02}m", s / 3600, s % 3600 / 60),
+        s if s >= 60 => format!("{}m {:02}s", s / 60, s % 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Format an ETA in seconds as the server reports it: negative values and qBittorrent's
+/// 8640000 (100 days) stand for "never" and show as `∞`.
+pub fn eta_human(secs: i64) -> String {
+    match secs {
+        0..8640000 => duration_human(Duration::from_secs(secs as u64)),
+        _ => "∞".to_string(),
+    }
+}
+
+/// A Unix timestamp as the UTC date and the seconds into that day.
+fn civil_utc(secs: u64) -> (i64, i64, i64, u64) {
+    // days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let days = (secs / 86400) as i64;
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, secs % 86400)
+}
+
+/// Format a Unix timestamp as `YYYY-MM-DD HH:MM UTC`.
+pub fn date_utc(secs: u64) -> String {
+    let (year, month, day, rem) = civil_utc(secs);
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, rem / 3600, rem % 3600 / 60)
+}
+
+/// Format a Unix timestamp to the second, as `YYYY-MM-DD HH:MM:SS` (UTC), e.g. for log lines.
+pub fn time_utc(secs: u64) -> String {
+    let (year, month, day, rem) = civil_utc(secs);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+/// Parse an ISO 8601 UTC timestamp such as `2024-05-01T12:30:00.000Z` (what Grafana sends)
+/// into Unix seconds. Fractional seconds are dropped.
+pub fn parse_date_utc(input: &str) -> anyhow::Result<u64> {
+    let bad = || anyhow::anyhow!("invalid UTC timestamp '{}' (expected e.g. 2024-05-01T12:30:00Z)", input);
+    let trimmed = input.trim().trim_end_matches('Z');
+    let (date, time) = trimmed.split_once(['T', ' ']).unwrap_or((trimmed, "00:00:00"));
+    let time = time.split('.').next().unwrap_or(time);
+    let num = |s: Option<&str>| s.and_then(|s| s.parse::<i64>().ok()).ok_or_else(bad);
+    let mut d = date.split('-');
+    let (year, month, day) = (num(d.next())?, num(d.next())?, num(d.next())?);
+    let mut t = time.split(':');
+    let (hour, minute) = (num(t.next())?, num(t.next())?);
+    let second = t.next().map(|s| num(Some(s))).transpose()?.unwrap_or(0);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return Err(bad());
+    }
+    // civil date to days since 1970-01-01, the inverse of `date_utc`
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second;
+    u64::try_from(secs).map_err(|_| bad())
+}
+
+/// Shorten `s` to `n` columns on a terminal, counting wide characters (CJK, most emoji) as two,
+/// and mark the cut with `...`. It never splits a character.
+pub fn truncate(s: &str, n: usize) -> String {
+    let mut width = 0;
+    let mut end = s.len();
+    for (i, c) in s.char_indices() {
+        width += c.width().unwrap_or(0);
+        if width > n {
+            end = i;
+            break;
+        }
+    }
+    if end == s.len() {
+        return s.to_string();
+    }
+    format!("{}...", &s[..end])
+}
+
+/// Binary units accepted by [`parse_size`], largest first. qBittorrent's Web UI counts in
+/// 1024s too, so `MB` and `MiB` (and a bare `M`) all mean 1024² bytes.
+const UNITS: [(&str, u64); 4] = [("T", 1 << 40), ("G", 1 << 30), ("M", 1 << 20), ("K", 1 << 10)];
+
+/// Parse a human-written byte count such as `500k`, `2MiB`, `1.5 MB` or `4096`.
+/// A trailing `/s` is accepted so rates can be written the way they are displayed.
+pub fn parse_size(input: &str) -> anyhow::Result<u64> {
+    let s = input.trim();
+    let s = s.strip_suffix("/s").unwrap_or(s).trim_end();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid size '{}': expected a number like 500k or 1.5MiB", input))?;
+
+    let unit = unit.trim().to_ascii_uppercase();
+    let unit = unit.strip_suffix('B').unwrap_or(&unit);
+    let unit = unit.strip_suffix('I').unwrap_or(unit);
+    let multiplier = if unit.is_empty() {
+        1
+    } else {
+        UNITS
+            .iter()
+            .find(|(u, _)| *u == unit)
+            .map(|(_, m)| *m)
+            .ok_or_else(|| anyhow::anyhow!("invalid size '{}': unknown unit (use B, K, M, G or T)", input))?
+    };
+
+    let bytes = (number * multiplier as f64).round();
+    if !bytes.is_finite() || bytes > u64::MAX as f64 {
+        anyhow::bail!("invalid size '{}': too large", input);
+    }
+    Ok(bytes as u64)
+}
+
+/// A speed limit in bytes per second, as taken on the command line and in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rate {
+    Unlimited,
+    BytesPerSec(u64),
+}
+
+impl Rate {
+    /// The value qBittorrent's limit endpoints expect, where 0 means unlimited.
+    pub fn to_api(self) -> u64 {
+        match self {
+            Rate::Unlimited => 0,
+            Rate::BytesPerSec(b) => b,
+        }
+    }
+
+    /// Read a limit returned by qBittorrent (0 or negative for unlimited).
+    pub fn from_api(value: i64) -> Self {
+        if value <= 0 {
+            Rate::Unlimited
+        } else {
+            Rate::BytesPerSec(value as u64)
+        }
+    }
+}
+
+impl FromStr for Rate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "unlimited" | "none" | "off" | "inf" => Ok(Rate::Unlimited),
+            _ => Ok(Rate::from_api(parse_size(s)?.min(i64::MAX as u64) as i64)),
+        }
+    }
+}
+
+/// Exact form that parses back to the same value: the largest unit that divides the rate evenly,
+/// e.g. `5MiB/s`, `1536KiB/s` or `100B/s`. For rounded table output use [`rate_human`].
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let b = match self {
+            Rate::Unlimited => return f.write_str("unlimited"),
+            Rate::BytesPerSec(b) => *b,
+        };
+        match UNITS.iter().find(|(_, m)| b % m == 0) {
+            Some((unit, m)) => write!(f, "{}{}iB/s", b / m, unit),
+            None => write!(f, "{}B/s", b),
+        }
+    }
+}
+
+/// Parse a share ratio limit: a non-negative number, `global` (qBittorrent's -2) or `unlimited` (-1).
+pub fn parse_ratio_limit(input: &str) -> anyhow::Result<f64> {
+    match input.trim().to_ascii_lowercase().as_str() {
+        "global" | "default" => Ok(-2.0),
+        "unlimited" | "none" | "off" | "inf" => Ok(-1.0),
+        s => match s.parse::<f64>() {
+            Ok(r) if r >= 0.0 && r.is_finite() => Ok(r),
+            _ => anyhow::bail!("invalid ratio '{}': expected a number like 2.0, 'global' or 'unlimited'", input),
+        },
+    }
+}
+
+/// Parse a time limit into minutes: a bare number of minutes or a number with an `m`, `h` or `d`
+/// unit (`90m`, `36h`, `1.5d`), `global` (qBittorrent's -2) or `unlimited` (-1).
+pub fn parse_minutes_limit(input: &str) -> anyhow::Result<i64> {
+    let s = input.trim().to_ascii_lowercase();
+    match s.as_str() {
+        "global" | "default" => return Ok(-2),
+        "unlimited" | "none" | "off" | "inf" => return Ok(-1),
+        _ => {}
+    }
+    let (number, minutes) = match s.char_indices().last() {
+        Some((i, 'm')) => (&s[..i], 1.0),
+        Some((i, 'h')) => (&s[..i], 60.0),
+        Some((i, 'd')) => (&s[..i], 1440.0),
+        _ => (s.as_str(), 1.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n >= 0.0 && n.is_finite() => Ok((n * minutes).round() as i64),
+        _ => anyhow::bail!("invalid time '{}': expected minutes or a duration like 36h or 7d", input),
+    }
+}
+
+/// Parse a duration: a bare number of seconds or a number with an `s`, `m`, `h` or `d` unit
+/// (`90s`, `30m`, `2h`, `1.5d`).
+pub fn parse_duration(input: &str) -> anyhow::Result<Duration> {
+    let s = input.trim().to_ascii_lowercase();
+    let (number, seconds) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1.0),
+        Some((i, 'm')) => (&s[..i], 60.0),
+        Some((i, 'h')) => (&s[..i], 3600.0),
+        Some((i, 'd')) => (&s[..i], 86400.0),
+        _ => (s.as_str(), 1.0),
+    };
+    match number.trim().parse::<f64>() {
+        Ok(n) if n >= 0.0 && n.is_finite() => Ok(Duration::from_secs_f64(n * seconds)),
+        _ => anyhow::bail!("invalid duration '{}': expected seconds or a duration like 30m or 2h", input),
+    }
+}
+
+/// Show a share ratio limit as set by `parse_ratio_limit`.
+pub fn ratio_limit_human(ratio: f64) -> String {
+    if ratio <= -2.0 {
+        "global".to_string()
+    } else if ratio < 0.0 {
+        "unlimited".to_string()
+    } else {
+        format!("{:.2}", ratio)
+    }
+}
+
+/// Show a time limit in minutes as set by `parse_minutes_limit`, e.g. `1d 12h`.
+pub fn minutes_limit_human(minutes: i64) -> String {
+    match minutes {
+        m if m <= -2 => "global".to_string(),
+        m if m < 0 => "unlimited".to_string(),
+        m => {
+            let parts = [(m / 1440, "d"), (m % 1440 / 60, "h"), (m % 60, "m")];
+            let shown: Vec<String> = parts.iter().filter(|(n, _)| *n > 0).map(|(n, u)| format!("{}{}", n, u)).collect();
+            if shown.is_empty() {
+                "0m".to_string()
+            } else {
+                shown.join(" ")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_sizes_rates_and_durations() {
+        assert_eq!(size_in(512, Units::Binary), "512 B");
+        assert_eq!(size_in(3 << 30, Units::Binary), "3.00 GiB");
+        assert_eq!(size_in(5 << 40, Units::Binary), "5.00 TiB");
+        assert_eq!(size_in(1_500_000, Units::Si), "1.50 MB");
+        assert_eq!(size_in(999, Units::Si), "999 B");
+        assert_eq!(duration_human(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(duration_human(Duration::from_secs(90000)), "1d 1h");
+        assert_eq!(eta_human(42), "42s");
+        assert_eq!(eta_human(8640000), "∞");
+        assert_eq!(eta_human(-1), "∞");
+    }
+
+    #[test]
+    fn truncates_by_display_width() {
+        assert_eq!(truncate("ubuntu.iso", 10), "ubuntu.iso");
+        assert_eq!(truncate("ubuntu-24.04.iso", 6), "ubuntu...");
+        assert_eq!(truncate("Ünïcödé name", 5), "Ünïcö...");
+        assert_eq!(truncate("日本語のファイル", 5), "日本...");
+    }
+
+    #[test]
+    fn parses_sizes_with_units() {
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("500k").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("2MiB").unwrap(), 2 << 20);
+        assert_eq!(parse_size("1.5 MB/s").unwrap(), 3 << 19);
+        assert_eq!(parse_size(" 1g ").unwrap(), 1 << 30);
+        assert_eq!(parse_size("10B").unwrap(), 10);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(parse_size("").is_err());
+        assert!(parse_size("fast").is_err());
+        assert!(parse_size("5 parsecs").is_err());
+        assert!(parse_size("1..5M").is_err());
+    }
+
+    #[test]
+    fn rate_unlimited_and_api_values() {
+        assert_eq!("unlimited".parse::<Rate>().unwrap(), Rate::Unlimited);
+        assert_eq!("0".parse::<Rate>().unwrap(), Rate::Unlimited);
+        assert_eq!(Rate::from_api(-1), Rate::Unlimited);
+        assert_eq!("5M".parse::<Rate>().unwrap().to_api(), 5 << 20);
+    }
+
+    #[test]
+    fn rate_display_round_trips() {
+        for input in ["unlimited", "5M", "1.5M", "100", "1023", "3G", "2.5k"] {
+            let rate: Rate = input.parse().unwrap();
+            assert_eq!(rate.to_string().parse::<Rate>().unwrap(), rate, "{} -> {}", input, rate);
+        }
+        assert_eq!("1.5M".parse::<Rate>().unwrap().to_string(), "1536KiB/s");
+    }
+
+    #[test]
+    fn share_limits() {
+        assert_eq!(parse_ratio_limit("2").unwrap(), 2.0);
+        assert_eq!(parse_ratio_limit("global").unwrap(), -2.0);
+        assert_eq!(parse_ratio_limit("unlimited").unwrap(), -1.0);
+        assert!(parse_ratio_limit("-3").is_err());
+        assert_eq!(parse_minutes_limit("90").unwrap(), 90);
+        assert_eq!(parse_minutes_limit("36h").unwrap(), 36 * 60);
+        assert_eq!(parse_minutes_limit("1.5d").unwrap(), 2160);
+        assert!(parse_minutes_limit("soon").is_err());
+        assert_eq!(minutes_limit_human(2160), "1d 12h");
+        assert_eq!(minutes_limit_human(-2), "global");
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert!(parse_duration("-1m").is_err());
+    }
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(date_utc(0), "1970-01-01 00:00 UTC");
+        assert_eq!(date_utc(1_700_000_000), "2023-11-14 22:13 UTC");
+        assert_eq!(date_utc(951_782_400), "2000-02-29 00:00 UTC");
+        assert_eq!(time_utc(1_700_000_005), "2023-11-14 22:13:25");
+    }
+
+    #[test]
+    fn parses_utc_dates() {
+        assert_eq!(parse_date_utc("1970-01-01T00:00:00Z").unwrap(), 0);
+        assert_eq!(parse_date_utc("2023-11-14T22:13:20.000Z").unwrap(), 1_700_000_000);
+        assert_eq!(parse_date_utc("2000-02-29").unwrap(), 951_782_400);
+        assert!(parse_date_utc("2024-13-01T00:00:00Z").is_err());
+        assert!(parse_date_utc("yesterday").is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! Shell-style wildcards, for the patterns `rbit add` expands itself (when quoted, or on
+//! Windows, where the shell leaves them alone) and for selecting torrents by name.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether `s` has a `*` or `?` in it.
+pub fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Shell-style match of one path component: `*` is any run of characters, `?` any one.
+/// Like the shell, a leading `.` has to be matched literally.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            // let the last `*` swallow one more character
+            star = Some((sp, sn + 1));
+            pi = sp + 1;
+            ni = sn + 1;
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Files matching a pattern with wildcards in any path component, sorted.
+pub fn expand_glob(pattern: &str) -> Vec<PathBuf> {
+    let mut found = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let part = component.as_os_str().to_string_lossy();
+        if !has_wildcard(&part) {
+            for path in &mut found {
+                path.push(component);
+            }
+            continue;
+        }
+        let mut next = Vec::new();
+        for dir in &found {
+            let read_from = if dir.as_os_str().is_empty() { Path::new(".") } else { dir.as_path() };
+            let Ok(entries) = fs::read_dir(read_from) else { continue };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if wildcard_match(&part, &name) {
+                    next.push(dir.join(name));
+                }
+            }
+        }
+        found = next;
+    }
+    let mut files: Vec<PathBuf> = found.into_iter().filter(|p| p.is_file()).collect();
+    files.sort();
+    files
+}
+
+/// The .torrent files in `dir` (and below it with `recursive`), sorted. Hidden entries are skipped.
+pub fn torrent_files_in(dir: &Path, recursive: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| anyhow::anyhow!("{}: {}", dir.display(), e))? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            if recursive {
+                files.extend(torrent_files_in(&path, true)?);
+            }
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("torrent")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_match_like_the_shell() {
+        assert!(wildcard_match("*.torrent", "ubuntu.torrent"));
+        assert!(wildcard_match("ubuntu-??.04*", "ubuntu-24.04-desktop.iso"));
+        assert!(!wildcard_match("ubuntu-?.04*", "ubuntu-24.04-desktop.iso"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(!wildcard_match("a*b*c", "aXbYbZ"));
+        // case counts; `select` lowercases both sides itself
+        assert!(!wildcard_match("*.TORRENT", "a.torrent"));
+        // `?` is one character, not one byte
+        assert!(wildcard_match("K?nig*", "König der Löwen"));
+        assert!(wildcard_match("*ö*", "Löwen"));
+        assert!(!wildcard_match("*", ".hidden.torrent"));
+        assert!(wildcard_match(".*", ".hidden.torrent"));
+        assert!(wildcard_match("", "") && !wildcard_match("", "a"));
+    }
+
+    #[test]
+    fn patterns_and_folders_expand_to_torrent_files() {
+        let dir = std::env::temp_dir().join(format!("rbit-glob-{}", std::process::id()));
+        fs::create_dir_all(dir.join("season 1")).unwrap();
+        fs::create_dir_all(dir.join(".trash")).unwrap();
+        for file in ["b.torrent", "a.TORRENT", "notes.txt", ".c.torrent", "season 1/e01.torrent", ".trash/old.torrent", "Über.torrent"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        let names = |files: Vec<PathBuf>| files.iter().map(|f| f.strip_prefix(&dir).unwrap().display().to_string()).collect::<Vec<_>>();
+
+        assert_eq!(names(torrent_files_in(&dir, false).unwrap()), ["a.TORRENT", "b.torrent", "Über.torrent"]);
+        assert_eq!(names(torrent_files_in(&dir, true).unwrap()), ["a.TORRENT", "b.torrent", "season 1/e01.torrent", "Über.torrent"]);
+        assert!(torrent_files_in(&dir.join("missing"), false).unwrap_err().to_string().contains("missing"));
+
+        let glob = |pattern: &str| names(expand_glob(&format!("{}/{}", dir.display(), pattern)));
+        assert_eq!(glob("*.torrent"), ["b.torrent", "Über.torrent"]);
+        assert_eq!(glob("?.*"), ["a.TORRENT", "b.torrent"]);
+        assert_eq!(glob("season*/*"), ["season 1/e01.torrent"]);
+        assert_eq!(glob("Ü*"), ["Über.torrent"]);
+        assert!(glob("missing/*").is_empty());
+        assert!(has_wildcard("*.torrent") && !has_wildcard("a.torrent"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
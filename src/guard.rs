@@ -0,0 +1,75 @@
+//! `[guard]`: a kill switch for `rbit daemon`. Before each pass it runs a check command, e.g.
+//! one that makes sure the VPN is up; while the check fails, the daemon pauses every torrent,
+//! and those that turn up on the server, and adds nothing from its folders. Once the check
+//! passes again it resumes the torrents it paused.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::TorrentInfo;
+use crate::hooks::shell;
+
+/// How long a check may take before it counts as failed.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `[guard]`: the condition torrents may run under
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GuardConfig {
+    /// Shell command that exits 0 while torrents may run, e.g. `ip link show wg0 up | grep -q UP`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl GuardConfig {
+    pub fn is_empty(&self) -> bool {
+        self.command.is_none()
+    }
+}
+
+/// Run the check `command`; why it failed, if it did. One that cannot be started or takes
+/// longer than [`TIMEOUT`] fails too.
+pub async fn check(command: &str) -> Result<(), String> {
+    check_within(command, TIMEOUT).await
+}
+
+async fn check_within(command: &str, timeout: Duration) -> Result<(), String> {
+    tracing::debug!("running the guard check: {}", command);
+    let mut child = tokio::process::Command::from(shell(command))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("could not run it: {}", e))?;
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(status.to_string()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => {
+            let _ = child.kill().await;
+            Err(format!("no answer in {}s", timeout.as_secs()))
+        }
+    }
+}
+
+/// Whether `t` is running, downloading or seeding, and so connects to peers.
+pub fn running(t: &TorrentInfo) -> bool {
+    !t.state.starts_with("paused") && !t.state.starts_with("stopped")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn checks_pass_on_exit_zero() {
+        assert_eq!(check("true").await, Ok(()));
+        assert!(check("exit 3").await.unwrap_err().contains('3'));
+        // one that hangs is given up on, and killed
+        let started = std::time::Instant::now();
+        assert!(check_within("sleep 10", Duration::from_millis(300)).await.unwrap_err().starts_with("no answer"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
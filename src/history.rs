@@ -0,0 +1,279 @@
+//! The stats history: snapshots of the server's transfer totals and per-torrent counters,
+//! appended one JSON object per line to `stats-<host>.jsonl` in rbit's data directory
+//! (`~/.local/share/rbit` on Linux).
+//!
+//! Every recorder goes through [`snapshot`] and [`append`], so the file has one schema no matter
+//! what wrote it. Besides `rbit stats record`, `rbit list` and `rbit daemon` record one now and
+//! then (see [`record_due`]) unless `[history] auto_record = false`.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{MainData, Session, TorrentInfo, TransferInfo};
+use crate::cache;
+use crate::i18n::tr;
+use crate::lock;
+
+/// Snapshots recorded along the way by `list` and the daemon are at least this far apart.
+pub const AUTO_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// `[history]`
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HistoryConfig {
+    /// Record snapshots while `rbit list` and `rbit daemon` run (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_record: Option<bool>,
+}
+
+impl HistoryConfig {
+    pub fn is_empty(&self) -> bool {
+        self.auto_record.is_none()
+    }
+
+    pub fn auto_record(&self) -> bool {
+        self.auto_record.unwrap_or(true)
+    }
+}
+
+/// Where rbit keeps its data: `~/.local/share/rbit` on Linux, `~/Library/Application
+/// Support/rbit` on macOS. On Windows it is `%LOCALAPPDATA%\rbit\data`, so a growing history
+/// does not roam with the profile; data an older rbit left in `%APPDATA%\rbit` is moved there.
+pub fn data_dir() -> Option<PathBuf> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dirs = BaseDirs::new()?;
+        if !cfg!(windows) {
+            return Some(dirs.data_dir().join("rbit"));
+        }
+        let dir = dirs.data_local_dir().join("rbit").join("data");
+        if let Err(e) = move_old_data(&dirs.data_dir().join("rbit"), &dir) {
+            eprintln!("{} {}", tr!("warning"), tr!("data-not-moved", path = dir.display().to_string(), reason = format!("{:#}", e)));
+        }
+        Some(dir)
+    })
+    .clone()
+}
+
+/// Move what rbit keeps in its data directory from `old` to `new`, unless `new` exists already.
+/// `old` may also hold the config, which stays.
+fn move_old_data(old: &Path, new: &Path) -> anyhow::Result<()> {
+    if new.exists() || !old.is_dir() {
+        return Ok(());
+    }
+    let ours = |name: &str| name == "notes.json" || name == "postprocess" || (name.starts_with("stats-") && name.ends_with(".jsonl"));
+    for entry in fs::read_dir(old)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if ours(&name) {
+            fs::create_dir_all(new)?;
+            fs::rename(entry.path(), new.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Default history file for a server.
+pub fn history_path(host: &str) -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(format!("stats-{}.jsonl", cache::file_key(host))))
+}
+
+/// One line of the history file.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Snapshot {
+    /// Unix time the snapshot was taken
+    pub time: u64,
+    pub host: String,
+    /// Speeds in bytes/s
+    pub dl_speed: u64,
+    pub up_speed: u64,
+    /// Bytes transferred since qBittorrent started
+    pub session_dl: u64,
+    pub session_ul: u64,
+    pub alltime_dl: Option<u64>,
+    pub alltime_ul: Option<u64>,
+    pub free_space: Option<u64>,
+    pub peers: Option<u64>,
+    #[serde(default)]
+    pub torrents: Vec<TorrentSample>,
+}
+
+/// A torrent's counters at the time of a snapshot.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TorrentSample {
+    pub hash: String,
+    pub name: String,
+    pub state: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub category: String,
+    pub progress: f64,
+    pub size: u64,
+    pub downloaded: u64,
+    pub uploaded: u64,
+    pub ratio: f64,
+}
+
+/// Query the server for a snapshot of its current state.
+pub async fn snapshot(session: &Session) -> anyhow::Result<Snapshot> {
+    let torrents = session.torrents("all").await?;
+    snapshot_with(session, torrents).await
+}
+
+/// A snapshot of the server with `torrents`, every torrent on it, already fetched.
+async fn snapshot_with(session: &Session, torrents: Vec<TorrentInfo>) -> anyhow::Result<Snapshot> {
+    let (transfer, maindata) = tokio::try_join!(
+        session.get_json::<TransferInfo>("transfer/info"),
+        session.get_json::<MainData>("sync/maindata"),
+    )?;
+    let state = maindata.server_state.unwrap_or_default();
+    Ok(Snapshot {
+        time: cache::now(),
+        host: session.host.clone(),
+        dl_speed: transfer.dl_info_speed,
+        up_speed: transfer.up_info_speed,
+        session_dl: transfer.dl_info_data,
+        session_ul: transfer.up_info_data,
+        alltime_dl: state.alltime_dl,
+        alltime_ul: state.alltime_ul,
+        free_space: state.free_space_on_disk,
+        peers: state.total_peer_connections,
+        torrents: torrents
+            .into_iter()
+            .map(|t| TorrentSample {
+                hash: t.hash,
+                name: t.name,
+                state: t.state,
+                category: t.category.unwrap_or_default(),
+                progress: t.progress.unwrap_or(0.0),
+                size: t.size.unwrap_or(0),
+                downloaded: t.downloaded.unwrap_or(0),
+                uploaded: t.uploaded.unwrap_or(0),
+                ratio: t.ratio.unwrap_or(0.0),
+            })
+            .collect(),
+    })
+}
+
+/// Record a snapshot in the server's history file, from `torrents` (every torrent on it), unless
+/// the file changed less than [`AUTO_INTERVAL`] ago. For commands that list the torrents anyway;
+/// a failure is only logged, it never fails the command.
+pub async fn record_due(session: &Session, torrents: &[TorrentInfo]) {
+    let Some(path) = history_path(&session.host) else {
+        return;
+    };
+    if session.dry_run {
+        return;
+    }
+    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+    if modified.is_some_and(|m| SystemTime::now().duration_since(m).unwrap_or_default() < AUTO_INTERVAL) {
+        return;
+    }
+    let recorded = match snapshot_with(session, torrents.to_vec()).await {
+        Ok(snapshot) => append(&path, &snapshot),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = recorded {
+        tracing::debug!("could not record a history snapshot: {:#}", e);
+    }
+}
+
+/// Record a snapshot now, as `rbit daemon` does when it is stopped, so the history does not end
+/// up to [`AUTO_INTERVAL`] before it did. A failure is only logged.
+pub async fn record_now(session: &Session) {
+    let Some(path) = history_path(&session.host) else {
+        return;
+    };
+    if session.dry_run {
+        return;
+    }
+    let recorded = match snapshot(session).await {
+        Ok(snapshot) => append(&path, &snapshot),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = recorded {
+        tracing::debug!("could not record a history snapshot: {:#}", e);
+    }
+}
+
+/// Append a snapshot as one line, creating the file (and its directory) if needed.
+pub fn append(path: &Path, snapshot: &Snapshot) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_vec(snapshot)?;
+    line.push(b'\n');
+    // a single write with O_APPEND, under a lock for systems where that alone does not keep
+    // concurrent recorders from interleaving lines
+    let _lock = lock::exclusive(&format!("history-{}", path.display()))?;
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)?;
+    Ok(())
+}
+
+/// Read every snapshot in a history file, oldest first. Lines that do not parse (say, one cut
+/// short by a full disk) are skipped.
+pub fn read(path: &Path) -> anyhow::Result<Vec<Snapshot>> {
+    let file = fs::File::open(path).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+    let mut snapshots = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(snapshot) = serde_json::from_str::<Snapshot>(&line?) {
+            snapshots.push(snapshot);
+        }
+    }
+    Ok(snapshots)
+}
+
+/// The snapshots taken at or after `from`, of a history read oldest first.
+pub fn since(snapshots: &[Snapshot], from: u64) -> &[Snapshot] {
+    &snapshots[snapshots.partition_point(|s| s.time < from)..]
+}
+
+/// How much a counter grew over a series of readings. A reading below the one before means the
+/// counter started again from zero (qBittorrent restarted, or the torrent was added again), so
+/// it counts in full.
+pub fn growth(readings: impl IntoIterator<Item = u64>) -> u64 {
+    let mut total = 0;
+    let mut last: Option<u64> = None;
+    for value in readings {
+        total += match last {
+            Some(before) if value >= before => value - before,
+            Some(_) => value,
+            None => 0,
+        };
+        last = Some(value);
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn growth_across_restarts() {
+        assert_eq!(growth([100, 150, 400]), 300);
+        // restarted after 400: the session counter starts again at 0
+        assert_eq!(growth([100, 400, 50, 80]), 380);
+        assert_eq!(growth([7]), 0);
+        assert_eq!(growth([]), 0);
+    }
+
+    #[test]
+    fn old_data_moves_and_the_config_stays() {
+        let old = std::env::temp_dir().join(format!("rbit-roaming-{}", std::process::id()));
+        let new = old.join("local").join("data");
+        fs::create_dir_all(old.join("postprocess")).unwrap();
+        for file in ["config.toml", "notes.json", "stats-seedbox.jsonl"] {
+            fs::write(old.join(file), "").unwrap();
+        }
+        move_old_data(&old, &new).unwrap();
+        assert!(new.join("notes.json").exists() && new.join("stats-seedbox.jsonl").exists() && new.join("postprocess").is_dir());
+        assert!(old.join("config.toml").exists() && !old.join("notes.json").exists());
+        fs::remove_dir_all(&old).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+//! Torrents `rbit daemon` pauses while a condition lasts (the disk low on space, a `[guard]`
+//! check failing) and resumes once it ends. Only the torrents it paused are resumed, so those
+//! paused by hand stay paused.
+
+use std::collections::HashSet;
+
+use crate::api::TorrentInfo;
+
+/// What a pass changed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Change {
+    Started,
+    Ended,
+}
+
+/// Whether the condition holds, and the torrents paused for it.
+#[derive(Debug, Default)]
+pub struct Hold {
+    on: bool,
+    /// Hashes of the torrents paused for the condition, to resume just those
+    paused: Vec<String>,
+    /// Torrents on the server at the last pass, to tell which are new
+    known: HashSet<String>,
+    primed: bool,
+}
+
+impl Hold {
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// Take in whether the condition holds now and the torrents on the server. Returns what
+    /// changed, if anything, and with `pause` the hashes of the torrents to pause now: those
+    /// `wanted` picks when it starts, and after that the ones added while it lasts.
+    pub fn update(&mut self, on: bool, pause: bool, torrents: &[TorrentInfo], wanted: impl Fn(&TorrentInfo) -> bool) -> (Option<Change>, Vec<String>) {
+        let change = match (self.on, on) {
+            (false, true) => Some(Change::Started),
+            (true, false) => Some(Change::Ended),
+            _ => None,
+        };
+        self.on = on;
+        let mut to_pause = Vec::new();
+        if on && pause {
+            let started = change == Some(Change::Started);
+            for t in torrents.iter().filter(|t| wanted(t)) {
+                let hash = t.hash.to_lowercase();
+                if (started || (self.primed && !self.known.contains(&hash))) && !self.paused.contains(&hash) {
+                    to_pause.push(hash);
+                }
+            }
+            self.paused.extend(to_pause.iter().cloned());
+        }
+        self.known = torrents.iter().map(|t| t.hash.to_lowercase()).collect();
+        self.primed = true;
+        (change, to_pause)
+    }
+
+    /// Take over torrents another hold paused, to resume them when this one ends.
+    pub fn adopt(&mut self, hashes: Vec<String>) {
+        for hash in hashes {
+            if !self.paused.contains(&hash) {
+                self.paused.push(hash);
+            }
+        }
+    }
+
+    /// The torrents to resume now that the condition ended, forgetting them.
+    pub fn take_paused(&mut self) -> Vec<String> {
+        let known = &self.known;
+        let mut paused = std::mem::take(&mut self.paused);
+        // those deleted while paused are gone
+        paused.retain(|h| known.contains(h));
+        paused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(hash: &str, state: &str) -> TorrentInfo {
+        serde_json::from_value(serde_json::json!({ "name": hash, "hash": hash, "state": state })).unwrap()
+    }
+
+    #[test]
+    fn pauses_what_runs_then_what_is_added() {
+        let running = |t: &TorrentInfo| !t.state.starts_with("paused");
+        let mut hold = Hold::default();
+        let before = [torrent("aa", "downloading"), torrent("bb", "pausedUP")];
+        assert_eq!(hold.update(false, true, &before, running), (None, vec![]));
+        assert_eq!(hold.update(true, true, &before, running), (Some(Change::Started), vec!["aa".to_string()]));
+        let added = [torrent("aa", "pausedDL"), torrent("bb", "pausedUP"), torrent("cc", "metaDL")];
+        assert_eq!(hold.update(true, true, &added, running), (None, vec!["cc".to_string()]));
+        // resumed by hand: left alone
+        let by_hand = [torrent("aa", "downloading"), torrent("bb", "pausedUP"), torrent("cc", "pausedDL")];
+        assert_eq!(hold.update(true, true, &by_hand, running), (None, vec![]));
+        assert!(hold.is_on());
+        assert_eq!(hold.update(false, true, &by_hand[..2], running).0, Some(Change::Ended));
+        assert_eq!(hold.take_paused(), ["aa"]);
+        hold.adopt(vec!["bb".to_string(), "bb".to_string()]);
+        assert_eq!(hold.take_paused(), ["bb"]);
+
+        // without `pause` it only tells
+        let mut hold = Hold::default();
+        assert_eq!(hold.update(true, false, &before, running), (Some(Change::Started), vec![]));
+        assert!(hold.take_paused().is_empty());
+    }
+}
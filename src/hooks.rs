@@ -0,0 +1,72 @@
+//! Commands rbit runs on the client machine when a torrent it is watching finishes (`wait`,
+//! `add --follow`, `daemon`), from `--on-complete` or the `[hooks]` config table. qBittorrent's own "Run
+//! external program" runs on the server instead.
+//!
+//! The command goes through the shell (`sh -c`, `cmd /C` on Windows) with the torrent described
+//! in `RBIT_*` environment variables.
+
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::TorrentInfo;
+
+/// `[hooks]`: commands run when a watched torrent finishes
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HooksConfig {
+    /// Shell command, e.g. `notify-send "$RBIT_NAME is done"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_complete: Option<String>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.on_complete.is_none()
+    }
+}
+
+/// The environment a hook gets for a torrent.
+fn hook_env(t: &TorrentInfo, host: &str) -> Vec<(&'static str, String)> {
+    vec![
+        ("RBIT_NAME", t.name.clone()),
+        ("RBIT_HASH", t.hash.to_lowercase()),
+        ("RBIT_SAVE_PATH", t.save_path.clone().unwrap_or_default()),
+        ("RBIT_CONTENT_PATH", t.content_path.clone().unwrap_or_default()),
+        ("RBIT_CATEGORY", t.category.clone().unwrap_or_default()),
+        ("RBIT_TAGS", t.tags.clone().unwrap_or_default()),
+        ("RBIT_SIZE", t.size.unwrap_or(0).to_string()),
+        ("RBIT_HOST", host.to_string()),
+    ]
+}
+
+/// `command` run through the shell.
+pub fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+/// Run `command` for a finished torrent and wait for it, without holding up the runtime; fails
+/// if it cannot be started or exits unsuccessfully.
+pub async fn on_complete(command: &str, t: &TorrentInfo, host: &str, dry_run: bool) -> anyhow::Result<()> {
+    if dry_run {
+        println!("[dry-run] would run `{}` for {}", command, t.name);
+        return Ok(());
+    }
+    tracing::debug!("running on-complete hook for {}: {}", t.name, command);
+    let status = tokio::process::Command::from(shell(command))
+        .envs(hook_env(t, host))
+        .status()
+        .await
+        .map_err(|e| anyhow::anyhow!("could not run the on-complete hook: {}", e))?;
+    if !status.success() {
+        anyhow::bail!("the on-complete hook failed for {} ({})", t.name, status);
+    }
+    Ok(())
+}
@@ -0,0 +1,308 @@
+//! What every request to the qBittorrent Web UI passes through: an optional rate limit
+//! (`[http] rate_limit`), so that `rbit daemon` and big batches do not hammer the server, the
+//! time each endpoint took, summed up in the `-v` log when the run ends, and `--trace-http`,
+//! which records each request with its response for bug reports.
+//!
+//! The trace is a JSON object per line. Secrets are replaced as in the log: secret form fields,
+//! cookies and `Authorization` headers, and the secret members of JSON bodies. URLs of other
+//! hosts (torrent files fetched from trackers) keep only their host, since their paths and
+//! queries often carry a passkey.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Request, Response, ResponseBuilderExt};
+use serde_json::{json, Map, Value};
+
+use crate::format::time_utc;
+use crate::logging::{redact_cookie, redact_url, redact_value, REDACTED};
+
+/// Bodies are cut short in the trace after this many bytes
+const TRACE_BODY_LIMIT: usize = 64 * 1024;
+
+static TRACE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Append a record of every request from now on to `path`, readable only by you.
+pub fn trace_to(path: &Path) -> anyhow::Result<()> {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let file = options.open(path).map_err(|e| anyhow::anyhow!("--trace-http {}: {}", path.display(), e))?;
+    let _ = TRACE.set(Mutex::new(file));
+    Ok(())
+}
+
+struct Throttle {
+    interval: Duration,
+    /// When the next request may go out
+    next: Mutex<Instant>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Timing {
+    count: u32,
+    failed: u32,
+    total: Duration,
+    max: Duration,
+}
+
+pub struct Middleware {
+    host: String,
+    throttle: Option<Throttle>,
+    timings: Mutex<BTreeMap<String, Timing>>,
+}
+
+impl Middleware {
+    /// For requests to the Web UI at `host`, without a rate limit.
+    pub fn new(host: &str) -> Middleware {
+        Middleware { host: host.to_string(), throttle: None, timings: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Send at most `per_second` requests a second; `None` (or 0) for no limit.
+    pub fn set_rate_limit(&mut self, per_second: Option<f64>) {
+        self.throttle = per_second
+            .filter(|r| r.is_finite() && *r > 0.0)
+            .map(|r| Throttle { interval: Duration::from_secs_f64(1.0 / r), next: Mutex::new(Instant::now()) });
+    }
+
+    /// Wait for the next free slot under the rate limit.
+    async fn wait_turn(&self) {
+        let Some(ref throttle) = self.throttle else {
+            return;
+        };
+        let wait = {
+            let mut next = throttle.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let turn = (*next).max(now);
+            *next = turn + throttle.interval;
+            turn - now
+        };
+        if !wait.is_zero() {
+            tracing::trace!("rate limit: waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn is_own(&self, request: &Request) -> bool {
+        request.url().as_str().starts_with(&self.host)
+    }
+
+    /// What the timings are kept by: `POST torrents/info` for the Web UI, `GET tracker.example`
+    /// for other hosts.
+    fn endpoint(&self, request: &Request) -> String {
+        let url = request.url();
+        let path = match url.as_str().strip_prefix(&format!("{}/api/v2/", self.host)) {
+            Some(rest) => rest.split(['?', '#']).next().unwrap_or_default().to_string(),
+            _ => url.host_str().unwrap_or_default().to_string(),
+        };
+        format!("{} {}", request.method(), path)
+    }
+
+    /// Send `request` with `client`: in its turn under the rate limit, timed, and traced with
+    /// `--trace-http`.
+    pub async fn send(&self, client: &Client, request: Request) -> reqwest::Result<Response> {
+        self.wait_turn().await;
+        let endpoint = self.endpoint(&request);
+        let traced = TRACE.get().map(|_| self.describe(&request));
+        let started = Instant::now();
+        let mut result = client.execute(request).await;
+        if let Some(request) = traced {
+            result = match result {
+                Ok(res) => traced_response(request, res, started).await,
+                Err(e) => {
+                    write_trace(request, json!({ "error": e.to_string() }), started);
+                    Err(e)
+                }
+            };
+        }
+        self.record(endpoint, started.elapsed(), result.as_ref().is_ok_and(|res| res.status().is_success()));
+        result
+    }
+
+    fn record(&self, endpoint: String, elapsed: Duration, ok: bool) {
+        let mut timings = self.timings.lock().unwrap_or_else(|e| e.into_inner());
+        let timing = timings.entry(endpoint).or_default();
+        timing.count += 1;
+        timing.failed += u32::from(!ok);
+        timing.total += elapsed;
+        timing.max = timing.max.max(elapsed);
+    }
+
+    /// The request as the trace shows it.
+    fn describe(&self, request: &Request) -> Value {
+        let url = request.url();
+        let shown = match self.is_own(request) {
+            true => url.to_string(),
+            false => redact_url(url.as_str()),
+        };
+        let form = request.headers().get(reqwest::header::CONTENT_TYPE).is_some_and(|v| v.as_bytes().starts_with(b"application/x-www-form-urlencoded"));
+        let body = match request.body().map(|b| b.as_bytes()) {
+            None => Value::Null,
+            Some(Some(bytes)) if form => form_json(bytes),
+            Some(Some(bytes)) => body_json(bytes),
+            // multipart uploads of .torrent files are streamed
+            Some(None) => json!("<multipart form, not recorded>"),
+        };
+        json!({ "method": request.method().as_str(), "url": shown, "headers": headers_json(request.headers()), "body": body })
+    }
+}
+
+/// The timings as log lines: the total, then each endpoint, the slowest in total first.
+fn summary(host: &str, timings: &BTreeMap<String, Timing>) -> Vec<String> {
+    let requests: u32 = timings.values().map(|t| t.count).sum();
+    let total: Duration = timings.values().map(|t| t.total).sum();
+    let mut lines = vec![format!("{} requests to {} in {} ms", requests, host, total.as_millis())];
+    let mut slowest: Vec<(&String, &Timing)> = timings.iter().collect();
+    slowest.sort_by_key(|(_, t)| Reverse(t.total));
+    for (endpoint, t) in slowest {
+        let failed = if t.failed > 0 { format!(", {} failed", t.failed) } else { String::new() };
+        lines.push(format!("  {}: {}x, avg {} ms, max {} ms{}", endpoint, t.count, (t.total / t.count).as_millis(), t.max.as_millis(), failed));
+    }
+    lines
+}
+
+impl Drop for Middleware {
+    fn drop(&mut self) {
+        let timings = self.timings.get_mut().unwrap_or_else(|e| e.into_inner());
+        if !timings.is_empty() {
+            for line in summary(&self.host, timings) {
+                tracing::debug!("{}", line);
+            }
+        }
+    }
+}
+
+fn headers_json(headers: &HeaderMap) -> Value {
+    let mut shown = Map::new();
+    for (name, value) in headers {
+        let value = value.to_str().unwrap_or("<not text>");
+        let value = match name.as_str() {
+            "cookie" => redact_cookie(value),
+            // the attributes after the cookie itself are no secret
+            "set-cookie" => match value.split_once(';') {
+                Some((cookie, attributes)) => format!("{};{}", redact_cookie(cookie), attributes),
+                None => redact_cookie(value),
+            },
+            "authorization" | "proxy-authorization" => REDACTED.to_string(),
+            _ => value.to_string(),
+        };
+        // a header sent more than once shows its values joined
+        match shown.get_mut(name.as_str()) {
+            Some(Value::String(earlier)) => {
+                earlier.push_str(", ");
+                earlier.push_str(&value);
+            }
+            _ => {
+                shown.insert(name.to_string(), Value::String(value));
+            }
+        }
+    }
+    Value::Object(shown)
+}
+
+/// A form body as an object of its fields, secrets replaced.
+fn form_json(bytes: &[u8]) -> Value {
+    let fields = reqwest::Url::parse(&format!("http://form/?{}", String::from_utf8_lossy(bytes)));
+    match fields {
+        Ok(url) => Value::Object(url.query_pairs().map(|(k, v)| (k.to_string(), Value::String(redact_value(&k, &v)))).collect()),
+        Err(_) => body_json(bytes),
+    }
+}
+
+/// A body as text, with the secret members of a JSON object replaced and cut short after
+/// [`TRACE_BODY_LIMIT`]; binary data only by its size.
+fn body_json(bytes: &[u8]) -> Value {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return json!(format!("<{} bytes of binary data>", bytes.len()));
+    };
+    let text = redact_value("body", text);
+    if text.len() <= TRACE_BODY_LIMIT {
+        return Value::String(text);
+    }
+    let mut end = TRACE_BODY_LIMIT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Value::String(format!("{}... ({} more bytes)", &text[..end], text.len() - end))
+}
+
+/// Read the body of `res` for the trace, and hand on a response with the same parts.
+async fn traced_response(request: Value, res: Response, started: Instant) -> reqwest::Result<Response> {
+    let (status, version, url, headers) = (res.status(), res.version(), res.url().clone(), res.headers().clone());
+    let body = match res.bytes().await {
+        Ok(body) => body,
+        Err(e) => {
+            write_trace(request, json!({ "status": status.as_u16(), "error": e.to_string() }), started);
+            return Err(e);
+        }
+    };
+    write_trace(request, json!({ "status": status.as_u16(), "headers": headers_json(&headers), "body": body_json(&body) }), started);
+    let mut rebuilt = hyper::http::Response::builder().status(status).version(version).url(url);
+    if let Some(parts) = rebuilt.headers_mut() {
+        *parts = headers;
+    }
+    Ok(Response::from(rebuilt.body(body).expect("the parts of a response that was received")))
+}
+
+fn write_trace(request: Value, response: Value, started: Instant) {
+    let Some(file) = TRACE.get() else {
+        return;
+    };
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let entry = json!({ "time": time_utc(now), "ms": started.elapsed().as_millis() as u64, "request": request, "response": response });
+    let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = writeln!(file, "{}", entry) {
+        tracing::debug!("could not write the HTTP trace: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traces_hide_secrets() {
+        let form = form_json(b"username=admin&password=hunter%202&json=%7B%22web_ui_password%22%3A%22x%22%7D");
+        assert_eq!(form, json!({ "username": "admin", "password": "<redacted>", "json": r#"{"web_ui_password":"<redacted>"}"# }));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("set-cookie", "SID=abc123; HttpOnly; path=/".parse().unwrap());
+        headers.insert("authorization", "Basic YWRtaW46eA==".parse().unwrap());
+        headers.insert("content-type", "text/plain".parse().unwrap());
+        assert_eq!(headers_json(&headers), json!({ "set-cookie": "SID=<redacted>; HttpOnly; path=/", "authorization": "<redacted>", "content-type": "text/plain" }));
+
+        assert_eq!(body_json(&[0xff, 0xfe, 0]), json!("<3 bytes of binary data>"));
+        let long = "é".repeat(TRACE_BODY_LIMIT);
+        assert!(body_json(long.as_bytes()).as_str().unwrap().ends_with(&format!("... ({} more bytes)", TRACE_BODY_LIMIT)));
+
+        let mut middleware = Middleware::new("http://localhost:8080");
+        middleware.set_rate_limit(Some(0.0));
+        assert!(middleware.throttle.is_none());
+        let request = Client::new().get("https://tracker.example/dl/PASSKEY/a.torrent").build().unwrap();
+        assert_eq!(middleware.describe(&request)["url"], "https://tracker.example/<redacted>");
+        assert_eq!(middleware.endpoint(&request), "GET tracker.example");
+        let request = Client::new().post("http://localhost:8080/api/v2/torrents/info?filter=all").build().unwrap();
+        assert_eq!(middleware.endpoint(&request), "POST torrents/info");
+    }
+
+    #[test]
+    fn timings_sum_up() {
+        let mut timings = BTreeMap::new();
+        timings.insert("POST torrents/info".to_string(), Timing { count: 2, failed: 0, total: Duration::from_millis(30), max: Duration::from_millis(20) });
+        timings.insert("POST auth/login".to_string(), Timing { count: 1, failed: 1, total: Duration::from_millis(50), max: Duration::from_millis(50) });
+        assert_eq!(
+            summary("http://nas:8080", &timings),
+            ["3 requests to http://nas:8080 in 80 ms", "  POST auth/login: 1x, avg 50 ms, max 50 ms, 1 failed", "  POST torrents/info: 2x, avg 15 ms, max 20 ms"]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Translations of rbit's messages, with Fluent (<https://projectfluent.org>). Each language is a
+//! `.ftl` file in `locales/`, built into the binary. English is the reference, and the fallback
+//! for any message a translation lacks.
+//!
+//! The language is `language` in the config file, or else the one `LC_ALL`, `LC_MESSAGES` or
+//! `LANG` asks for. Messages are looked up with [`tr!`]. Output meant for scripts (`--json`,
+//! `--porcelain`), clap's help and log lines stay in English.
+
+use std::sync::OnceLock;
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+use crate::error::RbitError;
+
+/// The shipped languages; English first.
+const LOCALES: [(&str, &str); 2] = [("en", include_str!("../locales/en/rbit.ftl")), ("de", include_str!("../locales/de/rbit.ftl"))];
+
+struct Messages {
+    /// `None` for English
+    chosen: Option<FluentBundle<FluentResource>>,
+    english: FluentBundle<FluentResource>,
+}
+
+static MESSAGES: OnceLock<Messages> = OnceLock::new();
+
+fn bundle(tag: &str, source: &str) -> FluentBundle<FluentResource> {
+    let lang: LanguageIdentifier = tag.parse().expect("the locale tags are valid");
+    let mut bundle = FluentBundle::new_concurrent(vec![lang]);
+    // terminals show the Unicode isolation marks around arguments as stray characters
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).expect("the shipped .ftl files parse");
+    bundle.add_resource(resource).expect("the shipped .ftl files have no duplicate messages");
+    bundle
+}
+
+/// The shipped language for a setting like `de`, `de-AT` or `de_DE.UTF-8`.
+fn shipped(wanted: &str) -> Option<&'static str> {
+    let code = wanted.split(['_', '-', '.', '@']).next().unwrap_or("").to_ascii_lowercase();
+    LOCALES.iter().map(|&(tag, _)| tag).find(|&tag| tag == code)
+}
+
+/// The language asked for by the environment, if rbit has it.
+fn from_env() -> Option<&'static str> {
+    let wanted = ["LC_ALL", "LC_MESSAGES", "LANG"].iter().find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))?;
+    shipped(&wanted)
+}
+
+/// Choose the language for this run: `configured` (from the config file), or the environment's.
+/// Later calls are ignored.
+pub fn init(configured: Option<&str>) -> Result<(), RbitError> {
+    let tag = match configured {
+        Some(wanted) => Some(shipped(wanted).ok_or_else(|| {
+            let available: Vec<&str> = LOCALES.iter().map(|&(tag, _)| tag).collect();
+            RbitError::InvalidInput(format!("language '{}' is not available (rbit has {})", wanted, available.join(", ")))
+        })?),
+        None => from_env(),
+    };
+    let chosen = LOCALES.iter().skip(1).find(|&&(t, _)| Some(t) == tag).map(|&(t, source)| bundle(t, source));
+    let _ = MESSAGES.set(Messages { chosen, english: bundle(LOCALES[0].0, LOCALES[0].1) });
+    Ok(())
+}
+
+/// Message `id` in the chosen language, formatted with `args`. Use [`tr!`] instead.
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    let messages = MESSAGES.get_or_init(|| Messages { chosen: None, english: bundle(LOCALES[0].0, LOCALES[0].1) });
+    for bundle in messages.chosen.iter().chain([&messages.english]) {
+        if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+            let mut errors = Vec::new();
+            return bundle.format_pattern(pattern, args, &mut errors).into_owned();
+        }
+    }
+    id.to_string()
+}
+
+/// Print `label: value` lines with the values lined up, however long the labels are in the
+/// chosen language. An empty label continues the value above it.
+pub fn print_fields(fields: &[(String, String)]) {
+    let width = fields.iter().map(|(label, _)| label.chars().count() + 1).max().unwrap_or(0);
+    for (label, value) in fields {
+        let label = if label.is_empty() { String::new() } else { format!("{}:", label) };
+        println!("{:<width$} {}", label, value, width = width);
+    }
+}
+
+/// A translated message: `tr!("list-hidden", hidden = 3, filter = "active")`.
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_and_translations() {
+        assert_eq!(shipped("de_DE.UTF-8"), Some("de"));
+        assert_eq!(shipped("DE-at"), Some("de"));
+        assert_eq!(shipped("C"), None);
+        assert_eq!(shipped("fr_FR"), None);
+
+        // every English message has a German one that formats with the same arguments
+        let german = bundle("de", LOCALES[1].1);
+        let mut args = FluentArgs::new();
+        for name in ["connection", "reason", "failed", "total", "missing", "holder", "hidden", "filter", "path", "name", "id", "saved", "dir", "skipped", "tracker", "profile", "host", "files", "addresses", "user", "groups", "group", "age", "save_path", "pieces", "question", "what", "version", "state", "destination", "existing", "dirs", "every", "limit", "command", "address", "why", "free", "sections", "rate", "time", "down", "up", "new", "old", "share", "copies", "availability", "latest", "from", "to", "client", "variable", "needed", "ratio", "seeding", "inactive"] {
+            args.set(name, "x");
+        }
+        for name in ["percent", "size", "expected", "count", "ok", "elapsed", "unchecked", "bad", "port", "ms", "seconds", "done", "with_files", "downloading", "uploading", "seeds", "leechers", "shown", "pid", "created", "updated", "unchanged", "removed"] {
+            args.set(name, 2);
+        }
+        let ids = LOCALES[0].1.lines().filter(|l| l.starts_with(|c: char| c.is_ascii_lowercase())).filter_map(|l| l.split_once(" ="));
+        for (id, _) in ids {
+            let pattern = german.get_message(id).and_then(|m| m.value()).unwrap_or_else(|| panic!("{} is not translated", id));
+            let mut errors = Vec::new();
+            german.format_pattern(pattern, Some(&args), &mut errors);
+            assert!(errors.is_empty(), "{}: {:?}", id, errors);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Ctrl-C, and SIGTERM where there is one (`kill`, `systemctl stop`), for the commands that run
+//! until they are stopped: `wait`, `add --follow`, `log --follow` and `rbit daemon`.
+//!
+//! Once a command listens, the signals no longer end the process at once. It draws its
+//! progress bars to a stop, records what it still has to and, when the profile says so
+//! (`logout_on_stop`), logs out before exiting.
+
+use crate::api::Session;
+
+/// The stop signals, listened for from [`Interrupt::listen`] on.
+pub struct Interrupt {
+    #[cfg(unix)]
+    signals: Option<(tokio::signal::unix::Signal, tokio::signal::unix::Signal)>,
+    #[cfg(windows)]
+    ctrl_c: Option<tokio::signal::windows::CtrlC>,
+}
+
+impl Interrupt {
+    /// Start listening. A signal that comes while the command is busy is kept for the next
+    /// [`Interrupt::recv`].
+    pub fn listen() -> Interrupt {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let signals = signal(SignalKind::interrupt()).and_then(|int| Ok((int, signal(SignalKind::terminate())?)));
+            Interrupt { signals: signals.ok() }
+        }
+        #[cfg(windows)]
+        {
+            Interrupt { ctrl_c: tokio::signal::windows::ctrl_c().ok() }
+        }
+    }
+
+    /// Resolves once the command is asked to stop.
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some((int, term)) = self.signals.as_mut() {
+            tokio::select! {
+                _ = int.recv() => {}
+                _ = term.recv() => {}
+            }
+            return;
+        }
+        #[cfg(windows)]
+        if let Some(ctrl_c) = self.ctrl_c.as_mut() {
+            ctrl_c.recv().await;
+            return;
+        }
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Log out of `session` if its profile has `logout_on_stop`, for a command that was stopped.
+/// A failure is only a warning: the command is on its way out anyway.
+pub async fn wrap_up(session: &Session) {
+    if !session.logout_on_stop {
+        return;
+    }
+    if let Err(e) = session.logout().await {
+        eprintln!("{} {:#}", crate::i18n::tr!("warning"), e);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn stops_on_sigint_and_sigterm() {
+        let mut interrupt = Interrupt::listen();
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            assert_eq!(unsafe { libc::raise(signal) }, 0);
+            assert!(tokio::time::timeout(Duration::from_secs(5), interrupt.recv()).await.is_ok());
+        }
+        // and nothing more until the next one
+        assert!(tokio::time::timeout(Duration::from_millis(100), interrupt.recv()).await.is_err());
+    }
+}
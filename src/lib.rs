@@ -0,0 +1,5 @@
+//! The parts of rbit that are useful outside the command-line tool: formatting and parsing of
+//! sizes, rates and dates, and terminal progress for transfers.
+
+pub mod format;
+pub mod progress;
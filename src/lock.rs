@@ -0,0 +1,125 @@
+//! Locks shared by the rbit processes on this machine, so `rbit daemon` and commands run by hand
+//! (or from cron) can work on the same server and the same files at once.
+//!
+//! They are advisory locks on files in `locks/` in the cache directory. The operating system
+//! releases them when the process exits, so a crash cannot leave one behind.
+//!
+//! - `session-<host>`: held while logging in, so processes reuse each other's fresh cookie
+//!   instead of each logging in again.
+//! - `bulk-<host>`: held (shared) by commands that change many torrents at once and (exclusive)
+//!   by each pass of `rbit daemon`, which pauses while such a command runs.
+//! - `daemon-<host>`: lets only one `rbit daemon` watch a server.
+//! - `cleanup-<host>`: lets only one `rbit cleanup` run at a time against a server.
+//! - `notes`, and one per stats history file: held while updating the file.
+
+use std::fs::{self, File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::cache;
+use crate::i18n::tr;
+
+/// How often a waiting process checks whether a lock was released.
+const RETRY: Duration = Duration::from_millis(200);
+
+/// A held lock, released when dropped.
+pub struct Lock {
+    _file: File,
+}
+
+fn open(name: &str) -> anyhow::Result<File> {
+    let dir = cache::cache_dir().ok_or_else(|| anyhow::anyhow!("no cache directory available"))?.join("locks");
+    open_in(&dir, name)
+}
+
+fn open_in(dir: &Path, name: &str) -> anyhow::Result<File> {
+    fs::create_dir_all(dir)?;
+    let path: PathBuf = dir.join(format!("{}.lock", cache::file_key(name)));
+    fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+}
+
+/// The name of a lock that is per server.
+pub fn for_host(kind: &str, host: &str) -> String {
+    format!("{}-{}", kind, cache::file_key(host))
+}
+
+/// Take lock `name`, blocking until it is free. Only for locks held briefly, like while a small
+/// file is rewritten.
+pub fn exclusive(name: &str) -> anyhow::Result<Lock> {
+    let file = open(name)?;
+    file.lock()?;
+    Ok(Lock { _file: file })
+}
+
+/// Take lock `name` if no one holds it.
+pub fn try_exclusive(name: &str) -> anyhow::Result<Option<Lock>> {
+    try_take(open(name)?)
+}
+
+fn try_take(file: File) -> anyhow::Result<Option<Lock>> {
+    match file.try_lock() {
+        Ok(()) => Ok(Some(Lock { _file: file })),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// Take lock `name`, waiting without blocking the runtime until it is free.
+pub async fn wait_exclusive(name: &str) -> anyhow::Result<Lock> {
+    wait_for(open(name)?).await
+}
+
+async fn wait_for(file: File) -> anyhow::Result<Lock> {
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(Lock { _file: file }),
+            Err(TryLockError::WouldBlock) => tokio::time::sleep(RETRY).await,
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+}
+
+/// Take lock `name` together with any other process taking it shared, waiting (without
+/// blocking the runtime) while someone holds it exclusively. Says once on stderr what it waits
+/// for.
+pub async fn shared(name: &str, holder: &str) -> anyhow::Result<Lock> {
+    let file = open(name)?;
+    let mut said = false;
+    loop {
+        match file.try_lock_shared() {
+            Ok(()) => return Ok(Lock { _file: file }),
+            Err(TryLockError::WouldBlock) => {
+                if !said {
+                    eprintln!("{}", tr!("lock-waiting", holder = holder));
+                    said = true;
+                }
+                tokio::time::sleep(RETRY).await;
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn exclusive_locks_exclude() {
+        let dir = std::env::temp_dir().join(format!("rbit-locks-{}", std::process::id()));
+        let held = try_take(open_in(&dir, "test").unwrap()).unwrap().expect("a fresh lock is free");
+        assert!(try_take(open_in(&dir, "test").unwrap()).unwrap().is_none());
+        let waiting = tokio::spawn(wait_for(open_in(&dir, "test").unwrap()));
+        tokio::time::sleep(RETRY * 2).await;
+        assert!(!waiting.is_finished());
+        drop(held);
+        let _lock = waiting.await.unwrap().unwrap();
+        assert!(try_take(open_in(&dir, "test").unwrap()).unwrap().is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,115 @@
+//! Diagnostic logging through `tracing`. `-v` logs each request with its status, retries and
+//! session handling; `-vv` adds form fields and response bodies; `-vvv` also logs the HTTP
+//! libraries underneath. Logs go to stderr, or appended to `--log-file`.
+//!
+//! Secrets never reach a log line: form fields that hold passwords or tokens, cookie values and
+//! the paths of URLs outside the Web UI are replaced before they are logged (or printed by a
+//! dry run).
+
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing::Level;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt;
+use tracing_subscriber::prelude::*;
+
+pub const REDACTED: &str = "<redacted>";
+
+/// Whether a form field or preference carries a secret, e.g. `password`, `web_ui_password`,
+/// `rss_cookie`, `SID`.
+pub fn is_secret(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key == "sid" || ["password", "passwd", "secret", "token", "cookie", "api_key"].iter().any(|s| key.contains(s))
+}
+
+/// A form value with secrets replaced: the whole value for a secret field, and the secret
+/// members of a JSON object (as `app/setPreferences` takes them).
+pub fn redact_value(key: &str, value: &str) -> String {
+    if is_secret(key) {
+        return REDACTED.to_string();
+    }
+    match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(value) {
+        Ok(mut object) if object.keys().any(|k| is_secret(k)) => {
+            for (k, v) in object.iter_mut() {
+                if is_secret(k) {
+                    *v = REDACTED.into();
+                }
+            }
+            serde_json::Value::Object(object).to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Form fields as `key=value, key=value`, secrets replaced.
+pub fn redact_form(params: &[(&str, &str)]) -> String {
+    let shown: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, redact_value(k, v))).collect();
+    shown.join(", ")
+}
+
+/// A `Cookie` header with every value replaced, keeping the names: `SID=<redacted>; uid=<redacted>`.
+pub fn redact_cookie(header: &str) -> String {
+    let shown: Vec<String> = header
+        .split(';')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) => format!("{}={}", name.trim(), REDACTED),
+            None => pair.trim().to_string(),
+        })
+        .collect();
+    shown.join("; ")
+}
+
+/// A URL outside the Web UI, e.g. a tracker's .torrent download, with only its scheme and host:
+/// trackers put passkeys in the path as often as in the query.
+pub fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => match url.port() {
+            Some(port) => format!("{}://{}:{}/{}", url.scheme(), url.host_str().unwrap_or_default(), port, REDACTED),
+            None => format!("{}://{}/{}", url.scheme(), url.host_str().unwrap_or_default(), REDACTED),
+        },
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+/// Install the log subscriber for `-v` given `verbosity` times. A log file alone logs as much
+/// as `-v`; without either nothing is logged.
+pub fn init(verbosity: u8, log_file: Option<&Path>) -> anyhow::Result<()> {
+    let verbosity = if log_file.is_some() { verbosity.max(1) } else { verbosity };
+    let filter = match verbosity {
+        0 => return Ok(()),
+        1 => Targets::new().with_target("rbit", Level::DEBUG),
+        2 => Targets::new().with_target("rbit", Level::TRACE),
+        // the cookie store logs every Set-Cookie with its value, so it stays quiet
+        _ => Targets::new().with_default(Level::TRACE).with_target("cookie_store", LevelFilter::OFF),
+    };
+    let registry = tracing_subscriber::registry().with(filter);
+    match log_file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| anyhow::anyhow!("log file {}: {}", path.display(), e))?;
+            registry.with(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))).init();
+        }
+        None => registry.with(fmt::layer().with_writer(std::io::stderr).without_time()).init(),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_redacted() {
+        let form = redact_form(&[("username", "admin"), ("password", "hunter2"), ("json", r#"{"web_ui_password":"x"}"#)]);
+        assert_eq!(form, r#"username=admin, password=<redacted>, json={"web_ui_password":"<redacted>"}"#);
+        assert!(is_secret("web_ui_password") && is_secret("SID") && !is_secret("save_path"));
+        assert_eq!(redact_cookie("SID=abc123; uid=7"), "SID=<redacted>; uid=<redacted>");
+        assert_eq!(redact_url("https://tracker.example/dl/PASSKEY/a.torrent?torrent_pass=x"), "https://tracker.example/<redacted>");
+        assert_eq!(redact_url("http://tracker.example:8080/a.torrent"), "http://tracker.example:8080/<redacted>");
+    }
+}
@@ -1,13 +1,57 @@
-use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use clap::Parser;
-use directories::BaseDirs;
-use reqwest::blocking::Client;
-use reqwest::blocking::multipart;
-use serde::Deserialize;
-use tabled::{Table, Tabled};
-use config::{Config as ConfigLoader, File as ConfigFile, FileFormat};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+mod api;
+mod batch;
+mod cache;
+mod client;
+mod commands;
+mod config;
+mod confirm;
+mod credentials;
+mod error;
+mod glob;
+mod guard;
+mod history;
+mod hold;
+mod hooks;
+mod http;
+mod i18n;
+mod interrupt;
+mod lock;
+mod logging;
+mod notes;
+mod notifications;
+mod plan;
+mod porcelain;
+mod postprocess;
+mod rules;
+mod savepath;
+mod schema;
+mod select;
+mod space;
+mod theme;
+mod torrent;
+mod tunnel;
+
+use api::{normalize_host, AddOptions, ConnectOptions, RetryPolicy, Session};
+use client::deluge::Deluge;
+use client::transmission::Transmission;
+use client::{Backend, Bulk, TorrentClient};
+use commands::daemon::WatchDir;
+use commands::prune::Policy;
+use commands::schedule::Schedule;
+use config::{config_sources, read_config, user_config_path, QBConfig, SavedSearch, ALL_PROFILES, DEFAULT_HOST, DEFAULT_PROFILE};
+use error::RbitError;
+use rbit::format::{self, Rate};
+use rules::Rules;
+use select::Selector;
+use tunnel::Tunnel;
+
+/// Why `--server all` has nothing to do
+const NO_PROFILES: &str = "there are no server profiles to go through; add [qbittorrent] or [servers.<name>] tables to the config";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "simple qBittorrent client", long_about = None)]
@@ -16,25 +60,98 @@ struct Cli {
     #[arg(short = 'c', long)]
     config: Option<PathBuf>,
 
+    /// Fail on unknown keys in config files instead of warning about them
+    #[arg(long, global = true)]
+    strict_config: bool,
+
+    /// Server profile from the `[servers]` config table (default: `[qbittorrent]`); `all` for every
+    /// profile with list, status, pause, resume, reannounce and recheck
+    #[arg(short = 's', long, global = true)]
+    server: Option<String>,
+
     /// qBittorrent host (overrides config)
-    #[arg(long)]
+    #[arg(long, env = "RBIT_HOST")]
     host: Option<String>,
 
     /// qBittorrent username (overrides config)
-    #[arg(long)]
+    #[arg(long, env = "RBIT_USERNAME")]
     username: Option<String>,
 
-    /// qBittorrent password (overrides config)
-    #[arg(long)]
+    /// qBittorrent password (overrides config; prompted for if a username is set but no password is)
+    #[arg(long, env = "RBIT_PASSWORD", hide_env_values = true)]
     password: Option<String>,
 
+    /// Proxy to reach the server through: http://, https://, socks5:// or socks5h:// URL
+    /// (overrides config; without it http_proxy/https_proxy apply)
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Accept any TLS certificate from the server, e.g. a self-signed one (overrides config)
+    #[arg(long)]
+    insecure: bool,
+
+    /// PEM file with a CA, or the server's self-signed certificate, to trust (overrides config)
+    #[arg(long, value_name = "FILE")]
+    ca_cert: Option<PathBuf>,
+
+    /// PEM client certificate to present to the server, with its key unless --client-key is given
+    #[arg(long, value_name = "FILE")]
+    client_cert: Option<PathBuf>,
+
+    /// PEM private key of --client-cert
+    #[arg(long, value_name = "FILE", requires = "client_cert")]
+    client_key: Option<PathBuf>,
+
+    /// Retries for requests that fail to connect or time out (overrides config)
+    #[arg(long, global = true)]
+    retries: Option<u32>,
+
+    /// Table style for this run (overrides `[theme] table_style`)
+    #[arg(long, global = true, value_enum, value_name = "STYLE")]
+    table_style: Option<theme::TableStyle>,
+
+    /// When to color output: auto (on a terminal, unless NO_COLOR is set), always or never
+    /// (overrides `[theme] color`)
+    #[arg(long, global = true, value_enum, value_name = "WHEN")]
+    color: Option<theme::ColorChoice>,
+
+    /// Show sizes and rates in 1000s: kB, MB, GB (overrides config)
+    #[arg(long, global = true, conflicts_with = "binary")]
+    si: bool,
+
+    /// Show sizes and rates in 1024s: KiB, MiB, GiB, the default (overrides config)
+    #[arg(long, global = true)]
+    binary: bool,
+
     /// Do not send requests; print what would be sent
     #[arg(long)]
     dry_run: bool,
 
-    /// Print verbose HTTP requests/responses
-    #[arg(long, short = 'v')]
-    verbose: bool,
+    /// Go ahead with destructive actions (`prune`, `cleanup`, `shutdown`) without asking for confirmation
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// With --dry-run: print the changes `cleanup`, `prune`, `category sync` and `rss rule sync` would make as JSON
+    #[arg(long, requires = "dry_run")]
+    json: bool,
+
+    /// Stable tab-separated output for scripts from `list` and `status`, in the given format version
+    #[arg(long, value_enum, value_name = "VERSION", num_args = 0..=1, require_equals = true, default_missing_value = "v1")]
+    porcelain: Option<porcelain::Version>,
+
+    /// Log requests to stderr: -v statuses and retries, -vv also form fields (secrets redacted)
+    /// and responses, -vvv also the HTTP libraries
+    #[arg(long, short = 'v', action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Append the log to this file instead of stderr (logs as much as -v if not given)
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Record each request to the Web UI and its response in this file, secrets removed, to
+    /// attach to a bug report
+    #[arg(long, value_name = "PATH")]
+    trace_http: Option<PathBuf>,
 
     #[command(subcommand)]
     command: Command,
@@ -44,265 +161,1581 @@ struct Cli {
 enum Command {
     /// Add a torrent (magnet link or .torrent file)
     Add {
-        /// Path to a .torrent file or a magnet link
-        input: String,
+        /// .torrent files, directories of them, quoted patterns like `'*.torrent'`, magnet links,
+        /// torrent URLs or bare info-hashes
+        #[arg(required_unless_present = "batch")]
+        inputs: Vec<String>,
+
+        /// Read magnet links / .torrent paths from a file, one per line (`-` for stdin)
+        #[arg(long, value_name = "FILE", conflicts_with = "inputs")]
+        batch: Option<PathBuf>,
 
-        /// Destination folder for the torrent content
+        /// Also take .torrent files from subdirectories of directory inputs
+        #[arg(short, long, conflicts_with = "batch")]
+        recursive: bool,
+
+        /// Delete local .torrent files once the server has them
+        #[arg(long, conflicts_with = "batch")]
+        delete_after_add: bool,
+
+        /// Destination folder for the torrent content; may be a template like `/media/{category}/{yyyy}`
         #[arg(short, long)]
         dest: Option<PathBuf>,
+
+        /// Print the .torrent file's metadata instead of adding it (does not contact the server)
+        #[arg(long, conflicts_with = "batch")]
+        inspect: bool,
+
+        #[command(flatten)]
+        flags: commands::add::AddFlags,
+    },
+    /// Show the metadata of local .torrent files: info-hash, size, files, trackers
+    Inspect {
+        /// .torrent files to inspect
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Exit 0 if the server has these torrents and 1 if not, e.g. as a guard before `add`
+    Exists {
+        /// Info-hashes, magnet links or .torrent files (info-hashes are computed locally)
+        #[arg(required = true)]
+        inputs: Vec<String>,
+
+        /// Print nothing, only set the exit code
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Print one field of a torrent, as the server reports it, with nothing around it, e.g. `rbit get a1b2c3 save_path`
+    Get {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Field of qBittorrent's torrent listing, e.g. `save_path`, `progress`, `state`
+        field: String,
+    },
+    /// Save torrents' .torrent files from the server (qBittorrent 4.5+), named after the torrents
+    Export {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present_any = ["all", "selector"])]
+        ids: Vec<String>,
+
+        /// Export every torrent on the server
+        #[arg(long, conflicts_with_all = ["ids", "selector"])]
+        all: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+
+        /// Folder to save them in
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Seed a complete torrent's data on another tracker too: re-add it with that tracker, on the same files
+    CrossSeed {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// The other tracker's announce URL, with your passkey
+        #[arg(long, value_name = "URL")]
+        tracker: String,
+
+        /// Source tag to put in the torrent, which many private trackers require (e.g. `RED`)
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Have qBittorrent check the data before seeding instead of trusting it
+        #[arg(long)]
+        check: bool,
+    },
+    /// Move torrents from another qBittorrent, Transmission or Deluge to this server, on the same data
+    Import {
+        /// Torrents to import (hash or unique hash prefix on the source; default: all of them)
+        ids: Vec<String>,
+
+        /// Where to import from: a server profile, or the URL of a Transmission server without a login
+        #[arg(long, value_name = "PROFILE|URL")]
+        from: String,
+
+        /// Also bring along the category, tags, share limits and whether the torrent is paused
+        #[arg(long)]
+        with_state: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+    },
+    /// Check a complete torrent's files where this machine can read them, without trusting the server
+    Verify {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Re-hash the data and compare it with the piece hashes of the torrent's .torrent file
+        #[arg(long)]
+        deep: bool,
+
+        /// Folder where the torrent's save path is mounted on this machine
+        #[arg(long, value_name = "DIR")]
+        data_root: Option<PathBuf>,
+
+        /// Take the piece hashes from this .torrent file instead of exporting it from the server
+        /// (which needs qBittorrent 4.5 or later)
+        #[arg(long, value_name = "FILE")]
+        torrent_file: Option<PathBuf>,
+    },
+    /// Wait until torrents are complete (or seeding), e.g. `rbit wait 3f2a && ./post-process.sh`
+    Wait {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required = true)]
+        ids: Vec<String>,
+
+        /// Give up after this long, e.g. `90s`, `30m` or `2h` (exit code 7)
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+        timeout: Option<std::time::Duration>,
+
+        /// State to wait for
+        #[arg(long, value_enum, default_value = "completed")]
+        state: commands::wait::WaitState,
+
+        /// Time between checks
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration, default_value = "5s")]
+        interval: std::time::Duration,
+
+        /// Shell command to run for each torrent as it gets there (overrides `[hooks] on_complete`)
+        #[arg(long, value_name = "COMMAND")]
+        on_complete: Option<String>,
     },
     /// List torrents (default: active torrents). Use --all to show all.
     List {
-        /// Show all torrents, not only active ones
+        #[command(flatten)]
+        options: commands::list::ListOptions,
+
+        #[command(flatten)]
+        layout: commands::list::Layout,
+
+        /// Show the torrents as the server listed them last time, without contacting it
+        #[arg(long)]
+        cached: bool,
+
+        /// List the torrents of every configured server profile in one table, like `--server all`
+        #[arg(long)]
+        everywhere: bool,
+    },
+    /// Print the magnet URI (with name and trackers) equivalent to local .torrent files
+    Magnet {
+        /// .torrent files to convert
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Manage credentials stored in the OS keyring for the selected server profile
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// List categories, or sync them and their save paths from the `[categories]` config table
+    Category {
+        #[command(subcommand)]
+        action: CategoryAction,
+    },
+    /// File torrents already on the server by the `[[rules]]`, as `rbit add` does with new ones
+    ApplyRules {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present_any = ["all", "selector"])]
+        ids: Vec<String>,
+
+        /// Apply the rules to every torrent on the server
+        #[arg(long, conflicts_with_all = ["ids", "selector"])]
+        all: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+    },
+    /// Delete torrents by the `[[retention]]` tag policies, showing why each tagged torrent is kept or deleted
+    Cleanup,
+    /// Delete complete torrents past a share ratio or seeding time (default: the `[prune]` policy)
+    Prune {
+        /// Delete torrents whose share ratio is over this
+        #[arg(long, value_name = "RATIO")]
+        ratio_over: Option<f64>,
+
+        /// Delete torrents that completed at least this long ago, e.g. `14d`
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+        seeded_for: Option<std::time::Duration>,
+
+        /// Only torrents in this category
+        #[arg(long, value_name = "NAME")]
+        category: Option<String>,
+
+        /// Delete the downloaded data too
+        #[arg(long)]
+        delete_data: bool,
+    },
+    /// Show the `[[schedule]]` for the alternative speed limits and what it says for now
+    Schedule {
+        /// Switch the alternative speed limits to what the schedule says for now
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Print a shell completion script, e.g. `rbit completions bash > /etc/bash_completion.d/rbit`
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Replace this binary with the latest GitHub release, after checking its SHA-256 checksum
+    SelfUpdate {
+        /// Only say whether a newer release is out
         #[arg(long)]
+        check: bool,
+    },
+    /// Print a man page, e.g. `rbit man add | man -l -`, or write them all with --output
+    Man {
+        /// Command to show the page of, e.g. `limit torrent` (default: rbit itself)
+        command: Vec<String>,
+
+        /// Print rbit.toml(5), the keys of the config file
+        #[arg(long, conflicts_with = "command")]
+        config_file: bool,
+
+        /// Write every page into DIR/man1 and DIR/man5 instead, e.g. /usr/local/share/man
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["command", "config_file"])]
+        output: Option<PathBuf>,
+    },
+    /// List torrent ids and names for the completion scripts
+    #[command(name = "__complete-hashes", hide = true)]
+    CompleteHashes,
+    /// Export or import rbit's own configuration (server profiles and settings)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Keep notes on torrents, e.g. why one is kept (stored locally, optionally as tags)
+    Note {
+        #[command(subcommand)]
+        action: NoteAction,
+    },
+    /// Move a torrent's data to a new location on the server
+    Move {
+        /// Torrent id (hash or unique hash prefix, as shown by `list`)
+        id: String,
+
+        /// New save location (a path on the qBittorrent machine)
+        location: String,
+    },
+    /// Rename a torrent (the name shown by qBittorrent, not the files on disk)
+    Rename {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// New torrent name
+        name: String,
+    },
+    /// Rename a file or folder inside a torrent
+    RenameFile {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Current path inside the torrent, e.g. `Show/e01.mkv`
+        old_path: String,
+
+        /// New path inside the torrent
+        new_path: String,
+
+        /// Rename a folder instead of a file
+        #[arg(long)]
+        folder: bool,
+    },
+    /// Make torrents announce to their trackers now
+    Reannounce {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present_any = ["all", "selector"])]
+        ids: Vec<String>,
+
+        /// Reannounce every torrent on the server
+        #[arg(long, conflicts_with_all = ["ids", "selector"])]
+        all: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+
+        /// Also ask torrents that announced too recently (see `[announce]` in the config)
+        #[arg(long)]
+        force: bool,
+    },
+    /// Re-verify torrents' data on disk, e.g. after restoring it from a backup
+    Recheck {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present_any = ["all", "selector"])]
+        ids: Vec<String>,
+
+        /// Recheck every torrent on the server
+        #[arg(long, conflicts_with_all = ["ids", "selector"])]
+        all: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+    },
+    /// Pause torrents
+    Pause {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present_any = ["all", "selector"])]
+        ids: Vec<String>,
+
+        /// Pause every torrent on the server
+        #[arg(long, conflicts_with_all = ["ids", "selector"])]
+        all: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+    },
+    /// Resume paused torrents
+    Resume {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present_any = ["all", "selector"])]
+        ids: Vec<String>,
+
+        /// Resume every torrent on the server
+        #[arg(long, conflicts_with_all = ["ids", "selector"])]
         all: bool,
+
+        #[command(flatten)]
+        selector: Selector,
+    },
+    /// Shut the qBittorrent application down, after asking
+    Shutdown,
+    /// Show the peers connected to a torrent, with client, flags, progress and speeds
+    Peers {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Ban a peer by IP (any connected port) or ip:port (repeatable)
+        #[arg(long, value_name = "IP")]
+        ban: Vec<String>,
+
+        /// Summarise clients, countries, encryption and connection types instead of listing peers
+        #[arg(long)]
+        summary: bool,
+    },
+    /// Show a torrent's pieces as a map of downloaded, downloading and missing ones, and how
+    /// available the rest are among connected peers
+    Pieces {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Cells per line of the map
+        #[arg(long, default_value_t = 64)]
+        width: usize,
+
+        /// Lines the map may take; with more pieces than cells, each cell stands for several
+        #[arg(long, default_value_t = 8)]
+        lines: usize,
+    },
+    /// Change torrents' queue positions (requires queueing to be enabled on the server)
+    Queue {
+        #[arg(value_enum)]
+        direction: commands::manage::QueueMove,
+
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present = "selector")]
+        ids: Vec<String>,
+
+        #[command(flatten)]
+        selector: Selector,
+    },
+    /// Turn sequential (in-order) downloading of a torrent on or off
+    Sequential {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        #[arg(value_enum)]
+        state: commands::manage::Switch,
+    },
+    /// Turn first/last piece priority of a torrent on or off
+    #[command(name = "firstlast")]
+    FirstLast {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        #[arg(value_enum)]
+        state: commands::manage::Switch,
+    },
+    /// Play a file of a torrent while it downloads: turns on sequential download, waits for the
+    /// start of the file and opens it in a media player
+    Stream {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// The file to play, by its index in the torrent (default: the largest)
+        #[arg(long, value_name = "INDEX")]
+        file: Option<usize>,
+
+        /// Media player command, given the file as its last argument (default: `[stream] player`, or mpv)
+        #[arg(long, value_name = "COMMAND")]
+        player: Option<String>,
+
+        /// Folder where the server's save path is mounted on this machine
+        #[arg(long, value_name = "DIR")]
+        data_root: Option<PathBuf>,
+
+        /// How much of the start of the file to have before playing, e.g. `50MiB` (default: `[stream] buffer`, or 20 MiB)
+        #[arg(long, value_name = "SIZE", value_parser = format::parse_size)]
+        buffer: Option<u64>,
+    },
+    /// Show a torrent's web seeds, optionally adding or removing some first
+    Webseeds {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Add a web seed URL (repeatable; needs qBittorrent 5.1 or later)
+        #[arg(long, value_name = "URL")]
+        add: Vec<String>,
+
+        /// Remove a web seed URL (repeatable; needs qBittorrent 5.1 or later)
+        #[arg(long, value_name = "URL")]
+        remove: Vec<String>,
+    },
+    /// Show a torrent's trackers, optionally adding, removing or replacing announce URLs first
+    Trackers {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+
+        /// Add an announce URL (repeatable)
+        #[arg(long, value_name = "URL")]
+        add: Vec<String>,
+
+        /// Remove an announce URL (repeatable)
+        #[arg(long, value_name = "URL")]
+        remove: Vec<String>,
+
+        /// Replace an announce URL, e.g. after a private tracker rotates its passkey (repeatable)
+        #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+        replace: Vec<String>,
+    },
+    /// Show or set speed limits: global, alternative, or per torrent
+    Limit {
+        #[command(subcommand)]
+        action: LimitAction,
+    },
+    /// Show or change qBittorrent's preferences, e.g. `prefs set listen_port=51413 dht=off`
+    Prefs {
+        #[command(subcommand)]
+        action: PrefsAction,
+    },
+    /// Show or change the port peers connect to, and test whether it is open
+    Port {
+        #[command(subcommand)]
+        action: PortAction,
+    },
+    /// Summarise the library: share ratios, usage per tracker, or disk usage per save path
+    Report {
+        #[arg(value_enum)]
+        kind: commands::report::ReportKind,
+
+        /// Output format; markdown gives a headed document for wikis and digests
+        #[arg(long, value_enum, default_value_t)]
+        format: commands::report::ReportFormat,
+    },
+    /// Manage RSS feeds and folders, show their articles, and export or import auto-download rules
+    Rss {
+        #[command(subcommand)]
+        action: RssAction,
+    },
+    /// Search with qBittorrent's search plugins; the same release from several plugins is shown once
+    Search {
+        #[arg(required_unless_present_any = ["saved", "add"])]
+        query: Option<String>,
+
+        /// Run a search saved in the `[searches]` config table instead
+        #[arg(long, value_name = "NAME", conflicts_with_all = ["query", "save"])]
+        saved: Option<String>,
+
+        /// Plugin category, e.g. `movies`, `tv`, `software` (default: all)
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Plugin to ask; repeat for several. `all` includes disabled plugins (default: the enabled ones)
+        #[arg(long = "plugin", value_name = "NAME")]
+        plugins: Vec<String>,
+
+        /// Add result number N of the last search instead of searching
+        #[arg(long, value_name = "N", conflicts_with_all = ["query", "saved", "save", "category", "plugins"])]
+        add: Option<usize>,
+
+        /// Save this query under a name in the config file (`--config` or the user config), then run it
+        #[arg(long, value_name = "NAME")]
+        save: Option<String>,
+
+        /// Show at most this many releases
+        #[arg(long, default_value_t = 25)]
+        limit: usize,
+
+        /// Stop waiting for slow plugins after this many seconds
+        #[arg(long, value_name = "SECS", default_value_t = 30)]
+        timeout: u64,
+
+        /// Show every plugin result instead of merging duplicates
+        #[arg(long)]
+        no_dedup: bool,
+    },
+    /// Show or set share ratio and seeding time limits of torrents
+    #[command(name = "seedlimits")]
+    SeedLimits {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required = true)]
+        ids: Vec<String>,
+
+        /// Share ratio limit, e.g. `2.0`, `global` or `unlimited`
+        #[arg(long, value_parser = format::parse_ratio_limit)]
+        ratio: Option<f64>,
+
+        /// Seeding time limit, e.g. `90` (minutes), `36h`, `7d`, `global` or `unlimited`
+        #[arg(long, value_name = "TIME", value_parser = format::parse_minutes_limit)]
+        seeding_time: Option<i64>,
+
+        /// Inactive seeding time limit (qBittorrent 4.6+), same format as --seeding-time
+        #[arg(long, value_name = "TIME", value_parser = format::parse_minutes_limit)]
+        inactive_seeding_time: Option<i64>,
+    },
+    /// Show global transfer statistics: speeds and limits, session and all-time data, DHT, free space
+    #[command(args_conflicts_with_subcommands = true)]
+    Stats {
+        #[command(subcommand)]
+        action: Option<StatsAction>,
+
+        /// Show totals and average speeds over this long from the stats history instead, e.g. `7d`
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+        since: Option<std::time::Duration>,
+    },
+    /// Show a torrent's progress, transfers and ratio over time, from the stats history
+    History {
+        /// Torrent id (hash or unique hash prefix); removed torrents are found by their hash
+        id: String,
+
+        /// Only snapshots from this long ago on, e.g. `7d`
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+        since: Option<std::time::Duration>,
+
+        /// Number of snapshots to show (the newest); the totals cover all of them
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Chart a torrent's progress, speeds and ratio over time from the stats history, to see when it downloaded
+    Timeline {
+        /// Torrent id (hash or unique hash prefix); removed torrents are found by their hash
+        id: String,
+
+        /// Only snapshots from this long ago on, e.g. `7d`
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+        since: Option<std::time::Duration>,
+
+        /// Number of columns in the chart
+        #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u16).range(1..))]
+        width: u16,
+    },
+    /// Estimate when downloads will be done, from speeds measured over a few seconds or the stats history, following the download queue
+    Forecast {
+        /// Only this torrent (hash or unique hash prefix); the forecast still covers the whole queue
+        id: Option<String>,
+
+        /// How long to measure download speeds for
+        #[arg(long, value_name = "DURATION", default_value = "10s", value_parser = format::parse_duration)]
+        window: std::time::Duration,
+
+        /// Use the average speeds over this long from the stats history instead of measuring, e.g. `1h`
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+        from_history: Option<std::time::Duration>,
+    },
+    /// Show qBittorrent's log, or its log of blocked and banned peers, e.g. `rbit log --level warning --follow`
+    Log {
+        /// Only entries at this severity or above
+        #[arg(long, value_enum, default_value = "normal", conflicts_with = "peers")]
+        level: commands::log::Level,
+
+        /// Show the peer log: IPs the IP filter blocked or that were banned
+        #[arg(long)]
+        peers: bool,
+
+        /// How many of the newest entries to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+
+        /// Keep printing new entries as the server logs them, until Ctrl-C
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Browse torrents full-screen: mark them with space (a range with `v`) and pause, resume,
+    /// delete or categorize them together
+    Tui {
+        /// Time between refreshes of the list
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration, default_value = "2s")]
+        interval: std::time::Duration,
+    },
+    /// Keep running and add .torrent and .magnet files dropped into watch folders
+    Daemon {
+        /// Folder to watch (repeatable), besides the `[[watch]]` tables of the config
+        #[arg(long = "watch-dir", value_name = "DIR")]
+        watch_dirs: Vec<PathBuf>,
+
+        /// Time between scans, e.g. `10s` or `1m`
+        #[arg(long, value_name = "DURATION", value_parser = format::parse_duration, default_value = "10s")]
+        poll: std::time::Duration,
+
+        /// Category for torrents from the --watch-dir folders
+        #[arg(long, value_name = "NAME", requires = "watch_dirs")]
+        category: Option<String>,
+
+        /// Save path on the server for torrents from the --watch-dir folders
+        #[arg(short, long, requires = "watch_dirs")]
+        dest: Option<String>,
+
+        /// Serve what the daemon is doing on this address: /status, /metrics and /events
+        #[arg(long, value_name = "ADDR")]
+        listen: Option<std::net::SocketAddr>,
+    },
+    /// Serve the stats history over HTTP: Prometheus metrics, or a Grafana JSON datasource with --json-api
+    Exporter {
+        /// Serve time-series queries for Grafana's JSON datasource instead of Prometheus metrics
+        #[arg(long)]
+        json_api: bool,
+
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:9187")]
+        listen: std::net::SocketAddr,
+
+        /// History file to serve (default: `stats-<host>.jsonl` in the data directory)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Run a post-processing command for a torrent once: skipped if it already ran or is running for it
+    RunOnce {
+        /// Info-hash of the torrent (`%I` in qBittorrent's "Run external program")
+        #[arg(long)]
+        hash: String,
+
+        /// Name of the step that markers are kept for (default: the program's file name)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Delete the step's marker so it runs again for this torrent
+        #[arg(long, conflicts_with = "command")]
+        forget: bool,
+
+        /// Program and arguments, after `--`
+        #[arg(last = true, required_unless_present = "forget")]
+        command: Vec<String>,
+    },
+    /// Check the way to the server step by step (config, DNS, TCP, login, API) and show what fails
+    #[command(visible_alias = "ping")]
+    Doctor,
+    /// Show a health summary for the server: reachability, version, active torrents, speeds, free space
+    Status {
+        /// Query every configured server profile concurrently
+        #[arg(long)]
+        all_profiles: bool,
     },
 }
 
-#[derive(Deserialize, Debug)]
-struct Config {
-    default_save_path: Option<String>,
-    qbittorrent: Option<QBConfig>,
+#[derive(clap::Subcommand, Debug)]
+enum AuthAction {
+    /// Prompt for username and password, verify them and store the password in the OS keyring
+    Login,
+    /// Remove the stored password from the OS keyring
+    Logout,
 }
 
-#[derive(Deserialize, Debug)]
-struct QBConfig {
-    host: String,
-    username: Option<String>,
-    password: Option<String>,
+#[derive(clap::Subcommand, Debug)]
+enum CategoryAction {
+    /// Show the categories with their save paths and torrent counts
+    List,
+    /// Create missing categories and update changed save paths to match the config
+    Sync {
+        /// Read the wanted categories from the `[categories]` config table
+        #[arg(long, required = true)]
+        from_config: bool,
+
+        /// Also remove categories the config does not list (their torrents keep no category)
+        #[arg(long)]
+        prune: bool,
+    },
 }
 
-fn read_config(path: Option<PathBuf>) -> Config {
-    // Build a config loader that reads from (in order):
-    // 1) explicit `--config` path (if provided)
-    // 2) XDG config path (if present)
-    // 3) local `./rbit.toml` (if present)
-    // All file sources are added as optional (not required) so missing files don't error.
-    let mut builder = ConfigLoader::builder();
+#[derive(clap::Subcommand, Debug)]
+enum NoteAction {
+    /// Set a torrent's note, replacing any earlier one
+    Set {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
 
-    if let Some(p) = path {
-        builder = builder.add_source(ConfigFile::from(p).format(FileFormat::Toml).required(false));
-    } else {
-        // Prefer ~/.config/rbit/config.toml per user preference
-        if let Some(basedirs) = BaseDirs::new() {
-            let xdg = basedirs.config_dir().join("rbit").join("config.toml");
-            builder = builder.add_source(ConfigFile::from(xdg).format(FileFormat::Toml).required(false));
-        }
-        // Also allow local ./rbit.toml for repo-level config
-    builder = builder.add_source(ConfigFile::from(PathBuf::from("rbit.toml")).format(FileFormat::Toml).required(false));
-    }
+        text: String,
 
-    // Build the config loader; if building or deserialization fails, return defaults
-    match builder.build() {
-        Ok(loader) => loader.try_deserialize::<Config>().unwrap_or(Config {
-            default_save_path: None,
-            qbittorrent: None,
-        }),
-        Err(_) => Config {
-            default_save_path: None,
-            qbittorrent: None,
-        },
-    }
+        /// Also put the note on the torrent as a `note:<text>` tag, for other machines to see
+        #[arg(long)]
+        tag: bool,
+    },
+    /// Show a torrent's note
+    Show {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+    },
+    /// Remove a torrent's note and its `note:` tags
+    Clear {
+        /// Torrent id (hash or unique hash prefix)
+        id: String,
+    },
+    /// Show every torrent on the server that has a note
+    List,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum LimitAction {
+    /// Show or set the global download/upload limits
+    Global {
+        /// Download limit, e.g. `5M`, `500k` or `unlimited`
+        #[arg(long)]
+        dl: Option<Rate>,
+
+        /// Upload limit
+        #[arg(long)]
+        up: Option<Rate>,
+    },
+    /// Show or switch the alternative speed limits (configured in qBittorrent)
+    Alt {
+        #[arg(value_enum)]
+        mode: Option<commands::limit::AltMode>,
+    },
+    /// Show or set per-torrent limits
+    Torrent {
+        /// Torrent ids (hash or unique hash prefix)
+        #[arg(required_unless_present = "selector")]
+        ids: Vec<String>,
+
+        #[command(flatten)]
+        selector: Selector,
+
+        /// Download limit, e.g. `5M`, `500k` or `unlimited`
+        #[arg(long)]
+        dl: Option<Rate>,
+
+        /// Upload limit
+        #[arg(long)]
+        up: Option<Rate>,
+    },
 }
 
-fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
-    let config = read_config(cli.config.clone());
+#[derive(clap::Subcommand, Debug)]
+enum PrefsAction {
+    /// Print preferences: all of them as a table, or the given keys
+    Get {
+        keys: Vec<String>,
 
-    let client = Client::builder().cookie_store(true).build()?;
+        /// Print the raw JSON values
+        #[arg(long)]
+        json: bool,
+    },
+    /// Change well-known preferences with typed values (`on`/`off`, `5M`, `36h`, `unlimited`)
+    Set {
+        #[arg(value_name = "KEY=VALUE", required_unless_present = "json")]
+        assignments: Vec<String>,
 
-    // Determine effective host and credentials (CLI overrides > config > default)
-    let host = if let Some(h) = cli.host.clone() {
-        h.trim_end_matches('/').to_string()
-    } else if let Some(ref qb) = config.qbittorrent {
-        qb.host.trim_end_matches('/').to_string()
-    } else {
-        "http://127.0.0.1:8080".to_string()
-    };
+        /// Also send these keys as given, for preferences rbit has no type for, e.g. '{"add_trackers_enabled": true}'
+        #[arg(long, value_name = "OBJECT")]
+        json: Option<String>,
+    },
+}
 
-    let username = cli.username.clone().or_else(|| config.qbittorrent.as_ref().and_then(|q| q.username.clone()));
-    let password = cli.password.clone().or_else(|| config.qbittorrent.as_ref().and_then(|q| q.password.clone()));
+#[derive(clap::Subcommand, Debug)]
+enum PortAction {
+    /// Print the listening port
+    Get,
+    /// Listen on this port
+    Set {
+        #[arg(value_parser = clap::value_parser!(u16).range(1..))]
+        port: u16,
+    },
+    /// Move to a random port of the dynamic range (49152-65535)
+    Random,
+    /// Check whether peers can reach the port: the server's own status, a direct dial and `[port] check_url`
+    Test,
+}
 
-    match cli.command {
-        Command::Add { input, dest } => {
-            // save path: CLI override > config.default_save_path > cwd
-            let save_path = if let Some(d) = dest {
-                d
-            } else if let Some(ref s) = config.default_save_path {
-                PathBuf::from(s)
-            } else {
-                std::env::current_dir()?
-            };
+#[derive(clap::Subcommand, Debug)]
+enum StatsAction {
+    /// Append one snapshot of transfer totals and per-torrent counters to the stats history (for cron)
+    Record {
+        /// History file to append to (default: `stats-<host>.jsonl` in the data directory)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+}
 
-            if input.starts_with("magnet:") {
-                add_magnet(&client, &host, username.as_deref(), password.as_deref(), &input, &save_path, cli.dry_run, cli.verbose)?;
-            } else {
-                add_torrent_file(&client, &host, username.as_deref(), password.as_deref(), PathBuf::from(input), &save_path, cli.dry_run, cli.verbose)?;
+#[derive(clap::Subcommand, Debug)]
+enum RssAction {
+    /// Show all folders and feeds with their article counts
+    List,
+    /// Subscribe to a feed
+    Add {
+        url: String,
+
+        /// Where to put it, e.g. `Linux\Ubuntu` (defaults to the URL at the top level)
+        #[arg(long)]
+        path: Option<String>,
+    },
+    /// Remove a feed, or a folder with everything in it
+    Remove {
+        path: String,
+    },
+    /// Fetch a feed or folder now (all feeds without a path)
+    Refresh {
+        path: Option<String>,
+    },
+    /// Show the articles of a feed or folder (all feeds without a path)
+    Items {
+        path: Option<String>,
+
+        /// Only articles not marked as read
+        #[arg(long)]
+        unread: bool,
+    },
+    /// Create a folder, e.g. `Linux` or `Linux\Distros`
+    AddFolder {
+        path: String,
+    },
+    /// Move a feed or folder into another folder (`""` for the top level)
+    Move {
+        /// Item path, folders separated by `\`
+        item: String,
+
+        /// Destination folder
+        folder: String,
+    },
+    /// Auto-download rules
+    Rule {
+        #[command(subcommand)]
+        action: RuleAction,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum RuleAction {
+    /// Show all auto-download rules
+    List,
+    /// Show one rule in full
+    Show {
+        name: String,
+    },
+    /// Create a rule, or change the given settings of an existing one
+    Add {
+        name: String,
+
+        #[command(flatten)]
+        flags: commands::rss::RuleFlags,
+    },
+    /// Delete a rule
+    Remove {
+        name: String,
+    },
+    /// Create or update the rules from the `[rss.rules]` config table on the server
+    Sync {
+        /// Also delete server rules that the config does not list
+        #[arg(long)]
+        prune: bool,
+    },
+    /// Write all rules as JSON (the Web UI's export format)
+    Export {
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Create rules from an exported JSON file
+    Import {
+        file: PathBuf,
+
+        /// Replace rules that already exist on the server
+        #[arg(long)]
+        overwrite: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ConfigAction {
+    /// Write a new config file interactively: server address, credentials, save path, more profiles
+    Init {
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the effective settings and where each comes from (command line, env, file or default)
+    Show,
+    /// Print the effective configuration as TOML
+    Export {
+        /// Leave out plaintext passwords
+        #[arg(long)]
+        redact: bool,
+
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge profiles and settings from an exported file into the config file (`--config` or the user config)
+    Import {
+        /// Exported TOML file
+        file: PathBuf,
+
+        /// Replace profiles and settings that already exist
+        #[arg(long)]
+        overwrite: bool,
+
+        /// Do not prompt for passwords of imported profiles to store in the keyring
+        #[arg(long)]
+        no_prompt: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if !matches!(e.downcast_ref::<RbitError>(), Some(RbitError::Absent { .. })) {
+                eprintln!("{} {:?}", i18n::tr!("error"), e);
             }
-            println!("Added to qBittorrent (destination: {})", save_path.display());
+            ExitCode::from(error::exit_code(&e))
         }
-        Command::List { all } => {
-            list_torrents(&client, &host, username.as_deref(), password.as_deref(), all, cli.verbose)?;
+    }
+}
+
+/// Whether `ids` may name several torrents.
+fn many(ids: &[String]) -> bool {
+    ids.len() > 1 || ids.iter().any(|id| api::parse_row_ref(id).is_some_and(|r| r.is_ok_and(|(a, b)| a != b)))
+}
+
+/// Commands that change many torrents at once; `rbit daemon` pauses while one runs. A followed
+/// add is not one, since it would pause the daemon until the downloads finish.
+fn is_bulk(command: &Command) -> bool {
+    match command {
+        Command::Add { inputs, batch, inspect: false, flags, .. } => {
+            !flags.follow && (batch.is_some() || inputs.len() > 1 || inputs.iter().any(|i| Path::new(i).is_dir()))
         }
+        Command::Reannounce { ids, all, selector, .. }
+        | Command::Recheck { ids, all, selector }
+        | Command::Pause { ids, all, selector }
+        | Command::Resume { ids, all, selector } => *all || many(ids) || !selector.is_empty(),
+        Command::SeedLimits { ids, .. } => many(ids),
+        Command::Cleanup | Command::ApplyRules { .. } | Command::Prune { .. } | Command::Import { .. } | Command::Category { action: CategoryAction::Sync { .. } } => true,
+        _ => false,
     }
+}
 
-    Ok(())
+/// Run `command` on a server that is not qBittorrent. Only the commands [`TorrentClient`] covers
+/// are available there, besides those that do not contact the server.
+async fn run_with(
+    client: &impl TorrentClient,
+    backend: Backend,
+    name: &str,
+    cli: Cli,
+    config: &config::Config,
+    profile_name: &str,
+    connect: &ConnectOptions,
+) -> anyhow::Result<()> {
+    match cli.command {
+        Command::Add { inputs, inspect: true, .. } => commands::inspect::inspect(&inputs),
+        Command::Inspect { files } => commands::inspect::inspect(&files),
+        Command::Magnet { files } => commands::inspect::magnet(&files),
+        Command::Add { inputs, batch: None, dest, flags, .. } => {
+            // the client's own default when neither --dest nor default_save_path is given
+            let fixed_save_path = dest.is_some();
+            let save_path = dest.or_else(|| config.default_save_path.as_ref().map(PathBuf::from));
+            let mut options = flags.into_options(save_path);
+            options.rules = Rules::from_config(&config.rules)?;
+            options.fixed_save_path = fixed_save_path;
+            client.login().await?;
+            for input in &inputs {
+                let options = savepath::for_input(input, &options)?;
+                client.add(input, &options).await.map_err(|e| anyhow::anyhow!("{}: {:#}", input, e))?;
+                println!("{}", i18n::tr!("add-done-to", name = input.as_str(), client = backend.name(), destination = options.destination()));
+            }
+            Ok(())
+        }
+        Command::List { options, layout, cached, .. } => commands::list::list_from(client, &options, &layout, &config.list, cli.porcelain, cached).await,
+        Command::Doctor => commands::doctor::doctor(client, profile_name, &config_sources(cli.config), connect).await,
+        Command::Reannounce { ids, all, selector, .. } => client::bulk(client, Bulk::Reannounce, &ids, all, &selector, cli.dry_run).await,
+        Command::Recheck { ids, all, selector } => client::bulk(client, Bulk::Recheck, &ids, all, &selector, cli.dry_run).await,
+        _ => Err(RbitError::InvalidInput(format!(
+            "`rbit {}` is not available for {} servers yet (there are list, add, reannounce, recheck and doctor)",
+            name,
+            backend.name()
+        ))
+        .into()),
+    }
 }
 
-#[derive(serde::Deserialize, Debug)]
-struct TorrentInfo {
-    name: String,
-    hash: String,
-    state: String,
-    progress: Option<f64>,
-    dlspeed: Option<u64>,
-    upspeed: Option<u64>,
+/// `rbit import` into `dest` from `from`: a server profile, or the URL of a Transmission server
+/// that needs no login.
+async fn import_from(from: &str, config: &config::Config, cli_connect: &ConnectOptions, dest: &Session, ids: &[String], selector: &Selector, with_state: bool) -> anyhow::Result<()> {
+    if from.contains("://") {
+        let source = Transmission::new(normalize_host(from)?, None, None, cli_connect, dest.dry_run)?;
+        return commands::import::import(&source, dest, ids, selector, with_state).await;
+    }
+    let qb = config.profile(from)?.ok_or_else(|| RbitError::InvalidInput("the default profile has no [qbittorrent] table to import from".to_string()))?;
+    let host = normalize_host(&qb.host).map_err(|e| anyhow::anyhow!("server profile '{}': {}", from, e))?;
+    // the forward closes when the import is done
+    let tunnel = match qb.ssh_tunnel.as_deref() {
+        Some(destination) => Some(Tunnel::open(destination, &host, qb.tunnel_port).await?),
+        None => None,
+    };
+    let host = tunnel.as_ref().map_or(host, |t| t.host.clone());
+    let connect = cli_connect.clone().or(qb.connect_options());
+    let password = qb.resolve_password(from);
+    match qb.kind {
+        Backend::Qbittorrent => {
+            let mut source = Session::new(host, qb.username.clone(), password, dest.dry_run)?;
+            source.set_connect_options(&connect)?;
+            source.retry = dest.retry;
+            source.set_rate_limit(config.http.rate_limit);
+            commands::import::import(&source, dest, ids, selector, with_state).await
+        }
+        Backend::Transmission => {
+            let source = Transmission::new(host, qb.username.clone(), password, &connect, dest.dry_run)?;
+            commands::import::import(&source, dest, ids, selector, with_state).await
+        }
+        Backend::Deluge => {
+            let source = Deluge::new(host, password, &connect, dest.dry_run)?;
+            commands::import::import(&source, dest, ids, selector, with_state).await
+        }
+    }
 }
 
-#[derive(Tabled)]
-struct TorrentRow {
-    id: String,
-    name: String,
-    status: String,
-    progress: String,
-    dl: String,
-    up: String,
+async fn run() -> anyhow::Result<()> {
+    let matches = commands::man::with_examples(Cli::command()).get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    logging::init(cli.verbose, cli.log_file.as_deref())?;
+    if let Some(ref path) = cli.trace_http {
+        http::trace_to(path)?;
+    }
+    let config = read_config(cli.config.clone(), cli.strict_config).map_err(|e| RbitError::InvalidInput(format!("{:#}", e)))?;
+    let mut output_theme = theme::Theme::from_config(&config.theme, cli.color)?;
+    if let Some(style) = cli.table_style {
+        output_theme.table_style = style;
+    }
+    i18n::init(config.language.as_deref())?;
+    theme::init(output_theme);
+    notifications::init(config.notifications.clone(), cli.dry_run);
+    confirm::init(cli.yes || config.no_confirm);
+    format::set_units(match (cli.si, cli.binary) {
+        (true, _) => format::Units::Si,
+        (_, true) => format::Units::Binary,
+        _ => config.units.unwrap_or_default(),
+    });
+    if let Command::Config { action } = cli.command {
+        return match action {
+            ConfigAction::Init { force } => {
+                let target = cli.config.clone().or_else(user_config_path).ok_or_else(|| anyhow::anyhow!("no config directory; pass --config"))?;
+                if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+                    anyhow::bail!("config init asks questions and needs a terminal");
+                }
+                commands::config::init(&target, force)
+            }
+            ConfigAction::Show => commands::config::show(&matches, &config, &config_sources(cli.config.clone())),
+            ConfigAction::Export { redact, output } => commands::config::export(&config, redact, output.as_deref()),
+            ConfigAction::Import { file, overwrite, no_prompt } => {
+                let target = cli.config.clone().or_else(user_config_path).ok_or_else(|| anyhow::anyhow!("no config directory; pass --config"))?;
+                let prompt = !no_prompt && std::io::IsTerminal::is_terminal(&std::io::stdin());
+                commands::config::import(&file, &target, overwrite, prompt)
+            }
+        };
+    }
+    if let Command::Completions { shell } = cli.command {
+        commands::completions::completions(&mut Cli::command(), shell);
+        return Ok(());
+    }
+    if let Command::Man { command, config_file, output } = cli.command {
+        return commands::man::man(Cli::command(), &command, config_file, output.as_deref());
+    }
+    if let Command::RunOnce { hash, name, forget, command } = cli.command {
+        return commands::run_once::run_once(&hash, name.as_deref(), forget, &command, cli.dry_run);
+    }
+    if let Command::SelfUpdate { check } = cli.command {
+        return commands::self_update::self_update(check, config.no_self_update, cli.dry_run).await;
+    }
+    if cli.server.as_deref() == Some(ALL_PROFILES) {
+        return run_everywhere(cli, &matches, &config).await;
+    }
+    if matches!(cli.command, Command::List { everywhere: true, .. } | Command::Status { all_profiles: true }) {
+        return run_on_profiles(cli, &matches, &config).await;
+    }
+    let profile_name = cli.server.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    run_on(cli, &matches, &config, profile_name).await
 }
 
-fn bytes_human(b: u64) -> String {
-    let kb = 1024u64;
-    if b >= kb * kb * kb {
-        format!("{:.2} GB/s", b as f64 / (kb * kb * kb) as f64)
-    } else if b >= kb * kb {
-        format!("{:.2} MB/s", b as f64 / (kb * kb) as f64)
-    } else if b >= kb {
-        format!("{:.2} KB/s", b as f64 / kb as f64)
-    } else {
-        format!("{} B/s", b)
+/// `--server all`: `list` and `status` cover every profile at once, and the commands that change
+/// many torrents run on each profile in turn.
+async fn run_everywhere(mut cli: Cli, matches: &ArgMatches, config: &config::Config) -> anyhow::Result<()> {
+    match cli.command {
+        Command::List { ref mut everywhere, .. } => *everywhere = true,
+        Command::Status { ref mut all_profiles } => *all_profiles = true,
+        Command::Reannounce { ref ids, .. } | Command::Recheck { ref ids, .. } | Command::Pause { ref ids, .. } | Command::Resume { ref ids, .. } => {
+            if !ids.is_empty() {
+                return Err(RbitError::InvalidInput(
+                    "torrent ids belong to one server; with --server all, pick torrents with --all or selectors such as --state or --category".to_string(),
+                )
+                .into());
+            }
+            return fan_out(matches, config).await;
+        }
+        _ => {
+            return Err(RbitError::InvalidInput(format!(
+                "`rbit {}` works on one server at a time; --server all is for list, status, pause, resume, reannounce and recheck",
+                matches.subcommand_name().unwrap_or_default()
+            ))
+            .into())
+        }
     }
+    run_on_profiles(cli, matches, config).await
 }
 
-fn truncate(s: &str, n: usize) -> String {
-    if s.len() <= n {
-        s.to_string()
-    } else {
-        let mut t = s[..n].to_string();
-        t.push_str("...");
-        t
+/// `list --everywhere` and `status --all-profiles`: one view of every qBittorrent profile. Only
+/// the profiles are connected to, not the default server; without any, `status` shows that.
+async fn run_on_profiles(cli: Cli, matches: &ArgMatches, config: &config::Config) -> anyhow::Result<()> {
+    let (servers, _tunnels) = profile_sessions(config, &cli_connect(&cli), retry_policy(&cli, config), config.http.concurrency.unwrap_or(4), cli.dry_run).await?;
+    if servers.is_empty() {
+        return match cli.command {
+            Command::Status { .. } => run_on(cli, matches, config, DEFAULT_PROFILE.to_string()).await,
+            _ => Err(RbitError::InvalidInput(NO_PROFILES.to_string()).into()),
+        };
+    }
+    match cli.command {
+        Command::List { options, layout, cached, .. } => {
+            commands::list::list_everywhere(&servers, &options, &layout, &config.list, cli.porcelain, config.history.auto_record(), cached).await
+        }
+        Command::Status { .. } => commands::status::status(&servers, cli.porcelain).await,
+        _ => unreachable!("only list and status cover every profile"),
     }
 }
 
-fn list_torrents(client: &Client, host: &str, username: Option<&str>, password: Option<&str>, all: bool, verbose: bool) -> anyhow::Result<()> {
-    login(client, host, username, password, verbose)?;
-    let url = format!("{}/api/v2/torrents/info?filter=all", host);
-    let res = client.get(&url).send()?;
-    let body = res.text()?;
-    let torrents: Vec<TorrentInfo> = serde_json::from_str(&body)?;
-
-    // filter active by default: progress < 1.0 or dlspeed/upspeed > 0
-    let rows: Vec<&TorrentInfo> = torrents.iter().filter(|t| {
-        if all {
-            return true;
-        }
-        let progress = t.progress.unwrap_or(0.0);
-        let dls = t.dlspeed.unwrap_or(0);
-        let ups = t.upspeed.unwrap_or(0);
-        progress < 1.0 || dls > 0 || ups > 0
-    }).collect();
-
-    let mut table_rows: Vec<TorrentRow> = Vec::new();
-    for t in rows {
-        let id = if t.hash.len() >= 8 { t.hash[..8].to_string() } else { t.hash.clone() };
-        let name = truncate(&t.name, 40);
-        let status = t.state.clone();
-        let progress = t.progress.map(|p| format!("{:.1}%", p * 100.0)).unwrap_or_else(|| "-".to_string());
-        let dl = bytes_human(t.dlspeed.unwrap_or(0));
-        let up = bytes_human(t.upspeed.unwrap_or(0));
-        table_rows.push(TorrentRow { id, name, status, progress, dl, up });
+/// Retries from `--retries` and `[http]`.
+fn retry_policy(cli: &Cli, config: &config::Config) -> RetryPolicy {
+    let mut retry = RetryPolicy::default();
+    if let Some(n) = cli.retries.or(config.http.retries) {
+        retry.retries = n;
     }
+    if let Some(ms) = config.http.retry_backoff_ms {
+        retry.backoff = std::time::Duration::from_millis(ms);
+    }
+    retry
+}
 
-    let table = Table::new(table_rows).with(tabled::Style::psql());
-    println!("{}", table);
-    Ok(())
+/// The connection options given on the command line, which override a profile's.
+fn cli_connect(cli: &Cli) -> ConnectOptions {
+    ConnectOptions { proxy: cli.proxy.clone(), insecure: cli.insecure, ca_cert: cli.ca_cert.clone(), client_cert: cli.client_cert.clone(), client_key: cli.client_key.clone() }
 }
 
-fn login(client: &Client, host: &str, username: Option<&str>, password: Option<&str>, verbose: bool) -> anyhow::Result<()> {
-    if let (Some(user), Some(pass)) = (username, password) {
-        let params = [("username", user), ("password", pass)];
-        let url = format!("{}/api/v2/auth/login", host);
-        let res = client.post(&url).form(&params).send()?;
-        let status = res.status();
-        let text = res.text()?;
-        if verbose {
-            println!("[verbose] POST {} -> {}", url, status);
-            println!("[verbose] response: {}", text);
-        }
-        if text != "Ok." {
-            anyhow::bail!("login failed: {}", text);
+/// Run the command of `matches` on every configured profile, one after the other so that their
+/// output and confirmations stay apart. A profile that fails does not stop the others, and one
+/// without matching torrents is only mentioned.
+async fn fan_out(matches: &ArgMatches, config: &config::Config) -> anyhow::Result<()> {
+    let names: Vec<String> = config.profiles().into_iter().map(|(name, _)| name).collect();
+    if names.is_empty() {
+        return Err(RbitError::InvalidInput(NO_PROFILES.to_string()).into());
+    }
+    let mut failed = 0;
+    let mut unmatched = None;
+    let mut matched = 0;
+    for name in &names {
+        println!("[{}]", name);
+        let cli = Cli::from_arg_matches(matches)?;
+        match run_on(cli, matches, config, name.clone()).await {
+            Ok(()) => matched += 1,
+            Err(e) => match e.downcast_ref::<RbitError>() {
+                Some(RbitError::NotFound(message)) => {
+                    println!("{}", message);
+                    unmatched = Some(message.clone());
+                }
+                _ => {
+                    eprintln!("{} {}: {:?}", i18n::tr!("error"), name, e);
+                    failed += 1;
+                }
+            },
         }
     }
+    if let (0, 0, Some(message)) = (failed, matched, unmatched) {
+        return Err(RbitError::NotFound(format!("{} on any server", message)).into());
+    }
+    if failed > 0 {
+        return Err(RbitError::PartialFailure { failed, total: names.len() }.into());
+    }
     Ok(())
 }
 
-fn add_magnet(client: &Client, host: &str, username: Option<&str>, password: Option<&str>, magnet: &str, save_path: &PathBuf, dry_run: bool, verbose: bool) -> anyhow::Result<()> {
-    let url = format!("{}/api/v2/torrents/add", host);
-    let save_path_s = save_path.to_string_lossy().to_string();
-    let params = [("urls", magnet), ("savepath", save_path_s.as_str())];
-    if dry_run {
-        println!("[dry-run] POST {}", url);
-        println!("[dry-run] form params: urls={}, savepath={}", magnet, save_path.display());
-        return Ok(());
-    }
-    login(client, host, username, password, verbose)?;
-    let res = client.post(&url).form(&params).send()?;
-    let status = res.status();
-    let body = res.text()?;
-    if verbose {
-        println!("[verbose] POST {} -> {}", url, status);
-        println!("[verbose] response: {}", body);
-    }
-    if status.is_success() {
-        Ok(())
-    } else {
-        anyhow::bail!("failed to add magnet: {}", body);
+/// A session for every qBittorrent profile in the config, with the SSH tunnels they go through;
+/// dropping the tunnels closes them. Profiles whose tunnel cannot be opened are skipped.
+async fn profile_sessions(
+    config: &config::Config,
+    cli_connect: &ConnectOptions,
+    retry: RetryPolicy,
+    concurrency: usize,
+    dry_run: bool,
+) -> anyhow::Result<(Vec<(String, Session)>, Vec<Tunnel>)> {
+    let mut servers = Vec::new();
+    let mut tunnels = Vec::new();
+    // `status` and `list --everywhere` speak qBittorrent's API only
+    for (name, qb) in config.profiles().into_iter().filter(|(_, qb)| qb.kind.is_default()) {
+        let mut host = normalize_host(&qb.host).map_err(|e| anyhow::anyhow!("server profile '{}': {}", name, e))?;
+        if let Some(ref destination) = qb.ssh_tunnel {
+            match Tunnel::open(destination, &host, qb.tunnel_port).await {
+                Ok(tunnel) => {
+                    host = tunnel.host.clone();
+                    tunnels.push(tunnel);
+                }
+                Err(e) => {
+                    eprintln!("{} {}", i18n::tr!("warning"), i18n::tr!("profile-skipped", profile = name.as_str(), reason = format!("{:#}", e)));
+                    continue;
+                }
+            }
+        }
+        let password = qb.resolve_password(&name);
+        let mut server = Session::new(host, qb.username.clone(), password, dry_run)?;
+        server.set_connect_options(&cli_connect.clone().or(qb.connect_options()))?;
+        server.retry = retry;
+        server.concurrency = concurrency;
+        server.set_rate_limit(config.http.rate_limit);
+        server.path_map = qb.path_map().map_err(|e| anyhow::anyhow!("server profile '{}': {}", name, e))?;
+        server.logout_on_stop = qb.logout_on_stop;
+        servers.push((name, server));
     }
+    Ok((servers, tunnels))
 }
 
-fn add_torrent_file(client: &Client, host: &str, _username: Option<&str>, _password: Option<&str>, file: PathBuf, save_path: &PathBuf, dry_run: bool, verbose: bool) -> anyhow::Result<()> {
-    let url = format!("{}/api/v2/torrents/add", host);
+/// Run `cli`'s command on the server of profile `profile_name`.
+async fn run_on(cli: Cli, matches: &ArgMatches, config: &config::Config, profile_name: String) -> anyhow::Result<()> {
+    let profile = config.profile(&profile_name)?;
+
+    // Determine effective host and credentials (CLI overrides > RBIT_* env vars > config > default)
+    let host = if let Some(h) = cli.host.clone() {
+        normalize_host(&h)?
+    } else if let Some(qb) = profile {
+        normalize_host(&qb.host).map_err(|e| anyhow::anyhow!("server profile '{}': {}", profile_name, e))?
+    } else {
+        DEFAULT_HOST.to_string()
+    };
+    // an explicit --host goes there directly; the forward closes when `run` returns
+    let tunnel = match profile.and_then(|qb| qb.ssh_tunnel.as_deref()) {
+        Some(destination) if cli.host.is_none() => Some(Tunnel::open(destination, &host, profile.and_then(|qb| qb.tunnel_port)).await?),
+        _ => None,
+    };
+    let host = tunnel.as_ref().map_or(host, |t| t.host.clone());
 
-    let filename = file
-        .file_name()
-        .and_then(|s| s.to_str())
-        .unwrap_or("upload.torrent")
-        .to_string();
+    let username = cli.username.clone().or_else(|| profile.and_then(|q| q.username.clone()));
+    let password = cli.password.clone().or_else(|| profile.and_then(|q| q.resolve_password(&profile_name)));
 
-    let file_part = multipart::Part::reader(File::open(&file)?).file_name(filename);
+    let retry = retry_policy(&cli, config);
+    let concurrency = config.http.concurrency.unwrap_or(4);
+    let cli_connect = cli_connect(&cli);
+    let connect = cli_connect.clone().or(profile.map(QBConfig::connect_options).unwrap_or_default());
 
-    if dry_run {
-        println!("[dry-run] POST {}", url);
-        println!("[dry-run] file: {}", file.display());
-        println!("[dry-run] savepath: {}", save_path.display());
-        return Ok(());
+    let backend = profile.map_or_else(Backend::default, |qb| qb.kind);
+    if !backend.is_default() {
+        let name = matches.subcommand_name().unwrap_or_default();
+        return match backend {
+            Backend::Transmission => {
+                let client = Transmission::new(host, username, password, &connect, cli.dry_run)?;
+                run_with(&client, backend, name, cli, config, &profile_name, &connect).await
+            }
+            Backend::Deluge => {
+                let client = Deluge::new(host, password, &connect, cli.dry_run)?;
+                run_with(&client, backend, name, cli, config, &profile_name, &connect).await
+            }
+            Backend::Qbittorrent => unreachable!("qBittorrent goes through Session"),
+        };
     }
 
-    // perform login first (no-op if no creds)
-    login(client, host, _username, _password, verbose)?;
+    let mut session = Session::new(host, username, password, cli.dry_run)?;
+    session.set_connect_options(&connect)?;
+    session.retry = retry;
+    session.concurrency = concurrency;
+    session.set_rate_limit(config.http.rate_limit);
+    if let Some(qb) = profile {
+        session.path_map = qb.path_map().map_err(|e| anyhow::anyhow!("server profile '{}': {}", profile_name, e))?;
+        session.logout_on_stop = qb.logout_on_stop;
+    }
 
-    let form = multipart::Form::new()
-        .part("torrents", file_part)
-        .text("savepath", save_path.to_string_lossy().to_string());
+    let _bulk = match is_bulk(&cli.command) && !cli.dry_run {
+        true => lock::shared(&lock::for_host("bulk", &session.host), &i18n::tr!("lock-holder-daemon")).await.ok(),
+        false => None,
+    };
 
-    let res = client.post(&url).multipart(form).send()?;
-    let status = res.status();
-    let body = res.text()?;
-    if verbose {
-        println!("[verbose] POST {} -> {}", url, status);
-        println!("[verbose] response: {}", body);
-    }
-    if status.is_success() {
-        Ok(())
-    } else {
-        anyhow::bail!("failed to add torrent file: {}", body);
+    match cli.command {
+        Command::Add { inputs, inspect: true, .. } => {
+            commands::inspect::inspect(&inputs)?;
+        }
+        Command::Inspect { files } => {
+            commands::inspect::inspect(&files)?;
+        }
+        Command::Magnet { files } => {
+            commands::inspect::magnet(&files)?;
+        }
+        Command::Add { inputs, batch, dest, recursive, delete_after_add, flags, .. } => {
+            // save path: CLI override > config.default_save_path > cwd; with automatic
+            // torrent management only an explicit --dest is sent
+            let fixed_save_path = dest.is_some();
+            let save_path = if let Some(d) = dest {
+                Some(d)
+            } else if flags.auto_tmm {
+                None
+            } else if let Some(ref s) = config.default_save_path {
+                Some(PathBuf::from(s))
+            } else {
+                Some(std::env::current_dir()?)
+            };
+
+            let mut options = flags.into_options(save_path);
+            options.rules = Rules::from_config(&config.rules)?;
+            options.fixed_save_path = fixed_save_path;
+            if options.follow && options.on_complete.is_none() {
+                options.on_complete = config.hooks.on_complete.clone();
+            }
+
+            if let Some(batch) = batch {
+                commands::add::add_batch(&session, &batch, &options).await?;
+            } else {
+                commands::add::add_inputs(&session, &inputs, &options, recursive, delete_after_add).await?;
+            }
+        }
+        Command::Exists { inputs, quiet } => {
+            commands::exists::exists(&session, &inputs, quiet).await?;
+        }
+        Command::Get { id, field } => {
+            commands::get::get(&session, &id, &field).await?;
+        }
+        Command::Export { ids, all, selector, dir } => {
+            commands::export::export(&session, &ids, all, &selector, &dir).await?;
+        }
+        Command::CrossSeed { id, tracker, source, check } => {
+            commands::cross_seed::cross_seed(&session, &id, &tracker, source.as_deref(), check).await?;
+        }
+        Command::Import { ids, from, with_state, selector } => {
+            import_from(&from, config, &cli_connect, &session, &ids, &selector, with_state).await?;
+        }
+        Command::Verify { id, deep, data_root, torrent_file } => {
+            commands::verify::verify(&session, &id, deep, data_root.as_deref(), torrent_file.as_deref()).await?;
+        }
+        Command::Wait { ids, timeout, state, interval, on_complete } => {
+            let on_complete = on_complete.or_else(|| config.hooks.on_complete.clone());
+            commands::wait::wait(&session, &ids, state, timeout, interval, on_complete.as_deref()).await?;
+        }
+        Command::List { options, layout, cached, .. } => {
+            commands::list::list_torrents(&session, &options, &layout, &config.list, cli.porcelain, config.history.auto_record(), cached).await?;
+        }
+        Command::Auth { action: AuthAction::Login } => {
+            commands::auth::login(&mut session, &profile_name).await?;
+        }
+        Command::Auth { action: AuthAction::Logout } => {
+            commands::auth::logout(&profile_name, session.dry_run)?;
+        }
+        Command::Note { action } => match action {
+            NoteAction::Set { id, text, tag } => commands::note::set(&session, &id, &text, tag).await?,
+            NoteAction::Show { id } => commands::note::show(&session, &id).await?,
+            NoteAction::Clear { id } => commands::note::clear(&session, &id).await?,
+            NoteAction::List => commands::note::list(&session).await?,
+        },
+        Command::Move { id, location } => {
+            commands::manage::move_torrent(&session, &id, &location).await?;
+        }
+        Command::Rename { id, name } => {
+            commands::manage::rename(&session, &id, &name).await?;
+        }
+        Command::RenameFile { id, old_path, new_path, folder } => {
+            commands::manage::rename_file(&session, &id, &old_path, &new_path, folder).await?;
+        }
+        Command::Reannounce { ids, all, selector, force } => {
+            commands::reannounce::reannounce(&session, &ids, all, &selector, force, &config.announce).await?;
+        }
+        Command::Recheck { ids, all, selector } => {
+            commands::manage::recheck(&session, &ids, all, &selector).await?;
+        }
+        Command::Pause { ids, all, selector } => {
+            commands::manage::pause(&session, &ids, all, &selector).await?;
+        }
+        Command::Resume { ids, all, selector } => {
+            commands::manage::resume(&session, &ids, all, &selector).await?;
+        }
+        Command::Shutdown => {
+            commands::shutdown::shutdown(&session).await?;
+        }
+        Command::Peers { id, ban, summary } => {
+            commands::peers::peers(&session, &id, &ban, summary).await?;
+        }
+        Command::Pieces { id, width, lines } => {
+            commands::pieces::pieces(&session, &id, width, lines).await?;
+        }
+        Command::Queue { direction, ids, selector } => {
+            commands::manage::queue(&session, direction, &ids, &selector).await?;
+        }
+        Command::Sequential { id, state } => {
+            commands::manage::sequential(&session, &id, state).await?;
+        }
+        Command::FirstLast { id, state } => {
+            commands::manage::first_last(&session, &id, state).await?;
+        }
+        Command::Stream { id, file, player, data_root, buffer } => {
+            commands::stream::stream(&session, &config.stream, &id, file, player.as_deref(), data_root.as_deref(), buffer).await?;
+        }
+        Command::Webseeds { id, add, remove } => {
+            commands::webseeds::webseeds(&session, &id, &add, &remove).await?;
+        }
+        Command::Trackers { id, add, remove, replace } => {
+            let replace = replace.chunks(2).map(|p| (p[0].clone(), p[1].clone())).collect();
+            let edits = commands::trackers::TrackerEdits { add, remove, replace };
+            commands::trackers::trackers(&session, &id, &edits).await?;
+        }
+        Command::Limit { action } => match action {
+            LimitAction::Global { dl, up } => commands::limit::global(&session, dl, up).await?,
+            LimitAction::Alt { mode } => commands::limit::alt(&session, mode).await?,
+            LimitAction::Torrent { ids, selector, dl, up } => commands::limit::torrent(&session, &ids, &selector, dl, up).await?,
+        },
+        Command::Cleanup => commands::cleanup::cleanup(&session, &config.retention, cli.json).await?,
+        Command::ApplyRules { ids, all, selector } => {
+            let rules = Rules::from_config(&config.rules)?;
+            commands::apply_rules::apply_rules(&session, &rules, &ids, all, &selector, cli.json).await?;
+        }
+        Command::Schedule { apply } => {
+            let schedule = Schedule::from_config(&config.schedule)?;
+            commands::schedule::schedule(&session, &schedule, apply).await?;
+        }
+        Command::Prune { ratio_over, seeded_for, category, delete_data } => {
+            // limits on the command line replace the configured policy
+            let policy = match ratio_over.is_some() || seeded_for.is_some() {
+                true => Policy { ratio_over, seeded_for, category, delete_data },
+                false => {
+                    let configured = config.prune.policy()?.ok_or_else(|| {
+                        RbitError::InvalidInput("nothing to prune by: pass --ratio-over or --seeded-for, or set them in [prune]".to_string())
+                    })?;
+                    Policy { category: category.or(configured.category), delete_data: delete_data || configured.delete_data, ..configured }
+                }
+            };
+            commands::prune::prune(&session, &policy, cli.json).await?
+        }
+        Command::Category { action } => match action {
+            CategoryAction::List => commands::category::list(&session).await?,
+            CategoryAction::Sync { prune, .. } => commands::category::sync(&session, &config.categories, prune, cli.json).await?,
+        },
+        Command::Prefs { action } => match action {
+            PrefsAction::Get { keys, json } => commands::prefs::get(&session, &keys, json).await?,
+            PrefsAction::Set { assignments, json } => commands::prefs::set(&session, &assignments, json.as_deref()).await?,
+        },
+        Command::Port { action } => match action {
+            PortAction::Get => commands::port::get(&session).await?,
+            PortAction::Set { port } => commands::port::set(&session, port).await?,
+            PortAction::Random => commands::port::random(&session).await?,
+            PortAction::Test => commands::port::test(&session, &config.port).await?,
+        },
+        Command::Report { kind, format } => {
+            commands::report::report(&session, kind, format).await?;
+        }
+        Command::Rss { action } => match action {
+            RssAction::List => commands::rss::list(&session).await?,
+            RssAction::Add { url, path } => commands::rss::add_feed(&session, &url, path.as_deref()).await?,
+            RssAction::Remove { path } => commands::rss::remove(&session, &path).await?,
+            RssAction::Refresh { path } => commands::rss::refresh(&session, path.as_deref()).await?,
+            RssAction::Items { path, unread } => commands::rss::items(&session, path.as_deref(), unread).await?,
+            RssAction::AddFolder { path } => commands::rss::add_folder(&session, &path).await?,
+            RssAction::Move { item, folder } => commands::rss::move_item(&session, &item, &folder).await?,
+            RssAction::Rule { action: RuleAction::List } => commands::rss::list_rules(&session).await?,
+            RssAction::Rule { action: RuleAction::Show { name } } => commands::rss::show_rule(&session, &name).await?,
+            RssAction::Rule { action: RuleAction::Add { name, flags } } => {
+                commands::rss::add_rule(&session, &name, &flags.into_rule()).await?
+            }
+            RssAction::Rule { action: RuleAction::Remove { name } } => commands::rss::remove_rule(&session, &name).await?,
+            RssAction::Rule { action: RuleAction::Sync { prune } } => {
+                commands::rss::sync_rules(&session, &config.rss.rules, prune, cli.json).await?
+            }
+            RssAction::Rule { action: RuleAction::Export { output } } => {
+                commands::rss::export_rules(&session, output.as_deref()).await?
+            }
+            RssAction::Rule { action: RuleAction::Import { file, overwrite } } => {
+                commands::rss::import_rules(&session, &file, overwrite).await?
+            }
+        },
+        Command::Search { add: Some(index), .. } => {
+            let options = AddOptions {
+                save_path: config.default_save_path.as_ref().map(PathBuf::from),
+                rules: Rules::from_config(&config.rules)?,
+                ..AddOptions::default()
+            };
+            commands::search::add_result(&session, index, &options).await?;
+        }
+        Command::Search { query, saved, category, plugins, save, limit, timeout, no_dedup, .. } => {
+            let mut search = match (saved, query) {
+                (Some(name), _) => config.saved_search(&name)?.clone(),
+                (None, Some(query)) => SavedSearch { query, category: None, plugins: Vec::new() },
+                (None, None) => unreachable!("clap requires a query, --saved or --add"),
+            };
+            // flags override what a saved search has stored
+            if category.is_some() {
+                search.category = category;
+            }
+            if !plugins.is_empty() {
+                search.plugins = plugins;
+            }
+            if let Some(name) = save {
+                let target = cli.config.clone().or_else(user_config_path).ok_or_else(|| anyhow::anyhow!("no config directory; pass --config"))?;
+                commands::search::save(&target, &name, &search)?;
+            }
+            commands::search::search(&session, &search, limit, std::time::Duration::from_secs(timeout), no_dedup).await?;
+        }
+        Command::SeedLimits { ids, ratio, seeding_time, inactive_seeding_time } => {
+            let limits = commands::limit::ShareLimits {
+                ratio,
+                seeding_minutes: seeding_time,
+                inactive_minutes: inactive_seeding_time,
+            };
+            commands::limit::share_limits(&session, &ids, &limits).await?;
+        }
+        Command::Stats { action: None, since: None } => {
+            commands::stats::stats(&session).await?;
+        }
+        Command::Stats { action: None, since: Some(since) } => {
+            commands::stats::over_time(&session.host, since)?;
+        }
+        Command::History { id, since, limit } => {
+            commands::history::history(&session, &id, since, limit).await?;
+        }
+        Command::Timeline { id, since, width } => {
+            commands::timeline::timeline(&session, &id, since, width.into()).await?;
+        }
+        Command::Forecast { id, window, from_history } => {
+            commands::forecast::forecast(&session, id.as_deref(), window, from_history).await?;
+        }
+        Command::Log { level, peers, lines, follow } => {
+            commands::log::log(&session, level, peers, lines, follow).await?;
+        }
+        Command::Tui { interval } => {
+            let defaults = AddOptions {
+                save_path: config.default_save_path.as_ref().map(PathBuf::from),
+                rules: Rules::from_config(&config.rules)?,
+                ..AddOptions::default()
+            };
+            commands::tui::tui(&session, interval, &defaults).await?;
+        }
+        Command::Stats { action: Some(StatsAction::Record { file }), .. } => {
+            commands::stats::record(&session, file.as_deref()).await?;
+        }
+        Command::Daemon { watch_dirs, poll, category, dest, listen } => {
+            let cli_watches: Vec<WatchDir> = watch_dirs.into_iter().map(|dir| WatchDir { dir, category: category.clone(), save_path: dest.clone() }).collect();
+            let settings = commands::daemon::Settings::from_config(config, &cli_watches)?;
+            let reload = commands::daemon::Reload::new(cli.config.clone(), cli.strict_config, config, cli_watches);
+            commands::daemon::run(&session, poll, settings, reload, listen).await?;
+        }
+        Command::Exporter { json_api, listen, file } => {
+            let file = match file {
+                Some(file) => file,
+                None => history::history_path(&session.host).ok_or_else(|| anyhow::anyhow!("no data directory available; pass --file"))?,
+            };
+            commands::exporter::serve(file, listen, json_api).await?;
+        }
+        Command::Config { .. } | Command::Completions { .. } | Command::RunOnce { .. } | Command::SelfUpdate { .. } | Command::Man { .. } => unreachable!("handled before connecting"),
+        Command::CompleteHashes => commands::completions::hashes(&mut session).await,
+        Command::Doctor => commands::doctor::doctor(&session, &profile_name, &config_sources(cli.config.clone()), &connect).await?,
+        Command::Status { .. } => {
+            commands::status::status(&[(profile_name, session)], cli.porcelain).await?;
+        }
     }
+
+    Ok(())
 }
@@ -0,0 +1,98 @@
+//! Free-form notes on torrents, for remembering why one is kept. They live on this machine in
+//! `notes.json` in rbit's data directory, keyed by info-hash so renames and moves keep them. A
+//! note can also be put on the torrent itself as a `note:<text>` tag, where other machines and
+//! the Web UI see it.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache;
+use crate::history;
+use crate::lock;
+
+pub const TAG_PREFIX: &str = "note:";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Note {
+    pub text: String,
+    pub updated_at: u64,
+}
+
+pub fn notes_path() -> Option<PathBuf> {
+    history::data_dir().map(|dir| dir.join("notes.json"))
+}
+
+/// Notes by lowercase info-hash; none when the file does not exist yet.
+pub fn load() -> anyhow::Result<BTreeMap<String, Note>> {
+    let Some(path) = notes_path() else {
+        return Ok(BTreeMap::new());
+    };
+    match fs::read(&path) {
+        Ok(data) => serde_json::from_slice(&data).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(anyhow::anyhow!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Write all notes, through a temporary file so a crash cannot leave half of them. Hold the
+/// `notes` lock from [`load`] to here when changing them, see [`set`].
+pub fn save(notes: &BTreeMap<String, Note>) -> anyhow::Result<()> {
+    let path = notes_path().ok_or_else(|| anyhow::anyhow!("no data directory available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+    fs::write(&tmp, serde_json::to_vec_pretty(notes)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+pub fn set(hash: &str, text: &str) -> anyhow::Result<()> {
+    // another rbit setting a note at the same time would otherwise lose one of them
+    let _lock = lock::exclusive("notes")?;
+    let mut notes = load()?;
+    notes.insert(hash.to_lowercase(), Note { text: text.to_string(), updated_at: cache::now() });
+    save(&notes)
+}
+
+/// Remove a torrent's note; false if it had none.
+pub fn remove(hash: &str) -> anyhow::Result<bool> {
+    let _lock = lock::exclusive("notes")?;
+    let mut notes = load()?;
+    let removed = notes.remove(&hash.to_lowercase()).is_some();
+    if removed {
+        save(&notes)?;
+    }
+    Ok(removed)
+}
+
+/// The `note:` tags in a torrent's comma-separated tags.
+pub fn note_tags(tags: &str) -> Vec<&str> {
+    tags.split(',').map(str::trim).filter(|t| t.starts_with(TAG_PREFIX)).collect()
+}
+
+/// The note in a torrent's first `note:` tag.
+pub fn from_tags(tags: &str) -> Option<String> {
+    note_tags(tags).first().map(|tag| tag[TAG_PREFIX.len()..].to_string())
+}
+
+/// The tag carrying `text`. Tags cannot contain commas, so those become semicolons.
+pub fn tag_for(text: &str) -> String {
+    format!("{}{}", TAG_PREFIX, text.replace(',', ";").trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_as_tags() {
+        assert_eq!(tag_for("seed for the ratio, until May"), "note:seed for the ratio; until May");
+        assert_eq!(note_tags("linux, note:keep me,iso"), ["note:keep me"]);
+        assert_eq!(from_tags("linux, note:keep me").as_deref(), Some("keep me"));
+        assert!(note_tags("").is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+//! Notifications when torrents complete, fail or stall, while rbit keeps watching them (`daemon`,
+//! `wait`, `add --follow`), and when the daemon finds the disk filling up (see [`crate::space`])
+//! or its guard check failing (see [`crate::guard`]):
+//! desktop notifications and webhooks, set up in `[notifications]`.
+//!
+//! A notification that cannot be delivered is reported as a warning; it never stops the command
+//! that raised it. A torrent's completion is notified once, even when several rbit processes
+//! (say, the daemon and a `wait`) see it.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::api::TorrentInfo;
+use crate::commands::follow::{progress_of, Progress};
+use crate::format::size_human;
+use crate::i18n::tr;
+use crate::postprocess::{self, Claim};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    /// Finished downloading
+    Complete,
+    /// Went into the `error` or `missingFiles` state
+    Error,
+    /// Stopped finding peers to download from (`stalledDL`)
+    Stalled,
+    /// Free space went below `[disk] min_free`, or back above `resume_above`
+    #[serde(rename = "low_space")]
+    LowSpace,
+    /// The daemon's `[guard]` check failed, or passes again
+    Guard,
+}
+
+/// How a webhook's body is shaped.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{"event", "name", "hash", "state", "size", "host", "message"}`
+    #[default]
+    Json,
+    Discord,
+    Slack,
+    /// The Bot API's `sendMessage`; needs `chat_id`
+    Telegram,
+}
+
+/// `[[notifications.webhooks]]`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Chat to post to, for `telegram`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+}
+
+/// `[notifications]`: where to send notifications, and for which events
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NotificationsConfig {
+    /// Show desktop notifications
+    #[serde(default)]
+    pub desktop: bool,
+    /// Events to notify about (default: all of them)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<EventKind>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<Webhook>,
+}
+
+impl NotificationsConfig {
+    pub fn is_empty(&self) -> bool {
+        !self.desktop && self.events.is_empty() && self.webhooks.is_empty()
+    }
+
+    fn wants(&self, kind: EventKind) -> bool {
+        (self.desktop || !self.webhooks.is_empty()) && (self.events.is_empty() || self.events.contains(&kind))
+    }
+}
+
+/// Something that happened to a torrent.
+pub struct Event<'a> {
+    pub kind: EventKind,
+    pub torrent: &'a TorrentInfo,
+    pub host: &'a str,
+}
+
+impl Event<'_> {
+    pub fn message(&self) -> String {
+        let t = self.torrent;
+        match self.kind {
+            EventKind::Complete => format!("Completed {} ({})", t.name, size_human(t.size.unwrap_or(0))),
+            EventKind::Error => format!("{} stopped: {}", t.name, t.state),
+            EventKind::Stalled => format!("{} stalled at {:.1}%", t.name, t.progress.unwrap_or(0.0) * 100.0),
+            EventKind::LowSpace => format!("{} is low on space", t.name),
+            EventKind::Guard => format!("{} is paused by the guard", t.name),
+        }
+    }
+
+    fn body(&self, hook: &Webhook) -> serde_json::Value {
+        let message = self.message();
+        match hook.format {
+            WebhookFormat::Json => json!({
+                "event": self.kind,
+                "name": self.torrent.name,
+                "hash": self.torrent.hash.to_lowercase(),
+                "state": self.torrent.state,
+                "size": self.torrent.size,
+                "host": self.host,
+                "message": message,
+            }),
+            WebhookFormat::Discord => json!({ "content": message }),
+            WebhookFormat::Slack => json!({ "text": message }),
+            WebhookFormat::Telegram => json!({ "chat_id": hook.chat_id, "text": message }),
+        }
+    }
+}
+
+/// Turns successive listings of torrents into events: a torrent that completes, fails or stalls
+/// between two listings raises one. The first listing only sets the baseline.
+#[derive(Default)]
+pub struct Tracker {
+    last: HashMap<String, (Progress, String)>,
+    primed: bool,
+}
+
+impl Tracker {
+    pub fn update<'a>(&mut self, torrents: &'a [TorrentInfo]) -> Vec<(EventKind, &'a TorrentInfo)> {
+        let mut events = Vec::new();
+        let mut now = HashMap::new();
+        for t in torrents {
+            let progress = progress_of(t);
+            if let (true, Some((before, state))) = (self.primed, self.last.get(&t.hash)) {
+                match progress {
+                    Progress::Complete if *before != Progress::Complete => events.push((EventKind::Complete, t)),
+                    Progress::Failed if *before != Progress::Failed => events.push((EventKind::Error, t)),
+                    Progress::Downloading if t.state == "stalledDL" && state != "stalledDL" => events.push((EventKind::Stalled, t)),
+                    _ => {}
+                }
+            }
+            now.insert(t.hash.clone(), (progress, t.state.clone()));
+        }
+        self.last = now;
+        self.primed = true;
+        events
+    }
+}
+
+struct Notifier {
+    config: NotificationsConfig,
+    client: reqwest::Client,
+    dry_run: bool,
+}
+
+static NOTIFIER: RwLock<Option<Arc<Notifier>>> = RwLock::new(None);
+
+fn notifier() -> Option<Arc<Notifier>> {
+    NOTIFIER.read().ok()?.clone()
+}
+
+/// Set up notifications for this run; later calls are ignored.
+pub fn init(config: NotificationsConfig, dry_run: bool) {
+    if let Ok(mut notifier) = NOTIFIER.write() {
+        notifier.get_or_insert_with(|| Arc::new(Notifier { config, client: reqwest::Client::new(), dry_run }));
+    }
+}
+
+/// Send notifications as `config` says from now on, e.g. after the daemon reloaded the config.
+pub fn reconfigure(config: NotificationsConfig) {
+    if let Ok(mut notifier) = NOTIFIER.write() {
+        let (client, dry_run) = notifier.as_ref().map_or_else(|| (reqwest::Client::new(), false), |n| (n.client.clone(), n.dry_run));
+        *notifier = Some(Arc::new(Notifier { config, client, dry_run }));
+    }
+}
+
+/// Whether this is the first process to notify about the torrent completing, by the run-once
+/// markers of [`postprocess`]. Without a data directory to keep them in, every one is first.
+fn first_completion(t: &TorrentInfo) -> bool {
+    let Some(dir) = postprocess::markers_dir() else {
+        return true;
+    };
+    match postprocess::claim(&dir, &t.hash, "notify-complete") {
+        Ok(Claim::Acquired(guard)) => {
+            let _ = guard.finish();
+            true
+        }
+        Ok(Claim::Done(_) | Claim::Running(..)) => false,
+        Err(_) => true,
+    }
+}
+
+/// Whether any notification is set up, so there is a reason to keep track of torrents.
+pub fn enabled() -> bool {
+    notifier().is_some_and(|n| n.config.desktop || !n.config.webhooks.is_empty())
+}
+
+/// Send `event` wherever `[notifications]` says, if it asks for this kind of event.
+pub async fn notify(event: Event<'_>) {
+    let Some(notifier) = notifier().filter(|n| n.config.wants(event.kind)) else {
+        return;
+    };
+    let message = event.message();
+    if notifier.dry_run {
+        println!("[dry-run] would notify: {}", message);
+        return;
+    }
+    if event.kind == EventKind::Complete && !first_completion(event.torrent) {
+        tracing::debug!("completion of {} was already notified", event.torrent.name);
+        return;
+    }
+    deliver(&notifier, &message, |hook| event.body(hook)).await;
+}
+
+/// Send `message` about the server as a whole rather than one torrent, as an event of `kind`.
+/// JSON webhooks get `{"event", "host", "message"}`.
+pub async fn notify_server(kind: EventKind, host: &str, message: &str) {
+    let Some(notifier) = notifier().filter(|n| n.config.wants(kind)) else {
+        return;
+    };
+    if notifier.dry_run {
+        println!("[dry-run] would notify: {}", message);
+        return;
+    }
+    deliver(&notifier, message, |hook| match hook.format {
+        WebhookFormat::Json => json!({ "event": kind, "host": host, "message": message }),
+        WebhookFormat::Discord => json!({ "content": message }),
+        WebhookFormat::Slack => json!({ "text": message }),
+        WebhookFormat::Telegram => json!({ "chat_id": hook.chat_id, "text": message }),
+    })
+    .await;
+}
+
+async fn deliver(notifier: &Notifier, message: &str, body: impl Fn(&Webhook) -> serde_json::Value) {
+    if notifier.config.desktop {
+        let shown = notify_rust::Notification::new().appname("rbit").summary("rbit").body(message).show();
+        if let Err(e) = shown {
+            eprintln!("{} {}", tr!("warning"), tr!("desktop-notification-failed", reason = e.to_string()));
+        }
+    }
+    for hook in &notifier.config.webhooks {
+        let sent = notifier.client.post(&hook.url).json(&body(hook)).send().await.and_then(|r| r.error_for_status());
+        if let Err(e) = sent {
+            eprintln!("{} {}", tr!("warning"), tr!("webhook-failed", address = hook.url.as_str(), reason = e.without_url().to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(state: &str, progress: f64) -> TorrentInfo {
+        serde_json::from_value(json!({ "name": "t", "hash": "aaaa", "state": state, "progress": progress })).unwrap()
+    }
+
+    #[test]
+    fn events_from_changes_between_listings() {
+        let mut tracker = Tracker::default();
+        assert!(tracker.update(&[torrent("downloading", 0.5)]).is_empty());
+        let stalled = [torrent("stalledDL", 0.6)];
+        assert_eq!(tracker.update(&stalled).iter().map(|(k, _)| *k).collect::<Vec<_>>(), [EventKind::Stalled]);
+        assert!(tracker.update(&stalled).is_empty());
+        let done = [torrent("uploading", 1.0)];
+        assert_eq!(tracker.update(&done)[0].0, EventKind::Complete);
+        assert!(tracker.update(&done).is_empty());
+    }
+}
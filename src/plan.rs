@@ -0,0 +1,181 @@
+//! The changes a sync-style command (`cleanup`, `category sync`, `rss rule sync`) is about to
+//! make. Commands collect them, with the requests that carry them out, before anything is sent:
+//! a dry run prints them as a diff (`+` create, `~` update, `-` delete) with a summary, or as
+//! JSON with `--dry-run --json`; a real run sends the requests.
+
+use serde::Serialize;
+
+use crate::api::Session;
+use crate::format::size_human;
+use crate::i18n::tr;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+}
+
+impl Action {
+    fn sign(self) -> char {
+        match self {
+            Action::Create => '+',
+            Action::Update => '~',
+            Action::Delete => '-',
+        }
+    }
+
+    fn verb(self) -> &'static str {
+        match self {
+            Action::Create => "create",
+            Action::Update => "update",
+            Action::Delete => "delete",
+        }
+    }
+
+    /// As printed after a real run: `created`, `updated`, `removed`.
+    fn done(self) -> String {
+        match self {
+            Action::Create => tr!("plan-created"),
+            Action::Update => tr!("plan-updated"),
+            Action::Delete => tr!("plan-removed"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Change {
+    pub action: Action,
+    /// What is changed: `torrent`, `category`, `rule`
+    pub kind: &'static str,
+    pub name: String,
+    /// What changes, or why
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+    /// Bytes of the torrents concerned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Plan {
+    pub command: &'static str,
+    pub changes: Vec<Change>,
+    /// Items that are already as wanted
+    pub unchanged: usize,
+    /// POSTs carrying out the changes, in order
+    #[serde(skip)]
+    requests: Vec<(&'static str, Vec<(&'static str, String)>)>,
+}
+
+/// `category` → `categories`, `torrent` → `torrents`
+fn plural(kind: &str, n: usize) -> String {
+    match kind.strip_suffix('y') {
+        _ if n == 1 => kind.to_string(),
+        Some(stem) => format!("{}ies", stem),
+        None => format!("{}s", kind),
+    }
+}
+
+impl Plan {
+    pub fn new(command: &'static str) -> Self {
+        Plan { command, changes: Vec::new(), unchanged: 0, requests: Vec::new() }
+    }
+
+    pub fn change(&mut self, action: Action, kind: &'static str, name: &str, detail: String) -> &mut Change {
+        self.changes.push(Change { action, kind, name: name.to_string(), detail, size: None });
+        self.changes.last_mut().expect("just pushed")
+    }
+
+    /// Queue the request that carries out one or more of the changes.
+    pub fn request(&mut self, endpoint: &'static str, params: &[(&'static str, &str)]) {
+        self.requests.push((endpoint, params.iter().map(|(k, v)| (*k, v.to_string())).collect()));
+    }
+
+    /// One line per kind of change, e.g. `would delete 12 torrents (84.00 GiB)`.
+    pub fn summary(&self) -> Vec<String> {
+        let mut groups: Vec<(Action, &str, usize, Option<u64>)> = Vec::new();
+        for c in &self.changes {
+            match groups.iter_mut().find(|(a, k, _, _)| *a == c.action && *k == c.kind) {
+                Some(group) => {
+                    group.2 += 1;
+                    group.3 = match (group.3, c.size) {
+                        (Some(a), Some(b)) => Some(a + b),
+                        (a, b) => a.or(b),
+                    };
+                }
+                None => groups.push((c.action, c.kind, 1, c.size)),
+            }
+        }
+        let mut lines: Vec<String> = groups
+            .into_iter()
+            .map(|(action, kind, n, size)| {
+                let size = size.map(|s| format!(" ({})", size_human(s))).unwrap_or_default();
+                format!("would {} {} {}{}", action.verb(), n, plural(kind, n), size)
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push("nothing to change".to_string());
+        }
+        if self.unchanged > 0 {
+            lines.push(format!("{} unchanged", self.unchanged));
+        }
+        lines
+    }
+
+    /// Show the plan of a dry run: a diff and the summary, or a JSON document.
+    pub fn print(&self, json: bool) -> anyhow::Result<()> {
+        if json {
+            let mut doc = serde_json::to_value(self)?;
+            doc["summary"] = serde_json::json!(self.summary());
+            println!("{}", serde_json::to_string_pretty(&doc)?);
+            return Ok(());
+        }
+        for c in &self.changes {
+            let size = c.size.map(|s| format!(" ({})", size_human(s))).unwrap_or_default();
+            let detail = if c.detail.is_empty() { String::new() } else { format!(": {}", c.detail) };
+            println!("{} {} {}{}{}", c.action.sign(), c.kind, c.name, size, detail);
+        }
+        println!("{}", self.summary().join(", "));
+        Ok(())
+    }
+
+    /// Send the queued requests.
+    pub async fn apply(&self, session: &Session) -> anyhow::Result<()> {
+        for (endpoint, params) in &self.requests {
+            let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+            session.post_form(endpoint, &params).await?;
+        }
+        Ok(())
+    }
+
+    /// After a real run: `created <name>: <detail>` per change, then the counts.
+    pub fn print_applied(&self) {
+        for c in &self.changes {
+            let detail = if c.detail.is_empty() { String::new() } else { format!(": {}", c.detail) };
+            println!("{} {}{}", c.action.done(), c.name, detail);
+        }
+        let count = |action| self.changes.iter().filter(|c| c.action == action).count();
+        println!(
+            "{}",
+            tr!("plan-applied", created = count(Action::Create), updated = count(Action::Update), unchanged = self.unchanged, removed = count(Action::Delete))
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_groups_changes() {
+        let mut plan = Plan::new("cleanup");
+        plan.change(Action::Delete, "torrent", "a", String::new()).size = Some(2 << 30);
+        plan.change(Action::Delete, "torrent", "b", String::new()).size = Some(1 << 30);
+        plan.change(Action::Create, "category", "tv", String::new());
+        plan.unchanged = 4;
+        assert_eq!(plan.summary(), ["would delete 2 torrents (3.00 GiB)", "would create 1 category", "4 unchanged"]);
+        assert_eq!(Plan::new("category sync").summary(), ["nothing to change"]);
+    }
+}
@@ -0,0 +1,42 @@
+//! `--porcelain`: output for scripts that does not change between releases, whatever happens
+//! to the tables. One record per line, fields separated by a tab, raw values (bytes, bytes/s,
+//! fractions) and `-` for a missing one. Within a version fields are only ever added at the end
+//! of a line; any other change gets a new version, so `--porcelain=v1` keeps working.
+
+/// Porcelain format versions; `--porcelain` alone means the first.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Version {
+    V1,
+}
+
+/// A field as printed: tabs and line breaks (which would split the record) become spaces, and
+/// an empty value `-`.
+pub fn field(value: &str) -> String {
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value.replace(['\t', '\n', '\r'], " ")
+    }
+}
+
+/// An optional value, `-` when missing.
+pub fn opt<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| field(&v.to_string())).unwrap_or_else(|| "-".to_string())
+}
+
+pub fn print_record(fields: &[String]) {
+    println!("{}", fields.join("\t"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_cannot_split_records() {
+        assert_eq!(field("a\tb\nc"), "a b c");
+        assert_eq!(field(""), "-");
+        assert_eq!(opt::<u64>(None), "-");
+        assert_eq!(opt(Some(0.5)), "0.5");
+    }
+}
@@ -0,0 +1,106 @@
+//! Run-once markers for post-processing a torrent: a step is claimed with a lock file that only
+//! one process can create, and marked done when it succeeds, so overlapping completion events or
+//! a re-run over old torrents do not run the same step twice for one torrent.
+//!
+//! Files live in `postprocess/` in rbit's data directory: `<hash>-<step>.lock` while the step
+//! runs, `<hash>-<step>.done` afterwards.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cache;
+use crate::history;
+use crate::i18n::tr;
+
+/// A lock older than this is left over from a crashed run and may be taken over.
+const STALE_LOCK_SECS: u64 = 12 * 3600;
+
+pub fn markers_dir() -> Option<PathBuf> {
+    history::data_dir().map(|dir| dir.join("postprocess"))
+}
+
+fn marker_path(dir: &Path, hash: &str, step: &str, extension: &str) -> PathBuf {
+    dir.join(format!("{}-{}.{}", hash.to_lowercase(), cache::file_key(step), extension))
+}
+
+/// Unix time stored in a marker or lock file (its first field).
+fn stamp(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.split_whitespace().next()?.parse().ok()
+}
+
+pub enum Claim {
+    /// This process holds the step; call [`Guard::finish`] once it succeeds.
+    Acquired(Guard),
+    /// The step already ran, at this Unix time
+    Done(u64),
+    /// Another process is running the step (pid, started at)
+    Running(String, u64),
+}
+
+/// Holds the lock of a step. Dropping it without [`Guard::finish`] (a failed step) releases the
+/// lock so a later event can try again.
+pub struct Guard {
+    lock: PathBuf,
+    done: PathBuf,
+    finished: bool,
+}
+
+impl Guard {
+    /// Record the step as done and release the lock.
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        fs::write(&self.done, format!("{}\n", cache::now()))?;
+        let _ = fs::remove_file(&self.lock);
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = fs::remove_file(&self.lock);
+        }
+    }
+}
+
+/// Try to take step `step` for torrent `hash`.
+pub fn claim(dir: &Path, hash: &str, step: &str) -> anyhow::Result<Claim> {
+    fs::create_dir_all(dir)?;
+    let done = marker_path(dir, hash, step, "done");
+    let lock = marker_path(dir, hash, step, "lock");
+    for _ in 0..2 {
+        if done.exists() {
+            return Ok(Claim::Done(stamp(&done).unwrap_or(0)));
+        }
+        // create_new is atomic: of several processes racing here exactly one succeeds
+        match fs::OpenOptions::new().write(true).create_new(true).open(&lock) {
+            Ok(mut file) => {
+                file.write_all(format!("{} {}\n", cache::now(), std::process::id()).as_bytes())?;
+                return Ok(Claim::Acquired(Guard { lock, done, finished: false }));
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let content = fs::read_to_string(&lock).unwrap_or_default();
+                let mut fields = content.split_whitespace();
+                let since = fields.next().and_then(|t| t.parse().ok()).unwrap_or(0);
+                let pid = fields.next().unwrap_or("?").to_string();
+                if cache::now().saturating_sub(since) < STALE_LOCK_SECS {
+                    return Ok(Claim::Running(pid, since));
+                }
+                eprintln!("{} {}", tr!("warning"), tr!("stale-lock", pid = pid, path = lock.display().to_string()));
+                let _ = fs::remove_file(&lock);
+            }
+            Err(e) => return Err(anyhow::anyhow!("{}: {}", lock.display(), e)),
+        }
+    }
+    anyhow::bail!("could not lock {}", lock.display())
+}
+
+/// Delete the done marker of a step so it runs again; false if there was none.
+pub fn forget(dir: &Path, hash: &str, step: &str) -> anyhow::Result<bool> {
+    match fs::remove_file(marker_path(dir, hash, step, "done")) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,220 @@
+//! Terminal progress for transfers: a speed sampled over a sliding window, an ETA smoothed so it
+//! does not jump with every sample, and a progress bar showing both.
+//!
+//! The pieces work on their own: feed [`SpeedSampler`] byte counts as they come in, pass its
+//! rate to [`EtaSmoother`], and print [`duration_human`](crate::format::duration_human); or let
+//! [`TransferBar`] do all of it.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use crate::format::{duration_human, eta_human, rate_human, size_human};
+
+/// Transfer speed from the byte counts of the last `window`, e.g. polled from the server.
+#[derive(Debug, Clone)]
+pub struct SpeedSampler {
+    window: Duration,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl SpeedSampler {
+    pub fn new(window: Duration) -> Self {
+        SpeedSampler { window, samples: VecDeque::new() }
+    }
+
+    /// Record the total number of bytes done at `at`. A count lower than the last one (the
+    /// torrent was rechecked) starts the window over.
+    pub fn record(&mut self, at: Instant, bytes: u64) {
+        if self.samples.back().is_some_and(|&(_, last)| bytes < last) {
+            self.samples.clear();
+        }
+        self.samples.push_back((at, bytes));
+        // keep one sample older than the window, so the window is always covered
+        while self.samples.len() > 2 && at.duration_since(self.samples[1].0) >= self.window {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Bytes per second over the window; `None` until two samples some time apart are recorded.
+    pub fn rate(&self) -> Option<f64> {
+        let (&(first_at, first), &(last_at, last)) = (self.samples.front()?, self.samples.back()?);
+        let elapsed = last_at.duration_since(first_at).as_secs_f64();
+        (elapsed > 0.0).then(|| (last - first) as f64 / elapsed)
+    }
+}
+
+/// An ETA from an exponentially smoothed rate: each new rate moves the estimate by `weight`
+/// (between 0 and 1) of the difference, so short stalls and bursts do not swing the ETA.
+#[derive(Debug, Clone)]
+pub struct EtaSmoother {
+    weight: f64,
+    rate: Option<f64>,
+}
+
+impl EtaSmoother {
+    pub fn new(weight: f64) -> Self {
+        EtaSmoother { weight: weight.clamp(0.0, 1.0), rate: None }
+    }
+
+    /// Take a new rate (bytes per second) into the estimate and return the smoothed rate.
+    pub fn update(&mut self, rate: f64) -> f64 {
+        let smoothed = match self.rate {
+            Some(previous) => previous + self.weight * (rate - previous),
+            None => rate,
+        };
+        self.rate = Some(smoothed);
+        smoothed
+    }
+
+    /// The smoothed rate, once there has been one.
+    pub fn rate(&self) -> Option<f64> {
+        self.rate
+    }
+
+    /// Time left for `remaining` bytes; `None` while the rate is unknown or zero.
+    pub fn eta(&self, remaining: u64) -> Option<Duration> {
+        match self.rate {
+            _ if remaining == 0 => Some(Duration::ZERO),
+            Some(rate) if rate >= 1.0 => Some(Duration::from_secs_f64(remaining as f64 / rate)),
+            _ => None,
+        }
+    }
+}
+
+/// A progress bar for one transfer: `label [=====>    ]  42% 1.20 GiB / 2.86 GiB, 3.10 MiB/s, ETA 9m 02s`,
+/// followed by the server's own ETA once given one (`(server 7m 40s)`). It is drawn on stderr
+/// and hidden when stderr is not a terminal.
+pub struct TransferBar {
+    bar: ProgressBar,
+    sampler: SpeedSampler,
+    smoother: EtaSmoother,
+    /// The ETA the server reports, in seconds
+    server_eta: Option<i64>,
+}
+
+impl TransferBar {
+    pub fn new(label: &str, total: u64) -> Self {
+        Self::with_target(label, total, ProgressDrawTarget::stderr())
+    }
+
+    /// A bar drawn to `target`, e.g. [`ProgressDrawTarget::hidden`], or added to an
+    /// [`indicatif::MultiProgress`] through [`TransferBar::bar`].
+    pub fn with_target(label: &str, total: u64, target: ProgressDrawTarget) -> Self {
+        let bar = ProgressBar::with_draw_target(Some(total), target);
+        bar.set_style(
+            ProgressStyle::with_template("{prefix} [{bar:30}] {percent:>3}% {msg}")
+                .expect("the template is valid")
+                .progress_chars("=> "),
+        );
+        bar.set_prefix(label.to_string());
+        TransferBar {
+            bar,
+            sampler: SpeedSampler::new(Duration::from_secs(10)),
+            smoother: EtaSmoother::new(0.3),
+            server_eta: None,
+        }
+    }
+
+    /// Show `done` of `total` bytes, as of now.
+    pub fn update(&mut self, done: u64, total: u64) {
+        self.update_at(Instant::now(), done, total);
+    }
+
+    pub fn update_at(&mut self, at: Instant, done: u64, total: u64) {
+        self.sampler.record(at, done);
+        if let Some(rate) = self.sampler.rate() {
+            self.smoother.update(rate);
+        }
+        self.bar.set_length(total);
+        self.bar.set_position(done.min(total));
+        self.bar.set_message(self.status(done, total));
+    }
+
+    /// The ETA the server reports (qBittorrent's `eta`, in seconds), shown after the smoothed one
+    /// from the next update on.
+    pub fn set_server_eta(&mut self, eta: Option<i64>) {
+        self.server_eta = eta;
+    }
+
+    /// The text after the bar: sizes, speed and ETA.
+    pub fn status(&self, done: u64, total: u64) -> String {
+        let mut parts = vec![format!("{} / {}", size_human(done), size_human(total))];
+        if let Some(rate) = self.smoother.rate() {
+            parts.push(rate_human(rate as u64));
+        }
+        let eta = self.smoother.eta(total.saturating_sub(done)).map(duration_human);
+        let mut eta = format!("ETA {}", eta.as_deref().unwrap_or("-"));
+        if let Some(server) = self.server_eta.filter(|_| done < total) {
+            eta.push_str(&format!(" (server {})", eta_human(server)));
+        }
+        parts.push(eta);
+        parts.join(", ")
+    }
+
+    /// The speed and ETA estimators, for showing them elsewhere too.
+    pub fn estimate(&self) -> (&SpeedSampler, &EtaSmoother) {
+        (&self.sampler, &self.smoother)
+    }
+
+    pub fn set_label(&self, label: &str) {
+        self.bar.set_prefix(label.to_string());
+    }
+
+    /// Print a line above the bar without breaking it.
+    pub fn println(&self, line: &str) {
+        self.bar.println(line);
+    }
+
+    /// Stop the bar and replace it with `message`.
+    pub fn finish(&self, message: &str) {
+        self.bar.finish_with_message(message.to_string());
+    }
+
+    /// Stop the bar, leaving it as it is, followed by `message`.
+    pub fn abandon(&self, message: &str) {
+        self.bar.abandon_with_message(message.to_string());
+    }
+
+    pub fn bar(&self) -> &ProgressBar {
+        &self.bar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_and_smoothed_eta() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut sampler = SpeedSampler::new(Duration::from_secs(10));
+        sampler.record(at(0), 0);
+        assert_eq!(sampler.rate(), None);
+        sampler.record(at(5), 5000);
+        assert_eq!(sampler.rate(), Some(1000.0));
+        for s in 6..=30 {
+            sampler.record(at(s), 5000 + (s - 5) * 2000);
+        }
+        assert_eq!(sampler.rate(), Some(2000.0));
+        sampler.record(at(31), 10);
+        assert_eq!(sampler.rate(), None);
+
+        let mut smoother = EtaSmoother::new(0.5);
+        assert_eq!(smoother.eta(100), None);
+        smoother.update(1000.0);
+        assert_eq!(smoother.update(3000.0), 2000.0);
+        assert_eq!(smoother.eta(60_000), Some(Duration::from_secs(30)));
+
+        let mut bar = TransferBar::with_target("t", 100, ProgressDrawTarget::hidden());
+        bar.update_at(at(0), 0, 100 << 20);
+        bar.update_at(at(10), 10 << 20, 100 << 20);
+        assert_eq!(bar.status(10 << 20, 100 << 20), "10.00 MiB / 100.00 MiB, 1.00 MiB/s, ETA 1m 30s");
+        bar.set_server_eta(Some(40));
+        assert_eq!(bar.status(10 << 20, 100 << 20), "10.00 MiB / 100.00 MiB, 1.00 MiB/s, ETA 1m 30s (server 40s)");
+        bar.set_server_eta(Some(8640000));
+        assert!(bar.status(10 << 20, 100 << 20).ends_with("(server ∞)"));
+    }
+}
@@ -0,0 +1,152 @@
+//! `[[rules]]`: file torrents by where they come from. Each rule names a tracker domain, a
+//! regular expression for the torrent name, or both, and what a torrent that matches gets: a
+//! category, tags and a save path. The first rule that matches applies, when the torrent is
+//! added and with `rbit apply-rules` to torrents already on the server.
+//!
+//! ```toml
+//! [[rules]]
+//! tracker = "tv-tracker.example"
+//! name = 'S\d{2}E\d{2}'
+//! category = "tv"
+//! tags = ["episode"]
+//! save_path = "/media/tv/{name}"
+//! ```
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::RbitError;
+
+/// One of the `[[rules]]`. A list rather than a table keyed by tracker, because the config
+/// loader lowercases keys and a tracker may have several rules.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Rule {
+    /// Tracker domain, e.g. `example.org`, which also matches its subdomains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tracker: Option<String>,
+    /// Regular expression found in the torrent name, ignoring case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Save path on the server; may be a template like `default_save_path`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+}
+
+/// The configured rules, checked and ready to match.
+#[derive(Debug, Clone, Default)]
+pub struct Rules(Vec<(Rule, Option<Regex>)>);
+
+/// The host of a tracker URL, lowercase; a bare domain is taken as it is.
+fn host_of(tracker: &str) -> Option<String> {
+    match reqwest::Url::parse(tracker) {
+        Ok(url) => url.host_str().map(|h| h.trim_matches(['[', ']']).to_ascii_lowercase()),
+        Err(_) => Some(tracker.trim().trim_end_matches('.').to_ascii_lowercase()).filter(|h| !h.is_empty()),
+    }
+}
+
+/// Whether the tracker at `url` is `domain` or one of its subdomains.
+pub fn tracker_matches(domain: &str, url: &str) -> bool {
+    let (Some(domain), Some(host)) = (host_of(domain), host_of(url)) else {
+        return false;
+    };
+    host == domain || host.strip_suffix(&domain).is_some_and(|rest| rest.ends_with('.'))
+}
+
+impl Rules {
+    pub fn from_config(rules: &[Rule]) -> Result<Rules, RbitError> {
+        let invalid = |i: usize, why: String| RbitError::InvalidInput(format!("[[rules]] #{}: {}", i + 1, why));
+        let mut checked = Vec::with_capacity(rules.len());
+        for (i, rule) in rules.iter().enumerate() {
+            if rule.tracker.is_none() && rule.name.is_none() {
+                return Err(invalid(i, "give a tracker, a name pattern or both".to_string()));
+            }
+            if rule.category.is_none() && rule.tags.is_empty() && rule.save_path.is_none() {
+                return Err(invalid(i, "give a category, tags or a save_path for the torrents it matches".to_string()));
+            }
+            let name = rule.name.as_deref().map(|pattern| RegexBuilder::new(pattern).case_insensitive(true).build());
+            checked.push((rule.clone(), name.transpose().map_err(|e| invalid(i, e.to_string()))?));
+        }
+        Ok(Rules(checked))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether any rule looks at the tracker, which is worth finding out for torrents that do
+    /// not show one.
+    pub fn use_trackers(&self) -> bool {
+        self.0.iter().any(|(rule, _)| rule.tracker.is_some())
+    }
+
+    /// The first rule matching a torrent with `name` from `tracker` (an announce URL), and its
+    /// number counted from 1. What is not known does not match.
+    pub fn find(&self, name: Option<&str>, tracker: Option<&str>) -> Option<(usize, &Rule)> {
+        let found = self.0.iter().position(|(rule, pattern)| {
+            let tracker_ok = match rule.tracker.as_deref() {
+                Some(domain) => tracker.is_some_and(|url| tracker_matches(domain, url)),
+                None => true,
+            };
+            let name_ok = match pattern {
+                Some(pattern) => name.is_some_and(|name| pattern.is_match(name)),
+                None => true,
+            };
+            tracker_ok && name_ok
+        })?;
+        Some((found + 1, &self.0[found].0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(tracker: Option<&str>, name: Option<&str>, category: &str) -> Rule {
+        Rule { tracker: tracker.map(String::from), name: name.map(String::from), category: Some(category.to_string()), ..Rule::default() }
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let rules = Rules::from_config(&[
+            rule(Some("tv.example"), Some(r"s\d{2}e\d{2}"), "episodes"),
+            rule(Some("tv.example"), None, "tv"),
+            rule(None, Some(r"\bflac\b"), "music"),
+        ])
+        .unwrap();
+        let category = |name, tracker| rules.find(name, tracker).map(|(n, r)| (n, r.category.clone().unwrap()));
+        assert_eq!(category(Some("Show.S01E02"), Some("https://announce.tv.example/a/123")), Some((1, "episodes".to_string())));
+        assert_eq!(category(Some("Show.Complete"), Some("udp://tv.example:6969")), Some((2, "tv".to_string())));
+        assert_eq!(category(Some("Album [FLAC]"), Some("https://other.example/announce")), Some((3, "music".to_string())));
+        assert_eq!(category(Some("Show.S01E02"), Some("https://nottv.example/announce")), None);
+        assert_eq!(category(None, None), None);
+
+        assert!(Rules::from_config(&[Rule { category: Some("tv".to_string()), ..Rule::default() }]).is_err());
+        assert!(Rules::from_config(&[Rule { tracker: Some("tv.example".to_string()), ..Rule::default() }]).is_err());
+        assert!(Rules::from_config(&[rule(None, Some("(unclosed"), "x")]).is_err());
+    }
+
+    #[test]
+    fn name_patterns_are_regular_expressions() {
+        let matches = |pattern: &str, name: &str| Rules::from_config(&[rule(None, Some(pattern), "x")]).unwrap().find(Some(name), None).is_some();
+        assert!(matches(r"S\d{2}E\d{2}", "Show.S01E02.1080p"));
+        assert!(!matches(r"S\d{2}E\d{2}", "Show.S1E2"));
+        assert!(matches(r"^the\.show\b", "The.Show.S01"));
+        assert!(matches(r"\b(720|1080|2160)p\b", "Movie 2160p WEB"));
+        assert!(!matches(r"\b(720|1080)p\b", "Movie 21080pX"));
+        assert!(matches(r"[^a-z ]+$", "track 01"));
+        assert!(matches(r"a(b|c)*d", "acbcbd"));
+        assert!(matches(r"colou?r", "color"));
+        assert!(matches(r"\[FLAC\]", "Album [flac]"));
+        for bad in ["(ab", "ab)", "*a", "[a-", r"\q", "a{3,1}"] {
+            assert!(Rules::from_config(&[rule(None, Some(bad), "x")]).is_err(), "{}", bad);
+        }
+    }
+}
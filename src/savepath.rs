@@ -0,0 +1,354 @@
+//! Save paths are paths on the server, which need not run the same system as this machine: a
+//! Windows laptop often drives a Linux seedbox, and the other way round. rbit sends them in the
+//! server's style, told from its default save path, rather than this machine's: `D:/media/` goes
+//! to a Windows server as `D:\media` and `downloads\linux` to a Linux one as `downloads/linux`.
+//! A path that only makes sense on the other kind of system is refused instead of ending up as a
+//! folder with an odd name.
+//!
+//! A profile's `path_map` goes further and translates between the server's folders and where
+//! they are on this machine, e.g. `/downloads -> Z:\downloads`: `--dest Z:\downloads\tv` is sent
+//! as `/downloads/tv`, and save paths are shown as `Z:\downloads\tv`; see [`PathMap`].
+//!
+//! A save path can also be a template, e.g. `/media/{category}/{yyyy}`, filled in for each
+//! torrent before it is sent; see [`expand`].
+
+use std::path::Path;
+
+use crate::api::AddOptions;
+use crate::commands::export::file_name_for;
+use crate::error::RbitError;
+use crate::format::date_utc;
+use crate::torrent::{is_torrent_url, Metainfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// `/downloads/linux`
+    Posix,
+    /// `D:\downloads\linux` or `\\nas\share\linux`
+    Windows,
+}
+
+impl Style {
+    fn name(self) -> &'static str {
+        match self {
+            Style::Posix => "Unix-style",
+            Style::Windows => "Windows",
+        }
+    }
+}
+
+/// The style of an absolute path; `None` for a relative one.
+pub fn style_of(path: &str) -> Option<Style> {
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if (drive && bytes.get(2).is_none_or(|&c| c == b'\\' || c == b'/')) || path.starts_with("\\\\") {
+        Some(Style::Windows)
+    } else if path.starts_with('/') {
+        Some(Style::Posix)
+    } else {
+        None
+    }
+}
+
+/// `path` with the separators of `style`, without doubled or trailing separators and, for
+/// Windows, with an uppercase drive letter.
+pub fn normalize(path: &str, style: Style) -> String {
+    let (sep, other) = match style {
+        Style::Posix => ('/', '\\'),
+        Style::Windows => ('\\', '/'),
+    };
+    // a UNC path keeps its leading pair of separators
+    let unc = style == Style::Windows && (path.starts_with("\\\\") || path.starts_with("//"));
+    let mut out = String::with_capacity(path.len());
+    if unc {
+        out.push_str("\\\\");
+    }
+    for c in path.chars().skip(if unc { 2 } else { 0 }).map(|c| if c == other { sep } else { c }) {
+        if !(c == sep && out.ends_with(sep)) {
+            out.push(c);
+        }
+    }
+    let drive = style == Style::Windows && !unc && style_of(&out) == Some(Style::Windows);
+    if drive {
+        out[..1].make_ascii_uppercase();
+        if out.len() == 2 {
+            out.push(sep);
+        }
+    }
+    // keep the separator of a root: `/`, `C:\` or the `\\` of a UNC path
+    let root = match style {
+        Style::Posix if out.starts_with('/') => 1,
+        Style::Windows if unc => 2,
+        Style::Windows if drive => 3,
+        _ => 0,
+    };
+    while out.len() > root && out.ends_with(sep) {
+        out.pop();
+    }
+    out
+}
+
+/// `path`, as typed on this machine, the way a server whose default save path is
+/// `server_default` expects it.
+pub fn for_server(path: &str, server_default: &str) -> Result<String, RbitError> {
+    let path = path.trim();
+    let server = style_of(server_default).unwrap_or(Style::Posix);
+    match style_of(path) {
+        Some(style) if style != server => Err(RbitError::InvalidInput(format!(
+            "{} is a {} path, but the server uses {} paths like {}; give the path as the server sees it",
+            path,
+            style.name(),
+            server.name(),
+            server_default
+        ))),
+        _ => Ok(normalize(path, server)),
+    }
+}
+
+/// The rest of `path` below the folder `folder`, both in `style`: `Some("")` for the folder
+/// itself, `None` when `path` is elsewhere. Windows paths compare without regard to case.
+fn below<'a>(path: &'a str, folder: &str, style: Style) -> Option<&'a str> {
+    let head = path.get(..folder.len())?;
+    let same = match style {
+        Style::Posix => head == folder,
+        Style::Windows => head.eq_ignore_ascii_case(folder),
+    };
+    let rest = &path[folder.len()..];
+    // `/downloads` is not a parent of `/downloads2`; a root such as `/` or `C:\` ends in its separator
+    let boundary = rest.is_empty() || rest.starts_with(['/', '\\']) || folder.ends_with(['/', '\\']);
+    (same && boundary).then(|| rest.trim_start_matches(['/', '\\']))
+}
+
+/// `[qbittorrent] path_map` (or a `[servers.*]` one): folders on the server and where they are
+/// mounted on this machine, as entries like `"/downloads -> Z:\downloads"`.
+#[derive(Debug, Clone, Default)]
+pub struct PathMap(Vec<(String, String)>);
+
+impl PathMap {
+    pub fn parse(entries: &[String]) -> Result<PathMap, RbitError> {
+        let tidy = |path: &str| normalize(path, style_of(path).unwrap_or(Style::Posix));
+        entries
+            .iter()
+            .map(|entry| match entry.split_once("->").map(|(server, local)| (server.trim(), local.trim())) {
+                Some((server, local)) if style_of(server).is_some() && style_of(local).is_some() => Ok((tidy(server), tidy(local))),
+                _ => Err(RbitError::InvalidInput(format!("path_map entry {:?} is not `<absolute server path> -> <absolute local path>`", entry))),
+            })
+            .collect::<Result<_, _>>()
+            .map(PathMap)
+    }
+
+    /// `path` moved out of the folder `pick` gives first for the entry it is deepest in, to the
+    /// one `pick` gives second, in that one's style.
+    fn rebase(&self, path: &str, pick: impl Fn(&(String, String)) -> (&str, &str)) -> Option<String> {
+        let (rest, to) = self
+            .0
+            .iter()
+            .map(&pick)
+            .filter_map(|(from, to)| {
+                let style = style_of(from).unwrap_or(Style::Posix);
+                below(&normalize(path, style), from, style).map(|rest| (from.len(), rest.to_string(), to))
+            })
+            .max_by_key(|(len, ..)| *len)
+            .map(|(_, rest, to)| (rest, to))?;
+        let style = style_of(to).unwrap_or(Style::Posix);
+        Some(if rest.is_empty() { to.to_string() } else { normalize(&format!("{}/{}", to, rest), style) })
+    }
+
+    /// Where the server's `path` is on this machine, if it is in a mapped folder.
+    pub fn to_local(&self, path: &str) -> Option<String> {
+        self.rebase(path, |(server, local)| (server, local))
+    }
+
+    /// This machine's `path` as the server sees it, if it is in a mapped folder.
+    pub fn to_server(&self, path: &str) -> Option<String> {
+        self.rebase(path, |(server, local)| (local, server))
+    }
+
+    /// The server's `path` for showing here: on this machine where mapped, as it is otherwise.
+    pub fn show(&self, path: &str) -> String {
+        self.to_local(path).unwrap_or_else(|| path.to_string())
+    }
+}
+
+/// What the variables of a save-path template stand for, for one torrent.
+#[derive(Debug, Default, Clone)]
+pub struct Vars {
+    pub name: Option<String>,
+    pub category: Option<String>,
+    /// The first announce URL
+    pub tracker: Option<String>,
+    /// Unix time of the add, for the date variables
+    pub now: u64,
+}
+
+impl Vars {
+    pub fn of_metainfo(meta: &Metainfo) -> Vars {
+        Vars { name: Some(meta.name.clone()), tracker: meta.trackers.first().cloned(), ..Vars::default() }
+    }
+
+    /// What a magnet link tells: its `dn=` and first `tr=`. Torrent URLs tell nothing.
+    pub fn of_link(link: &str) -> Vars {
+        let mut vars = Vars::default();
+        if let Some(url) = reqwest::Url::parse(link).ok().filter(|u| u.scheme() == "magnet") {
+            for (key, value) in url.query_pairs() {
+                match &*key {
+                    "dn" if vars.name.is_none() => vars.name = Some(value.into_owned()),
+                    "tr" if vars.tracker.is_none() => vars.tracker = Some(value.into_owned()),
+                    _ => {}
+                }
+            }
+        }
+        vars
+    }
+}
+
+/// The variables a template can use
+const VARIABLES: [&str; 6] = ["name", "category", "tracker_domain", "yyyy", "mm", "dd"];
+
+/// Whether `path` is a template, which has to be filled in for each torrent.
+pub fn is_template(path: &str) -> bool {
+    path.contains('{')
+}
+
+/// A tracker's domain without the host's own name: `tracker.example.org` gives `example.org`.
+fn tracker_domain(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']');
+    if host.parse::<std::net::IpAddr>().is_ok() {
+        return Some(host.to_string());
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    Some(labels[labels.len().saturating_sub(2)..].join("."))
+}
+
+fn value(variable: &str, vars: &Vars) -> Result<String, RbitError> {
+    let missing = |why: &str| RbitError::InvalidInput(format!("{{{}}} in the save path has no value: {}", variable, why));
+    let date = date_utc(vars.now);
+    Ok(match variable {
+        "name" => vars.name.clone().ok_or_else(|| {
+            missing("the torrent's name is not known before qBittorrent has its metadata (--download-first fetches a torrent URL here first)")
+        })?,
+        "category" => vars.category.clone().filter(|c| !c.is_empty()).ok_or_else(|| missing("the torrent is added without a category"))?,
+        "tracker_domain" => vars.tracker.as_deref().and_then(tracker_domain).ok_or_else(|| missing("the torrent names no tracker"))?,
+        "yyyy" => date[..4].to_string(),
+        "mm" => date[5..7].to_string(),
+        "dd" => date[8..10].to_string(),
+        other => {
+            let known: Vec<String> = VARIABLES.iter().map(|v| format!("{{{}}}", v)).collect();
+            return Err(RbitError::InvalidInput(format!("unknown variable {{{}}} in the save path (there are {})", other, known.join(", "))));
+        }
+    })
+}
+
+/// `template` with its variables filled in: `{name}`, `{category}`, `{tracker_domain}`, and the
+/// date of the add (UTC) as `{yyyy}`, `{mm}` and `{dd}`. Each value becomes one folder name, so
+/// any separators in it are replaced. `{{` and `}}` stand for braces.
+pub fn expand(template: &str, vars: &Vars) -> Result<String, RbitError> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        let brace = &rest[i..i + 1];
+        if rest[i + 1..].starts_with(brace) {
+            out.push_str(brace);
+            rest = &rest[i + 2..];
+            continue;
+        }
+        if brace == "}" {
+            return Err(RbitError::InvalidInput(format!("unmatched }} in the save path {} (write }}}} for a brace)", template)));
+        }
+        let end = rest[i..].find('}').ok_or_else(|| RbitError::InvalidInput(format!("unclosed {{ in the save path {} (write {{{{ for a brace)", template)))?;
+        out.push_str(&file_name_for(&value(&rest[i + 1..i + end], vars)?));
+        rest = &rest[i + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `options` for `input` (a .torrent file or a link), with the rules applied and the save path
+/// filled in, for clients rbit does not send the template to as it is.
+pub fn for_input(input: &str, options: &AddOptions) -> anyhow::Result<AddOptions> {
+    if !options.per_torrent() {
+        return Ok(options.clone());
+    }
+    let vars = if input.starts_with("magnet:") || is_torrent_url(input) { Vars::of_link(input) } else { Vars::of_metainfo(&Metainfo::from_file(Path::new(input))?) };
+    options.for_torrent(vars)
+}
+
+/// Why a Unix-style path may have arrived as a Windows one: Git Bash and other MSYS shells
+/// rewrite arguments like `/downloads` into `C:/Program Files/Git/downloads`.
+pub fn msys_hint() -> Option<&'static str> {
+    std::env::var_os("MSYSTEM").map(|_| "the shell may have rewritten a /path; run with MSYS_NO_PATHCONV=1 or write //downloads")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_and_normalizing() {
+        assert_eq!(style_of("/downloads"), Some(Style::Posix));
+        assert_eq!(style_of("d:\\media"), Some(Style::Windows));
+        assert_eq!(style_of("D:"), Some(Style::Windows));
+        assert_eq!(style_of("\\\\nas\\share"), Some(Style::Windows));
+        assert_eq!(style_of("downloads/linux"), None);
+        assert_eq!(style_of("dc:/x"), None);
+
+        assert_eq!(normalize("/downloads//linux/", Style::Posix), "/downloads/linux");
+        assert_eq!(normalize("downloads\\linux", Style::Posix), "downloads/linux");
+        assert_eq!(normalize("/", Style::Posix), "/");
+        assert_eq!(normalize("d:/media//tv/", Style::Windows), "D:\\media\\tv");
+        assert_eq!(normalize("d:", Style::Windows), "D:\\");
+        assert_eq!(normalize("C:\\", Style::Windows), "C:\\");
+        assert_eq!(normalize("tv\\", Style::Windows), "tv");
+        assert_eq!(normalize("//nas/share/tv/", Style::Windows), "\\\\nas\\share\\tv");
+    }
+
+    #[test]
+    fn paths_for_the_server() {
+        assert_eq!(for_server("/downloads/linux/", "/downloads").unwrap(), "/downloads/linux");
+        assert_eq!(for_server("incoming\\linux", "/downloads").unwrap(), "incoming/linux");
+        assert_eq!(for_server("e:/media", "C:\\Users\\me\\Downloads").unwrap(), "E:\\media");
+        assert!(for_server("C:/Program Files/Git/downloads", "/downloads").is_err());
+        assert!(for_server("/downloads", "D:\\Downloads").is_err());
+        // a server whose default cannot be told apart is taken for a Unix-like one
+        assert_eq!(for_server("a\\b", "").unwrap(), "a/b");
+    }
+
+    #[test]
+    fn mapped_folders() {
+        let map = PathMap::parse(&["/downloads -> Z:\\downloads".to_string(), "/downloads/tv -> \\\\nas\\tv\\".to_string(), "D:\\Media -> /mnt/media".to_string()]).unwrap();
+        assert_eq!(map.to_server("z:/Downloads/linux/").as_deref(), Some("/downloads/linux"));
+        assert_eq!(map.to_server("Z:\\downloads").as_deref(), Some("/downloads"));
+        assert_eq!(map.to_local("/downloads/linux").as_deref(), Some("Z:\\downloads\\linux"));
+        // the longest folder wins
+        assert_eq!(map.to_local("/downloads/tv/Show").as_deref(), Some("\\\\nas\\tv\\Show"));
+        assert_eq!(map.to_local("/downloads2/x"), None);
+        assert_eq!(map.to_server("/mnt/media/films").as_deref(), Some("D:\\Media\\films"));
+        assert_eq!(map.show("/srv/other"), "/srv/other");
+
+        assert!(PathMap::parse(&["/downloads = Z:\\downloads".to_string()]).is_err());
+        assert!(PathMap::parse(&["downloads -> Z:\\downloads".to_string()]).is_err());
+    }
+
+    #[test]
+    fn templates() {
+        let vars = Vars {
+            name: Some("Show S01/E02".to_string()),
+            category: Some("tv".to_string()),
+            tracker: Some("https://tracker.example.org:443/announce".to_string()),
+            // 2024-03-05
+            now: 1_709_640_000,
+        };
+        assert_eq!(expand("/media/{category}/{yyyy}-{mm}/{name}", &vars).unwrap(), "/media/tv/2024-03/Show S01_E02");
+        assert_eq!(expand("/t/{tracker_domain}/{dd}/{{x}}", &vars).unwrap(), "/t/example.org/05/{x}");
+        assert!(expand("/media/{category}", &Vars::default()).is_err());
+        assert!(expand("/media/{genre}", &vars).is_err());
+        assert!(expand("/media/{category", &vars).is_err());
+
+        let magnet = Vars::of_link("magnet:?xt=urn:btih:abc&dn=Big%20Buck+Bunny&tr=udp%3A%2F%2F10.0.0.1%3A1337");
+        assert_eq!(magnet.name.as_deref(), Some("Big Buck Bunny"));
+        assert_eq!(magnet.tracker.as_deref().and_then(tracker_domain).as_deref(), Some("10.0.0.1"));
+        assert!(Vars::of_link("https://example.org/a.torrent").name.is_none());
+    }
+}
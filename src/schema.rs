@@ -0,0 +1,215 @@
+//! The shape of the config file, read from the serde definitions themselves: the keys of every
+//! table, their types and the values of enums. Documentation built from it (`rbit man`) lists
+//! exactly the keys rbit reads, so it cannot fall behind the code.
+//!
+//! [`probe`] deserializes a type from a deserializer that makes up a value for whatever is asked
+//! of it and notes down what was asked: a struct's field names, an enum's variants, and so on.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+
+/// What a config value is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Bool,
+    Integer,
+    Float,
+    String,
+    /// One of these strings
+    Choice(Vec<&'static str>),
+    List(Box<Kind>),
+    /// A table of the given keys
+    Table(Vec<(&'static str, Kind)>),
+    /// A table with keys of the user's choosing, e.g. `[servers.<name>]`
+    Map(Box<Kind>),
+    Any,
+}
+
+/// The shape of `T`.
+pub fn probe<T: DeserializeOwned>() -> Kind {
+    let mut kind = Kind::Any;
+    // a value made up here can only be rejected by a type's own checks, and then the part of the
+    // shape that was read up to that point is still right
+    let _ = T::deserialize(Probe { out: &mut kind });
+    kind
+}
+
+#[derive(Debug)]
+struct Error(String);
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+struct Probe<'a> {
+    out: &'a mut Kind,
+}
+
+macro_rules! scalar {
+    ($($method:ident => $kind:ident, $visit:ident($value:expr);)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            *self.out = Kind::$kind;
+            visitor.$visit($value)
+        })*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Probe<'_> {
+    type Error = Error;
+
+    scalar! {
+        deserialize_bool => Bool, visit_bool(false);
+        deserialize_i8 => Integer, visit_u64(0);
+        deserialize_i16 => Integer, visit_u64(0);
+        deserialize_i32 => Integer, visit_u64(0);
+        deserialize_i64 => Integer, visit_u64(0);
+        deserialize_u8 => Integer, visit_u64(0);
+        deserialize_u16 => Integer, visit_u64(0);
+        deserialize_u32 => Integer, visit_u64(0);
+        deserialize_u64 => Integer, visit_u64(0);
+        deserialize_f32 => Float, visit_f64(0.0);
+        deserialize_f64 => Float, visit_f64(0.0);
+        deserialize_char => String, visit_char(' ');
+        deserialize_str => String, visit_str("");
+        deserialize_string => String, visit_str("");
+        deserialize_bytes => String, visit_str("");
+        deserialize_byte_buf => String, visit_str("");
+        deserialize_identifier => String, visit_str("");
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        *self.out = Kind::Any;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut item = Kind::Any;
+        let value = visitor.visit_seq(One { item: Some(&mut item) });
+        *self.out = Kind::List(Box::new(item));
+        value
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut value_kind = Kind::Any;
+        let value = visitor.visit_map(Keys { keys: vec![""], kinds: vec![&mut value_kind], next: 0 });
+        *self.out = Kind::Map(Box::new(value_kind));
+        value
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        let mut kinds = vec![Kind::Any; fields.len()];
+        let value = visitor.visit_map(Keys { keys: fields.to_vec(), kinds: kinds.iter_mut().collect(), next: 0 });
+        *self.out = Kind::Table(fields.iter().copied().zip(kinds).collect());
+        value
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        *self.out = Kind::Choice(variants.to_vec());
+        let first = variants.first().copied().unwrap_or_default();
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(first))
+    }
+}
+
+/// A sequence of one item, whose shape stands for all of them.
+struct One<'a> {
+    item: Option<&'a mut Kind>,
+}
+
+impl<'de> de::SeqAccess<'de> for One<'_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        match self.item.take() {
+            Some(out) => seed.deserialize(Probe { out }).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A table with each of `keys` once.
+struct Keys<'a> {
+    keys: Vec<&'static str>,
+    kinds: Vec<&'a mut Kind>,
+    next: usize,
+}
+
+impl<'de> de::MapAccess<'de> for Keys<'_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.keys.get(self.next) {
+            Some(&key) => seed.deserialize(IntoDeserializer::<Error>::into_deserializer(key)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let out = &mut *self.kinds[self.next];
+        self.next += 1;
+        seed.deserialize(Probe { out })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn key<'a>(kind: &'a Kind, path: &str) -> Option<&'a Kind> {
+        path.split('.').try_fold(kind, |kind, name| match kind {
+            Kind::Table(keys) => keys.iter().find(|(k, _)| *k == name).map(|(_, kind)| kind),
+            Kind::Map(value) if name == "*" => Some(&**value),
+            Kind::List(item) if name == "[]" => Some(&**item),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn config_shape_comes_from_the_structs() {
+        let config = probe::<Config>();
+        assert_eq!(key(&config, "no_confirm"), Some(&Kind::Bool));
+        assert_eq!(key(&config, "units"), Some(&Kind::Choice(vec!["binary", "si"])));
+        assert_eq!(key(&config, "servers.*.type"), Some(&Kind::Choice(vec!["qbittorrent", "transmission", "deluge"])));
+        assert_eq!(key(&config, "servers.*.tunnel_port"), Some(&Kind::Integer));
+        assert_eq!(key(&config, "schedule.[].days"), Some(&Kind::String));
+        assert_eq!(key(&config, "rss.rules.*.feeds"), Some(&Kind::List(Box::new(Kind::String))));
+        assert!(key(&config, "theme.colors.*").is_some());
+    }
+}
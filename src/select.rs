@@ -0,0 +1,176 @@
+//! Selectors: `--name`, `--state`, `--tag`, `--category`, `--ratio` and `--older-than` pick the
+//! torrents a command acts on by what they are instead of by id. Given together, a torrent
+//! has to match all of them; given with ids, they narrow those down.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::api::{self, TorrentInfo};
+use crate::cache;
+use crate::client::{self, TorrentClient};
+use crate::commands::manage::short_id;
+use crate::error::RbitError;
+use crate::format;
+use crate::glob::wildcard_match;
+use crate::theme::state_group;
+
+/// `--ratio`: a comparison with a share ratio, e.g. `>2.0`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatioFilter {
+    op: &'static str,
+    value: f64,
+}
+
+impl RatioFilter {
+    /// `>`, `>=`, `<`, `<=` or `=` followed by a ratio; a bare ratio means `=`.
+    pub fn parse(input: &str) -> anyhow::Result<RatioFilter> {
+        let s = input.trim();
+        let (op, rest) = [">=", "<=", ">", "<", "="].into_iter().find_map(|op| s.strip_prefix(op).map(|rest| (op, rest))).unwrap_or(("=", s));
+        match rest.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(RatioFilter { op, value }),
+            _ => anyhow::bail!("invalid ratio comparison '{}': expected e.g. >2.0 or <=1", input),
+        }
+    }
+
+    fn matches(&self, ratio: f64) -> bool {
+        match self.op {
+            ">=" => ratio >= self.value,
+            "<=" => ratio <= self.value,
+            ">" => ratio > self.value,
+            "<" => ratio < self.value,
+            // as precise as `list` shows ratios
+            _ => (ratio - self.value).abs() < 0.005,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+#[group(id = "selector", multiple = true)]
+pub struct Selector {
+    /// Torrents whose name matches this pattern (`*` and `?`, any case), e.g. `'ubuntu*'`
+    #[arg(long, value_name = "PATTERN")]
+    pub name: Option<String>,
+
+    /// Torrents in this state: downloading, seeding, stalled, paused, queued, checking, error,
+    /// or one of qBittorrent's own such as `stalledUP`
+    #[arg(long)]
+    pub state: Option<String>,
+
+    /// Torrents with this tag
+    #[arg(long)]
+    pub tag: Option<String>,
+
+    /// Torrents in this category
+    #[arg(long)]
+    pub category: Option<String>,
+
+    /// Torrents whose share ratio compares like this, e.g. `'>2.0'` or `'<=1'`
+    #[arg(long, value_name = "COMPARISON", value_parser = RatioFilter::parse)]
+    pub ratio: Option<RatioFilter>,
+
+    /// Torrents added longer ago than this, e.g. `30d` or `12h`
+    #[arg(long, value_name = "DURATION", value_parser = format::parse_duration)]
+    pub older_than: Option<Duration>,
+}
+
+impl Selector {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.state.is_none() && self.tag.is_none() && self.category.is_none() && self.ratio.is_none() && self.older_than.is_none()
+    }
+
+    /// Whether `t` matches every selector given; `now` is the Unix time `--older-than` counts from.
+    pub fn matches(&self, t: &TorrentInfo, now: u64) -> bool {
+        let name = self.name.as_ref().is_none_or(|p| wildcard_match(&p.to_lowercase(), &t.name.to_lowercase()));
+        let state = self.state.as_ref().is_none_or(|s| t.state.eq_ignore_ascii_case(s) || state_group(&t.state) == Some(s.to_lowercase().as_str()));
+        let tag = self.tag.as_ref().is_none_or(|tag| t.tags.as_deref().unwrap_or("").split(',').any(|t| t.trim() == tag));
+        let category = self.category.as_ref().is_none_or(|c| t.category.as_deref() == Some(c.as_str()));
+        let ratio = self.ratio.is_none_or(|r| t.ratio.is_some_and(|ratio| r.matches(ratio)));
+        let age = self.older_than.is_none_or(|d| t.added_on.is_some_and(|at| at > 0 && now.saturating_sub(at as u64) > d.as_secs()));
+        name && state && tag && category && ratio && age
+    }
+
+    /// The torrents a command acts on: those `ids` name, or every one with `all` or when there
+    /// are only selectors, narrowed down to the ones the selectors match. With `dry_run` the
+    /// matches are listed.
+    pub async fn select(&self, client: &impl TorrentClient, ids: &[String], all: bool, dry_run: bool) -> anyhow::Result<Vec<TorrentInfo>> {
+        if self.is_empty() {
+            return if all { client.torrents().await } else { client::resolve(client, ids).await };
+        }
+        let torrents = client.torrents().await?;
+        let candidates = match ids.is_empty() {
+            true => torrents,
+            false => api::pick(&torrents, api::expand_refs(client.host(), ids)?)?,
+        };
+        let now = cache::now();
+        let matched: Vec<TorrentInfo> = candidates.into_iter().filter(|t| self.matches(t, now)).collect();
+        if matched.is_empty() {
+            return Err(RbitError::NotFound(format!("no torrent matches {}", self)).into());
+        }
+        if dry_run {
+            println!("[dry-run] {} torrent(s) match {}:", matched.len(), self);
+            for t in &matched {
+                println!("[dry-run]   {}  {:<12}  {}", short_id(t), t.state, t.name);
+            }
+        }
+        Ok(matched)
+    }
+}
+
+/// The selectors as given on the command line, for messages.
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(ref name) = self.name {
+            parts.push(format!("--name '{}'", name));
+        }
+        if let Some(ref state) = self.state {
+            parts.push(format!("--state {}", state));
+        }
+        if let Some(ref tag) = self.tag {
+            parts.push(format!("--tag {}", tag));
+        }
+        if let Some(ref category) = self.category {
+            parts.push(format!("--category {}", category));
+        }
+        if let Some(r) = self.ratio {
+            parts.push(format!("--ratio '{}{}'", r.op, r.value));
+        }
+        if let Some(d) = self.older_than {
+            let secs = d.as_secs();
+            let (n, unit) = [(86400, "d"), (3600, "h"), (60, "m")].into_iter().find(|&(u, _)| secs > 0 && secs % u == 0).map_or((secs, "s"), |(u, unit)| (secs / u, unit));
+            parts.push(format!("--older-than {}{}", n, unit));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selectors_must_all_match() {
+        let t = TorrentInfo {
+            name: "Ubuntu 24.04 Desktop".to_string(),
+            state: "stalledUP".to_string(),
+            tags: Some("linux, old".to_string()),
+            category: Some("iso".to_string()),
+            ratio: Some(2.5),
+            added_on: Some(1000),
+            ..Default::default()
+        };
+        let now = 1000 + 40 * 86400;
+        let select = |s: Selector| s.matches(&t, now);
+        assert!(select(Selector { name: Some("ubuntu*".to_string()), state: Some("stalled".to_string()), ..Default::default() }));
+        assert!(select(Selector { state: Some("STALLEDup".to_string()), tag: Some("old".to_string()), ..Default::default() }));
+        assert!(select(Selector { ratio: Some(RatioFilter::parse(">2.0").unwrap()), older_than: Some(Duration::from_secs(30 * 86400)), ..Default::default() }));
+        assert!(!select(Selector { name: Some("debian*".to_string()), ..Default::default() }));
+        assert!(!select(Selector { state: Some("seeding".to_string()), ..Default::default() }));
+        assert!(!select(Selector { category: Some("tv".to_string()), ratio: Some(RatioFilter::parse(">2").unwrap()), ..Default::default() }));
+        assert!(!select(Selector { older_than: Some(Duration::from_secs(60 * 86400)), ..Default::default() }));
+
+        assert!(RatioFilter::parse("<=1").unwrap().matches(1.0));
+        assert!(RatioFilter::parse("1.5").unwrap().matches(1.501));
+        assert!(RatioFilter::parse(">x").is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! `[disk]`: what `rbit daemon` does when the disk fills up. Below `min_free` it sends a
+//! notification and pauses the torrents still downloading, and those added while space is low;
+//! once there is `resume_above` free again it says so and resumes them. The gap between the two
+//! keeps a disk hovering around the limit from raising a notification every pass.
+//!
+//! Free space is what qBittorrent reports for its default save path, and that of any local
+//! `paths` (e.g. where other save paths are mounted); the lowest of them counts.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::TorrentInfo;
+use crate::commands::follow::{progress_of, Progress};
+use crate::format::{parse_size, size_human};
+
+/// `[disk]`: the free space `rbit daemon` keeps downloads from going below
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DiskConfig {
+    /// Free space below which downloads are paused, e.g. `20GiB`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_free: Option<String>,
+    /// Free space at which they are resumed (default: `min_free` and a tenth more)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_above: Option<String>,
+    /// Local folders whose disks count too
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+    /// Only notify, and leave the downloads running
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub notify_only: bool,
+}
+
+impl DiskConfig {
+    pub fn is_empty(&self) -> bool {
+        self.min_free.is_none() && self.resume_above.is_none() && self.paths.is_empty() && !self.notify_only
+    }
+
+    /// The configured limits; `None` without a `min_free`.
+    pub fn threshold(&self) -> anyhow::Result<Option<Threshold>> {
+        let size = |key: &str, value: &str| parse_size(value).map_err(|e| anyhow::anyhow!("[disk] {}: {}", key, e));
+        let Some(min_free) = self.min_free.as_deref().map(|v| size("min_free", v)).transpose()? else {
+            return Ok(None);
+        };
+        let resume_above = match self.resume_above.as_deref() {
+            Some(v) => size("resume_above", v)?,
+            None => min_free + min_free / 10,
+        };
+        if resume_above < min_free {
+            anyhow::bail!("[disk] resume_above ({}) is below min_free ({})", size_human(resume_above), size_human(min_free));
+        }
+        Ok(Some(Threshold { min_free, resume_above, paths: self.paths.clone(), pause: !self.notify_only }))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Threshold {
+    pub min_free: u64,
+    pub resume_above: u64,
+    pub paths: Vec<PathBuf>,
+    /// Pause downloads while space is low
+    pub pause: bool,
+}
+
+/// Free bytes on the disk holding `path`, for an unprivileged user.
+#[cfg(unix)]
+pub fn local_free(path: &Path) -> anyhow::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: `stat` is plain integers, for which all zeroes is a valid value
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: statvfs only reads the NUL-terminated `c_path` and writes `stat`, both of which
+    // live through the call
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        anyhow::bail!("{}: {}", path.display(), std::io::Error::last_os_error());
+    }
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn local_free(path: &Path) -> anyhow::Result<u64> {
+    anyhow::bail!("{}: checking local free space is only supported on Unix", path.display())
+}
+
+/// Whether `t` is downloading, or waiting to, and so takes more space.
+pub fn downloading(t: &TorrentInfo) -> bool {
+    progress_of(t) == Progress::Downloading && !t.state.starts_with("paused") && !t.state.starts_with("stopped") && !t.state.starts_with("checking")
+}
+
+impl Threshold {
+    /// Whether space is low with `free` bytes left, given whether it was at the last pass.
+    pub fn low(&self, was_low: bool, free: u64) -> bool {
+        if was_low {
+            free < self.resume_above
+        } else {
+            free < self.min_free
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_with_a_gap() {
+        let config = DiskConfig { min_free: Some("10GiB".to_string()), ..Default::default() };
+        let threshold = config.threshold().unwrap().unwrap();
+        assert_eq!(threshold.resume_above, 11 << 30);
+        assert!(DiskConfig { min_free: Some("10G".to_string()), resume_above: Some("5G".to_string()), ..Default::default() }.threshold().is_err());
+        assert!(DiskConfig::default().threshold().unwrap().is_none());
+
+        let gib = |n: u64| n << 30;
+        assert!(!threshold.low(false, gib(20)));
+        assert!(threshold.low(false, gib(9)));
+        // between the limits it stays as it was
+        assert!(threshold.low(true, gib(10) + 1));
+        assert!(!threshold.low(false, gib(10) + 1));
+        assert!(!threshold.low(true, gib(12)));
+    }
+}
@@ -0,0 +1,293 @@
+//! Output styling from the `[theme]` config table: table borders, torrent state colors and the
+//! accent color used for table headers.
+
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use tabled::object::Rows;
+use tabled::{Format, Modify, Style, Table, Tabled};
+
+/// Border styles offered by tabled, selected with `--table-style` or `[theme] table_style`.
+#[derive(Serialize, Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TableStyle {
+    #[default]
+    Psql,
+    Ascii,
+    Modern,
+    /// `modern` with rounded corners
+    Rounded,
+    Extended,
+    Dots,
+    /// GitHub-flavoured markdown, for pasting into issues and wikis
+    Markdown,
+    Rst,
+    Blank,
+    /// No borders or separators at all
+    #[serde(alias = "plain")]
+    #[value(alias = "plain")]
+    Empty,
+}
+
+/// Built-in color sets. `color-blind` avoids telling states apart by red versus green.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    #[default]
+    Default,
+    #[serde(alias = "colorblind")]
+    ColorBlind,
+    /// No colors at all
+    Mono,
+}
+
+/// Whether output is colored, from `--color` or `[theme] color`.
+#[derive(Serialize, Deserialize, clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorChoice {
+    /// When stdout is a terminal and `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// `[theme]`: how tables and torrent states are shown
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ThemeConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<Preset>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table_style: Option<TableStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorChoice>,
+    /// Color for table headers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    /// Per state-group overrides, e.g. `seeding = "green"` or `error = "196"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl ThemeConfig {
+    pub fn is_empty(&self) -> bool {
+        self.preset.is_none() && self.table_style.is_none() && self.color.is_none() && self.accent.is_none() && self.colors.is_empty()
+    }
+}
+
+/// Groups of qBittorrent states that share a color.
+pub const STATE_GROUPS: [&str; 7] = ["downloading", "seeding", "stalled", "paused", "queued", "checking", "error"];
+
+/// Which of [`STATE_GROUPS`] a qBittorrent torrent state (or rbit's `unregistered`) belongs to.
+pub fn state_group(state: &str) -> Option<&'static str> {
+    Some(match state {
+        "downloading" | "forcedDL" | "metaDL" | "forcedMetaDL" => "downloading",
+        "uploading" | "forcedUP" => "seeding",
+        "stalledDL" | "stalledUP" => "stalled",
+        "pausedDL" | "pausedUP" | "stoppedDL" | "stoppedUP" => "paused",
+        "queuedDL" | "queuedUP" => "queued",
+        "checkingDL" | "checkingUP" | "checkingResumeData" | "allocating" | "moving" => "checking",
+        "error" | "missingFiles" | "unregistered" => "error",
+        _ => return None,
+    })
+}
+
+/// How `rbit list` names a qBittorrent torrent state, mostly as qBittorrent's Web UI does;
+/// states rbit does not know are shown as they are.
+pub fn state_label(state: &str) -> &str {
+    match state {
+        "downloading" => "Downloading",
+        "forcedDL" => "Downloading (forced)",
+        "metaDL" => "Fetching metadata",
+        "forcedMetaDL" => "Fetching metadata (forced)",
+        "uploading" => "Seeding",
+        "stalledUP" => "Seeding (stalled)",
+        "forcedUP" => "Seeding (forced)",
+        "stalledDL" => "Stalled",
+        "pausedDL" | "stoppedDL" => "Paused",
+        "pausedUP" | "stoppedUP" => "Completed",
+        "queuedDL" | "queuedUP" => "Queued",
+        "checkingDL" | "checkingUP" => "Checking",
+        "checkingResumeData" => "Checking resume data",
+        "allocating" => "Allocating",
+        "moving" => "Moving",
+        "error" => "Errored",
+        "missingFiles" => "Missing files",
+        "unregistered" => "Unregistered",
+        other => other,
+    }
+}
+
+/// Whether the console shows escape codes as colors. Legacy Windows consoles print them as text
+/// unless virtual terminal processing is turned on; where that fails, output has no colors.
+#[cfg(windows)]
+fn ansi_console() -> bool {
+    enable_ansi_support::enable_ansi_support().is_ok()
+}
+
+#[cfg(not(windows))]
+fn ansi_console() -> bool {
+    true
+}
+
+/// Parse a color name (`red`, `bright-blue`, ...) or a 256-color index into an SGR parameter.
+fn parse_color(name: &str) -> anyhow::Result<String> {
+    const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+    let name = name.trim().to_ascii_lowercase();
+    if let Ok(index) = name.parse::<u8>() {
+        return Ok(format!("38;5;{}", index));
+    }
+    let (bright, base) = match name.strip_prefix("bright-") {
+        Some(base) => (true, base),
+        None => (false, name.as_str()),
+    };
+    let base = if base == "grey" || base == "gray" { "black" } else { base };
+    let bright = bright || name == "grey" || name == "gray";
+    match NAMES.iter().position(|n| *n == base) {
+        Some(i) => Ok(format!("{}", if bright { 90 } else { 30 } + i)),
+        None => anyhow::bail!("unknown color '{}' (use a name like 'red' or 'bright-blue', or a 256-color index)", name),
+    }
+}
+
+fn preset_colors(preset: Preset) -> Vec<(&'static str, &'static str)> {
+    match preset {
+        Preset::Default => vec![
+            ("accent", "cyan"),
+            ("downloading", "blue"),
+            ("seeding", "green"),
+            ("stalled", "yellow"),
+            ("paused", "grey"),
+            ("queued", "white"),
+            ("checking", "magenta"),
+            ("error", "red"),
+        ],
+        // Okabe-Ito palette, approximated in the 256-color cube
+        Preset::ColorBlind => vec![
+            ("accent", "74"),
+            ("downloading", "74"),
+            ("seeding", "25"),
+            ("stalled", "227"),
+            ("paused", "245"),
+            ("queued", "175"),
+            ("checking", "36"),
+            ("error", "166"),
+        ],
+        Preset::Mono => Vec::new(),
+    }
+}
+
+/// The resolved theme for this run.
+#[derive(Debug, Default)]
+pub struct Theme {
+    pub table_style: TableStyle,
+    /// SGR parameters by state group, plus `accent`
+    colors: BTreeMap<String, String>,
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+impl Theme {
+    /// Resolve `config` against its preset. With `auto` (`color` overrides the config's choice),
+    /// colors are dropped when stdout is not a terminal or `NO_COLOR` is set.
+    pub fn from_config(config: &ThemeConfig, color: Option<ColorChoice>) -> anyhow::Result<Self> {
+        let preset = config.preset.unwrap_or_default();
+        let mut colors = BTreeMap::new();
+        for (group, color) in preset_colors(preset) {
+            colors.insert(group.to_string(), parse_color(color)?);
+        }
+        if let Some(ref accent) = config.accent {
+            colors.insert("accent".to_string(), parse_color(accent)?);
+        }
+        for (group, color) in &config.colors {
+            if !STATE_GROUPS.contains(&group.as_str()) {
+                anyhow::bail!("[theme.colors]: unknown state group '{}' (expected one of: {})", group, STATE_GROUPS.join(", "));
+            }
+            colors.insert(group.clone(), parse_color(color).map_err(|e| anyhow::anyhow!("[theme.colors] {}: {}", group, e))?);
+        }
+        let use_color = match color.or(config.color).unwrap_or_default() {
+            ColorChoice::Auto => io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        };
+        let use_color = use_color && preset != Preset::Mono && ansi_console();
+        if !use_color {
+            colors.clear();
+        }
+        Ok(Theme { table_style: config.table_style.unwrap_or_default(), colors })
+    }
+
+    /// Color `text` in the color for `key`: a state group, or `accent`.
+    pub fn paint(&self, key: &str, text: &str) -> String {
+        match self.colors.get(key) {
+            Some(sgr) if !text.is_empty() => format!("\x1b[{}m{}\x1b[0m", sgr, text),
+            _ => text.to_string(),
+        }
+    }
+
+    /// The SGR parameters for `key`, for drawing outside of plain text (`rbit tui`); `None`
+    /// when it has no color.
+    pub fn sgr(&self, key: &str) -> Option<&str> {
+        self.colors.get(key).map(String::as_str)
+    }
+
+    /// Color `text`, e.g. a cell of a torrent's row, in the color of the torrent's `state`.
+    pub fn by_state(&self, state: &str, text: &str) -> String {
+        match state_group(state) {
+            Some(group) => self.paint(group, text),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Install the theme for this run; later calls are ignored.
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// Render rows as a table in the configured style, with the header in the accent color.
+pub fn table<T: Tabled>(rows: impl IntoIterator<Item = T>) -> String {
+    styled(Table::new(rows))
+}
+
+/// Like [`table`], for a table the caller has already adjusted (e.g. with hidden columns).
+pub fn styled(table: Table) -> String {
+    render(table, current().table_style)
+}
+
+/// Render in a specific style. Markdown tables never get a colored header, since escape codes
+/// would end up in the document.
+pub fn render(table: Table, style: TableStyle) -> String {
+    let theme = current();
+    let table = match style {
+        TableStyle::Psql => table.with(Style::psql()),
+        TableStyle::Ascii => table.with(Style::ascii()),
+        TableStyle::Modern => table.with(Style::modern()),
+        TableStyle::Rounded => table.with(
+            Style::modern()
+                .top_left_corner('╭')
+                .top_right_corner('╮')
+                .bottom_left_corner('╰')
+                .bottom_right_corner('╯'),
+        ),
+        TableStyle::Extended => table.with(Style::extended()),
+        TableStyle::Dots => table.with(Style::dots()),
+        // tabled draws '+' where the header rule meets a column, which GitHub does not accept
+        TableStyle::Markdown => table.with(Style::github_markdown().header_intersection('|')),
+        TableStyle::Rst => table.with(Style::re_structured_text()),
+        TableStyle::Blank => table.with(Style::blank()),
+        TableStyle::Empty => table.with(Style::empty()),
+    };
+    if theme.colors.contains_key("accent") && style != TableStyle::Markdown {
+        table
+            .with(Modify::new(Rows::first()).with(Format::new(|s| theme.paint("accent", s))))
+            .to_string()
+    } else {
+        table.to_string()
+    }
+}
@@ -0,0 +1,454 @@
+//! Local .torrent parsing: a minimal bencode decoder and the metainfo fields rbit cares about.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use sha1::{Digest, Sha1};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    Dict(BTreeMap<Vec<u8>, Value>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Dict(d) => d.get(key.as_bytes()),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Byte string as UTF-8, replacing invalid sequences.
+    pub fn as_str(&self) -> Option<String> {
+        self.as_bytes().map(|b| String::from_utf8_lossy(b).into_owned())
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Value::List(l) => Some(l),
+            _ => None,
+        }
+    }
+}
+
+/// How deep lists and dictionaries may nest; real metainfo needs a handful of levels, and
+/// a crafted file should not get to exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    /// Lists and dictionaries open around `pos`
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn peek(&self) -> anyhow::Result<u8> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("unexpected end of bencode data"))
+    }
+
+    /// Read up to (not including) `end`, advancing past it.
+    fn read_until(&mut self, end: u8) -> anyhow::Result<&'a [u8]> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|&b| b == end)
+            .ok_or_else(|| anyhow::anyhow!("unterminated bencode token at byte {}", self.pos))?;
+        self.pos += len + 1;
+        Ok(&rest[..len])
+    }
+
+    fn int(&mut self) -> anyhow::Result<i64> {
+        self.pos += 1; // 'i'
+        let digits = self.read_until(b'e')?;
+        std::str::from_utf8(digits)?
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid bencode integer at byte {}", self.pos))
+    }
+
+    fn bytes(&mut self) -> anyhow::Result<Vec<u8>> {
+        let len: usize = std::str::from_utf8(self.read_until(b':')?)?
+            .parse()
+            .map_err(|_| anyhow::anyhow!("invalid bencode string length at byte {}", self.pos))?;
+        let end = self.pos.checked_add(len).filter(|&e| e <= self.data.len());
+        let end = end.ok_or_else(|| anyhow::anyhow!("bencode string runs past end of data"))?;
+        let b = self.data[self.pos..end].to_vec();
+        self.pos = end;
+        Ok(b)
+    }
+
+    /// Step into a list or dictionary.
+    fn open(&mut self) -> anyhow::Result<()> {
+        if self.depth == MAX_DEPTH {
+            anyhow::bail!("bencode data nests deeper than {} levels at byte {}", MAX_DEPTH, self.pos);
+        }
+        self.depth += 1;
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Step past the `e` that ends a list or dictionary.
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.pos += 1;
+    }
+
+    fn value(&mut self) -> anyhow::Result<Value> {
+        match self.peek()? {
+            b'i' => Ok(Value::Int(self.int()?)),
+            b'l' => {
+                self.open()?;
+                let mut items = Vec::new();
+                while self.peek()? != b'e' {
+                    items.push(self.value()?);
+                }
+                self.close();
+                Ok(Value::List(items))
+            }
+            b'd' => {
+                self.open()?;
+                let mut items = BTreeMap::new();
+                while self.peek()? != b'e' {
+                    let key = self.bytes()?;
+                    let value = self.value()?;
+                    items.insert(key, value);
+                }
+                self.close();
+                Ok(Value::Dict(items))
+            }
+            b'0'..=b'9' => Ok(Value::Bytes(self.bytes()?)),
+            c => anyhow::bail!("unexpected byte {:?} at {} in bencode data", c as char, self.pos),
+        }
+    }
+}
+
+pub fn decode(data: &[u8]) -> anyhow::Result<Value> {
+    let mut d = Decoder { data, pos: 0, depth: 0 };
+    d.value()
+}
+
+/// Bencode `value`. Dictionaries come out with their keys sorted, as the format requires.
+pub fn encode(value: &Value) -> Vec<u8> {
+    fn write_bytes(b: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(format!("{}:", b.len()).as_bytes());
+        out.extend_from_slice(b);
+    }
+    fn write_value(v: &Value, out: &mut Vec<u8>) {
+        match v {
+            Value::Int(i) => out.extend_from_slice(format!("i{}e", i).as_bytes()),
+            Value::Bytes(b) => write_bytes(b, out),
+            Value::List(items) => {
+                out.push(b'l');
+                items.iter().for_each(|item| write_value(item, out));
+                out.push(b'e');
+            }
+            Value::Dict(items) => {
+                out.push(b'd');
+                for (k, item) in items {
+                    write_bytes(k, out);
+                    write_value(item, out);
+                }
+                out.push(b'e');
+            }
+        }
+    }
+    let mut out = Vec::new();
+    write_value(value, &mut out);
+    out
+}
+
+/// Byte range of the top-level `info` value, which is what the info-hash is computed over.
+fn info_span(data: &[u8]) -> anyhow::Result<(usize, usize)> {
+    let mut d = Decoder { data, pos: 0, depth: 0 };
+    if d.peek()? != b'd' {
+        anyhow::bail!("not a torrent file: top level is not a dictionary");
+    }
+    d.open()?;
+    while d.peek()? != b'e' {
+        let key = d.bytes()?;
+        let start = d.pos;
+        d.value()?;
+        if key == b"info" {
+            return Ok((start, d.pos));
+        }
+    }
+    anyhow::bail!("not a torrent file: missing info dictionary")
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Whether `s` looks like a bare BitTorrent v1 info-hash: 40 hex digits or 32 base32 characters.
+pub fn is_info_hash(s: &str) -> bool {
+    (s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit()))
+        || (s.len() == 32 && s.chars().all(|c| matches!(c.to_ascii_uppercase(), 'A'..='Z' | '2'..='7')))
+}
+
+/// A bare info-hash (see [`is_info_hash`]) as lowercase hex, decoding the base32 form.
+pub fn info_hash_hex(s: &str) -> Option<String> {
+    if !is_info_hash(s) {
+        return None;
+    }
+    if s.len() == 40 {
+        return Some(s.to_ascii_lowercase());
+    }
+    let mut bytes = Vec::with_capacity(20);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in s.bytes().map(|c| c.to_ascii_uppercase()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            _ => c - b'2' + 26,
+        };
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(hex(&bytes))
+}
+
+/// The v1 info-hash in a magnet URI's `xt=urn:btih:` parameter, as lowercase hex.
+pub fn magnet_info_hash(uri: &str) -> Option<String> {
+    let query = uri.strip_prefix("magnet:?")?;
+    query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .filter(|(key, _)| *key == "xt" || key.starts_with("xt."))
+        .find_map(|(_, value)| {
+            let value = value.replace("%3A", ":").replace("%3a", ":");
+            let hash = value.strip_prefix("urn:btih:").or_else(|| value.strip_prefix("urn:BTIH:"))?;
+            info_hash_hex(hash)
+        })
+}
+
+/// The exact length (`xl=`) a magnet URI gives for its content, in bytes.
+pub fn magnet_size(uri: &str) -> Option<u64> {
+    let query = uri.strip_prefix("magnet:?")?;
+    query.split('&').filter_map(|param| param.split_once('=')).find(|(key, _)| *key == "xl").and_then(|(_, v)| v.parse().ok())
+}
+
+/// Whether `s` is an `http://` or `https://` link, e.g. a .torrent download copied from a browser.
+pub fn is_torrent_url(s: &str) -> bool {
+    let lower = s.get(..8).unwrap_or(s).to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// Percent-encode a magnet URI parameter value (everything but RFC 3986 unreserved characters).
+pub fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+/// Build a magnet URI from an info-hash, an optional display name and tracker URLs.
+pub fn magnet_uri(info_hash: &str, name: Option<&str>, trackers: &[String]) -> String {
+    let mut uri = format!("magnet:?xt=urn:btih:{}", info_hash);
+    if let Some(name) = name.filter(|n| !n.is_empty()) {
+        uri.push_str("&dn=");
+        uri.push_str(&percent_encode(name));
+    }
+    for tr in trackers {
+        uri.push_str("&tr=");
+        uri.push_str(&percent_encode(tr));
+    }
+    uri
+}
+
+#[derive(Debug)]
+pub struct TorrentFile {
+    pub path: String,
+    pub length: u64,
+    /// A BEP 47 padding file: zeros that clients do not write to disk
+    pub pad: bool,
+}
+
+/// The parts of a .torrent's metainfo that rbit shows or uses.
+#[derive(Debug)]
+pub struct Metainfo {
+    /// v1 info-hash as lowercase hex
+    pub info_hash: String,
+    pub name: String,
+    pub piece_length: u64,
+    /// SHA-1 of each piece
+    pub pieces: Vec<[u8; 20]>,
+    pub private: bool,
+    pub files: Vec<TorrentFile>,
+    /// Announce URLs in tier order, deduplicated
+    pub trackers: Vec<String>,
+    pub comment: Option<String>,
+}
+
+impl Metainfo {
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let root = decode(data)?;
+        let (start, end) = info_span(data)?;
+        let info_hash = hex(&Sha1::digest(&data[start..end]));
+        let info = root.get("info").ok_or_else(|| anyhow::anyhow!("missing info dictionary"))?;
+
+        let name = info.get("name").and_then(Value::as_str).unwrap_or_default();
+        let piece_length = match info.get("piece length").and_then(Value::as_int) {
+            Some(n) if n > 0 => n as u64,
+            _ => anyhow::bail!("the info dictionary has no valid piece length"),
+        };
+        let hashes = info.get("pieces").and_then(Value::as_bytes).ok_or_else(|| anyhow::anyhow!("the info dictionary has no piece hashes"))?;
+        if hashes.len() % 20 != 0 {
+            anyhow::bail!("the piece hashes are {} bytes, not a multiple of 20", hashes.len());
+        }
+        let pieces: Vec<[u8; 20]> = hashes.chunks_exact(20).map(|c| c.try_into().expect("chunks of 20")).collect();
+        let private = info.get("private").and_then(Value::as_int) == Some(1);
+
+        let mut files = Vec::new();
+        if let Some(list) = info.get("files").and_then(Value::as_list) {
+            for f in list {
+                let length = f.get("length").and_then(Value::as_int).unwrap_or(0).max(0) as u64;
+                let parts: Vec<String> = f
+                    .get("path")
+                    .and_then(Value::as_list)
+                    .unwrap_or(&[])
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect();
+                let pad = f.get("attr").and_then(Value::as_str).is_some_and(|a| a.contains('p'));
+                files.push(TorrentFile { path: format!("{}/{}", name, parts.join("/")), length, pad });
+            }
+        } else {
+            let length = info.get("length").and_then(Value::as_int).unwrap_or(0).max(0) as u64;
+            files.push(TorrentFile { path: name.clone(), length, pad: false });
+        }
+
+        let mut trackers: Vec<String> = Vec::new();
+        let mut push = |url: String| {
+            if !url.is_empty() && !trackers.contains(&url) {
+                trackers.push(url);
+            }
+        };
+        if let Some(tiers) = root.get("announce-list").and_then(Value::as_list) {
+            for tier in tiers {
+                for url in tier.as_list().unwrap_or(&[]) {
+                    if let Some(u) = url.as_str() {
+                        push(u);
+                    }
+                }
+            }
+        }
+        if let Some(u) = root.get("announce").and_then(Value::as_str) {
+            push(u);
+        }
+
+        let comment = root.get("comment").and_then(Value::as_str);
+
+        let total = files
+            .iter()
+            .try_fold(0u64, |total, f| total.checked_add(f.length))
+            .ok_or_else(|| anyhow::anyhow!("the file lengths add up to more than 2^64 bytes"))?;
+        if pieces.len() as u64 != total.div_ceil(piece_length) {
+            anyhow::bail!("{} piece hashes do not cover {} bytes in pieces of {}", pieces.len(), total, piece_length);
+        }
+
+        Ok(Metainfo { info_hash, name, piece_length, pieces, private, files, trackers, comment })
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read(path)?;
+        Self::parse(&data).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))
+    }
+
+    pub fn magnet(&self) -> String {
+        magnet_uri(&self.info_hash, Some(&self.name), &self.trackers)
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.length).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single-file metainfo with these info fields, after `length` and `name`.
+    fn single(length: u64, info: &str) -> Vec<u8> {
+        format!("d8:announce17:http://t/announce4:infod6:lengthi{}e4:name1:x{}ee", length, info).into_bytes()
+    }
+
+    #[test]
+    fn parses_and_validates_metainfo() {
+        let data = single(20000, &format!("12:piece lengthi16384e6:pieces40:{}", "a".repeat(40)));
+        let meta = Metainfo::parse(&data).unwrap();
+        assert_eq!((meta.piece_length, meta.pieces.len(), meta.total_size()), (16384, 2, 20000));
+        assert_eq!(meta.trackers, ["http://t/announce"]);
+        let (start, end) = info_span(&data).unwrap();
+        assert!(data[start..end].starts_with(b"d6:length") && data[end..] == *b"e");
+        assert_eq!(meta.info_hash, hex(&Sha1::digest(&data[start..end])));
+
+        // no or zero piece length, ragged hashes, and hashes for another size
+        assert!(Metainfo::parse(&single(5, &format!("6:pieces20:{}", "a".repeat(20)))).is_err());
+        assert!(Metainfo::parse(&single(5, &format!("12:piece lengthi0e6:pieces20:{}", "a".repeat(20)))).is_err());
+        assert!(Metainfo::parse(&single(5, "12:piece lengthi16384e")).is_err());
+        assert!(Metainfo::parse(&single(5, &format!("12:piece lengthi16384e6:pieces19:{}", "a".repeat(19)))).is_err());
+        assert!(Metainfo::parse(&single(20000, &format!("12:piece lengthi16384e6:pieces20:{}", "a".repeat(20)))).is_err());
+        assert!(info_span(b"d4:name1:xe").is_err());
+
+        // file lengths that overflow when added up
+        let huge = format!("d6:lengthi{}e4:pathl1:xee", i64::MAX);
+        let data = format!("d4:infod5:filesl{}{}{}e4:name1:x12:piece lengthi16384e6:pieces20:{}ee", huge, huge, huge, "a".repeat(20));
+        assert!(Metainfo::parse(data.as_bytes()).unwrap_err().to_string().contains("more than 2^64"));
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let nested = |depth: usize| format!("{}{}", "l".repeat(depth), "e".repeat(depth)).into_bytes();
+        assert!(decode(&nested(MAX_DEPTH)).is_ok());
+        assert!(decode(&nested(MAX_DEPTH + 1)).is_err());
+        // without the matching ends, as a crafted file would be
+        assert!(decode(&vec![b'l'; 1_000_000]).is_err());
+        assert!(Metainfo::parse(&[b"d4:info".as_slice(), &vec![b'd'; 100_000]].concat()).is_err());
+    }
+
+    #[test]
+    fn info_hashes_from_text() {
+        let hash = "5bd1a2c81b701e2d76a4f3b4d1ea90c2b3e5f6a7";
+        assert_eq!(info_hash_hex(&hash.to_uppercase()).as_deref(), Some(hash));
+        // the same hash in base32, either case
+        assert_eq!(info_hash_hex("LPI2FSA3OAPC25VE6O2ND2UQYKZ6L5VH").as_deref(), Some(hash));
+        assert_eq!(info_hash_hex("lpi2fsa3oapc25ve6o2nd2uqykz6l5vh").as_deref(), Some(hash));
+        assert_eq!(info_hash_hex("LPI2FSA3OAPC25VE6O2ND2UQYKZ6L5V1"), None);
+        assert_eq!(info_hash_hex(&hash[1..]), None);
+
+        assert_eq!(magnet_info_hash(&format!("magnet:?dn=x&xt=urn:btih:{}", hash)).as_deref(), Some(hash));
+        assert_eq!(magnet_info_hash("magnet:?xt=urn%3Abtih%3ALPI2FSA3OAPC25VE6O2ND2UQYKZ6L5VH&dn=x").as_deref(), Some(hash));
+        assert_eq!(magnet_info_hash(&format!("magnet:?xt.1=urn:BTIH:{}", hash)).as_deref(), Some(hash));
+        assert_eq!(magnet_info_hash("magnet:?xt=urn:btmh:1220abcd"), None);
+        assert_eq!(magnet_info_hash(&format!("http://x/?xt=urn:btih:{}", hash)), None);
+    }
+}
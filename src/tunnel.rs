@@ -0,0 +1,101 @@
+//! `ssh_tunnel`: reach a Web UI that only listens on the remote machine's localhost through an
+//! SSH local port forward. rbit runs the system `ssh`, so `~/.ssh/config`, keys and the agent
+//! apply as usual, and stops it when the command is done.
+
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use reqwest::Url;
+
+use crate::error::RbitError;
+
+/// How long ssh gets to log in and set up the forward.
+const SETUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A running `ssh -L`; the forward closes when this is dropped.
+pub struct Tunnel {
+    child: Child,
+    /// `host` with its address replaced by the local end of the forward
+    pub host: String,
+}
+
+/// A local port for the forward to `destination` and `target`, the same on every run so the
+/// caches keyed by host (login cookie, `%N` listing) keep working.
+fn default_port(destination: &str, target: &str) -> u16 {
+    // FNV-1a; std's hasher is not stable across releases
+    let hash = format!("{} {}", destination, target).bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    20000 + (hash % 20000) as u16
+}
+
+/// A free port on 127.0.0.1: `wanted` when it is free, otherwise one the OS picks.
+fn free_port(wanted: u16) -> anyhow::Result<u16> {
+    if TcpListener::bind((Ipv4Addr::LOCALHOST, wanted)).is_ok() {
+        return Ok(wanted);
+    }
+    tracing::debug!("port {} is taken, using another one for the tunnel", wanted);
+    Ok(TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?.local_addr()?.port())
+}
+
+impl Tunnel {
+    /// Forward a local port to `host` (as seen from `destination`) and wait until it is up.
+    pub async fn open(destination: &str, host: &str, port: Option<u16>) -> anyhow::Result<Tunnel> {
+        let url = Url::parse(host)?;
+        let remote_host = url.host_str().ok_or_else(|| RbitError::InvalidInput(format!("{} has no host to tunnel to", host)))?;
+        let remote_port = url.port_or_known_default().unwrap_or(80);
+        let target = format!("{}:{}", remote_host, remote_port);
+        let local = match port {
+            Some(port) => port,
+            None => free_port(default_port(destination, &target))?,
+        };
+
+        tracing::debug!("ssh -L {}:{} -- {}", local, target, destination);
+        let mut child = Command::new("ssh")
+            .args(["-N", "-o", "ExitOnForwardFailure=yes", "-L"])
+            .arg(format!("127.0.0.1:{}:{}", local, target))
+            // a destination like `-oProxyCommand=…` is a host name, not an option
+            .arg("--")
+            .arg(destination)
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|e| RbitError::Connection(format!("cannot run ssh for the tunnel to {}: {}", destination, e)))?;
+
+        let started = Instant::now();
+        while TcpStream::connect((Ipv4Addr::LOCALHOST, local)).is_err() {
+            if let Some(status) = child.try_wait()? {
+                return Err(RbitError::Connection(format!("the SSH tunnel to {} failed (ssh exited with {})", destination, status)).into());
+            }
+            if started.elapsed() > SETUP_TIMEOUT {
+                let _ = child.kill();
+                return Err(RbitError::Connection(format!("the SSH tunnel to {} was not up after {}s", destination, SETUP_TIMEOUT.as_secs())).into());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        let mut local_url = url.clone();
+        local_url.set_host(Some("127.0.0.1"))?;
+        local_url.set_port(Some(local)).map_err(|_| anyhow::anyhow!("cannot set the port of {}", host))?;
+        let host = local_url.as_str().trim_end_matches('/').to_string();
+        Ok(Tunnel { child, host })
+    }
+}
+
+impl Drop for Tunnel {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_default_port_is_stable() {
+        let port = default_port("me@seedbox", "localhost:8080");
+        assert_eq!(port, default_port("me@seedbox", "localhost:8080"));
+        assert_ne!(port, default_port("me@nas", "localhost:8080"));
+        assert!((20000..40000).contains(&port));
+    }
+}
@@ -0,0 +1,336 @@
+//! A qBittorrent Web UI stand-in for the end-to-end tests, and a way to run the `rbit` binary
+//! against it. The server answers the endpoints the tests use from a list of torrents, expects
+//! the `SID` cookie it handed out at login, and records every request it gets.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use futures::channel::oneshot;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use serde_json::{json, Value};
+
+pub const USERNAME: &str = "admin";
+pub const PASSWORD: &str = "adminadmin";
+const SID: &str = "e2e-session";
+
+/// A request the server got.
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    /// The part after `/api/v2/`, e.g. `torrents/info`
+    pub endpoint: String,
+    pub query: Vec<(String, String)>,
+    /// The fields of a form body
+    pub form: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub cookie: Option<String>,
+}
+
+impl Recorded {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.form.iter().chain(&self.query).find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    torrents: Vec<Value>,
+    requests: Vec<Recorded>,
+    /// Endpoints made to fail, with the status they answer
+    failing: HashMap<String, u16>,
+    /// `free_space_on_disk`, if not the default of a terabyte
+    free_space: Option<u64>,
+    /// Bumped to make the `SID` handed out so far invalid, as a restart of qBittorrent does
+    sessions: usize,
+}
+
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+/// A torrent as `torrents/info` lists it, with the fields the tests look at.
+pub fn torrent(name: &str, hash: &str, state: &str) -> Value {
+    json!({
+        "name": name,
+        "hash": hash,
+        "state": state,
+        "progress": if state.ends_with("UP") || state == "uploading" { 1.0 } else { 0.5 },
+        "size": 1_000_000,
+        "total_size": 1_000_000,
+        "dlspeed": 0,
+        "upspeed": 0,
+        "category": "",
+        "tags": "",
+        "save_path": "/downloads",
+        "added_on": 1_700_000_000,
+    })
+}
+
+/// A small single-file .torrent
+pub fn torrent_file() -> Vec<u8> {
+    let mut data = b"d8:announce31:http://tracker.example/announce4:infod6:lengthi5e4:name5:a.txt12:piece lengthi16384e6:pieces20:".to_vec();
+    data.extend([7u8; 20]);
+    data.extend(b"ee");
+    data
+}
+
+fn pairs(text: &str) -> Vec<(String, String)> {
+    match reqwest::Url::parse(&format!("http://form/?{}", text)) {
+        Ok(url) => url.query_pairs().map(|(k, v)| (k.into_owned(), v.into_owned())).collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn reply(status: u16, content_type: &str, body: impl Into<Body>) -> Response<Body> {
+    Response::builder().status(status).header("Content-Type", content_type).body(body.into()).expect("a valid response")
+}
+
+fn json_reply(value: Value) -> Response<Body> {
+    reply(200, "application/json", value.to_string())
+}
+
+async fn handle(state: Arc<Mutex<State>>, request: Request<Body>) -> Response<Body> {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let query = pairs(request.uri().query().unwrap_or_default());
+    let cookie = request.headers().get("cookie").and_then(|v| v.to_str().ok()).map(String::from);
+    let form_body = request.headers().get("content-type").is_some_and(|v| v.as_bytes().starts_with(b"application/x-www-form-urlencoded"));
+    let body = hyper::body::to_bytes(request.into_body()).await.map(|b| b.to_vec()).unwrap_or_default();
+    let form = if form_body { pairs(&String::from_utf8_lossy(&body)) } else { Vec::new() };
+    let Some(endpoint) = path.strip_prefix("/api/v2/").map(String::from) else {
+        return reply(404, "text/plain", "Not Found");
+    };
+    let recorded = Recorded { method, endpoint: endpoint.clone(), query, form, body, cookie };
+
+    // refuse a stale cookie after a beat, so requests sent together with it are all refused
+    let stale = {
+        let state = state.lock().unwrap();
+        let sid = format!("SID={}-{}", SID, state.sessions);
+        recorded.cookie.as_deref().is_some_and(|c| c.contains("SID=") && !c.split(';').any(|pair| pair.trim() == sid))
+    };
+    if stale {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(recorded.clone());
+    if let Some(&status) = state.failing.get(&endpoint) {
+        return reply(status, "text/plain", StatusCode::from_u16(status).map(|s| s.to_string()).unwrap_or_default());
+    }
+    let sid = format!("SID={}-{}", SID, state.sessions);
+    if endpoint == "auth/login" {
+        return match (recorded.field("username"), recorded.field("password")) {
+            (Some(USERNAME), Some(PASSWORD)) => Response::builder()
+                .header("Set-Cookie", format!("{}; HttpOnly; path=/", sid))
+                .body(Body::from("Ok."))
+                .expect("a valid response"),
+            // qBittorrent answers a wrong password with 200 and this body
+            _ => reply(200, "text/plain", "Fails."),
+        };
+    }
+    if !recorded.cookie.as_deref().is_some_and(|c| c.split(';').any(|pair| pair.trim() == sid)) {
+        return reply(403, "text/plain", "Forbidden");
+    }
+    match endpoint.as_str() {
+        "auth/logout" => reply(200, "text/plain", ""),
+        "app/version" => reply(200, "text/plain", "v4.6.2"),
+        "app/webapiVersion" => reply(200, "text/plain", "2.9.3"),
+        "app/defaultSavePath" => reply(200, "text/plain", "/downloads"),
+        "app/preferences" => json_reply(json!({ "save_path": "/downloads", "queueing_enabled": false })),
+        "sync/maindata" => {
+            let free = state.free_space.unwrap_or(1_000_000_000_000);
+            json_reply(json!({ "rid": 1, "full_update": true, "server_state": { "free_space_on_disk": free } }))
+        }
+        "torrents/categories" => json_reply(json!({})),
+        "torrents/tags" => json_reply(json!([])),
+        "torrents/info" => {
+            let hashes: Option<Vec<&str>> = recorded.field("hashes").map(|h| h.split('|').collect());
+            let torrents: Vec<&Value> = state.torrents.iter().filter(|t| hashes.as_ref().is_none_or(|hs| hs.contains(&t["hash"].as_str().unwrap_or_default()))).collect();
+            json_reply(json!(torrents))
+        }
+        "torrents/add" => reply(200, "text/plain", "Ok."),
+        "torrents/pause" | "torrents/resume" | "torrents/stop" | "torrents/start" => reply(200, "text/plain", ""),
+        _ => reply(404, "text/plain", "Not Found"),
+    }
+}
+
+impl MockServer {
+    pub fn start() -> MockServer {
+        let state = Arc::new(Mutex::new(State::default()));
+        let (addr_tx, addr_rx) = std::sync::mpsc::channel();
+        let (shutdown, stop) = oneshot::channel::<()>();
+        let shared = state.clone();
+        let thread = thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().expect("a runtime for the mock server");
+            runtime.block_on(async move {
+                let make_service = make_service_fn(move |_| {
+                    let state = shared.clone();
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |request| {
+                            let state = state.clone();
+                            async move { Ok::<_, Infallible>(handle(state, request).await) }
+                        }))
+                    }
+                });
+                let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+                addr_tx.send(server.local_addr()).expect("the test waits for the address");
+                let _ = server
+                    .with_graceful_shutdown(async {
+                        let _ = stop.await;
+                    })
+                    .await;
+            });
+        });
+        let addr = addr_rx.recv().expect("the mock server to start");
+        MockServer { addr, state, shutdown: Some(shutdown), thread: Some(thread) }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn set_torrents(&self, torrents: Vec<Value>) {
+        self.state.lock().unwrap().torrents = torrents;
+    }
+
+    pub fn set_free_space(&self, bytes: u64) {
+        self.state.lock().unwrap().free_space = Some(bytes);
+    }
+
+    /// Answer requests to `endpoint` with `status` from now on.
+    pub fn fail(&self, endpoint: &str, status: u16) {
+        self.state.lock().unwrap().failing.insert(endpoint.to_string(), status);
+    }
+
+    /// Forget the sessions logged in so far, so their `SID` is answered with 403.
+    pub fn expire_sessions(&self) {
+        self.state.lock().unwrap().sessions += 1;
+    }
+
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// The requests to `endpoint`, in order.
+    pub fn requests_to(&self, endpoint: &str) -> Vec<Recorded> {
+        self.requests().into_iter().filter(|r| r.endpoint == endpoint).collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A directory of its own for one test, removed when it goes out of scope.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> TempDir {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!("rbit-e2e-{}-{}", std::process::id(), COUNT.fetch_add(1, Ordering::Relaxed)));
+        std::fs::create_dir_all(&dir).expect("a temporary directory");
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// `rbit` with its config, cache and data in `home`, talking to `host` as the test user. Extra
+/// global flags go before the subcommand in `args`.
+pub fn rbit(home: &TempDir, host: &str, args: &[&str]) -> Output {
+    rbit_as(home, host, USERNAME, PASSWORD, args)
+}
+
+/// [`rbit`] with other credentials.
+pub fn rbit_as(home: &TempDir, host: &str, username: &str, password: &str, args: &[&str]) -> Output {
+    let mut global = vec!["--host", host, "--username", username, "--password", password];
+    global.extend(args);
+    rbit_configured(home, &global)
+}
+
+/// `rbit` with only the servers in `home`'s config file (see [`write_config`]). Requests are
+/// not retried unless `args` has `--retries`.
+pub fn rbit_configured(home: &TempDir, args: &[&str]) -> Output {
+    command(home, args).output().expect("the rbit binary to run")
+}
+
+/// [`rbit`] left running in the background, e.g. `rbit daemon`; its output is discarded.
+pub fn rbit_spawn(home: &TempDir, host: &str, args: &[&str]) -> Child {
+    let mut global = vec!["--host", host, "--username", USERNAME, "--password", PASSWORD];
+    global.extend(args);
+    command(home, &global).stdout(Stdio::null()).stderr(Stdio::null()).spawn().expect("the rbit binary to start")
+}
+
+fn command(home: &TempDir, args: &[&str]) -> Command {
+    let dir = home.path();
+    let retries: &[&str] = if args.contains(&"--retries") { &[] } else { &["--retries", "0"] };
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rbit"));
+    command
+        .args(retries)
+        .args(args)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", dir)
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .env("XDG_CACHE_HOME", dir.join("cache"))
+        .env("XDG_DATA_HOME", dir.join("data"))
+        .env("LANG", "C")
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null());
+    command
+}
+
+/// Wait up to a few seconds for `done`, checking now and then.
+pub fn eventually(done: impl Fn() -> bool) -> bool {
+    let started = std::time::Instant::now();
+    while started.elapsed() < std::time::Duration::from_secs(10) {
+        if done() {
+            return true;
+        }
+        thread::sleep(std::time::Duration::from_millis(50));
+    }
+    done()
+}
+
+/// Write `toml` as the config file `rbit` reads in `home`.
+pub fn write_config(home: &TempDir, toml: &str) {
+    let dir = home.path().join("config").join("rbit");
+    std::fs::create_dir_all(&dir).expect("a config directory");
+    std::fs::write(dir.join("config.toml"), toml).expect("a config file");
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
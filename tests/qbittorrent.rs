@@ -0,0 +1,407 @@
+//! End to end: the `rbit` binary against a mock qBittorrent Web UI (see `common`), for changes
+//! to the API layer. `real_qbittorrent.rs` runs the same kind of checks against a real server.
+
+mod common;
+
+use std::net::TcpListener;
+
+use common::{eventually, rbit, rbit_as, rbit_configured, rbit_spawn, stderr, stdout, torrent, torrent_file, write_config, MockServer, TempDir, PASSWORD, USERNAME};
+
+const UBUNTU: &str = "aaaabbbbccccddddeeeeffff0000111122223333";
+const DEBIAN: &str = "1111222233334444555566667777888899990000";
+
+#[test]
+fn logs_in_and_lists() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading"), torrent("debian.iso", DEBIAN, "stalledUP")]);
+    let home = TempDir::new();
+
+    let out = rbit(&home, &server.url(), &["--porcelain", "list", "--all"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let rows: Vec<Vec<String>> = stdout(&out).lines().map(|l| l.split('\t').map(String::from).collect()).collect();
+    assert_eq!(rows.len(), 2, "{:?}", rows);
+    assert_eq!((rows[0][0].as_str(), rows[0][1].as_str()), (UBUNTU, "downloading"));
+    assert_eq!(rows[1].last().map(String::as_str), Some("debian.iso"));
+
+    let logins = server.requests_to("auth/login");
+    assert_eq!(logins.len(), 1);
+    assert_eq!(logins[0].field("username"), Some(USERNAME));
+    let listed = server.requests_to("torrents/info");
+    assert!(listed.iter().all(|r| r.cookie.as_deref().is_some_and(|c| c.contains("SID="))), "{:?}", listed);
+
+    // the next run reuses the session cookie
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(server.requests_to("auth/login").len(), 1);
+    assert_eq!(server.requests_to("torrents/info").last().and_then(|r| r.cookie.clone()), listed[0].cookie);
+}
+
+#[test]
+fn an_expired_session_logs_in_again() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading")]);
+    let home = TempDir::new();
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let first = server.requests_to("torrents/info")[0].cookie.clone();
+
+    // e.g. qBittorrent was restarted: the cached SID is refused once, then replaced
+    server.expire_sessions();
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out).lines().count(), 1);
+    assert_eq!(server.requests_to("auth/login").len(), 2);
+    let listed = server.requests_to("torrents/info");
+    assert_eq!(listed.len(), 3, "{:?}", listed);
+    assert_eq!(listed[1].cookie, first);
+    assert_ne!(listed[2].cookie, first);
+
+    // and the new one is cached for the run after
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(server.requests_to("auth/login").len(), 2);
+}
+
+#[test]
+fn requests_refused_together_log_in_once() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading")]);
+    let home = TempDir::new();
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let first = server.requests_to("torrents/info")[0].cookie.clone();
+
+    // `list` asks for the torrents and the preferences at once, and both are refused
+    server.expire_sessions();
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    for endpoint in ["torrents/info", "app/preferences"] {
+        let sent = server.requests_to(endpoint);
+        assert_eq!(sent.len(), 3, "{:?}", sent);
+        assert_eq!(sent[1].cookie, first);
+        assert_ne!(sent[2].cookie, first);
+    }
+    assert_eq!(server.requests_to("auth/login").len(), 2);
+}
+
+#[test]
+fn a_wrong_password_is_an_auth_error() {
+    let server = MockServer::start();
+    let home = TempDir::new();
+    let out = rbit_as(&home, &server.url(), USERNAME, "wrong", &["list"]);
+    assert_eq!(out.status.code(), Some(3), "{}", stderr(&out));
+    assert!(server.requests_to("torrents/info").is_empty());
+}
+
+#[test]
+fn adds_a_magnet_link() {
+    let server = MockServer::start();
+    let home = TempDir::new();
+    let magnet = format!("magnet:?xt=urn:btih:{}&dn=ubuntu.iso", UBUNTU);
+    let out = rbit(&home, &server.url(), &["add", &magnet, "--category", "linux", "--paused"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let added = server.requests_to("torrents/add");
+    assert_eq!(added.len(), 1);
+    assert_eq!(added[0].field("urls"), Some(magnet.as_str()));
+    assert_eq!(added[0].field("category"), Some("linux"));
+    assert_eq!(added[0].field("paused"), Some("true"));
+}
+
+#[test]
+fn adds_a_torrent_file() {
+    let server = MockServer::start();
+    let home = TempDir::new();
+    let file = home.path().join("a.torrent");
+    std::fs::write(&file, torrent_file()).unwrap();
+    let out = rbit(&home, &server.url(), &["add", file.to_str().unwrap()]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let added = server.requests_to("torrents/add");
+    assert_eq!(added.len(), 1);
+    let body = &added[0].body;
+    let contains = |needle: &[u8]| body.windows(needle.len()).any(|w| w == needle);
+    assert!(contains(b"name=\"torrents\""), "{}", String::from_utf8_lossy(body));
+    assert!(contains(&torrent_file()));
+}
+
+#[test]
+fn uploads_a_torrent_it_cannot_read_unchecked() {
+    let server = MockServer::start();
+    let home = TempDir::new();
+    // a v2-only torrent has a file tree instead of `pieces`
+    let v2 = b"d4:infod9:file treed5:a.txtd0:d6:lengthi5e11:pieces root32:0123456789abcdef0123456789abcdefeee12:meta versioni2e4:name5:a.txt12:piece lengthi16384eee";
+    let file = home.path().join("v2.torrent");
+    std::fs::write(&file, v2).unwrap();
+    for args in [vec!["add", file.to_str().unwrap()], vec!["add", "--force", file.to_str().unwrap()]] {
+        let out = rbit(&home, &server.url(), &args);
+        assert!(out.status.success(), "{}", stderr(&out));
+    }
+    assert!(stderr(&rbit(&home, &server.url(), &["add", file.to_str().unwrap()])).contains("without checking"));
+    assert!(server.requests_to("torrents/info").is_empty());
+    assert_eq!(server.requests_to("torrents/add").len(), 3);
+}
+
+#[test]
+fn a_server_that_is_not_there_is_a_connection_error() {
+    // a port that was free a moment ago
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let home = TempDir::new();
+    let out = rbit(&home, &format!("http://127.0.0.1:{}", port), &["list"]);
+    assert_eq!(out.status.code(), Some(4), "{}", stderr(&out));
+    assert!(stderr(&out).contains("cannot connect"), "{}", stderr(&out));
+    let out = rbit(&home, &format!("http://127.0.0.1:{}", port), &["status"]);
+    assert_eq!(out.status.code(), Some(4), "{}", stderr(&out));
+}
+
+#[test]
+fn server_errors_are_reported() {
+    let server = MockServer::start();
+    server.fail("torrents/info", 500);
+    let home = TempDir::new();
+    let out = rbit(&home, &server.url(), &["--porcelain", "list"]);
+    assert!(!out.status.success());
+    assert!(stderr(&out).contains("500"), "{}", stderr(&out));
+    assert!(stdout(&out).is_empty());
+}
+
+#[test]
+fn only_reads_are_retried() {
+    let server = MockServer::start();
+    server.fail("torrents/add", 503);
+    let home = TempDir::new();
+    write_config(&home, "[http]\nretry_backoff_ms = 1\n");
+
+    // the add may have gone through behind the proxy; sending it again would get `Fails.`
+    let magnet = format!("magnet:?xt=urn:btih:{}", UBUNTU);
+    let out = rbit(&home, &server.url(), &["--retries", "2", "add", &magnet]);
+    assert!(!out.status.success());
+    assert_eq!(server.requests_to("torrents/add").len(), 1);
+
+    server.fail("torrents/info", 503);
+    let before = server.requests_to("torrents/info").len();
+    let out = rbit(&home, &server.url(), &["--retries", "2", "--porcelain", "list"]);
+    assert!(!out.status.success());
+    assert_eq!(server.requests_to("torrents/info").len() - before, 3);
+}
+
+#[test]
+fn gets_one_field() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading")]);
+    let home = TempDir::new();
+
+    let out = rbit(&home, &server.url(), &["get", &UBUNTU[..6], "save_path"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(stdout(&out), "/downloads\n");
+    let out = rbit(&home, &server.url(), &["get", UBUNTU, "progress"]);
+    assert_eq!(stdout(&out), "0.5\n");
+
+    let out = rbit(&home, &server.url(), &["get", UBUNTU, "no_such_field"]);
+    assert_eq!(out.status.code(), Some(5), "{}", stderr(&out));
+    assert!(stdout(&out).is_empty());
+    let out = rbit(&home, &server.url(), &["get", DEBIAN, "state"]);
+    assert_eq!(out.status.code(), Some(5), "{}", stderr(&out));
+}
+
+#[test]
+fn pausing_several_asks_first() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading"), torrent("debian.iso", DEBIAN, "stalledDL")]);
+    let home = TempDir::new();
+
+    // nobody can answer off a terminal
+    let out = rbit(&home, &server.url(), &["pause", "--all"]);
+    assert_eq!(out.status.code(), Some(2), "{}", stderr(&out));
+    let out = rbit(&home, &server.url(), &["pause", "--name", "*.iso"]);
+    assert_eq!(out.status.code(), Some(2), "{}", stderr(&out));
+    assert!(server.requests_to("torrents/pause").is_empty());
+
+    let out = rbit(&home, &server.url(), &["pause", UBUNTU]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let out = rbit(&home, &server.url(), &["--yes", "pause", "--all"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let paused: Vec<Option<String>> = server.requests_to("torrents/pause").iter().map(|r| r.field("hashes").map(String::from)).collect();
+    assert_eq!(paused, [Some(UBUNTU.to_string()), Some("all".to_string())]);
+}
+
+#[test]
+fn lists_and_pauses_on_every_server() {
+    let (seedbox, nas) = (MockServer::start(), MockServer::start());
+    seedbox.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "stalledDL")]);
+    nas.set_torrents(vec![torrent("debian.iso", DEBIAN, "downloading")]);
+    let home = TempDir::new();
+    let profile = |name: &str, server: &MockServer| format!("[servers.{}]\nhost = \"{}\"\nusername = \"{}\"\npassword = \"{}\"\n", name, server.url(), USERNAME, PASSWORD);
+    // a default server rbit cannot list from is skipped, not connected to first
+    let default = "[qbittorrent]\ntype = \"transmission\"\nhost = \"http://127.0.0.1:1\"\n";
+    write_config(&home, &(default.to_string() + &profile("seedbox", &seedbox) + &profile("nas", &nas)));
+
+    let out = rbit_configured(&home, &["--porcelain", "--server", "all", "list", "--all"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let mut rows: Vec<(String, String)> = stdout(&out)
+        .lines()
+        .map(|l| l.split('\t').collect::<Vec<_>>())
+        .map(|fields| (fields[0].to_string(), fields.last().unwrap().to_string()))
+        .collect();
+    rows.sort();
+    assert_eq!(rows, [(DEBIAN.to_string(), "nas".to_string()), (UBUNTU.to_string(), "seedbox".to_string())]);
+
+    write_config(&home, &(profile("seedbox", &seedbox) + &profile("nas", &nas)));
+    let out = rbit_configured(&home, &["--server", "all", "pause", "--state", "stalled"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    let paused = seedbox.requests_to("torrents/pause");
+    assert_eq!(paused.len(), 1);
+    assert_eq!(paused[0].field("hashes"), Some(UBUNTU));
+    assert!(nas.requests_to("torrents/pause").is_empty());
+
+    // ids only mean something on one server
+    let out = rbit_configured(&home, &["--server", "all", "pause", UBUNTU]);
+    assert_eq!(out.status.code(), Some(2), "{}", stderr(&out));
+}
+
+#[test]
+#[cfg(unix)]
+fn the_daemon_runs_the_completion_hook() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading"), torrent("debian.iso", DEBIAN, "stalledUP")]);
+    let home = TempDir::new();
+    let (watch, done) = (home.path().join("watch"), home.path().join("done.txt"));
+    std::fs::create_dir_all(&watch).unwrap();
+    write_config(&home, &format!("[hooks]\non_complete = \"echo $RBIT_NAME >> '{}'\"\n", done.display()));
+
+    let mut daemon = rbit_spawn(&home, &server.url(), &["daemon", "--watch-dir", watch.to_str().unwrap(), "--poll", "0.1s"]);
+    let passes = || server.requests_to("torrents/info").iter().filter(|r| r.field("filter") == Some("all")).count();
+    assert!(eventually(|| passes() >= 1));
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "stalledUP"), torrent("debian.iso", DEBIAN, "stalledUP")]);
+    let ran = eventually(|| done.exists());
+    // and only once, a few passes later
+    let later = passes() + 3;
+    eventually(|| passes() >= later);
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+    assert!(ran);
+    // debian.iso was done before the daemon started
+    assert_eq!(std::fs::read_to_string(&done).unwrap(), "ubuntu.iso\n");
+}
+
+#[test]
+fn the_daemon_pauses_downloads_while_space_is_low() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading"), torrent("debian.iso", DEBIAN, "stalledUP")]);
+    server.set_free_space(5 << 30);
+    let home = TempDir::new();
+    write_config(&home, "[disk]\nmin_free = \"10GiB\"\nresume_above = \"20GiB\"\n");
+
+    let mut daemon = rbit_spawn(&home, &server.url(), &["daemon", "--poll", "0.1s"]);
+    let paused = eventually(|| !server.requests_to("torrents/pause").is_empty());
+    // between the limits it stays paused
+    server.set_free_space(15 << 30);
+    let passes = || server.requests_to("sync/maindata").len();
+    let later = passes() + 3;
+    eventually(|| passes() >= later);
+    let early = server.requests_to("torrents/resume").len();
+    server.set_free_space(50 << 30);
+    let resumed = eventually(|| !server.requests_to("torrents/resume").is_empty());
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+    assert!(paused && resumed);
+    assert_eq!(early, 0);
+    assert_eq!(server.requests_to("torrents/pause")[0].field("hashes"), Some(UBUNTU));
+    assert_eq!(server.requests_to("torrents/resume")[0].field("hashes"), Some(UBUNTU));
+    assert_eq!(server.requests_to("torrents/pause").len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn wait_runs_the_hook_for_a_completed_torrent() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "uploading")]);
+    let home = TempDir::new();
+    let done = home.path().join("done.txt");
+    let hook = format!("echo \"$RBIT_NAME $RBIT_HASH\" > '{}'", done.display());
+    let out = rbit(&home, &server.url(), &["wait", UBUNTU, "--on-complete", &hook]);
+    assert!(out.status.success(), "{}", stderr(&out));
+    assert_eq!(std::fs::read_to_string(&done).unwrap(), format!("ubuntu.iso {}\n", UBUNTU));
+}
+
+#[cfg(unix)]
+#[test]
+fn a_stopped_wait_exits_130_and_logs_out() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading")]);
+    let home = TempDir::new();
+    write_config(&home, &format!("[qbittorrent]\nhost = \"{}\"\nlogout_on_stop = true\n", server.url()));
+
+    let mut wait = rbit_spawn(&home, &server.url(), &["wait", UBUNTU, "--interval", "0.1s"]);
+    assert!(eventually(|| server.requests_to("torrents/info").len() >= 3));
+    assert_eq!(unsafe { libc::kill(wait.id() as i32, libc::SIGTERM) }, 0);
+    assert_eq!(wait.wait().unwrap().code(), Some(130));
+    assert_eq!(server.requests_to("auth/logout").len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn the_daemon_pauses_everything_while_the_guard_fails() {
+    let server = MockServer::start();
+    server.set_torrents(vec![torrent("ubuntu.iso", UBUNTU, "downloading"), torrent("debian.iso", DEBIAN, "stalledUP")]);
+    let home = TempDir::new();
+    let up = home.path().join("vpn-up");
+    std::fs::write(&up, "").unwrap();
+    write_config(&home, &format!("[guard]\ncommand = \"test -e '{}'\"\n", up.display()));
+
+    let mut daemon = rbit_spawn(&home, &server.url(), &["daemon", "--poll", "0.1s"]);
+    let passes = || server.requests_to("torrents/info").iter().filter(|r| r.field("filter") == Some("all")).count();
+    assert!(eventually(|| passes() >= 2));
+    let early = server.requests_to("torrents/pause").len();
+    std::fs::remove_file(&up).unwrap();
+    let paused = eventually(|| !server.requests_to("torrents/pause").is_empty());
+    std::fs::write(&up, "").unwrap();
+    let resumed = eventually(|| !server.requests_to("torrents/resume").is_empty());
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+    assert_eq!(early, 0);
+    assert!(paused && resumed);
+    let both = format!("{}|{}", UBUNTU, DEBIAN);
+    assert_eq!(server.requests_to("torrents/pause")[0].field("hashes"), Some(both.as_str()));
+    assert_eq!(server.requests_to("torrents/resume")[0].field("hashes"), Some(both.as_str()));
+}
+
+/// The body of a plain GET to `path` on `addr`, once something listens there.
+fn http_get(addr: &str, path: &str) -> Option<String> {
+    use std::io::{Read, Write};
+    let mut stream = std::net::TcpStream::connect(addr).ok()?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, addr).ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    response.split_once("\r\n\r\n").map(|(_, body)| body.to_string())
+}
+
+#[test]
+fn the_daemon_reports_what_it_does() {
+    let server = MockServer::start();
+    let home = TempDir::new();
+    let watch = home.path().join("watch");
+    std::fs::create_dir_all(&watch).unwrap();
+    write_config(&home, "[[rules]]\nname = \"ubuntu\"\ncategory = \"linux\"\n");
+    std::fs::write(watch.join("u.magnet"), format!("magnet:?xt=urn:btih:{}&dn=ubuntu.iso\n", UBUNTU)).unwrap();
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(60);
+    std::fs::File::options().write(true).open(watch.join("u.magnet")).unwrap().set_modified(old).unwrap();
+    let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+
+    let mut daemon = rbit_spawn(&home, &server.url(), &["daemon", "--watch-dir", watch.to_str().unwrap(), "--poll", "0.1s", "--listen", &addr]);
+    let status = || http_get(&addr, "/status").and_then(|body| serde_json::from_str::<serde_json::Value>(&body).ok());
+    let synced = eventually(|| status().is_some_and(|s| s["actions"]["added"] == 1 && !s["last_sync"].is_null()));
+    let status = status();
+    let events = http_get(&addr, "/events").unwrap_or_default();
+    let metrics = http_get(&addr, "/metrics").unwrap_or_default();
+    daemon.kill().unwrap();
+    daemon.wait().unwrap();
+    assert!(synced, "{:?}", status);
+    let status = status.unwrap();
+    assert_eq!(status["rules"]["matches"]["#1"], 1, "{}", status);
+    assert!(status["passes"].as_u64().unwrap() >= 1);
+    let events: Vec<serde_json::Value> = serde_json::from_str(&events).unwrap();
+    assert_eq!((events[0]["kind"].as_str(), events[0]["subject"].as_str()), (Some("added"), Some("u.magnet")));
+    assert!(metrics.contains("rbit_daemon_rule_matches_total{host=\""), "{}", metrics);
+    assert_eq!(server.requests_to("torrents/add")[0].field("category"), Some("linux"));
+}
@@ -0,0 +1,74 @@
+//! The end-to-end checks of `qbittorrent.rs` against a real qBittorrent, for what a mock cannot
+//! tell: that the real Web UI takes rbit's requests. Ignored unless asked for:
+//!
+//! ```sh
+//! docker compose -f tests/docker-compose.yml up -d
+//! # the Web UI's temporary password for user admin is in the log
+//! docker compose -f tests/docker-compose.yml logs | grep password
+//! RBIT_E2E_HOST=http://127.0.0.1:18080 RBIT_E2E_PASSWORD=... cargo test --test real_qbittorrent -- --ignored
+//! ```
+//!
+//! The torrents added are stopped and stay on the server.
+
+mod common;
+
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use common::{rbit_as, stderr, stdout, torrent_file, TempDir};
+
+const MAGNET_HASH: &str = "5bd1a2c81b701e2d76a4f3b4d1ea90c2b3e5f6a7";
+
+struct Server {
+    host: String,
+    username: String,
+    password: String,
+}
+
+fn server() -> Server {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    Server {
+        host: var("RBIT_E2E_HOST").expect("RBIT_E2E_HOST, the Web UI to test against"),
+        username: var("RBIT_E2E_USERNAME").unwrap_or_else(|| "admin".to_string()),
+        password: var("RBIT_E2E_PASSWORD").expect("RBIT_E2E_PASSWORD"),
+    }
+}
+
+/// The rows of `rbit --porcelain list --all` once `done` holds for them, within a while.
+fn listed_until(server: &Server, home: &TempDir, done: impl Fn(&[Vec<String>]) -> bool) -> Vec<Vec<String>> {
+    let started = Instant::now();
+    loop {
+        let out = rbit_as(home, &server.host, &server.username, &server.password, &["--porcelain", "list", "--all"]);
+        assert!(out.status.success(), "{}", stderr(&out));
+        let rows: Vec<Vec<String>> = stdout(&out).lines().map(|l| l.split('\t').map(String::from).collect()).collect();
+        if done(&rows) || started.elapsed() > Duration::from_secs(20) {
+            return rows;
+        }
+        sleep(Duration::from_millis(500));
+    }
+}
+
+#[test]
+#[ignore = "needs a qBittorrent server, see tests/docker-compose.yml"]
+fn login_add_and_list() {
+    let server = server();
+    let home = TempDir::new();
+
+    // a single failed login; qBittorrent bans the address after a few
+    let out = rbit_as(&home, &server.host, &server.username, "not-the-password", &["list"]);
+    assert_eq!(out.status.code(), Some(3), "{}", stderr(&out));
+
+    let magnet = format!("magnet:?xt=urn:btih:{}&dn=rbit-e2e-magnet", MAGNET_HASH);
+    let out = rbit_as(&home, &server.host, &server.username, &server.password, &["add", &magnet, "--paused", "--dest", "/downloads"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let file = home.path().join("a.torrent");
+    std::fs::write(&file, torrent_file()).unwrap();
+    let out = rbit_as(&home, &server.host, &server.username, &server.password, &["add", file.to_str().unwrap(), "--paused", "--dest", "/downloads"]);
+    assert!(out.status.success(), "{}", stderr(&out));
+
+    let has = |rows: &[Vec<String>], column: usize, value: &str| rows.iter().any(|row| row.get(column).map(String::as_str) == Some(value));
+    let rows = listed_until(&server, &home, |rows| has(rows, 0, MAGNET_HASH) && rows.iter().any(|row| row.last().map(String::as_str) == Some("a.txt")));
+    assert!(has(&rows, 0, MAGNET_HASH), "{:?}", rows);
+    assert!(rows.iter().any(|row| row.last().map(String::as_str) == Some("a.txt")), "{:?}", rows);
+}